@@ -0,0 +1,73 @@
+//! Benchmarks of reading and parsing `/proc` files.
+//!
+//! These use the unstable `test` crate, so they require a nightly compiler:
+//!
+//! ```text
+//! cargo +nightly bench --features nightly
+//! ```
+
+#![feature(test)]
+
+extern crate procinfo;
+extern crate test;
+
+use std::fs;
+
+use procinfo::LoadAvg;
+use procinfo::pid::{Limits, Stat, Statm, Status};
+use test::Bencher;
+
+#[bench]
+fn bench_loadavg(b: &mut Bencher) {
+    b.iter(|| test::black_box(procinfo::loadavg()));
+}
+
+#[bench]
+fn bench_loadavg_parse(b: &mut Bencher) {
+    let loadavg = fs::read("/proc/loadavg").unwrap();
+    b.iter(|| test::black_box(LoadAvg::parse(&loadavg)));
+}
+
+#[bench]
+fn bench_limits(b: &mut Bencher) {
+    b.iter(|| test::black_box(procinfo::pid::limits(1)));
+}
+
+#[bench]
+fn bench_limits_parse(b: &mut Bencher) {
+    let limits = fs::read("/proc/1/limits").unwrap();
+    b.iter(|| test::black_box(Limits::parse(&limits)));
+}
+
+#[bench]
+fn bench_stat(b: &mut Bencher) {
+    b.iter(|| test::black_box(procinfo::pid::stat(1)));
+}
+
+#[bench]
+fn bench_stat_parse(b: &mut Bencher) {
+    let stat = fs::read("/proc/1/stat").unwrap();
+    b.iter(|| test::black_box(Stat::parse(&stat)));
+}
+
+#[bench]
+fn bench_statm(b: &mut Bencher) {
+    b.iter(|| test::black_box(procinfo::pid::statm(1)));
+}
+
+#[bench]
+fn bench_statm_parse(b: &mut Bencher) {
+    let statm = fs::read("/proc/1/statm").unwrap();
+    b.iter(|| test::black_box(Statm::parse(&statm)));
+}
+
+#[bench]
+fn bench_status(b: &mut Bencher) {
+    b.iter(|| test::black_box(procinfo::pid::status(1)));
+}
+
+#[bench]
+fn bench_status_parse(b: &mut Bencher) {
+    let status = fs::read("/proc/1/status").unwrap();
+    b.iter(|| test::black_box(Status::parse(&status)));
+}
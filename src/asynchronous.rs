@@ -0,0 +1,205 @@
+//! Asynchronous variants of the accessors, for use with the tokio runtime.
+//!
+//! Files are read on the blocking thread pool of the runtime, and parsed with the same parsers as
+//! the synchronous accessors. Requires the `async` feature.
+
+use std::ffi::OsString;
+use std::fmt;
+use std::fs;
+use std::future::Future;
+use std::io::{Error, Read, Result};
+use std::path::PathBuf;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+use libc::pid_t;
+use tokio::task::{self, JoinHandle};
+
+use loadavg::LoadAvg;
+use parsers::with_path;
+use pid::{self, Limits, Stat, Statm, Status, open_at, parse_cmdline, parse_oom_adj,
+          parse_oom_score};
+
+/// A blocking read of the contents of a file.
+type ReadFn = Box<dyn FnOnce() -> Result<Vec<u8>> + Send>;
+
+/// A future which reads a file and parses its contents.
+///
+/// The read starts when the future is first polled, which must be within a tokio runtime.
+pub struct ReadFuture<T> {
+    /// The read, until it is started on the blocking thread pool.
+    read: Option<ReadFn>,
+    /// The started read.
+    handle: Option<JoinHandle<Result<Vec<u8>>>>,
+    path: PathBuf,
+    parse: fn(&[u8]) -> Result<T>,
+}
+
+impl<T> ReadFuture<T> {
+    fn new(path: PathBuf, read: ReadFn, parse: fn(&[u8]) -> Result<T>) -> ReadFuture<T> {
+        ReadFuture { read: Some(read), handle: None, path, parse }
+    }
+}
+
+impl<T> Future for ReadFuture<T> {
+    type Output = Result<T>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context) -> Poll<Result<T>> {
+        let this = &mut *self;
+        if let Some(read) = this.read.take() {
+            this.handle = Some(task::spawn_blocking(read));
+        }
+        let handle = this.handle.as_mut().expect("ReadFuture polled after completion");
+        match Pin::new(handle).poll(cx) {
+            Poll::Ready(Ok(Ok(buf))) => {
+                Poll::Ready((this.parse)(&buf).map_err(|error| with_path(error, &this.path)))
+            },
+            Poll::Ready(Ok(Err(error))) => Poll::Ready(Err(error)),
+            Poll::Ready(Err(error)) => Poll::Ready(Err(Error::other(error))),
+            Poll::Pending => Poll::Pending,
+        }
+    }
+}
+
+impl<T> fmt::Debug for ReadFuture<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("ReadFuture").field("path", &self.path).finish()
+    }
+}
+
+/// Returns the system load average.
+pub fn loadavg() -> ReadFuture<LoadAvg> {
+    ReadFuture::new(PathBuf::from("/proc/loadavg"),
+                    Box::new(|| fs::read("/proc/loadavg")),
+                    LoadAvg::parse)
+}
+
+/// A handle to a process, with asynchronous accessors.
+///
+/// See `pid::Process`; a process which has exited is never confused with a later process which
+/// has been assigned the same pid.
+#[derive(Debug)]
+pub struct Process {
+    inner: pid::Process,
+}
+
+impl Process {
+    /// Opens a handle to the process with the provided pid.
+    ///
+    /// Opening the handle does not block on I/O, so it is not asynchronous.
+    pub fn new(pid: pid_t) -> Result<Process> {
+        pid::Process::new(pid).map(Process::from)
+    }
+
+    /// Opens a handle to the current process.
+    pub fn myself() -> Result<Process> {
+        pid::Process::myself().map(Process::from)
+    }
+
+    /// Returns the pid of the process.
+    pub fn pid(&self) -> pid_t {
+        self.inner.pid()
+    }
+
+    /// Returns a future which reads the file with the provided name relative to the process
+    /// directory.
+    ///
+    /// The future owns a duplicate of the directory file descriptor, so it reads the files of
+    /// this process even if the handle is dropped before the future completes.
+    fn read<T>(&self, name: &str, parse: fn(&[u8]) -> Result<T>) -> ReadFuture<T> {
+        let path = self.inner.path(name);
+        let dir = self.inner.try_clone_dir();
+        let name = name.to_owned();
+        let read = move || -> Result<Vec<u8>> {
+            let mut buf = Vec::new();
+            open_at(&dir?, &name)?.read_to_end(&mut buf)?;
+            Ok(buf)
+        };
+        ReadFuture::new(path, Box::new(read), parse)
+    }
+
+    /// Returns the command line arguments of the process.
+    pub fn cmdline(&self) -> ReadFuture<Vec<OsString>> {
+        self.read("cmdline", |buf| Ok(parse_cmdline(buf)))
+    }
+
+    /// Returns resource limit information of the process.
+    pub fn limits(&self) -> ReadFuture<Limits> {
+        self.read("limits", Limits::parse)
+    }
+
+    /// Returns the current OOM killer badness score of the process.
+    pub fn oom_score(&self) -> ReadFuture<u32> {
+        self.read("oom_score", parse_oom_score)
+    }
+
+    /// Returns the OOM score adjustment of the process.
+    pub fn oom_score_adj(&self) -> ReadFuture<i32> {
+        self.read("oom_score_adj", parse_oom_adj)
+    }
+
+    /// Returns status information of the process.
+    pub fn stat(&self) -> ReadFuture<Stat> {
+        self.read("stat", Stat::parse)
+    }
+
+    /// Returns memory status information of the process.
+    pub fn statm(&self) -> ReadFuture<Statm> {
+        self.read("statm", Statm::parse)
+    }
+
+    /// Returns detailed status information of the process.
+    pub fn status(&self) -> ReadFuture<Status> {
+        self.read("status", Status::parse)
+    }
+}
+
+impl From<pid::Process> for Process {
+    fn from(inner: pid::Process) -> Process {
+        Process { inner }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::env;
+    use std::fs::File;
+    use std::future::Future;
+
+    use libc;
+    use tokio::runtime::Builder;
+
+    use super::{Process, loadavg};
+
+    fn block_on<F: Future>(future: F) -> F::Output {
+        Builder::new_current_thread().build().unwrap().block_on(future)
+    }
+
+    #[test]
+    fn test_loadavg() {
+        block_on(loadavg()).unwrap();
+    }
+
+    #[test]
+    fn test_process() {
+        let process = Process::myself().unwrap();
+        let pid = unsafe { libc::getpid() };
+        assert_eq!(pid, block_on(process.stat()).unwrap().pid);
+        assert_eq!(pid, block_on(process.status()).unwrap().pid);
+        assert_eq!(env::args_os().collect::<Vec<_>>(), block_on(process.cmdline()).unwrap());
+        block_on(process.statm()).unwrap();
+        block_on(process.limits()).unwrap();
+        block_on(process.oom_score()).unwrap();
+        block_on(process.oom_score_adj()).unwrap();
+        assert_eq!(1, block_on(Process::new(1).unwrap().stat()).unwrap().pid);
+    }
+
+    /// Test that a future reads the files of its process after the handle is dropped.
+    #[test]
+    fn test_future_outlives_process() {
+        let future = Process::new(1).unwrap().stat();
+        // Reuse the file descriptor number of the dropped process directory.
+        let _file = File::open("/proc/self").unwrap();
+        assert_eq!(1, block_on(future).unwrap().pid);
+    }
+}
@@ -0,0 +1,54 @@
+//! System boot time from the `btime` line of `/proc/stat`.
+
+use std::io::Result;
+use std::sync::OnceLock;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use cpu::parse_stat;
+use parsers::{invalid_input, parse, parse_str};
+
+/// Parses the boot time, in seconds since the Unix epoch, from the stat file contents.
+fn parse_boot_time(input: &[u8]) -> Result<u64> {
+    parse_str(input)?.lines()
+                     .find_map(|line| line.strip_prefix("btime "))
+                     .ok_or_else(|| invalid_input("missing btime line"))
+                     .and_then(|btime| parse(btime.trim()))
+}
+
+/// Returns the time at which the system booted.
+///
+/// The boot time does not change while the system is running, so it is read once and cached. It
+/// has a resolution of one second.
+pub fn boot_time() -> Result<SystemTime> {
+    static BOOT_TIME: OnceLock<SystemTime> = OnceLock::new();
+    if let Some(&boot_time) = BOOT_TIME.get() {
+        return Ok(boot_time);
+    }
+    let boot_time = UNIX_EPOCH + Duration::from_secs(parse_stat(parse_boot_time)?);
+    Ok(*BOOT_TIME.get_or_init(|| boot_time))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::SystemTime;
+
+    use super::{boot_time, parse_boot_time};
+
+    #[test]
+    fn test_boot_time() {
+        let boot_time = boot_time().unwrap();
+        assert!(boot_time < SystemTime::now());
+        assert_eq!(boot_time, super::boot_time().unwrap());
+    }
+
+    #[test]
+    fn test_parse_boot_time() {
+        let text = b"cpu  33072 10 3831 79018 249 1 4 207 5 6\n\
+                     intr 147652 0 0\n\
+                     ctxt 1990473\n\
+                     btime 1792161072\n\
+                     processes 2915\n";
+        assert_eq!(1792161072, parse_boot_time(text).unwrap());
+        parse_boot_time(b"cpu  1 2 3 4\n").unwrap_err();
+    }
+}
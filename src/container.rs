@@ -0,0 +1,153 @@
+//! Detection of the container runtime, if any, that the current process runs in.
+
+use std::fs;
+use std::path::Path;
+
+use pid::environ;
+
+/// A container runtime.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "export", derive(Serialize))]
+pub enum Container {
+    /// Docker.
+    Docker,
+    /// Podman.
+    Podman,
+    /// containerd, including Kubernetes pods run with the containerd CRI plugin.
+    Containerd,
+    /// LXC or LXD.
+    Lxc,
+    /// systemd-nspawn.
+    SystemdNspawn,
+    /// Another container runtime, named by the `container` environment variable of the init
+    /// process, or `None` if it could only be inferred that the process runs in a container.
+    Other(Option<String>),
+}
+
+/// Classifies the value of the `container` environment variable, which container runtimes set
+/// for the init process following the systemd container interface.
+fn parse_container_env(value: &str) -> Container {
+    match value {
+        "docker"              => Container::Docker,
+        "podman"              => Container::Podman,
+        "containerd"          => Container::Containerd,
+        "lxc" | "lxc-libvirt" => Container::Lxc,
+        "systemd-nspawn"      => Container::SystemdNspawn,
+        other                 => Container::Other(Some(other.to_owned())),
+    }
+}
+
+/// Classifies the cgroup paths of a `/proc/[pid]/cgroup` file.
+fn parse_cgroup(input: &str) -> Option<Container> {
+    for line in input.lines() {
+        // Lines are of the form `hierarchy-ID:controller-list:cgroup-path`.
+        let path = match line.splitn(3, ':').nth(2) {
+            Some(path) => path,
+            None => continue,
+        };
+        if path.contains("/docker/") || path.contains("/docker-") {
+            return Some(Container::Docker);
+        }
+        if path.contains("/libpod-") {
+            return Some(Container::Podman);
+        }
+        if path.contains("containerd") {
+            return Some(Container::Containerd);
+        }
+        if path.contains("/lxc/") || path.contains("/lxc.payload") {
+            return Some(Container::Lxc);
+        }
+        if path.contains("/machine.slice/machine-") {
+            return Some(Container::SystemdNspawn);
+        }
+    }
+    None
+}
+
+/// Parses the pid from the header line of a `/proc/[pid]/sched` file, of the form
+/// `command (pid, #threads: n)`.
+fn parse_sched_pid(input: &str) -> Option<u32> {
+    let header = input.lines().next()?;
+    let start = header.rfind('(')? + 1;
+    let end = start + header[start..].find(',')?;
+    header[start..end].trim().parse().ok()
+}
+
+/// Returns the container runtime that the current process runs in, or `None` if the process does
+/// not appear to run in a container.
+///
+/// The following hints are checked, in order:
+///
+/// * the `container` environment variable of the init process (`/proc/1/environ`), which requires
+///   permission to read the environment of the init process;
+/// * the `/.dockerenv` and `/run/.containerenv` marker files;
+/// * the cgroup paths of the init process (`/proc/1/cgroup`), which identify the runtime when the
+///   cgroup namespace is not unshared;
+/// * the pid of the init process reported by `/proc/1/sched`, which on older kernels is the pid
+///   outside of the pid namespace.
+///
+/// Detection is best effort: a runtime which sets none of these hints is not detected, and hints
+/// which can not be read are skipped.
+pub fn detect_container() -> Option<Container> {
+    if let Ok(environ) = environ(1) {
+        if let Some(value) = environ.get("container") {
+            return Some(parse_container_env(&value.to_string_lossy()));
+        }
+    }
+    if Path::new("/.dockerenv").exists() {
+        return Some(Container::Docker);
+    }
+    if Path::new("/run/.containerenv").exists() {
+        return Some(Container::Podman);
+    }
+    if let Ok(cgroup) = fs::read_to_string("/proc/1/cgroup") {
+        if let Some(container) = parse_cgroup(&cgroup) {
+            return Some(container);
+        }
+    }
+    let sched = fs::read_to_string("/proc/1/sched").ok();
+    match sched.as_ref().and_then(|sched| parse_sched_pid(sched)) {
+        Some(pid) if pid != 1 => Some(Container::Other(None)),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Container, detect_container, parse_cgroup, parse_container_env, parse_sched_pid};
+
+    /// Test that detection does not fail in the current environment.
+    #[test]
+    fn test_detect_container() {
+        detect_container();
+    }
+
+    #[test]
+    fn test_parse_container_env() {
+        assert_eq!(Container::Docker, parse_container_env("docker"));
+        assert_eq!(Container::SystemdNspawn, parse_container_env("systemd-nspawn"));
+        assert_eq!(Container::Other(Some("oci".to_owned())), parse_container_env("oci"));
+    }
+
+    #[test]
+    fn test_parse_cgroup() {
+        assert_eq!(None, parse_cgroup("12:pids:/\n0::/init.scope\n"));
+        assert_eq!(Some(Container::Docker),
+                   parse_cgroup("12:pids:/docker/3f4b1c2d\n0::/docker/3f4b1c2d\n"));
+        assert_eq!(Some(Container::Docker),
+                   parse_cgroup("0::/system.slice/docker-3f4b1c2d.scope\n"));
+        assert_eq!(Some(Container::Containerd),
+                   parse_cgroup("0::/kubepods/besteffort/pod1/cri-containerd-3f4b1c2d.scope\n"));
+        assert_eq!(Some(Container::Lxc), parse_cgroup("0::/lxc.payload.c1\n"));
+        assert_eq!(Some(Container::SystemdNspawn),
+                   parse_cgroup("0::/machine.slice/machine-c1.scope/payload\n"));
+    }
+
+    #[test]
+    fn test_parse_sched_pid() {
+        assert_eq!(Some(1), parse_sched_pid("systemd (1, #threads: 1)\n-------\n"));
+        assert_eq!(Some(4321), parse_sched_pid("bash (4321, #threads: 1)\n"));
+        assert_eq!(Some(7), parse_sched_pid("my (odd) name (7, #threads: 1)\n"));
+        assert_eq!(None, parse_sched_pid(""));
+    }
+}
@@ -0,0 +1,326 @@
+//! System CPU time statistics and kernel activity counters from `/proc/stat`.
+
+use std::io::{Read, Result};
+use std::str;
+use std::time::Duration;
+
+use parsers::{invalid_input, parse_file};
+use sysconf::ticks_to_duration;
+
+/// Time spent by a CPU in each mode, measured in clock ticks (divide by `ticks_per_second()`).
+///
+/// The `*_duration` methods convert the times to `Duration`s.
+///
+/// Fields which are not reported by older kernels are zero.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "export", derive(Serialize))]
+pub struct Cpu {
+    /// The number of the CPU, or `None` for the aggregate of all CPUs.
+    pub id: Option<usize>,
+    /// Time spent in user mode, including guest time.
+    pub user: u64,
+    /// Time spent in user mode with low priority (nice), including guest nice time.
+    pub nice: u64,
+    /// Time spent in system mode.
+    pub system: u64,
+    /// Time spent in the idle task.
+    pub idle: u64,
+    /// Time spent waiting for I/O to complete (since Linux 2.5.41).
+    pub iowait: u64,
+    /// Time spent servicing interrupts (since Linux 2.6.0).
+    pub irq: u64,
+    /// Time spent servicing softirqs (since Linux 2.6.0).
+    pub softirq: u64,
+    /// Stolen time, which is the time spent in other operating systems when running in a
+    /// virtualized environment (since Linux 2.6.11).
+    pub steal: u64,
+    /// Time spent running a virtual CPU for guest operating systems (since Linux 2.6.24).
+    pub guest: u64,
+    /// Time spent running a niced guest (since Linux 2.6.33).
+    pub guest_nice: u64,
+}
+
+impl Cpu {
+    /// Returns the total time, excluding the guest times which are already accounted for in
+    /// `user` and `nice`.
+    pub fn total(&self) -> u64 {
+        self.user + self.nice + self.system + self.idle + self.iowait + self.irq + self.softirq
+            + self.steal
+    }
+
+    /// Returns the total time, excluding the guest times which are already accounted for in
+    /// `user` and `nice`.
+    pub fn total_duration(&self) -> Duration {
+        ticks_to_duration(self.total())
+    }
+
+    /// Returns the time spent in user mode, including guest time.
+    pub fn user_duration(&self) -> Duration {
+        ticks_to_duration(self.user)
+    }
+
+    /// Returns the time spent in user mode with low priority, including guest nice time.
+    pub fn nice_duration(&self) -> Duration {
+        ticks_to_duration(self.nice)
+    }
+
+    /// Returns the time spent in system mode.
+    pub fn system_duration(&self) -> Duration {
+        ticks_to_duration(self.system)
+    }
+
+    /// Returns the time spent in the idle task.
+    pub fn idle_duration(&self) -> Duration {
+        ticks_to_duration(self.idle)
+    }
+
+    /// Returns the time spent in waiting for I/O to complete.
+    pub fn iowait_duration(&self) -> Duration {
+        ticks_to_duration(self.iowait)
+    }
+
+    /// Returns the time spent in servicing interrupts.
+    pub fn irq_duration(&self) -> Duration {
+        ticks_to_duration(self.irq)
+    }
+
+    /// Returns the time spent in servicing softirqs.
+    pub fn softirq_duration(&self) -> Duration {
+        ticks_to_duration(self.softirq)
+    }
+
+    /// Returns the stolen time, spent in other operating systems when running in a virtualized
+    /// environment.
+    pub fn steal_duration(&self) -> Duration {
+        ticks_to_duration(self.steal)
+    }
+
+    /// Returns the time spent in running a virtual CPU for guest operating systems.
+    pub fn guest_duration(&self) -> Duration {
+        ticks_to_duration(self.guest)
+    }
+
+    /// Returns the time spent in running a niced guest.
+    pub fn guest_nice_duration(&self) -> Duration {
+        ticks_to_duration(self.guest_nice)
+    }
+
+    /// Parses a `cpu` or `cpuN` line of a `/proc/stat` file, without the line terminator.
+    pub fn parse_line(input: &[u8]) -> Result<Cpu> {
+        let line = str::from_utf8(input).map_err(|_| invalid_input("cpu line is not valid UTF-8"))?;
+        if !line.starts_with("cpu") {
+            return Err(invalid_input("not a cpu line"));
+        }
+        let rest = &line[3..];
+        let (id, fields) = rest.split_at(rest.find(' ').unwrap_or(rest.len()));
+        let id = match id {
+            "" => None,
+            id => Some(id.parse().map_err(|_| invalid_input("invalid cpu number"))?),
+        };
+
+        let mut values = [0; 10];
+        let mut count = 0;
+        for field in fields.split_whitespace() {
+            if count == values.len() {
+                break;
+            }
+            values[count] = field.parse().map_err(|_| invalid_input("invalid cpu time"))?;
+            count += 1;
+        }
+        if count < 4 {
+            return Err(invalid_input("too few cpu times"));
+        }
+
+        Ok(Cpu {
+            id,
+            user: values[0],
+            nice: values[1],
+            system: values[2],
+            idle: values[3],
+            iowait: values[4],
+            irq: values[5],
+            softirq: values[6],
+            steal: values[7],
+            guest: values[8],
+            guest_nice: values[9],
+        })
+    }
+}
+
+/// Kernel activity counters from `/proc/stat`.
+///
+/// Counters which are not reported by the running kernel are zero.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "export", derive(Serialize))]
+pub struct StatCounters {
+    /// Interrupts serviced since boot, including unnumbered architecture-specific interrupts.
+    pub intr: u64,
+    /// Context switches since boot.
+    pub ctxt: u64,
+    /// Processes and threads created since boot.
+    pub processes: u64,
+    /// Threads currently runnable (since Linux 2.5.45).
+    pub procs_running: u64,
+    /// Threads currently blocked waiting for I/O to complete (since Linux 2.5.45).
+    pub procs_blocked: u64,
+}
+
+impl StatCounters {
+    /// Parses the counters from the contents of a `/proc/stat` file.
+    ///
+    /// Lines other than the counter lines are skipped.
+    pub fn parse(input: &[u8]) -> Result<StatCounters> {
+        let input = str::from_utf8(input).map_err(|_| invalid_input("stat is not valid UTF-8"))?;
+        let mut counters = StatCounters::default();
+        for line in input.lines() {
+            let mut parts = line.split_whitespace();
+            let field = match parts.next() {
+                Some("intr") => &mut counters.intr,
+                Some("ctxt") => &mut counters.ctxt,
+                Some("processes") => &mut counters.processes,
+                Some("procs_running") => &mut counters.procs_running,
+                Some("procs_blocked") => &mut counters.procs_blocked,
+                _ => continue,
+            };
+            // Only the total of the intr line is kept; the per-interrupt counts follow it.
+            *field = parts.next()
+                          .and_then(|value| value.parse().ok())
+                          .ok_or_else(|| invalid_input("invalid stat counter"))?;
+        }
+        Ok(counters)
+    }
+}
+
+/// Reads the stat file and parses its contents, adding the path to parse errors.
+pub(crate) fn parse_stat<T, F>(parse: F) -> Result<T> where F: FnOnce(&[u8]) -> Result<T> {
+    parse_file("/proc/stat", |file| {
+        let mut buf = Vec::new();
+        file.read_to_end(&mut buf)?;
+        parse(&buf)
+    })
+}
+
+/// Returns the `cpuN` lines of the provided stat file contents.
+fn cpu_lines(input: &[u8]) -> impl Iterator<Item = &[u8]> {
+    input.split(|&b| b == b'\n').filter(|line| line.starts_with(b"cpu"))
+}
+
+/// Parses the aggregate cpu line of the provided stat file contents.
+fn parse_cpu(input: &[u8]) -> Result<Cpu> {
+    match cpu_lines(input).next() {
+        Some(line) if line.starts_with(b"cpu ") => Cpu::parse_line(line),
+        _ => Err(invalid_input("missing aggregate cpu line")),
+    }
+}
+
+/// Returns the time spent by all CPUs in each mode.
+pub fn cpu() -> Result<Cpu> {
+    parse_stat(parse_cpu)
+}
+
+/// Parses every cpu line of the provided stat file contents.
+fn parse_cpus(input: &[u8]) -> Result<Vec<Cpu>> {
+    cpu_lines(input).map(Cpu::parse_line).collect()
+}
+
+/// Returns the time spent by each CPU in each mode.
+///
+/// The first entry is the aggregate of all CPUs, followed by an entry for each online CPU. Offline
+/// CPUs are not listed, so the CPU numbers may not be contiguous.
+pub fn cpus() -> Result<Vec<Cpu>> {
+    parse_stat(parse_cpus)
+}
+
+/// Returns the number of CPUs listed in `/proc/stat`, which are the online CPUs.
+pub fn cpu_count() -> Result<usize> {
+    parse_stat(|input| Ok(cpu_lines(input).filter(|line| !line.starts_with(b"cpu ")).count()))
+}
+
+/// Returns the kernel activity counters.
+pub fn stat_counters() -> Result<StatCounters> {
+    parse_stat(StatCounters::parse)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::{Cpu, StatCounters, cpu, cpu_count, cpus, parse_cpu, parse_cpus, stat_counters};
+
+    /// Test that the system stat file can be parsed.
+    #[test]
+    fn test_cpu() {
+        let cpu = cpu().unwrap();
+        assert!(cpu.total() > 0);
+        assert!(cpu.total_duration() > Duration::from_secs(0));
+        assert!(cpu_count().unwrap() > 0);
+
+        let cpus = cpus().unwrap();
+        assert_eq!(None, cpus[0].id);
+        assert_eq!(cpu_count().unwrap(), cpus.len() - 1);
+    }
+
+    #[test]
+    fn test_parse_cpu() {
+        let text = b"cpu  33072 10 3831 79018 249 1 4 207 5 6\n\
+                     cpu0 16536 5 1915 39509 124 0 2 103 2 3\n\
+                     cpu1 16536 5 1916 39509 125 1 2 104 3 3\n\
+                     intr 147652 0 0\n";
+        assert_eq!(Cpu {
+                       id: None,
+                       user: 33072,
+                       nice: 10,
+                       system: 3831,
+                       idle: 79018,
+                       iowait: 249,
+                       irq: 1,
+                       softirq: 4,
+                       steal: 207,
+                       guest: 5,
+                       guest_nice: 6,
+                   },
+                   parse_cpu(text).unwrap());
+
+        let cpus = parse_cpus(text).unwrap();
+        assert_eq!(vec![None, Some(0), Some(1)], cpus.iter().map(|cpu| cpu.id).collect::<Vec<_>>());
+        assert_eq!(16536, cpus[2].user);
+        assert_eq!(104, cpus[2].steal);
+    }
+
+    #[test]
+    fn test_parse_line() {
+        let cpu = Cpu::parse_line(b"cpu3 1 2 3 4").unwrap();
+        assert_eq!(Some(3), cpu.id);
+        assert_eq!(4, cpu.idle);
+        assert_eq!(0, cpu.guest_nice);
+        Cpu::parse_line(b"cpu3 1 2 3").unwrap_err();
+        Cpu::parse_line(b"intr 1 2 3 4").unwrap_err();
+        Cpu::parse_line(b"cpux 1 2 3 4").unwrap_err();
+    }
+
+    #[test]
+    fn test_stat_counters() {
+        let counters = stat_counters().unwrap();
+        assert!(counters.ctxt > 0);
+        assert!(counters.processes > 0);
+        assert!(counters.procs_running > 0);
+
+        let text = b"cpu  33072 10 3831 79018 249 1 4 207 5 6\n\
+                     intr 147652 0 12 0\n\
+                     ctxt 2695902\n\
+                     btime 1700000000\n\
+                     processes 154864\n\
+                     procs_running 3\n\
+                     procs_blocked 1\n\
+                     softirq 96911 0 1 2\n";
+        assert_eq!(StatCounters {
+                       intr: 147652,
+                       ctxt: 2695902,
+                       processes: 154864,
+                       procs_running: 3,
+                       procs_blocked: 1,
+                   },
+                   StatCounters::parse(text).unwrap());
+        StatCounters::parse(b"ctxt\n").unwrap_err();
+    }
+}
@@ -0,0 +1,298 @@
+//! Sets of CPUs and NUMA memory nodes, as found in the range lists and masks of
+//! `/proc/[pid]/status` and cpuset(7).
+
+use std::fmt;
+use std::io::Result;
+use std::iter::FromIterator;
+
+#[cfg(feature = "export")]
+use serde::{Serialize, Serializer};
+
+use parsers::parse_range_list;
+
+/// Defines a set of ids, such as CPU or NUMA node numbers, stored as a bitmask.
+macro_rules! id_set {
+    ($(#[$attr:meta])* $set:ident, $iter:ident, $id:literal, $list:literal, $mask:literal) => {
+        $(#[$attr])*
+        ///
+        /// The `Display` implementation formats the set as a range list, such as `0-3,5,7-9`.
+        #[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+        pub struct $set {
+            /// A bitmask with bit `i % 64` of word `i / 64` set for each id `i`, without trailing
+            /// zero words.
+            words: Vec<u64>,
+        }
+
+        impl $set {
+            /// Returns an empty set.
+            pub fn new() -> $set {
+                $set::default()
+            }
+
+            #[doc = concat!("Parses a range list, such as `0-3,5,7-9`, as found in the `", $list,
+                            "` field of `/proc/[pid]/status`.")]
+            pub fn parse_list(input: &str) -> Result<$set> {
+                let mut set = $set::new();
+                parse_range_list(input.trim(), |start, end| {
+                    for id in start..=end {
+                        set.insert(id);
+                    }
+                })?;
+                Ok(set)
+            }
+
+            #[doc = concat!("Returns the set with the ", $id, "s of a mask in the format of \
+                             `Status::", $mask, "`.")]
+            pub fn from_mask(mask: &[u8]) -> $set {
+                let mut set = $set::new();
+                for (i, &byte) in mask.iter().enumerate() {
+                    for bit in 0..8 {
+                        // The most significant bit of each byte is the lowest id.
+                        if byte & (0x80 >> bit) != 0 {
+                            set.insert(i * 8 + bit);
+                        }
+                    }
+                }
+                set
+            }
+
+            #[doc = concat!("Returns whether the set contains the ", $id, ".")]
+            pub fn contains(&self, id: usize) -> bool {
+                self.words.get(id / 64).is_some_and(|word| word & (1 << (id % 64)) != 0)
+            }
+
+            #[doc = concat!("Adds the ", $id, " to the set, and returns whether it was not already \
+                             present.")]
+            pub fn insert(&mut self, id: usize) -> bool {
+                if self.words.len() <= id / 64 {
+                    self.words.resize(id / 64 + 1, 0);
+                }
+                let word = &mut self.words[id / 64];
+                let absent = *word & (1 << (id % 64)) == 0;
+                *word |= 1 << (id % 64);
+                absent
+            }
+
+            #[doc = concat!("Removes the ", $id, " from the set, and returns whether it was \
+                             present.")]
+            pub fn remove(&mut self, id: usize) -> bool {
+                let present = self.contains(id);
+                if present {
+                    self.words[id / 64] &= !(1 << (id % 64));
+                    self.trim();
+                }
+                present
+            }
+
+            /// Removes the trailing zero words, so that equal sets have equal representations.
+            fn trim(&mut self) {
+                while self.words.last() == Some(&0) {
+                    self.words.pop();
+                }
+            }
+
+            #[doc = concat!("Returns the number of ", $id, "s in the set.")]
+            pub fn len(&self) -> usize {
+                self.words.iter().map(|word| word.count_ones() as usize).sum()
+            }
+
+            /// Returns whether the set is empty.
+            pub fn is_empty(&self) -> bool {
+                self.words.is_empty()
+            }
+
+            #[doc = concat!("Returns the lowest ", $id, " in the set.")]
+            pub fn first(&self) -> Option<usize> {
+                self.iter().next()
+            }
+
+            #[doc = concat!("Returns an iterator over the ", $id, "s in the set, in ascending \
+                             order.")]
+            pub fn iter(&self) -> $iter<'_> {
+                let word = self.words.first().cloned().unwrap_or(0);
+                $iter { words: &self.words, index: 0, word }
+            }
+
+            #[doc = concat!("Returns the ", $id, "s which are in both sets.")]
+            pub fn intersection(&self, other: &$set) -> $set {
+                let mut set = $set {
+                    words: self.words.iter().zip(&other.words).map(|(a, b)| a & b).collect(),
+                };
+                set.trim();
+                set
+            }
+
+            #[doc = concat!("Returns the ", $id, "s which are in either set.")]
+            pub fn union(&self, other: &$set) -> $set {
+                let (long, short) = if self.words.len() >= other.words.len() {
+                    (self, other)
+                } else {
+                    (other, self)
+                };
+                let mut words = long.words.clone();
+                for (word, other) in words.iter_mut().zip(&short.words) {
+                    *word |= other;
+                }
+                $set { words }
+            }
+
+            #[doc = concat!("Returns the ", $id, "s which are in this set but not in the other.")]
+            pub fn difference(&self, other: &$set) -> $set {
+                let mut words = self.words.clone();
+                for (word, other) in words.iter_mut().zip(&other.words) {
+                    *word &= !other;
+                }
+                let mut set = $set { words };
+                set.trim();
+                set
+            }
+
+            #[doc = concat!("Returns whether every ", $id, " in this set is also in the other.")]
+            pub fn is_subset(&self, other: &$set) -> bool {
+                self.difference(other).is_empty()
+            }
+        }
+
+        impl fmt::Display for $set {
+            fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                let mut ids = self.iter().peekable();
+                let mut first = true;
+                while let Some(start) = ids.next() {
+                    let mut end = start;
+                    while ids.peek() == Some(&(end + 1)) {
+                        end = ids.next().unwrap();
+                    }
+                    if !first {
+                        f.write_str(",")?;
+                    }
+                    first = false;
+                    if start == end {
+                        write!(f, "{}", start)?;
+                    } else {
+                        write!(f, "{}-{}", start, end)?;
+                    }
+                }
+                Ok(())
+            }
+        }
+
+        impl FromIterator<usize> for $set {
+            fn from_iter<I>(iter: I) -> $set where I: IntoIterator<Item = usize> {
+                let mut set = $set::new();
+                for id in iter {
+                    set.insert(id);
+                }
+                set
+            }
+        }
+
+        impl<'a> IntoIterator for &'a $set {
+            type Item = usize;
+            type IntoIter = $iter<'a>;
+
+            fn into_iter(self) -> $iter<'a> {
+                self.iter()
+            }
+        }
+
+        #[doc = concat!("Serializes the set as a sequence of ", $id, " numbers.")]
+        #[cfg(feature = "export")]
+        impl Serialize for $set {
+            fn serialize<S>(&self, serializer: S) -> ::std::result::Result<S::Ok, S::Error>
+            where S: Serializer {
+                serializer.collect_seq(self.iter())
+            }
+        }
+
+        #[doc = concat!("An iterator over the ", $id, "s in a `", stringify!($set),
+                        "`, in ascending order.")]
+        #[derive(Clone, Debug)]
+        pub struct $iter<'a> {
+            words: &'a [u64],
+            index: usize,
+            /// The bits of the current word which have not been returned.
+            word: u64,
+        }
+
+        impl<'a> Iterator for $iter<'a> {
+            type Item = usize;
+
+            fn next(&mut self) -> Option<usize> {
+                while self.word == 0 {
+                    self.index += 1;
+                    self.word = *self.words.get(self.index)?;
+                }
+                let bit = self.word.trailing_zeros() as usize;
+                self.word &= self.word - 1;
+                Some(self.index * 64 + bit)
+            }
+        }
+    };
+}
+
+id_set! {
+    /// A set of CPU numbers.
+    CpuSet, CpuSetIter, "CPU", "Cpus_allowed_list", "cpus_allowed"
+}
+
+id_set! {
+    /// A set of NUMA memory node numbers.
+    NodeSet, NodeSetIter, "node", "Mems_allowed_list", "mems_allowed"
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{CpuSet, NodeSet};
+
+    #[test]
+    fn test_parse_list() {
+        let set = CpuSet::parse_list("0-3,5,7-9,64,130-131\n").unwrap();
+        assert_eq!(vec![0, 1, 2, 3, 5, 7, 8, 9, 64, 130, 131], set.iter().collect::<Vec<_>>());
+        assert_eq!(11, set.len());
+        assert!(set.contains(64));
+        assert!(!set.contains(4));
+        assert!(!set.contains(1000));
+        assert_eq!("0-3,5,7-9,64,130-131", set.to_string());
+        assert_eq!(Some(0), set.first());
+
+        let empty = CpuSet::parse_list("").unwrap();
+        assert!(empty.is_empty());
+        assert_eq!("", empty.to_string());
+        assert_eq!(None, empty.first());
+
+        CpuSet::parse_list("0-3,a").unwrap_err();
+    }
+
+    #[test]
+    fn test_from_mask() {
+        assert_eq!(CpuSet::parse_list("0-15").unwrap(), CpuSet::from_mask(&[0xff, 0xff, 0, 0]));
+        assert_eq!(CpuSet::parse_list("7").unwrap(), CpuSet::from_mask(&[0x01]));
+    }
+
+    #[test]
+    fn test_set_operations() {
+        let a = CpuSet::parse_list("0-7,100").unwrap();
+        let b = CpuSet::parse_list("4-11").unwrap();
+        assert_eq!("4-7", a.intersection(&b).to_string());
+        assert_eq!("0-11,100", a.union(&b).to_string());
+        assert_eq!("0-3,100", a.difference(&b).to_string());
+        assert_eq!(CpuSet::parse_list("8-11").unwrap(), b.difference(&a));
+        assert!(a.intersection(&b).is_subset(&a));
+        assert!(!a.is_subset(&b));
+
+        let mut set = [3, 1, 200].iter().cloned().collect::<CpuSet>();
+        assert!(!set.insert(3));
+        assert!(set.remove(200));
+        assert!(!set.remove(200));
+        assert_eq!(CpuSet::parse_list("1,3").unwrap(), set);
+    }
+
+    #[test]
+    fn test_node_set() {
+        let nodes = NodeSet::parse_list("0,2-3").unwrap();
+        assert_eq!(3, nodes.len());
+        assert_eq!(NodeSet::from_mask(&[0xb0]), nodes);
+        assert_eq!(1, nodes.intersection(&NodeSet::parse_list("1-2").unwrap()).len());
+        assert_eq!("0,2-3", nodes.to_string());
+    }
+}
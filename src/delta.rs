@@ -0,0 +1,442 @@
+//! Rates and utilization percentages computed from pairs of cumulative counter readings.
+//!
+//! Counters are differenced with wrapping subtraction, so a counter which wraps around at the
+//! width of its type between the readings still yields the correct delta. Counters which are
+//! reset (for example when a network interface is recreated) yield meaningless results.
+//!
+//! CPU times in clock ticks are the exception: they never wrap, but may decrease, so they are
+//! differenced with saturating subtraction.
+
+use std::time::Duration;
+
+use cpu::{Cpu, StatCounters};
+use diskstats::DiskStat;
+use net::dev::DeviceStatus;
+use pid::{Io, Stat};
+use sysconf::ticks_per_second;
+use vmstat::Vmstat;
+
+/// Returns the difference between two readings of a cumulative counter.
+fn delta(earlier: u64, later: u64) -> f64 {
+    later.wrapping_sub(earlier) as f64
+}
+
+/// Returns the difference between two readings of a CPU time in clock ticks, or 0 if it decreased.
+///
+/// `iowait` in `/proc/stat` may decrease, and the times of a CPU are reset when it is brought
+/// back online.
+fn tick_delta(earlier: u64, later: u64) -> f64 {
+    later.saturating_sub(earlier) as f64
+}
+
+/// Returns the difference between two readings of a counter which the kernel keeps as an
+/// `unsigned long`.
+///
+/// Such counters wrap around at 32 bits on 32-bit kernels, which is detected when the earlier
+/// reading fits in 32 bits and the later reading is smaller.
+fn delta_ulong(earlier: u64, later: u64) -> f64 {
+    if later < earlier && earlier <= u64::from(u32::MAX) {
+        (later + (1 << 32) - earlier) as f64
+    } else {
+        delta(earlier, later)
+    }
+}
+
+/// Returns the per-second rate of a delta over the provided interval.
+fn rate(delta: f64, interval: Duration) -> f64 {
+    let secs = interval.as_secs_f64();
+    if secs > 0.0 { delta / secs } else { 0.0 }
+}
+
+/// Returns the percentage of a CPU represented by two readings of a time in clock ticks taken
+/// `interval` apart.
+pub(crate) fn tick_percent(earlier: u64, later: u64, interval: Duration) -> f64 {
+    percent(rate(tick_delta(earlier, later), interval), ticks_per_second() as f64)
+}
+
+/// Returns `part` as a percentage of `total`.
+fn percent(part: f64, total: f64) -> f64 {
+    if total > 0.0 { part * 100.0 / total } else { 0.0 }
+}
+
+/// The percentage of CPU time spent in each mode between two readings.
+///
+/// The percentages of all modes except `guest` and `guest_nice`, which are included in `user`
+/// and `nice`, sum to 100.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "export", derive(Serialize))]
+pub struct CpuUsage {
+    /// Percentage of time spent in user mode, including guest time.
+    pub user: f64,
+    /// Percentage of time spent in user mode with low priority, including guest nice time.
+    pub nice: f64,
+    /// Percentage of time spent in system mode.
+    pub system: f64,
+    /// Percentage of time spent idle.
+    pub idle: f64,
+    /// Percentage of time spent waiting for I/O to complete.
+    pub iowait: f64,
+    /// Percentage of time spent servicing interrupts.
+    pub irq: f64,
+    /// Percentage of time spent servicing softirqs.
+    pub softirq: f64,
+    /// Percentage of time stolen by other operating systems in a virtualized environment.
+    pub steal: f64,
+    /// Percentage of time spent running a virtual CPU for guest operating systems.
+    pub guest: f64,
+    /// Percentage of time spent running a niced guest.
+    pub guest_nice: f64,
+}
+
+impl CpuUsage {
+    /// Returns the percentage of time the CPU was busy, that is neither idle nor waiting for I/O.
+    pub fn busy(&self) -> f64 {
+        100.0 - self.idle - self.iowait
+    }
+}
+
+impl Cpu {
+    /// Returns the percentage of CPU time spent in each mode since the `earlier` reading.
+    pub fn delta(&self, earlier: &Cpu) -> CpuUsage {
+        let total = tick_delta(earlier.total(), self.total());
+        let usage = |earlier: u64, later: u64| percent(tick_delta(earlier, later), total);
+        CpuUsage {
+            user: usage(earlier.user, self.user),
+            nice: usage(earlier.nice, self.nice),
+            system: usage(earlier.system, self.system),
+            idle: usage(earlier.idle, self.idle),
+            iowait: usage(earlier.iowait, self.iowait),
+            irq: usage(earlier.irq, self.irq),
+            softirq: usage(earlier.softirq, self.softirq),
+            steal: usage(earlier.steal, self.steal),
+            guest: usage(earlier.guest, self.guest),
+            guest_nice: usage(earlier.guest_nice, self.guest_nice),
+        }
+    }
+}
+
+/// The CPU usage of a process between two readings, as a percentage of a single CPU.
+///
+/// A multithreaded process may use more than 100% of a CPU.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "export", derive(Serialize))]
+pub struct ProcessCpuUsage {
+    /// Percentage of a CPU used in user mode.
+    pub user: f64,
+    /// Percentage of a CPU used in kernel mode.
+    pub system: f64,
+}
+
+impl ProcessCpuUsage {
+    /// Returns the CPU usage of a process between two readings of its stat file taken `interval`
+    /// apart.
+    pub fn between(earlier: &Stat, later: &Stat, interval: Duration) -> ProcessCpuUsage {
+        ProcessCpuUsage {
+            user: tick_percent(earlier.utime, later.utime, interval),
+            system: tick_percent(earlier.stime, later.stime, interval),
+        }
+    }
+
+    /// Returns the percentage of a CPU used in user and kernel mode.
+    pub fn total(&self) -> f64 {
+        self.user + self.system
+    }
+}
+
+/// Per-second I/O rates of a process between two readings.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "export", derive(Serialize))]
+pub struct IoRates {
+    /// Bytes read per second by `read(2)` and similar system calls.
+    pub rchar: f64,
+    /// Bytes written per second by `write(2)` and similar system calls.
+    pub wchar: f64,
+    /// Read system calls per second.
+    pub syscr: f64,
+    /// Write system calls per second.
+    pub syscw: f64,
+    /// Bytes fetched from the storage layer per second.
+    pub read_bytes: f64,
+    /// Bytes sent to the storage layer per second.
+    pub write_bytes: f64,
+    /// Bytes of written page cache truncated before being sent to the storage layer per second.
+    pub cancelled_write_bytes: f64,
+}
+
+impl IoRates {
+    /// Returns the I/O rates of a process between two readings of its io file taken `interval`
+    /// apart.
+    pub fn between(earlier: &Io, later: &Io, interval: Duration) -> IoRates {
+        let per_sec = |earlier: u64, later: u64| rate(delta(earlier, later), interval);
+        IoRates {
+            rchar: per_sec(earlier.rchar, later.rchar),
+            wchar: per_sec(earlier.wchar, later.wchar),
+            syscr: per_sec(earlier.syscr, later.syscr),
+            syscw: per_sec(earlier.syscw, later.syscw),
+            read_bytes: per_sec(earlier.read_bytes, later.read_bytes),
+            write_bytes: per_sec(earlier.write_bytes, later.write_bytes),
+            cancelled_write_bytes: per_sec(earlier.cancelled_write_bytes,
+                                           later.cancelled_write_bytes),
+        }
+    }
+}
+
+/// Per-second paging, swapping, and page fault rates between two readings of `/proc/vmstat`.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "export", derive(Serialize))]
+pub struct VmstatRates {
+    /// kB paged in from block devices per second.
+    pub pgpgin: f64,
+    /// kB paged out to block devices per second.
+    pub pgpgout: f64,
+    /// Pages swapped in per second.
+    pub pswpin: f64,
+    /// Pages swapped out per second.
+    pub pswpout: f64,
+    /// Page faults per second, both minor and major.
+    pub pgfault: f64,
+    /// Major page faults per second.
+    pub pgmajfault: f64,
+}
+
+impl VmstatRates {
+    /// Returns the paging rates between two readings of the virtual memory statistics taken
+    /// `interval` apart.
+    pub fn between(earlier: &Vmstat, later: &Vmstat, interval: Duration) -> VmstatRates {
+        let per_sec = |earlier: u64, later: u64| rate(delta(earlier, later), interval);
+        VmstatRates {
+            pgpgin: per_sec(earlier.pgpgin, later.pgpgin),
+            pgpgout: per_sec(earlier.pgpgout, later.pgpgout),
+            pswpin: per_sec(earlier.pswpin, later.pswpin),
+            pswpout: per_sec(earlier.pswpout, later.pswpout),
+            pgfault: per_sec(earlier.pgfault, later.pgfault),
+            pgmajfault: per_sec(earlier.pgmajfault, later.pgmajfault),
+        }
+    }
+}
+
+/// Per-second kernel activity rates between two readings of `/proc/stat`.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "export", derive(Serialize))]
+pub struct StatRates {
+    /// Interrupts per second.
+    pub intr: f64,
+    /// Context switches per second.
+    pub ctxt: f64,
+    /// Processes and threads created per second.
+    pub processes: f64,
+}
+
+impl StatRates {
+    /// Returns the activity rates between two readings of the kernel activity counters taken
+    /// `interval` apart.
+    pub fn between(earlier: &StatCounters, later: &StatCounters, interval: Duration) -> StatRates {
+        let per_sec = |earlier: u64, later: u64| rate(delta(earlier, later), interval);
+        StatRates {
+            intr: per_sec(earlier.intr, later.intr),
+            ctxt: per_sec(earlier.ctxt, later.ctxt),
+            processes: per_sec(earlier.processes, later.processes),
+        }
+    }
+}
+
+/// Per-second traffic rates of a network device between two readings.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "export", derive(Serialize))]
+pub struct NetRates {
+    /// Bytes received per second.
+    pub receive_bytes: f64,
+    /// Packets received per second.
+    pub receive_packets: f64,
+    /// Receive errors per second.
+    pub receive_errs: f64,
+    /// Incoming packets dropped per second.
+    pub receive_drop: f64,
+    /// Bytes transmitted per second.
+    pub transmit_bytes: f64,
+    /// Packets transmitted per second.
+    pub transmit_packets: f64,
+    /// Transmit errors per second.
+    pub transmit_errs: f64,
+    /// Outgoing packets dropped per second.
+    pub transmit_drop: f64,
+}
+
+impl NetRates {
+    /// Returns the traffic rates of a network device between two readings taken `interval`
+    /// apart.
+    pub fn between(earlier: &DeviceStatus, later: &DeviceStatus, interval: Duration) -> NetRates {
+        let per_sec = |earlier: u64, later: u64| rate(delta(earlier, later), interval);
+        NetRates {
+            receive_bytes: per_sec(earlier.receive_bytes, later.receive_bytes),
+            receive_packets: per_sec(earlier.receive_packets, later.receive_packets),
+            receive_errs: per_sec(earlier.receive_errs, later.receive_errs),
+            receive_drop: per_sec(earlier.receive_drop, later.receive_drop),
+            transmit_bytes: per_sec(earlier.transmit_bytes, later.transmit_bytes),
+            transmit_packets: per_sec(earlier.transmit_packets, later.transmit_packets),
+            transmit_errs: per_sec(earlier.transmit_errs, later.transmit_errs),
+            transmit_drop: per_sec(earlier.transmit_drop, later.transmit_drop),
+        }
+    }
+}
+
+/// The size of a sector in the block device statistics.
+const SECTOR_SIZE: f64 = 512.0;
+
+/// Per-second I/O rates and utilization of a block device between two readings.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "export", derive(Serialize))]
+pub struct DiskRates {
+    /// Reads completed per second.
+    pub reads: f64,
+    /// Writes completed per second.
+    pub writes: f64,
+    /// Bytes read per second.
+    pub read_bytes: f64,
+    /// Bytes written per second.
+    pub write_bytes: f64,
+    /// The average time in milliseconds from issue to completion of the reads.
+    pub read_latency: f64,
+    /// The average time in milliseconds from issue to completion of the writes.
+    pub write_latency: f64,
+    /// The average time in milliseconds an I/O spent queued and in progress, from the weighted
+    /// time spent doing I/Os.
+    pub latency: f64,
+    /// The average number of I/Os queued or in progress.
+    pub queue_size: f64,
+    /// The percentage of time the device had I/Os in progress.
+    pub utilization: f64,
+}
+
+impl DiskRates {
+    /// Returns the I/O rates of a block device between two readings taken `interval` apart.
+    ///
+    /// Counters which wrapped around at 32 bits, as they do on 32-bit kernels, are accounted for.
+    pub fn between(earlier: &DiskStat, later: &DiskStat, interval: Duration) -> DiskRates {
+        let reads = delta_ulong(earlier.reads_completed, later.reads_completed);
+        let writes = delta_ulong(earlier.writes_completed, later.writes_completed);
+        let latency = |time: f64, ios: f64| if ios > 0.0 { time / ios } else { 0.0 };
+        let millis = interval.as_secs_f64() * 1000.0;
+        let weighted_time_io = delta_ulong(earlier.weighted_time_io, later.weighted_time_io);
+        DiskRates {
+            reads: rate(reads, interval),
+            writes: rate(writes, interval),
+            read_bytes: rate(delta_ulong(earlier.sectors_read, later.sectors_read) * SECTOR_SIZE,
+                             interval),
+            write_bytes: rate(delta_ulong(earlier.sectors_written, later.sectors_written)
+                              * SECTOR_SIZE,
+                              interval),
+            read_latency: latency(delta_ulong(earlier.time_reading, later.time_reading), reads),
+            write_latency: latency(delta_ulong(earlier.time_writing, later.time_writing), writes),
+            latency: latency(weighted_time_io, reads + writes),
+            queue_size: if millis > 0.0 { weighted_time_io / millis } else { 0.0 },
+            utilization: percent(delta_ulong(earlier.time_io, later.time_io), millis).min(100.0),
+        }
+    }
+
+    /// Returns the reads and writes completed per second.
+    pub fn iops(&self) -> f64 {
+        self.reads + self.writes
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use cpu::{Cpu, StatCounters};
+    use diskstats::DiskStat;
+    use pid::Io;
+    use vmstat::Vmstat;
+    use super::{DiskRates, IoRates, StatRates, VmstatRates, delta, delta_ulong, tick_percent};
+
+    #[test]
+    fn test_cpu_delta() {
+        let earlier = Cpu { user: 100, system: 50, idle: 850, ..Default::default() };
+        let later = Cpu { user: 150, system: 100, idle: 950, guest: 10, ..Default::default() };
+        let usage = later.delta(&earlier);
+        assert_eq!(25.0, usage.user);
+        assert_eq!(25.0, usage.system);
+        assert_eq!(50.0, usage.idle);
+        assert_eq!(5.0, usage.guest);
+        assert_eq!(50.0, usage.busy());
+    }
+
+    /// Test that a decrease of a CPU time yields no usage instead of a wrapped delta.
+    #[test]
+    fn test_cpu_delta_decrease() {
+        let earlier = Cpu { user: 100, idle: 850, iowait: 50, ..Default::default() };
+        let later = Cpu { user: 200, idle: 950, iowait: 49, ..Default::default() };
+        let usage = later.delta(&earlier);
+        assert_eq!(0.0, usage.iowait);
+        assert!(usage.idle <= 100.0);
+        assert!(usage.busy() >= 0.0);
+        assert_eq!(0.0, tick_percent(100, 99, Duration::from_secs(1)));
+    }
+
+    #[test]
+    fn test_io_rates() {
+        let earlier = Io { rchar: 1000, write_bytes: u64::MAX - 99, ..Default::default() };
+        let later = Io { rchar: 5000, write_bytes: 100, ..Default::default() };
+        let rates = IoRates::between(&earlier, &later, Duration::from_secs(2));
+        assert_eq!(2000.0, rates.rchar);
+        assert_eq!(100.0, rates.write_bytes);
+        assert_eq!(0.0, IoRates::between(&earlier, &later, Duration::from_secs(0)).rchar);
+    }
+
+    #[test]
+    fn test_disk_rates() {
+        let earlier = DiskStat { reads_completed: 100, sectors_read: 1000, time_reading: 50,
+                                 time_io: 100, ..Default::default() };
+        let later = DiskStat { reads_completed: 300, sectors_read: 5000, time_reading: 450,
+                               time_io: 600, ..Default::default() };
+        let rates = DiskRates::between(&earlier, &later, Duration::from_secs(2));
+        assert_eq!(100.0, rates.reads);
+        assert_eq!(1024000.0, rates.read_bytes);
+        assert_eq!(2.0, rates.read_latency);
+        assert_eq!(0.0, rates.write_latency);
+        assert_eq!(25.0, rates.utilization);
+        assert_eq!(100.0, rates.iops());
+    }
+
+    #[test]
+    fn test_disk_rates_queue() {
+        let earlier = DiskStat { reads_completed: 10, writes_completed: u64::from(u32::MAX) - 9,
+                                 weighted_time_io: 1000, ..Default::default() };
+        let later = DiskStat { reads_completed: 40, writes_completed: 10,
+                               weighted_time_io: 3000, ..Default::default() };
+        let rates = DiskRates::between(&earlier, &later, Duration::from_secs(1));
+        assert_eq!(20.0, rates.writes);
+        assert_eq!(50.0, rates.iops());
+        assert_eq!(40.0, rates.latency);
+        assert_eq!(2.0, rates.queue_size);
+    }
+
+    #[test]
+    fn test_vmstat_rates() {
+        let earlier = Vmstat { pgpgin: 1000, pswpout: 10, pgfault: 500, ..Default::default() };
+        let later = Vmstat { pgpgin: 3000, pswpout: 30, pgfault: 900, ..Default::default() };
+        let rates = VmstatRates::between(&earlier, &later, Duration::from_secs(2));
+        assert_eq!(1000.0, rates.pgpgin);
+        assert_eq!(10.0, rates.pswpout);
+        assert_eq!(200.0, rates.pgfault);
+        assert_eq!(0.0, rates.pgmajfault);
+    }
+
+    #[test]
+    fn test_stat_rates() {
+        let earlier = StatCounters { ctxt: 1000, processes: 10, ..Default::default() };
+        let later = StatCounters { ctxt: 3000, processes: 14, ..Default::default() };
+        let rates = StatRates::between(&earlier, &later, Duration::from_millis(500));
+        assert_eq!(4000.0, rates.ctxt);
+        assert_eq!(8.0, rates.processes);
+        assert_eq!(0.0, rates.intr);
+    }
+
+    #[test]
+    fn test_delta_wraps() {
+        assert_eq!(10.0, delta(u64::MAX - 4, 5));
+        assert_eq!(10.0, delta_ulong(u64::from(u32::MAX) - 4, 5));
+        assert_eq!(10.0, delta_ulong(u64::MAX - 4, 5));
+    }
+}
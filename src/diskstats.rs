@@ -0,0 +1,150 @@
+//! Block device I/O statistics from `/proc/diskstats`.
+
+use std::fs::File;
+use std::io::{Error, ErrorKind, Read, Result};
+use std::path::Path;
+use std::str;
+
+use parsers::{parse_lines, with_path};
+
+/// I/O statistics of a block device.
+///
+/// Times are in milliseconds. See `Linux/Documentation/admin-guide/iostats.rst`.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "export", derive(Serialize))]
+pub struct DiskStat {
+    /// The major number of the device.
+    pub major: u32,
+    /// The minor number of the device.
+    pub minor: u32,
+    /// The name of the device.
+    pub name: String,
+    /// The number of reads completed successfully.
+    pub reads_completed: u64,
+    /// The number of adjacent reads merged.
+    pub reads_merged: u64,
+    /// The number of 512-byte sectors read.
+    pub sectors_read: u64,
+    /// The total time spent by all reads.
+    pub time_reading: u64,
+    /// The number of writes completed successfully.
+    pub writes_completed: u64,
+    /// The number of adjacent writes merged.
+    pub writes_merged: u64,
+    /// The number of 512-byte sectors written.
+    pub sectors_written: u64,
+    /// The total time spent by all writes.
+    pub time_writing: u64,
+    /// The number of I/Os currently in progress.
+    pub ios_in_progress: u64,
+    /// The time spent with I/Os in progress.
+    pub time_io: u64,
+    /// The time spent doing I/Os, weighted by the number of I/Os in progress.
+    pub weighted_time_io: u64,
+    /// The number of discards completed successfully (since Linux 4.18).
+    pub discards_completed: Option<u64>,
+    /// The number of adjacent discards merged (since Linux 4.18).
+    pub discards_merged: Option<u64>,
+    /// The number of 512-byte sectors discarded (since Linux 4.18).
+    pub sectors_discarded: Option<u64>,
+    /// The total time spent by all discards (since Linux 4.18).
+    pub time_discarding: Option<u64>,
+    /// The number of flush requests completed successfully (since Linux 5.5).
+    pub flush_requests_completed: Option<u64>,
+    /// The total time spent by all flush requests (since Linux 5.5).
+    pub time_flushing: Option<u64>,
+}
+
+fn invalid_line(line: &str) -> Error {
+    Error::new(ErrorKind::InvalidInput, format!("invalid diskstats line: {:?}", line))
+}
+
+impl DiskStat {
+    /// Parses a line of a `/proc/diskstats` file, without the line terminator.
+    pub fn parse_line(input: &[u8]) -> Result<DiskStat> {
+        let line = str::from_utf8(input)
+                       .map_err(|_| Error::new(ErrorKind::InvalidInput, "diskstats is not UTF-8"))?;
+        let mut fields = line.split_whitespace();
+        let major = fields.next().and_then(|f| f.parse().ok()).ok_or_else(|| invalid_line(line))?;
+        let minor = fields.next().and_then(|f| f.parse().ok()).ok_or_else(|| invalid_line(line))?;
+        let name = fields.next().ok_or_else(|| invalid_line(line))?.to_owned();
+        let values = fields.map(|f| f.parse()).collect::<::std::result::Result<Vec<u64>, _>>()
+                           .map_err(|_| invalid_line(line))?;
+        if values.len() < 11 {
+            return Err(invalid_line(line));
+        }
+        let opt = |i: usize| values.get(i).cloned();
+
+        Ok(DiskStat {
+            major,
+            minor,
+            name,
+            reads_completed: values[0],
+            reads_merged: values[1],
+            sectors_read: values[2],
+            time_reading: values[3],
+            writes_completed: values[4],
+            writes_merged: values[5],
+            sectors_written: values[6],
+            time_writing: values[7],
+            ios_in_progress: values[8],
+            time_io: values[9],
+            weighted_time_io: values[10],
+            discards_completed: opt(11),
+            discards_merged: opt(12),
+            sectors_discarded: opt(13),
+            time_discarding: opt(14),
+            flush_requests_completed: opt(15),
+            time_flushing: opt(16),
+        })
+    }
+}
+
+/// Parses the provided diskstats file contents.
+fn parse_diskstats(input: &[u8]) -> Result<Vec<DiskStat>> {
+    parse_lines(input, 0, DiskStat::parse_line)
+}
+
+/// Returns I/O statistics of all block devices.
+pub fn diskstats() -> Result<Vec<DiskStat>> {
+    let mut buf = Vec::new();
+    File::open("/proc/diskstats")?.read_to_end(&mut buf)?;
+    parse_diskstats(&buf).map_err(|error| with_path(error, Path::new("/proc/diskstats")))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{DiskStat, diskstats, parse_diskstats};
+
+    /// Test that the system diskstats file can be parsed.
+    #[test]
+    fn test_diskstats() {
+        diskstats().unwrap();
+    }
+
+    #[test]
+    fn test_parse_diskstats() {
+        let text = b"   8 0 sda 9616 2966 578662 4024 2398 3329 90298 3284 0 5240 7860 0 0 0 0\n\
+                     \x20  8 1 sda1 150 0 8424 52 1 0 1 0 0 72 52\n\
+                     \x20259 0 nvme0n1 1 2 3 4 5 6 7 8 9 10 11 12 13 14 15 16 17\n";
+        let stats = parse_diskstats(text).unwrap();
+        assert_eq!(3, stats.len());
+
+        assert_eq!(8, stats[0].major);
+        assert_eq!(0, stats[0].minor);
+        assert_eq!("sda", stats[0].name);
+        assert_eq!(9616, stats[0].reads_completed);
+        assert_eq!(90298, stats[0].sectors_written);
+        assert_eq!(7860, stats[0].weighted_time_io);
+        assert_eq!(Some(0), stats[0].time_discarding);
+        assert_eq!(None, stats[0].flush_requests_completed);
+
+        assert_eq!("sda1", stats[1].name);
+        assert_eq!(None, stats[1].discards_completed);
+
+        assert_eq!(259, stats[2].major);
+        assert_eq!(Some(17), stats[2].time_flushing);
+
+        DiskStat::parse_line(b"8 0 sda 1 2 3").unwrap_err();
+    }
+}
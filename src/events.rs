@@ -0,0 +1,445 @@
+//! Process lifecycle events from the kernel process events connector.
+//!
+//! Rather than rescanning `/proc` to discover processes which started or exited, a `ProcEvents`
+//! subscription receives a message from the kernel for every `fork(2)`, `execve(2)`, and exit as it
+//! happens. Events only identify the processes involved; `ProcEvent::process` opens a handle to the
+//! process, through which the per-pid files can be read while the process is alive.
+//!
+//! Subscribing requires `CAP_NET_ADMIN` and a kernel built with `CONFIG_PROC_EVENTS`. This module
+//! is only built with the `events` feature.
+
+use std::io::{Error, ErrorKind, Result};
+use std::mem;
+use std::os::unix::io::{AsRawFd, FromRawFd, OwnedFd, RawFd};
+use std::time::Duration;
+
+use byteorder::{ByteOrder, NativeEndian};
+use libc::{self, c_void, pid_t};
+
+use pid::Process;
+
+/// The netlink protocol of the kernel connector (`NETLINK_CONNECTOR`).
+const NETLINK_CONNECTOR: libc::c_int = 11;
+/// The connector index and value of the process events connector (`CN_IDX_PROC`, `CN_VAL_PROC`).
+const CN_IDX_PROC: u32 = 1;
+const CN_VAL_PROC: u32 = 1;
+/// The multicast operations of the process events connector (`enum proc_cn_mcast_op`).
+const PROC_CN_MCAST_LISTEN: u32 = 1;
+const PROC_CN_MCAST_IGNORE: u32 = 2;
+
+/// The process event types (`enum what` of `struct proc_event`).
+const PROC_EVENT_FORK: u32 = 0x0000_0001;
+const PROC_EVENT_EXEC: u32 = 0x0000_0002;
+const PROC_EVENT_EXIT: u32 = 0x8000_0000;
+
+/// The sizes of `struct nlmsghdr`, `struct cn_msg`, and the header of `struct proc_event`.
+const NLMSG_HDR_LEN: usize = 16;
+const CN_MSG_LEN: usize = 20;
+const PROC_EVENT_HDR_LEN: usize = 16;
+
+/// The kind of a process event, with the pids of the processes involved.
+///
+/// Each process is identified by its thread id (`pid`) and thread group id (`tgid`), which is the
+/// pid of the process as seen from user space. The two are equal for the main thread.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "export", derive(Serialize))]
+pub enum ProcEventKind {
+    /// A process or thread was created by `fork(2)` or `clone(2)`.
+    Fork {
+        /// The thread id of the parent.
+        parent_pid: pid_t,
+        /// The thread group id of the parent.
+        parent_tgid: pid_t,
+        /// The thread id of the child.
+        child_pid: pid_t,
+        /// The thread group id of the child, which equals `parent_tgid` for a new thread.
+        child_tgid: pid_t,
+    },
+    /// A process called `execve(2)`.
+    Exec {
+        /// The thread id of the process.
+        pid: pid_t,
+        /// The thread group id of the process.
+        tgid: pid_t,
+    },
+    /// A process or thread exited.
+    Exit {
+        /// The thread id of the process.
+        pid: pid_t,
+        /// The thread group id of the process.
+        tgid: pid_t,
+        /// The exit status, as returned by `wait(2)`.
+        exit_code: u32,
+        /// The signal sent to the parent on exit, usually `SIGCHLD`.
+        exit_signal: u32,
+    },
+}
+
+/// A process event.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "export", derive(Serialize))]
+pub struct ProcEvent {
+    /// The CPU on which the event happened.
+    pub cpu: u32,
+    /// The time of the event, as the time since boot of the monotonic clock.
+    pub timestamp: Duration,
+    /// The kind of the event.
+    pub kind: ProcEventKind,
+}
+
+impl ProcEvent {
+    /// Returns the thread id of the process the event is about: the child for `Fork` events.
+    pub fn pid(&self) -> pid_t {
+        match self.kind {
+            ProcEventKind::Fork { child_pid, .. } => child_pid,
+            ProcEventKind::Exec { pid, .. } | ProcEventKind::Exit { pid, .. } => pid,
+        }
+    }
+
+    /// Returns the thread group id of the process the event is about: the child for `Fork`
+    /// events.
+    pub fn tgid(&self) -> pid_t {
+        match self.kind {
+            ProcEventKind::Fork { child_tgid, .. } => child_tgid,
+            ProcEventKind::Exec { tgid, .. } | ProcEventKind::Exit { tgid, .. } => tgid,
+        }
+    }
+
+    /// Opens a handle to the thread the event is about, from which its per-pid files can be read.
+    ///
+    /// This fails with `NotFound` if the thread has already exited, which is always the case for
+    /// `Exit` events once the parent has reaped it. The pid may have been reused by another
+    /// process since the event, although this is unlikely shortly after it.
+    pub fn process(&self) -> Result<Process> {
+        Process::new(self.pid())
+    }
+}
+
+/// Parses a single process event from the payload of a `struct cn_msg`.
+///
+/// Returns `None` for events of other kinds, such as changes of credentials or `comm`.
+fn parse_event(data: &[u8]) -> Result<Option<ProcEvent>> {
+    if data.len() < PROC_EVENT_HDR_LEN {
+        return Err(Error::new(ErrorKind::InvalidData, "truncated process event"));
+    }
+    let what = NativeEndian::read_u32(&data[0..]);
+    let cpu = NativeEndian::read_u32(&data[4..]);
+    let timestamp = Duration::from_nanos(NativeEndian::read_u64(&data[8..]));
+    let event = &data[PROC_EVENT_HDR_LEN..];
+    let len = match what {
+        PROC_EVENT_FORK => 16,
+        PROC_EVENT_EXEC => 8,
+        PROC_EVENT_EXIT => 16,
+        _ => return Ok(None),
+    };
+    if event.len() < len {
+        return Err(Error::new(ErrorKind::InvalidData, "truncated process event"));
+    }
+    let field = |i: usize| NativeEndian::read_i32(&event[i * 4..]);
+    let kind = match what {
+        PROC_EVENT_FORK => ProcEventKind::Fork {
+            parent_pid: field(0),
+            parent_tgid: field(1),
+            child_pid: field(2),
+            child_tgid: field(3),
+        },
+        PROC_EVENT_EXEC => ProcEventKind::Exec { pid: field(0), tgid: field(1) },
+        _ => ProcEventKind::Exit {
+            pid: field(0),
+            tgid: field(1),
+            exit_code: field(2) as u32,
+            exit_signal: field(3) as u32,
+        },
+    };
+    Ok(Some(ProcEvent { cpu, timestamp, kind }))
+}
+
+/// Parses the process events from a datagram received from the connector, which may hold several
+/// netlink messages.
+fn parse_datagram(mut buf: &[u8], events: &mut Vec<ProcEvent>) -> Result<()> {
+    while buf.len() >= NLMSG_HDR_LEN {
+        let len = NativeEndian::read_u32(&buf[0..]) as usize;
+        let kind = NativeEndian::read_u16(&buf[4..]) as libc::c_int;
+        if len < NLMSG_HDR_LEN || len > buf.len() {
+            return Err(Error::new(ErrorKind::InvalidData, "invalid netlink message length"));
+        }
+        let payload = &buf[NLMSG_HDR_LEN..len];
+        match kind {
+            libc::NLMSG_NOOP => (),
+            libc::NLMSG_ERROR if payload.len() >= 4 => {
+                let errno = NativeEndian::read_i32(payload);
+                if errno != 0 {
+                    return Err(Error::from_raw_os_error(-errno));
+                }
+            },
+            _ => {
+                if payload.len() < CN_MSG_LEN {
+                    return Err(Error::new(ErrorKind::InvalidData, "truncated connector message"));
+                }
+                let idx = NativeEndian::read_u32(&payload[0..]);
+                let val = NativeEndian::read_u32(&payload[4..]);
+                let data_len = NativeEndian::read_u16(&payload[16..]) as usize;
+                let data = &payload[CN_MSG_LEN..];
+                if idx == CN_IDX_PROC && val == CN_VAL_PROC && data_len <= data.len() {
+                    if let Some(event) = parse_event(&data[..data_len])? {
+                        events.push(event);
+                    }
+                }
+            },
+        }
+        // Messages are aligned to 4 bytes.
+        let aligned = (len + 3) & !3;
+        buf = &buf[aligned.min(buf.len())..];
+    }
+    Ok(())
+}
+
+/// A subscription to the process events connector.
+///
+/// Iterating blocks until the next event arrives. Events of kinds other than fork, exec, and exit
+/// are skipped. If events arrive faster than they are read, the kernel drops them and the iterator
+/// yields an error with the raw OS error `ENOBUFS`; iteration can continue after it.
+///
+/// # Example
+///
+/// ```no_run
+/// use procinfo::events::{ProcEventKind, ProcEvents};
+///
+/// for event in ProcEvents::subscribe().unwrap() {
+///     let event = event.unwrap();
+///     if let ProcEventKind::Exec { .. } = event.kind {
+///         if let Ok(process) = event.process() {
+///             println!("exec {}: {:?}", event.pid(), process.stat().map(|stat| stat.command));
+///         }
+///     }
+/// }
+/// ```
+#[derive(Debug)]
+pub struct ProcEvents {
+    fd: OwnedFd,
+    buf: Vec<u8>,
+    pending: Vec<ProcEvent>,
+}
+
+impl ProcEvents {
+    /// Subscribes to process events.
+    pub fn subscribe() -> Result<ProcEvents> {
+        let fd = unsafe {
+            libc::socket(libc::AF_NETLINK, libc::SOCK_DGRAM | libc::SOCK_CLOEXEC, NETLINK_CONNECTOR)
+        };
+        if fd < 0 {
+            return Err(Error::last_os_error());
+        }
+        let fd = unsafe { OwnedFd::from_raw_fd(fd) };
+
+        let mut addr: libc::sockaddr_nl = unsafe { mem::zeroed() };
+        addr.nl_family = libc::AF_NETLINK as libc::sa_family_t;
+        addr.nl_groups = CN_IDX_PROC;
+        let ret = unsafe {
+            libc::bind(fd.as_raw_fd(),
+                       &addr as *const libc::sockaddr_nl as *const libc::sockaddr,
+                       mem::size_of::<libc::sockaddr_nl>() as libc::socklen_t)
+        };
+        if ret < 0 {
+            return Err(Error::last_os_error());
+        }
+
+        let events = ProcEvents { fd, buf: vec![0; 8192], pending: Vec::new() };
+        events.control(PROC_CN_MCAST_LISTEN)?;
+        Ok(events)
+    }
+
+    /// Sends a multicast operation to the process events connector.
+    fn control(&self, op: u32) -> Result<()> {
+        let mut msg = [0u8; NLMSG_HDR_LEN + CN_MSG_LEN + 4];
+        let len = msg.len();
+        NativeEndian::write_u32(&mut msg[0..], len as u32);
+        NativeEndian::write_u16(&mut msg[4..], libc::NLMSG_DONE as u16);
+        NativeEndian::write_u32(&mut msg[12..], unsafe { libc::getpid() } as u32);
+        let cn_msg = &mut msg[NLMSG_HDR_LEN..];
+        NativeEndian::write_u32(&mut cn_msg[0..], CN_IDX_PROC);
+        NativeEndian::write_u32(&mut cn_msg[4..], CN_VAL_PROC);
+        NativeEndian::write_u16(&mut cn_msg[16..], 4);
+        NativeEndian::write_u32(&mut cn_msg[CN_MSG_LEN..], op);
+        let ret = unsafe {
+            libc::send(self.fd.as_raw_fd(), msg.as_ptr() as *const c_void, msg.len(), 0)
+        };
+        if ret < 0 {
+            return Err(Error::last_os_error());
+        }
+        Ok(())
+    }
+
+    /// Sets the timeout of the blocking reads of the iterator, or removes it with `None`.
+    ///
+    /// When the timeout elapses without an event, the iterator yields a `WouldBlock` error.
+    pub fn set_read_timeout(&self, timeout: Option<Duration>) -> Result<()> {
+        let timeout = timeout.unwrap_or_default();
+        let tv = libc::timeval {
+            tv_sec: timeout.as_secs() as libc::time_t,
+            tv_usec: timeout.subsec_micros() as libc::suseconds_t,
+        };
+        let ret = unsafe {
+            libc::setsockopt(self.fd.as_raw_fd(),
+                             libc::SOL_SOCKET,
+                             libc::SO_RCVTIMEO,
+                             &tv as *const libc::timeval as *const c_void,
+                             mem::size_of::<libc::timeval>() as libc::socklen_t)
+        };
+        if ret < 0 {
+            return Err(Error::last_os_error());
+        }
+        Ok(())
+    }
+
+    /// Receives a datagram from the connector and queues its events.
+    fn receive(&mut self) -> Result<()> {
+        loop {
+            let len = unsafe {
+                libc::recv(self.fd.as_raw_fd(),
+                           self.buf.as_mut_ptr() as *mut c_void,
+                           self.buf.len(),
+                           0)
+            };
+            if len < 0 {
+                let error = Error::last_os_error();
+                if error.kind() == ErrorKind::Interrupted {
+                    continue;
+                }
+                return Err(error);
+            }
+            parse_datagram(&self.buf[..len as usize], &mut self.pending)?;
+            // The events are yielded from the end of the queue.
+            self.pending.reverse();
+            return Ok(());
+        }
+    }
+}
+
+impl Iterator for ProcEvents {
+    type Item = Result<ProcEvent>;
+
+    fn next(&mut self) -> Option<Result<ProcEvent>> {
+        loop {
+            if let Some(event) = self.pending.pop() {
+                return Some(Ok(event));
+            }
+            if let Err(error) = self.receive() {
+                return Some(Err(error));
+            }
+        }
+    }
+}
+
+impl AsRawFd for ProcEvents {
+    fn as_raw_fd(&self) -> RawFd {
+        self.fd.as_raw_fd()
+    }
+}
+
+impl Drop for ProcEvents {
+    fn drop(&mut self) {
+        // The kernel counts listeners, and only sends events while there is at least one.
+        let _ = self.control(PROC_CN_MCAST_IGNORE);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::ErrorKind;
+    use std::process::Command;
+    use std::time::{Duration, Instant};
+
+    use byteorder::{ByteOrder, NativeEndian};
+    use libc;
+
+    use super::{ProcEvent, ProcEventKind, ProcEvents, parse_datagram};
+
+    /// Builds a datagram holding a single process event.
+    fn datagram(what: u32, fields: &[u32]) -> Vec<u8> {
+        let data_len = 16 + fields.len() * 4;
+        let len = 16 + 20 + data_len;
+        let mut buf = vec![0u8; len];
+        NativeEndian::write_u32(&mut buf[0..], len as u32);
+        NativeEndian::write_u16(&mut buf[4..], 3);
+        NativeEndian::write_u32(&mut buf[16..], 1);
+        NativeEndian::write_u32(&mut buf[20..], 1);
+        NativeEndian::write_u16(&mut buf[32..], data_len as u16);
+        NativeEndian::write_u32(&mut buf[36..], what);
+        NativeEndian::write_u32(&mut buf[40..], 3);
+        NativeEndian::write_u64(&mut buf[44..], 1_500_000_000);
+        for (i, &field) in fields.iter().enumerate() {
+            NativeEndian::write_u32(&mut buf[52 + i * 4..], field);
+        }
+        buf
+    }
+
+    #[test]
+    fn test_parse_datagram() {
+        let mut buf = datagram(0x1, &[100, 100, 200, 200]);
+        buf.extend(datagram(0x2, &[200, 200]));
+        buf.extend(datagram(0x200, &[200, 200, 0, 0, 0, 0]));
+        buf.extend(datagram(0x8000_0000, &[200, 200, 256, 17, 100, 100]));
+        let mut events = Vec::new();
+        parse_datagram(&buf, &mut events).unwrap();
+        assert_eq!(3, events.len());
+        assert_eq!(ProcEvent {
+                       cpu: 3,
+                       timestamp: Duration::from_millis(1500),
+                       kind: ProcEventKind::Fork {
+                           parent_pid: 100,
+                           parent_tgid: 100,
+                           child_pid: 200,
+                           child_tgid: 200,
+                       },
+                   },
+                   events[0]);
+        assert_eq!(ProcEventKind::Exec { pid: 200, tgid: 200 }, events[1].kind);
+        assert_eq!(ProcEventKind::Exit { pid: 200, tgid: 200, exit_code: 256, exit_signal: 17 },
+                   events[2].kind);
+        assert_eq!(200, events[0].pid());
+
+        let buf = datagram(0x1, &[100, 100]);
+        parse_datagram(&buf, &mut events).unwrap_err();
+    }
+
+    /// Test that the exec and exit of a child are observed, if subscribing is permitted.
+    #[test]
+    fn test_proc_events() {
+        let mut events = match ProcEvents::subscribe() {
+            Ok(events) => events,
+            // Subscribing requires CAP_NET_ADMIN.
+            Err(ref error) if error.raw_os_error() == Some(libc::EPERM)
+                           || error.raw_os_error() == Some(libc::EACCES) => return,
+            Err(error) => panic!("failed to subscribe to process events: {}", error),
+        };
+        let mut child = Command::new("true").spawn().unwrap();
+        let pid = child.id() as i32;
+        child.wait().unwrap();
+
+        let deadline = Instant::now() + Duration::from_secs(5);
+        let (mut exec, mut exit) = (false, false);
+        while !(exec && exit) {
+            let remaining = match deadline.checked_duration_since(Instant::now()) {
+                Some(remaining) => remaining,
+                None => panic!("timed out waiting for the events of child {} (exec: {}, exit: {})",
+                               pid, exec, exit),
+            };
+            // A zero timeout would block indefinitely.
+            events.set_read_timeout(Some(remaining.max(Duration::from_millis(1)))).unwrap();
+            let event = match events.next().unwrap() {
+                Ok(event) => event,
+                Err(ref error) if error.kind() == ErrorKind::WouldBlock => continue,
+                Err(error) => panic!("failed to read process events: {}", error),
+            };
+            match event.kind {
+                ProcEventKind::Exec { tgid, .. } if tgid == pid => exec = true,
+                ProcEventKind::Exit { tgid, exit_code, .. } if tgid == pid => {
+                    assert_eq!(0, exit_code);
+                    exit = true;
+                },
+                _ => (),
+            }
+        }
+    }
+}
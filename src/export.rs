@@ -0,0 +1,167 @@
+//! Export of parsed structures as JSON and CSV.
+//!
+//! Every structure parsed by this crate implements `serde::Serialize` when the `export` feature is
+//! enabled, and can be written as JSON with `to_json` or `write_json`. Tabular data implements
+//! `CsvRecord`, and is written as CSV with `write_csv`. The CSV columns are the fields of the
+//! structure, in declaration order.
+
+use std::io::{Result, Write};
+
+use serde::Serialize;
+use serde_json;
+
+use diskstats::DiskStat;
+use net::dev::DeviceStatus;
+use pid::{ProcessState, Stat};
+
+/// Serializes the value as a JSON string.
+pub fn to_json<T: Serialize + ?Sized>(value: &T) -> Result<String> {
+    Ok(serde_json::to_string(value)?)
+}
+
+/// Serializes the value as JSON to the writer.
+pub fn write_json<W: Write, T: Serialize + ?Sized>(writer: W, value: &T) -> Result<()> {
+    Ok(serde_json::to_writer(writer, value)?)
+}
+
+/// A structure which can be written as a row of a CSV table.
+pub trait CsvRecord {
+    /// Returns the names of the columns.
+    fn header() -> &'static [&'static str];
+
+    /// Returns the values of the columns, in the same order as the header.
+    fn fields(&self) -> Vec<String>;
+}
+
+/// Quotes a CSV field if it contains a delimiter, quote, or line terminator.
+fn escape(field: &str) -> String {
+    if field.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_owned()
+    }
+}
+
+/// Writes the records as a CSV table, with a header row, to the writer.
+pub fn write_csv<W: Write, T: CsvRecord>(mut writer: W, records: &[T]) -> Result<()> {
+    writeln!(writer, "{}", T::header().join(","))?;
+    for record in records {
+        let fields = record.fields().iter().map(|field| escape(field)).collect::<Vec<_>>();
+        writeln!(writer, "{}", fields.join(","))?;
+    }
+    Ok(())
+}
+
+/// A value which can be written as a CSV field.
+trait CsvField {
+    fn to_field(&self) -> String;
+}
+
+macro_rules! csv_field_display {
+    ($($t:ty),*) => {
+        $(
+            impl CsvField for $t {
+                fn to_field(&self) -> String {
+                    self.to_string()
+                }
+            }
+        )*
+    }
+}
+
+csv_field_display!(i32, i64, u32, u64, usize, String);
+
+impl<T: CsvField> CsvField for Option<T> {
+    /// Missing values are written as empty fields.
+    fn to_field(&self) -> String {
+        self.as_ref().map(CsvField::to_field).unwrap_or_default()
+    }
+}
+
+impl CsvField for ProcessState {
+    fn to_field(&self) -> String {
+        format!("{:?}", self)
+    }
+}
+
+/// Implements `CsvRecord` with a column per listed field.
+macro_rules! csv_record {
+    ($t:ty { $($field:ident),* $(,)* }) => {
+        impl CsvRecord for $t {
+            fn header() -> &'static [&'static str] {
+                &[$(stringify!($field)),*]
+            }
+
+            fn fields(&self) -> Vec<String> {
+                vec![$(self.$field.to_field()),*]
+            }
+        }
+    }
+}
+
+csv_record!(DiskStat {
+    major, minor, name, reads_completed, reads_merged, sectors_read, time_reading,
+    writes_completed, writes_merged, sectors_written, time_writing, ios_in_progress, time_io,
+    weighted_time_io, discards_completed, discards_merged, sectors_discarded, time_discarding,
+    flush_requests_completed, time_flushing,
+});
+
+csv_record!(DeviceStatus {
+    interface, receive_bytes, receive_packets, receive_errs, receive_drop, receive_fifo,
+    receive_frame, receive_compressed, receive_multicast, transmit_bytes, transmit_packets,
+    transmit_errs, transmit_drop, transmit_fifo, transmit_colls, transmit_carrier,
+    transmit_compressed,
+});
+
+csv_record!(Stat {
+    pid, command, state, ppid, pgrp, session, tty_nr, tty_pgrp, flags, minflt, cminflt, majflt,
+    cmajflt, utime, stime, cutime, cstime, priority, nice, num_threads, start_time, vsize, rss,
+    rsslim, start_code, end_code, startstack, kstkeep, kstkeip, signal, blocked, sigignore,
+    sigcatch, wchan, exit_signal, processor, rt_priority, policy, delayacct_blkio_ticks,
+    guest_time, cguest_time, start_data, end_data, start_brk, arg_start, arg_end, env_start,
+    env_end, exit_code,
+});
+
+#[cfg(test)]
+mod tests {
+    use diskstats::diskstats;
+    use net::dev::dev;
+    use pid::{Stat, stat_self};
+    use snapshot::Snapshot;
+    use super::{CsvRecord, escape, to_json, write_csv};
+
+    #[test]
+    fn test_to_json() {
+        let json = to_json(&Snapshot::capture().unwrap()).unwrap();
+        assert!(json.starts_with("{\"time\":"));
+        assert!(!json.contains("instant"));
+        assert!(to_json(&stat_self().unwrap()).unwrap().contains("\"command\":"));
+    }
+
+    #[test]
+    fn test_write_csv() {
+        let mut buf = Vec::new();
+        write_csv(&mut buf, &[stat_self().unwrap()]).unwrap();
+        let csv = String::from_utf8(buf).unwrap();
+        let lines = csv.lines().collect::<Vec<_>>();
+        assert_eq!(2, lines.len());
+        assert!(lines[0].starts_with("pid,command,state,ppid,"));
+        assert_eq!(Stat::header().len(), lines[0].split(',').count());
+
+        let stat = Stat { command: "a,b".to_owned(), ..Stat::default() };
+        assert_eq!(Stat::header().len(), stat.fields().len());
+        let mut buf = Vec::new();
+        write_csv(&mut buf, &[stat]).unwrap();
+        assert!(String::from_utf8(buf).unwrap().contains(",\"a,b\",Running,"));
+
+        write_csv(Vec::new(), &diskstats().unwrap()).unwrap();
+        write_csv(Vec::new(), &dev().unwrap()).unwrap();
+    }
+
+    #[test]
+    fn test_escape() {
+        assert_eq!("abc", escape("abc"));
+        assert_eq!("\"a,b\"", escape("a,b"));
+        assert_eq!("\"say \"\"hi\"\"\"", escape("say \"hi\""));
+    }
+}
@@ -0,0 +1,82 @@
+//! Detection of the optional `/proc` files provided by the running kernel.
+
+use std::path::Path;
+
+/// The optional `/proc` files provided by the running kernel.
+///
+/// Which files exist depends on the kernel version and build configuration, and on the options
+/// the kernel was booted with. Checking once with `probe` lets callers pick the files to read
+/// up front, rather than handling `NotFound` errors from every call.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "export", derive(Serialize))]
+pub struct ProcFeatures {
+    /// `/proc/[pid]/smaps` (`CONFIG_PROC_PAGE_MONITOR`).
+    pub smaps: bool,
+    /// `/proc/[pid]/smaps_rollup` (since Linux 4.14, `CONFIG_PROC_PAGE_MONITOR`).
+    pub smaps_rollup: bool,
+    /// `/proc/[pid]/io` (`CONFIG_TASK_IO_ACCOUNTING`).
+    pub io: bool,
+    /// `/proc/[pid]/timerslack_ns` (since Linux 4.6).
+    pub timerslack_ns: bool,
+    /// `/proc/[pid]/ns/time` (since Linux 5.6, `CONFIG_TIME_NS`).
+    pub time_namespace: bool,
+    /// `/proc/pressure/cpu` (since Linux 4.20, `CONFIG_PSI`, unless booted with `psi=0`).
+    pub pressure_cpu: bool,
+    /// `/proc/pressure/io` (since Linux 4.20, `CONFIG_PSI`, unless booted with `psi=0`).
+    pub pressure_io: bool,
+    /// `/proc/pressure/memory` (since Linux 4.20, `CONFIG_PSI`, unless booted with `psi=0`).
+    pub pressure_memory: bool,
+    /// `/proc/pressure/irq` (since Linux 6.1, `CONFIG_IRQ_TIME_ACCOUNTING`).
+    pub pressure_irq: bool,
+    /// `/proc/timer_list` (since Linux 2.6.21). Unlike the removed `/proc/timer_stats`, it does not
+    /// depend on `CONFIG_TIMER_STATS`.
+    pub timer_list: bool,
+    /// `/proc/kpagecgroup` (since Linux 4.3, `CONFIG_MEMCG`).
+    pub kpagecgroup: bool,
+    /// `/proc/config.gz` (`CONFIG_IKCONFIG_PROC`).
+    pub config_gz: bool,
+    /// `/proc/sysrq-trigger` (`CONFIG_MAGIC_SYSRQ`).
+    pub sysrq_trigger: bool,
+    /// `/proc/net/tcp6`, `/proc/net/udp6`, and the other IPv6 tables (`CONFIG_IPV6`, unless
+    /// disabled with `ipv6.disable=1`).
+    pub ipv6: bool,
+}
+
+/// Returns which optional `/proc` files the running kernel provides.
+///
+/// The per-process files are checked for the current process. Files which exist may still be
+/// unreadable due to insufficient permissions.
+pub fn probe() -> ProcFeatures {
+    let exists = |path: &str| Path::new(path).exists();
+    ProcFeatures {
+        smaps: exists("/proc/self/smaps"),
+        smaps_rollup: exists("/proc/self/smaps_rollup"),
+        io: exists("/proc/self/io"),
+        timerslack_ns: exists("/proc/self/timerslack_ns"),
+        time_namespace: exists("/proc/self/ns/time"),
+        pressure_cpu: exists("/proc/pressure/cpu"),
+        pressure_io: exists("/proc/pressure/io"),
+        pressure_memory: exists("/proc/pressure/memory"),
+        pressure_irq: exists("/proc/pressure/irq"),
+        timer_list: exists("/proc/timer_list"),
+        kpagecgroup: exists("/proc/kpagecgroup"),
+        config_gz: exists("/proc/config.gz"),
+        sysrq_trigger: exists("/proc/sysrq-trigger"),
+        ipv6: exists("/proc/net/if_inet6"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pid::{io_self, smaps_rollup_self};
+    use super::probe;
+
+    /// Test that the probed features agree with the accessors.
+    #[test]
+    fn test_probe() {
+        let features = probe();
+        assert_eq!(features.io, io_self().is_ok());
+        assert_eq!(features.smaps_rollup, smaps_rollup_self().is_ok());
+        assert!(!features.smaps_rollup || features.smaps);
+    }
+}
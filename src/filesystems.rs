@@ -0,0 +1,136 @@
+//! Supported filesystem types from `/proc/filesystems`.
+
+use std::fs::File;
+use std::io::{Read, Result};
+
+use parsers::{invalid_input, parse_lines, parse_str};
+use pid::{Mountinfo, mountinfo_self};
+
+/// A filesystem type supported by the kernel, either built in or provided by a loaded module.
+///
+/// See `filesystems(5)` and `Linux/fs/filesystems.c`.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "export", derive(Serialize))]
+pub struct Filesystem {
+    /// The filesystem type name, as passed to `mount(2)`.
+    pub name: String,
+    /// Whether the filesystem is not backed by a block device (e.g. `proc` or `tmpfs`).
+    pub nodev: bool,
+}
+
+impl Filesystem {
+    /// Parses a line of a `/proc/filesystems` file, without the line terminator.
+    pub fn parse_line(input: &[u8]) -> Result<Filesystem> {
+        let line = parse_str(input)?;
+        let (flags, name) = line.split_once('\t').ok_or_else(|| {
+            invalid_input(format!("invalid filesystems line: {:?}", line))
+        })?;
+        let nodev = match flags {
+            "" => false,
+            "nodev" => true,
+            _ => return Err(invalid_input(format!("invalid filesystem flags: {:?}", flags))),
+        };
+        if name.is_empty() {
+            return Err(invalid_input("missing filesystem name"));
+        }
+        Ok(Filesystem { name: name.to_owned(), nodev })
+    }
+}
+
+/// A supported filesystem type, and how many times it is mounted.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "export", derive(Serialize))]
+pub struct FilesystemUsage {
+    /// The filesystem type.
+    pub filesystem: Filesystem,
+    /// The number of mounts of the filesystem type in the mount namespace of the current process.
+    pub mounts: usize,
+}
+
+impl FilesystemUsage {
+    /// Returns `true` if the filesystem type is mounted at least once.
+    pub fn is_mounted(&self) -> bool {
+        self.mounts > 0
+    }
+}
+
+/// Parses the filesystems file, which has a line per filesystem type.
+fn parse_filesystems(input: &[u8]) -> Result<Vec<Filesystem>> {
+    parse_lines(input, 0, Filesystem::parse_line)
+}
+
+/// Counts the mounts of each filesystem type.
+///
+/// Mounts are matched by their main type, so `fuse.sshfs` mounts count towards `fuse`.
+fn usage(filesystems: Vec<Filesystem>, mounts: &[Mountinfo]) -> Vec<FilesystemUsage> {
+    filesystems.into_iter()
+               .map(|filesystem| {
+                   let mounts = mounts.iter()
+                                      .filter(|mount| mount.fs_type.0 == filesystem.name)
+                                      .count();
+                   FilesystemUsage { filesystem, mounts }
+               })
+               .collect()
+}
+
+/// Returns the filesystem types supported by the kernel.
+pub fn filesystems() -> Result<Vec<Filesystem>> {
+    let mut buf = Vec::new();
+    File::open("/proc/filesystems")?.read_to_end(&mut buf)?;
+    parse_filesystems(&buf)
+}
+
+/// Returns the filesystem types supported by the kernel, with the number of times each is mounted.
+///
+/// Only mounts visible in the mount namespace of the current process are counted, so a type
+/// which is unused here may still be in use in another namespace.
+pub fn filesystems_with_usage() -> Result<Vec<FilesystemUsage>> {
+    Ok(usage(filesystems()?, &mountinfo_self()?))
+}
+
+#[cfg(test)]
+mod tests {
+    use pid::Mountinfo;
+    use super::{Filesystem, filesystems, filesystems_with_usage, parse_filesystems, usage};
+
+    /// Test that the system filesystems file can be parsed.
+    #[test]
+    fn test_filesystems() {
+        let filesystems = filesystems().unwrap();
+        assert!(filesystems.contains(&Filesystem { name: "proc".to_owned(), nodev: true }));
+    }
+
+    /// Test that the proc filesystem is reported as mounted.
+    #[test]
+    fn test_filesystems_with_usage() {
+        let usage = filesystems_with_usage().unwrap();
+        assert!(usage.iter().any(|fs| fs.filesystem.name == "proc" && fs.is_mounted()));
+    }
+
+    #[test]
+    fn test_parse_filesystems() {
+        let text = b"nodev\tsysfs\n\
+                     nodev\tproc\n\
+                     \text4\n\
+                     nodev\tfuse\n\
+                     \tvfat\n";
+        let filesystems = parse_filesystems(text).unwrap();
+        assert_eq!(5, filesystems.len());
+        assert_eq!(Filesystem { name: "ext4".to_owned(), nodev: false }, filesystems[2]);
+        assert!(filesystems[3].nodev);
+
+        Filesystem::parse_line(b"ext4").unwrap_err();
+        Filesystem::parse_line(b"dev\text4").unwrap_err();
+        Filesystem::parse_line(b"nodev\t").unwrap_err();
+
+        let mounts = [
+            Mountinfo::parse_line(b"19 23 0:4 / /proc rw - proc proc rw").unwrap(),
+            Mountinfo::parse_line(b"20 23 0:5 / /a rw - fuse.sshfs a:/ rw").unwrap(),
+            Mountinfo::parse_line(b"21 23 0:6 / /b rw - fuse.sshfs b:/ rw").unwrap(),
+        ];
+        let usage = usage(filesystems, &mounts);
+        let counts = usage.iter().map(|fs| fs.mounts).collect::<Vec<_>>();
+        assert_eq!(vec![0, 1, 0, 2, 0], counts);
+        assert!(!usage[0].is_mounted());
+    }
+}
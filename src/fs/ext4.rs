@@ -0,0 +1,312 @@
+//! Per-filesystem ext4 information from `/proc/fs/ext4/<device>/`.
+
+use std::collections::BTreeMap;
+use std::fs::{self, File};
+use std::io::{ErrorKind, Read, Result};
+use std::path::Path;
+use std::time::Duration;
+
+use parsers::{Fields, invalid_input, parse, parse_str, with_line, with_path};
+
+const FS_EXT4_DIR: &str = "/proc/fs/ext4";
+
+/// Extent status cache statistics of an ext4 filesystem.
+///
+/// See `Linux/fs/ext4/extents_status.c`.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "export", derive(Serialize))]
+pub struct Ext4ExtentStatusStats {
+    /// The number of cached extents.
+    pub objects: u64,
+    /// The number of cached extents which can be reclaimed by the shrinker.
+    pub reclaimable_objects: u64,
+    /// The number of extent lookups answered from the cache.
+    pub cache_hits: u64,
+    /// The number of extent lookups which missed the cache.
+    pub cache_misses: u64,
+    /// The number of inodes with reclaimable extents.
+    pub inodes_on_list: u64,
+    /// The average duration of a shrinker scan.
+    pub avg_scan_time: Duration,
+    /// The average number of extents reclaimed by a shrinker scan.
+    pub avg_shrunk_objects: u64,
+    /// The inode with the most cached extents, and its number of cached and reclaimable extents,
+    /// if any inode has cached extents.
+    pub max_inode: Option<(u64, u64, u64)>,
+    /// The longest duration of a shrinker scan.
+    pub max_scan_time: Duration,
+}
+
+/// Parses a `<count> <description>` line of the extent status statistics into the statistics.
+///
+/// Lines which are not known, such as the section headers, are ignored.
+fn parse_es_shrinker_line(stats: &mut Ext4ExtentStatusStats, line: &str) -> Result<()> {
+    let (value, description) = match line.trim().split_once(' ') {
+        Some(split) => split,
+        None => return Ok(()),
+    };
+    match description {
+        "objects" => stats.objects = parse(value)?,
+        "reclaimable objects" => stats.reclaimable_objects = parse(value)?,
+        "cache hits/misses" => {
+            let (hits, misses) = value.split_once('/').ok_or_else(|| {
+                invalid_input(format!("invalid cache hits/misses: {:?}", value))
+            })?;
+            stats.cache_hits = parse(hits)?;
+            stats.cache_misses = parse(misses)?;
+        },
+        "inodes on list" => stats.inodes_on_list = parse(value)?,
+        "us scan time" => stats.avg_scan_time = Duration::from_micros(parse(value)?),
+        "shrunk objects" => stats.avg_shrunk_objects = parse(value)?,
+        "us max scan time" => stats.max_scan_time = Duration::from_micros(parse(value)?),
+        _ => {
+            // The maximum inode, of the form `1206 inode (305 objects, 305 reclaimable)`.
+            if let Some(counts) = description.strip_prefix("inode (") {
+                let mut fields = Fields::new(counts);
+                let objects = fields.parse()?;
+                fields.next_str()?;
+                let reclaimable = fields.parse()?;
+                stats.max_inode = Some((parse(value)?, objects, reclaimable));
+            }
+        },
+    }
+    Ok(())
+}
+
+impl Ext4ExtentStatusStats {
+    /// Parses the contents of an ext4 `es_shrinker_info` file.
+    pub fn parse(input: &[u8]) -> Result<Ext4ExtentStatusStats> {
+        let mut stats = Ext4ExtentStatusStats::default();
+        for (i, line) in parse_str(input)?.lines().enumerate() {
+            parse_es_shrinker_line(&mut stats, line).map_err(|error| with_line(error, i + 1))?;
+        }
+        Ok(stats)
+    }
+}
+
+/// The free space of an ext4 block group, from the multiblock allocator.
+///
+/// See `Linux/fs/ext4/mballoc.c`.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "export", derive(Serialize))]
+pub struct Ext4Group {
+    /// The block group number.
+    pub group: u32,
+    /// The number of free clusters.
+    pub free: u32,
+    /// The number of free extents.
+    pub fragments: u32,
+    /// The first free cluster.
+    pub first: u32,
+    /// The number of free extents of each power-of-two size, in clusters, indexed by order.
+    pub free_extents: Vec<u32>,
+}
+
+impl Ext4Group {
+    /// Parses a line of an ext4 `mb_groups` file, without the line terminator.
+    ///
+    /// The line is of the form `#0    : 40    1     2264  [ 0     0     1     0     ]`.
+    pub fn parse_line(input: &[u8]) -> Result<Ext4Group> {
+        let line = parse_str(input)?;
+        let invalid = || invalid_input(format!("invalid mb_groups line: {:?}", line));
+        let (group, rest) = line.strip_prefix('#')
+                                .and_then(|line| line.split_once(':'))
+                                .ok_or_else(invalid)?;
+        let (counts, free_extents) = rest.split_once('[').ok_or_else(invalid)?;
+        let free_extents = free_extents.strip_suffix(']').ok_or_else(invalid)?;
+
+        let mut fields = Fields::new(counts);
+        let ext4_group = Ext4Group {
+            group: parse(group.trim())?,
+            free: fields.parse()?,
+            fragments: fields.parse()?,
+            first: fields.parse()?,
+            free_extents: free_extents.split_whitespace().map(parse).collect::<Result<_>>()?,
+        };
+        fields.finish()?;
+        Ok(ext4_group)
+    }
+}
+
+/// Parses the mb_groups file, which has a header line followed by a line per block group.
+///
+/// Groups whose buddy bitmap could not be loaded are printed as `#<group>: I/O error`, and are
+/// skipped.
+fn parse_mb_groups(input: &[u8]) -> Result<Vec<Ext4Group>> {
+    let mut groups = Vec::new();
+    for (i, line) in input.split(|&c| c == b'\n').enumerate().skip(1) {
+        if line.is_empty() || line.ends_with(b"I/O error") {
+            continue;
+        }
+        groups.push(Ext4Group::parse_line(line).map_err(|error| with_line(error, i + 1))?);
+    }
+    Ok(groups)
+}
+
+/// The information of a mounted ext4 filesystem.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "export", derive(Serialize))]
+pub struct Ext4 {
+    /// The name of the block device holding the filesystem, e.g. `sda1` or `dm-0`.
+    pub device: String,
+    /// The effective mount options, including the defaults, by name. Options of the form
+    /// `name=value` have a value.
+    pub options: BTreeMap<String, Option<String>>,
+    /// The extent status cache statistics (since Linux 3.19).
+    pub es_shrinker_info: Option<Ext4ExtentStatusStats>,
+}
+
+/// Parses the contents of an ext4 `options` file, which has an option per line.
+fn parse_options(input: &[u8]) -> Result<BTreeMap<String, Option<String>>> {
+    Ok(parse_str(input)?.lines()
+                        .filter(|line| !line.is_empty())
+                        .map(|line| match line.split_once('=') {
+                            Some((name, value)) => (name.to_owned(), Some(value.to_owned())),
+                            None => (line.to_owned(), None),
+                        })
+                        .collect())
+}
+
+/// Reads the file at the path, and parses it with the provided function.
+fn read_file<T>(path: &Path, parse: fn(&[u8]) -> Result<T>) -> Result<T> {
+    let mut buf = Vec::new();
+    File::open(path)?.read_to_end(&mut buf)?;
+    parse(&buf).map_err(|error| with_path(error, path))
+}
+
+/// Reads the file at the path, treating a missing file as absent.
+fn read_optional_file<T>(path: &Path, parse: fn(&[u8]) -> Result<T>) -> Result<Option<T>> {
+    match read_file(path, parse) {
+        Ok(value) => Ok(Some(value)),
+        Err(ref error) if error.kind() == ErrorKind::NotFound => Ok(None),
+        Err(error) => Err(error),
+    }
+}
+
+/// Returns the information of the ext4 filesystem on the provided block device, e.g. `sda1`.
+pub fn ext4_device(device: &str) -> Result<Ext4> {
+    let dir = Path::new(FS_EXT4_DIR).join(device);
+    Ok(Ext4 {
+        device: device.to_owned(),
+        options: read_file(&dir.join("options"), parse_options)?,
+        es_shrinker_info: read_optional_file(&dir.join("es_shrinker_info"),
+                                             Ext4ExtentStatusStats::parse)?,
+    })
+}
+
+/// Returns the information of each mounted ext4 filesystem, ordered by device name.
+///
+/// Returns no filesystems if the ext4 driver is not loaded.
+pub fn ext4() -> Result<Vec<Ext4>> {
+    let dir = match fs::read_dir(FS_EXT4_DIR) {
+        Ok(dir) => dir,
+        Err(ref error) if error.kind() == ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(error) => return Err(error),
+    };
+    let mut filesystems = Vec::new();
+    for entry in dir {
+        let device = entry?.file_name().to_string_lossy().into_owned();
+        // Filesystems may be unmounted while the directory is being read.
+        match ext4_device(&device) {
+            Ok(filesystem) => filesystems.push(filesystem),
+            Err(ref error) if error.kind() == ErrorKind::NotFound => continue,
+            Err(error) => return Err(error),
+        }
+    }
+    filesystems.sort_by(|a, b| a.device.cmp(&b.device));
+    Ok(filesystems)
+}
+
+/// Returns the free space of each block group of the ext4 filesystem on the provided block device.
+///
+/// Reading the block groups loads the block bitmap of every group which is not yet cached, which
+/// may take a long time on a large filesystem.
+pub fn ext4_mb_groups(device: &str) -> Result<Vec<Ext4Group>> {
+    read_file(&Path::new(FS_EXT4_DIR).join(device).join("mb_groups"), parse_mb_groups)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::{Ext4ExtentStatusStats, Ext4Group, ext4, ext4_mb_groups, parse_mb_groups,
+                parse_options};
+
+    /// Test that the ext4 filesystems can be read, if any.
+    #[test]
+    fn test_ext4() {
+        for filesystem in ext4().unwrap() {
+            assert!(filesystem.options.contains_key("rw") || filesystem.options.contains_key("ro"));
+            if let Some(group) = ext4_mb_groups(&filesystem.device).unwrap().first() {
+                assert_eq!(0, group.group);
+            }
+        }
+    }
+
+    #[test]
+    fn test_parse_options() {
+        let options = parse_options(b"rw\nbsddf\nerrors=continue\ncommit=5\n").unwrap();
+        assert_eq!(4, options.len());
+        assert_eq!(Some(&None), options.get("rw"));
+        assert_eq!(Some(&Some("continue".to_owned())), options.get("errors"));
+        assert_eq!(None, options.get("ro"));
+    }
+
+    #[test]
+    fn test_parse_es_shrinker_info() {
+        let text = b"stats:\n\
+                     \x20 28361 objects\n\
+                     \x20 28256 reclaimable objects\n\
+                     \x20 7481776/29956 cache hits/misses\n\
+                     \x20 15142 inodes on list\n\
+                     average:\n\
+                     \x20 12 us scan time\n\
+                     \x20 128 shrunk objects\n\
+                     maximum:\n\
+                     \x20 1206 inode (305 objects, 300 reclaimable)\n\
+                     \x20 250 us max scan time\n";
+        let stats = Ext4ExtentStatusStats::parse(text).unwrap();
+        assert_eq!(Ext4ExtentStatusStats {
+                       objects: 28361,
+                       reclaimable_objects: 28256,
+                       cache_hits: 7_481_776,
+                       cache_misses: 29956,
+                       inodes_on_list: 15142,
+                       avg_scan_time: Duration::from_micros(12),
+                       avg_shrunk_objects: 128,
+                       max_inode: Some((1206, 305, 300)),
+                       max_scan_time: Duration::from_micros(250),
+                   },
+                   stats);
+
+        Ext4ExtentStatusStats::parse(b"stats:\n  x objects\n").unwrap_err();
+        Ext4ExtentStatusStats::parse(b"stats:\n  7481776 cache hits/misses\n").unwrap_err();
+    }
+
+    #[test]
+    fn test_parse_mb_groups() {
+        let text = b"#group: free  frags first [ 2^0   2^1   2^2   2^3   ]\n\
+                     #0    : 40    1     2264  [ 0     0     0     5     ]\n\
+                     #1    : I/O error\n\
+                     #2    : 0     0     32768 [ 0     0     0     0     ]\n";
+        let groups = parse_mb_groups(text).unwrap();
+        assert_eq!(vec![Ext4Group {
+                            group: 0,
+                            free: 40,
+                            fragments: 1,
+                            first: 2264,
+                            free_extents: vec![0, 0, 0, 5],
+                        },
+                        Ext4Group {
+                            group: 2,
+                            free: 0,
+                            fragments: 0,
+                            first: 32768,
+                            free_extents: vec![0; 4],
+                        }],
+                   groups);
+
+        Ext4Group::parse_line(b"#0    : 40    1     2264  [ 0     0").unwrap_err();
+        Ext4Group::parse_line(b"#0    : 40    1     [ 0     0     ]").unwrap_err();
+    }
+}
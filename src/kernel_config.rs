@@ -0,0 +1,150 @@
+//! Kernel build configuration from `/proc/config.gz`.
+
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{ErrorKind, Read, Result};
+
+use flate2::read::GzDecoder;
+
+use parsers::{invalid_input, parse_str};
+use sys::kernel::osrelease;
+
+/// The value of a kernel configuration option.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "export", derive(Serialize))]
+pub enum ConfigValue {
+    /// The option is built in (`y`).
+    Yes,
+    /// The option is built as a loadable module (`m`).
+    Module,
+    /// The option is not set (`# CONFIG_FOO is not set`).
+    No,
+    /// A string, integer, or hexadecimal value. String values are unquoted and unescaped.
+    Value(String),
+}
+
+/// Parses the value of an option, unquoting string values.
+fn parse_config_value(s: &str) -> Result<ConfigValue> {
+    let value = match s {
+        "y" => ConfigValue::Yes,
+        "m" => ConfigValue::Module,
+        "n" => ConfigValue::No,
+        _ => match s.strip_prefix('"') {
+            Some(quoted) => {
+                let quoted = quoted.strip_suffix('"').ok_or_else(|| {
+                    invalid_input(format!("unterminated config string: {:?}", s))
+                })?;
+                let mut value = String::with_capacity(quoted.len());
+                let mut chars = quoted.chars();
+                while let Some(c) = chars.next() {
+                    match c {
+                        '\\' => value.extend(chars.next()),
+                        c => value.push(c),
+                    }
+                }
+                ConfigValue::Value(value)
+            },
+            None => ConfigValue::Value(s.to_owned()),
+        },
+    };
+    Ok(value)
+}
+
+/// Parses a kernel configuration file.
+///
+/// Options are keyed by their full name, including the `CONFIG_` prefix.
+fn parse_kernel_config(input: &[u8]) -> Result<HashMap<String, ConfigValue>> {
+    let mut config = HashMap::new();
+    for line in parse_str(input)?.lines() {
+        if let Some(comment) = line.strip_prefix("# ") {
+            if let Some(name) = comment.strip_suffix(" is not set") {
+                config.insert(name.to_owned(), ConfigValue::No);
+            }
+            continue;
+        }
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let (name, value) = line.split_once('=').ok_or_else(|| {
+            invalid_input(format!("invalid config line: {:?}", line))
+        })?;
+        config.insert(name.to_owned(), parse_config_value(value)?);
+    }
+    Ok(config)
+}
+
+/// Returns the build configuration of the running kernel, keyed by option name (e.g.
+/// `CONFIG_PSI`).
+///
+/// The configuration is read from `/proc/config.gz`, which is only present when the kernel is built
+/// with `CONFIG_IKCONFIG_PROC`, falling back to `/boot/config-<release>`. Options which are neither
+/// set nor listed as unset are missing from the map; they are typically unavailable on the kernel's
+/// architecture or depend on unset options.
+///
+/// Requires the `kernel-config` feature.
+pub fn kernel_config() -> Result<HashMap<String, ConfigValue>> {
+    let mut buf = Vec::new();
+    match File::open("/proc/config.gz") {
+        Ok(file) => {
+            GzDecoder::new(file).read_to_end(&mut buf)?;
+        },
+        Err(ref error) if error.kind() == ErrorKind::NotFound => {
+            File::open(format!("/boot/config-{}", osrelease()?))?.read_to_end(&mut buf)?;
+        },
+        Err(error) => return Err(error),
+    }
+    parse_kernel_config(&buf)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::ErrorKind;
+
+    use super::{ConfigValue, kernel_config, parse_config_value, parse_kernel_config};
+
+    /// Test that the kernel configuration can be parsed, if it is available.
+    #[test]
+    fn test_kernel_config() {
+        match kernel_config() {
+            Ok(config) => assert_eq!(Some(&ConfigValue::Yes), config.get("CONFIG_PROC_FS")),
+            Err(error) => assert_eq!(ErrorKind::NotFound, error.kind()),
+        }
+    }
+
+    #[test]
+    fn test_parse_kernel_config() {
+        let text = b"#\n\
+                     # Automatically generated file; DO NOT EDIT.\n\
+                     # Linux/x86 6.18.44 Kernel Configuration\n\
+                     #\n\
+                     CONFIG_CC_VERSION_TEXT=\"gcc (GCC) 15.3.0\"\n\
+                     CONFIG_GCC_VERSION=150300\n\
+                     CONFIG_PROC_FS=y\n\
+                     CONFIG_EXT4_FS=m\n\
+                     # CONFIG_DEBUG_KERNEL is not set\n\
+                     \n\
+                     #\n\
+                     # General setup\n\
+                     #\n\
+                     CONFIG_PHYSICAL_START=0x1000000\n";
+        let config = parse_kernel_config(text).unwrap();
+        assert_eq!(6, config.len());
+        assert_eq!(ConfigValue::Value("gcc (GCC) 15.3.0".to_owned()),
+                   config["CONFIG_CC_VERSION_TEXT"]);
+        assert_eq!(ConfigValue::Value("150300".to_owned()), config["CONFIG_GCC_VERSION"]);
+        assert_eq!(ConfigValue::Yes, config["CONFIG_PROC_FS"]);
+        assert_eq!(ConfigValue::Module, config["CONFIG_EXT4_FS"]);
+        assert_eq!(ConfigValue::No, config["CONFIG_DEBUG_KERNEL"]);
+        assert_eq!(ConfigValue::Value("0x1000000".to_owned()), config["CONFIG_PHYSICAL_START"]);
+
+        parse_kernel_config(b"CONFIG_PROC_FS\n").unwrap_err();
+    }
+
+    #[test]
+    fn test_parse_config_value() {
+        assert_eq!(ConfigValue::Value(r#"a "b" \c"#.to_owned()),
+                   parse_config_value(r#""a \"b\" \\c""#).unwrap());
+        assert_eq!(ConfigValue::Value(String::new()), parse_config_value("\"\"").unwrap());
+        parse_config_value("\"abc").unwrap_err();
+    }
+}
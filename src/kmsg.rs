@@ -0,0 +1,315 @@
+//! Kernel log records from `/dev/kmsg` and `/proc/kmsg`.
+
+use std::fs::{File, OpenOptions};
+use std::io::{BufRead, BufReader, ErrorKind, Read, Result};
+use std::os::unix::fs::OpenOptionsExt;
+use std::time::Duration;
+
+use libc;
+
+use parsers::{invalid_input, parse, parse_str};
+
+/// The severity of a kernel log record.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "export", derive(Serialize))]
+pub enum LogLevel {
+    /// The system is unusable (`KERN_EMERG`).
+    Emergency,
+    /// Action must be taken immediately (`KERN_ALERT`).
+    Alert,
+    /// A critical condition (`KERN_CRIT`).
+    Critical,
+    /// An error condition (`KERN_ERR`).
+    Error,
+    /// A warning condition (`KERN_WARNING`).
+    Warning,
+    /// A normal but significant condition (`KERN_NOTICE`).
+    Notice,
+    /// An informational message (`KERN_INFO`).
+    Info,
+    /// A debug-level message (`KERN_DEBUG`).
+    Debug,
+}
+
+impl LogLevel {
+    /// Returns the level with the provided value, which is in the range 0 to 7.
+    fn from_raw(level: u32) -> LogLevel {
+        match level & 7 {
+            0 => LogLevel::Emergency,
+            1 => LogLevel::Alert,
+            2 => LogLevel::Critical,
+            3 => LogLevel::Error,
+            4 => LogLevel::Warning,
+            5 => LogLevel::Notice,
+            6 => LogLevel::Info,
+            _ => LogLevel::Debug,
+        }
+    }
+}
+
+/// A kernel log record.
+///
+/// See `Linux/Documentation/ABI/testing/dev-kmsg`.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "export", derive(Serialize))]
+pub struct KmsgRecord {
+    /// The severity of the record.
+    pub level: LogLevel,
+    /// The syslog facility of the record, e.g. `0` (`LOG_KERN`) for kernel messages, or `1`
+    /// (`LOG_USER`) for messages written to `/dev/kmsg` by user space.
+    pub facility: u32,
+    /// The sequence number of the record, or `None` if read from `/proc/kmsg`.
+    ///
+    /// A gap between the sequence numbers of consecutive records means records were overwritten
+    /// before they were read.
+    pub sequence: Option<u64>,
+    /// The time the record was logged, since boot on the monotonic clock, or `None` if read from
+    /// `/proc/kmsg` with `printk.time` disabled.
+    pub timestamp: Option<Duration>,
+    /// The message.
+    pub message: String,
+    /// The `KEY=value` properties attached to the record, such as `SUBSYSTEM` and `DEVICE`.
+    pub properties: Vec<(String, String)>,
+}
+
+/// Reverses the `\xNN` escaping the kernel applies to non-printable bytes and backslashes.
+fn unescape(input: &str) -> String {
+    let bytes = input.as_bytes();
+    let mut unescaped = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        if bytes[i] == b'\\' && bytes.get(i + 1) == Some(&b'x') {
+            let byte = input.get(i + 2..i + 4).and_then(|hex| u8::from_str_radix(hex, 16).ok());
+            if let Some(byte) = byte {
+                unescaped.push(byte);
+                i += 4;
+                continue;
+            }
+        }
+        unescaped.push(bytes[i]);
+        i += 1;
+    }
+    String::from_utf8_lossy(&unescaped).into_owned()
+}
+
+impl KmsgRecord {
+    /// Parses a record read from `/dev/kmsg`.
+    ///
+    /// A record has a header of the form `priority,sequence,timestamp,flags[,...];`, followed by
+    /// the message and a line per property, each indented by a space.
+    pub fn parse(input: &[u8]) -> Result<KmsgRecord> {
+        let input = parse_str(input)?;
+        let (header, body) = input.split_once(';').ok_or_else(|| {
+            invalid_input(format!("invalid kmsg record: {:?}", input))
+        })?;
+        let mut fields = header.split(',');
+        let mut field = || {
+            fields.next().ok_or_else(|| invalid_input(format!("invalid kmsg header: {:?}", header)))
+        };
+        let priority: u32 = parse(field()?)?;
+        let sequence = parse(field()?)?;
+        let timestamp = Duration::from_micros(parse(field()?)?);
+        // The flags and any fields added by later kernels are ignored.
+
+        let mut lines = body.lines();
+        let message = unescape(lines.next().unwrap_or(""));
+        let properties = lines.filter_map(|line| line.strip_prefix(' '))
+                              .filter_map(|line| line.split_once('='))
+                              .map(|(key, value)| (unescape(key), unescape(value)))
+                              .collect();
+
+        Ok(KmsgRecord {
+            level: LogLevel::from_raw(priority),
+            facility: priority >> 3,
+            sequence: Some(sequence),
+            timestamp: Some(timestamp),
+            message,
+            properties,
+        })
+    }
+}
+
+/// Parses a line read from `/proc/kmsg`, of the form `<priority>[seconds.micros] message`.
+fn parse_proc_kmsg_line(line: &str) -> Result<KmsgRecord> {
+    let (priority, mut message) = line.strip_prefix('<')
+                                      .and_then(|line| line.split_once('>'))
+                                      .ok_or_else(|| {
+                                          invalid_input(format!("invalid kmsg line: {:?}", line))
+                                      })?;
+    let priority: u32 = parse(priority)?;
+    let mut timestamp = None;
+    if let Some((time, rest)) = message.strip_prefix('[').and_then(|m| m.split_once("] ")) {
+        if let Some((secs, micros)) = time.trim_start().split_once('.') {
+            timestamp = Some(Duration::from_secs(parse(secs)?)
+                             + Duration::from_micros(parse(micros)?));
+            message = rest;
+        }
+    }
+    Ok(KmsgRecord {
+        level: LogLevel::from_raw(priority),
+        facility: priority >> 3,
+        sequence: None,
+        timestamp,
+        message: message.to_owned(),
+        properties: Vec::new(),
+    })
+}
+
+/// The source of kernel log records.
+#[derive(Debug)]
+enum Source {
+    /// `/dev/kmsg`, from which each read returns a single record.
+    DevKmsg(File, Vec<u8>),
+    /// `/proc/kmsg`, which returns a line per record.
+    ProcKmsg(BufReader<File>, String),
+}
+
+/// An iterator over kernel log records.
+///
+/// Created by `kmsg` or `kmsg_follow`.
+#[derive(Debug)]
+pub struct Kmsg {
+    source: Source,
+}
+
+impl Iterator for Kmsg {
+    type Item = Result<KmsgRecord>;
+
+    fn next(&mut self) -> Option<Result<KmsgRecord>> {
+        loop {
+            let result = match self.source {
+                Source::DevKmsg(ref mut file, ref mut buf) => match file.read(buf) {
+                    Ok(0) => Ok(None),
+                    Ok(n) => Ok(Some(KmsgRecord::parse(&buf[..n]))),
+                    Err(error) => Err(error),
+                },
+                Source::ProcKmsg(ref mut reader, ref mut line) => {
+                    line.clear();
+                    match reader.read_line(line) {
+                        Ok(0) => Ok(None),
+                        Ok(_) => Ok(Some(parse_proc_kmsg_line(line.trim_end_matches('\n')))),
+                        Err(error) => Err(error),
+                    }
+                },
+            };
+            return match result {
+                Ok(record) => record,
+                // There are no more records, and the file is non-blocking.
+                Err(ref error) if error.kind() == ErrorKind::WouldBlock => None,
+                Err(ref error) if error.kind() == ErrorKind::Interrupted => continue,
+                // The next record was overwritten before it was read; reading resumes at the
+                // oldest remaining record.
+                Err(ref error) if error.raw_os_error() == Some(libc::EPIPE) => continue,
+                Err(error) => Some(Err(error)),
+            };
+        }
+    }
+}
+
+/// Opens the kernel log, preferring `/dev/kmsg` and falling back to `/proc/kmsg`.
+fn open_kmsg(flags: i32) -> Result<Kmsg> {
+    let mut options = OpenOptions::new();
+    options.read(true).custom_flags(flags);
+    let source = match options.open("/dev/kmsg") {
+        // Records are at most 8KiB, and reads into a smaller buffer fail.
+        Ok(file) => Source::DevKmsg(file, vec![0; 8192]),
+        Err(ref error) if error.kind() == ErrorKind::NotFound => {
+            Source::ProcKmsg(BufReader::new(options.open("/proc/kmsg")?), String::new())
+        },
+        Err(error) => return Err(error),
+    };
+    Ok(Kmsg { source })
+}
+
+/// Returns an iterator over the records in the kernel log buffer, oldest first, which ends when
+/// the buffer has been read.
+///
+/// Records are read from `/dev/kmsg`, which requires `CAP_SYSLOG` when `kernel.dmesg_restrict` is
+/// set. Each reader of `/dev/kmsg` has its own position in the buffer. If `/dev/kmsg` does not
+/// exist, records are read from `/proc/kmsg`, which always requires `CAP_SYSLOG`; reads from it
+/// consume records, so it should not be read while a syslog daemon is running.
+pub fn kmsg() -> Result<Kmsg> {
+    open_kmsg(libc::O_NONBLOCK)
+}
+
+/// Returns an iterator over the records in the kernel log buffer, oldest first, which blocks
+/// waiting for new records when the buffer has been read.
+///
+/// See `kmsg` for the permissions required.
+pub fn kmsg_follow() -> Result<Kmsg> {
+    open_kmsg(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::ErrorKind;
+    use std::time::Duration;
+
+    use super::{KmsgRecord, LogLevel, kmsg, parse_proc_kmsg_line, unescape};
+
+    /// Test that the kernel log can be read, if permitted.
+    #[test]
+    fn test_kmsg() {
+        match kmsg() {
+            Ok(records) => {
+                let mut count = 0;
+                for record in records {
+                    record.unwrap();
+                    count += 1;
+                }
+                assert!(count > 0);
+            },
+            Err(error) => assert!(error.kind() == ErrorKind::PermissionDenied
+                                  || error.kind() == ErrorKind::NotFound),
+        }
+    }
+
+    #[test]
+    fn test_parse_record() {
+        let record = KmsgRecord::parse(b"6,339,5140900,-;NET: Registered protocol family 10\n")
+                                .unwrap();
+        assert_eq!(KmsgRecord {
+                       level: LogLevel::Info,
+                       facility: 0,
+                       sequence: Some(339),
+                       timestamp: Some(Duration::from_micros(5140900)),
+                       message: "NET: Registered protocol family 10".to_owned(),
+                       properties: Vec::new(),
+                   },
+                   record);
+
+        let record = KmsgRecord::parse(b"12,1084,10582369,-,caller=T1;tab\\x09here \\x5c\n \
+                                         SUBSYSTEM=usb\n DEVICE=c189:1\n").unwrap();
+        assert_eq!(LogLevel::Warning, record.level);
+        assert_eq!(1, record.facility);
+        assert_eq!("tab\there \\", record.message);
+        assert_eq!(vec![("SUBSYSTEM".to_owned(), "usb".to_owned()),
+                        ("DEVICE".to_owned(), "c189:1".to_owned())],
+                   record.properties);
+
+        KmsgRecord::parse(b"6,339;message\n").unwrap_err();
+        KmsgRecord::parse(b"message\n").unwrap_err();
+    }
+
+    #[test]
+    fn test_parse_proc_kmsg_line() {
+        let record = parse_proc_kmsg_line("<4>[    5.140900] a [bracketed] message").unwrap();
+        assert_eq!(LogLevel::Warning, record.level);
+        assert_eq!(None, record.sequence);
+        assert_eq!(Some(Duration::from_micros(5140900)), record.timestamp);
+        assert_eq!("a [bracketed] message", record.message);
+
+        let record = parse_proc_kmsg_line("<14>message").unwrap();
+        assert_eq!((LogLevel::Info, 1, None), (record.level, record.facility, record.timestamp));
+        assert_eq!("message", record.message);
+
+        parse_proc_kmsg_line("message").unwrap_err();
+    }
+
+    #[test]
+    fn test_unescape() {
+        assert_eq!("a\tb\\", unescape("a\\x09b\\x5c"));
+        assert_eq!("\\xzz", unescape("\\xzz"));
+        assert_eq!("\\x", unescape("\\x"));
+    }
+}
@@ -0,0 +1,85 @@
+//! Memory cgroup ownership of physical pages from `/proc/kpagecgroup`.
+
+use std::fs::File;
+use std::io::Result;
+use std::ops::Range;
+use std::os::unix::fs::FileExt;
+
+use byteorder::{ByteOrder, NativeEndian};
+
+/// The number of entries read at a time.
+const CHUNK_LEN: usize = 512;
+
+/// Reads the entries for the page frames in the range from a kpagecgroup file.
+fn read_kpagecgroup(file: &File, pfns: Range<u64>) -> Result<Vec<u64>> {
+    let len = pfns.end.saturating_sub(pfns.start);
+    let mut inodes = Vec::with_capacity(len.min(1 << 20) as usize);
+    let mut buf = [0; CHUNK_LEN * 8];
+    let mut pfn = pfns.start;
+    while pfn < pfns.end {
+        let remaining = ((pfns.end - pfn) as usize).min(CHUNK_LEN);
+        let n = file.read_at(&mut buf[..remaining * 8], pfn.saturating_mul(8))?;
+        // The file ends at the last page frame of physical memory.
+        if n < 8 {
+            break;
+        }
+        for entry in buf[..n - n % 8].chunks(8) {
+            inodes.push(NativeEndian::read_u64(entry));
+        }
+        pfn += (n / 8) as u64;
+    }
+    Ok(inodes)
+}
+
+/// Returns the inode of the memory cgroup which owns each physical page frame in the range,
+/// indexed from the start of the range.
+///
+/// An inode of `0` means the page is not charged to a memory cgroup. The inode identifies the
+/// cgroup directory in the cgroup filesystem, and matches the `st_ino` of that directory. Entries
+/// are only returned for page frames up to the end of physical memory, so the result is shorter
+/// than the range if the range extends past it.
+///
+/// Requires `CAP_SYS_ADMIN` and a kernel built with `CONFIG_MEMCG`. See
+/// `Linux/Documentation/admin-guide/mm/pagemap.rst`.
+pub fn kpagecgroup(pfns: Range<u64>) -> Result<Vec<u64>> {
+    read_kpagecgroup(&File::open("/proc/kpagecgroup")?, pfns)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::env;
+    use std::fs::{self, File};
+    use std::io::{ErrorKind, Write};
+    use std::process;
+
+    use super::{CHUNK_LEN, kpagecgroup, read_kpagecgroup};
+
+    /// Test that the kpagecgroup file can be read, if permitted.
+    #[test]
+    fn test_kpagecgroup() {
+        match kpagecgroup(0..1024) {
+            Ok(inodes) => assert!(inodes.len() <= 1024),
+            Err(error) => assert!(error.kind() == ErrorKind::PermissionDenied
+                                  || error.kind() == ErrorKind::NotFound),
+        }
+    }
+
+    #[test]
+    fn test_read_kpagecgroup() {
+        let path = env::temp_dir().join(format!("procinfo-kpagecgroup-{}", process::id()));
+        let len = CHUNK_LEN as u64 + 10;
+        {
+            let mut file = File::create(&path).unwrap();
+            for inode in 0..len {
+                file.write_all(&(inode * 3).to_ne_bytes()).unwrap();
+            }
+        }
+        let file = File::open(&path).unwrap();
+        assert_eq!(vec![6, 9, 12], read_kpagecgroup(&file, 2..5).unwrap());
+        let inodes = read_kpagecgroup(&file, 5..len + 100).unwrap();
+        assert_eq!((len - 5) as usize, inodes.len());
+        assert_eq!((len - 1) * 3, *inodes.last().unwrap());
+        assert!(read_kpagecgroup(&file, 5..5).unwrap().is_empty());
+        fs::remove_file(&path).unwrap();
+    }
+}
@@ -1,22 +1,79 @@
-#![recursion_limit = "1000"]
-#![cfg_attr(rustc_nightly, feature(test))]
-
 #![doc(html_root_url = "https://docs.rs/procinfo/0.4.2")]
 
 #![allow(dead_code)] // TODO: remove
 
 #[macro_use]
-extern crate nom;
+extern crate bitflags;
 
 extern crate byteorder;
+#[cfg(feature = "kernel-config")]
+extern crate flate2;
+#[cfg(feature = "export")]
+#[macro_use]
+extern crate serde;
+#[cfg(feature = "export")]
+extern crate serde_json;
+#[cfg(feature = "metrics")]
+extern crate prometheus;
 extern crate libc;
+#[cfg(feature = "async")]
+extern crate tokio;
 
-#[macro_use]
 mod parsers;
 
+#[cfg(feature = "async")]
+pub mod asynchronous;
+mod boot_time;
+mod container;
+pub mod cpu;
+mod cpuset;
+pub mod delta;
+mod diskstats;
+#[cfg(feature = "events")]
+pub mod events;
+#[cfg(feature = "export")]
+pub mod export;
+mod features;
+mod filesystems;
+pub mod fs;
+#[cfg(feature = "kernel-config")]
+mod kernel_config;
+mod kmsg;
+mod kpagecgroup;
 mod loadavg;
+mod meminfo;
+pub mod memory;
+#[cfg(feature = "metrics")]
+pub mod metrics;
 pub mod pid;
+mod pressure;
+pub mod sampler;
 pub mod sys;
 pub mod net;
+mod snapshot;
+mod sysconf;
+#[cfg(feature = "sysrq")]
+pub mod sysrq;
+mod tty;
+mod unmangle;
+mod vmstat;
 
+pub use boot_time::boot_time;
+pub use container::{Container, detect_container};
+pub use cpuset::{CpuSet, CpuSetIter, NodeSet, NodeSetIter};
+pub use diskstats::{DiskStat, diskstats};
+pub use features::{ProcFeatures, probe};
+pub use filesystems::{Filesystem, FilesystemUsage, filesystems, filesystems_with_usage};
+#[cfg(feature = "kernel-config")]
+pub use kernel_config::{ConfigValue, kernel_config};
+pub use kmsg::{Kmsg, KmsgRecord, LogLevel, kmsg, kmsg_follow};
+pub use kpagecgroup::kpagecgroup;
 pub use loadavg::{LoadAvg, loadavg};
+pub use meminfo::{Meminfo, meminfo};
+pub use parsers::{ParseError, ParseWarning};
+pub use pressure::{Pressure, PressureRecord, PressureResource, PsiTrigger, pressure};
+pub use snapshot::{Snapshot, SnapshotDelta};
+pub use sysconf::{page_size, ticks_per_second};
+pub use tty::{TtyDriver, TtyDriverType, tty_drivers};
+pub use unmangle::{mangled_path, unmangled_path};
+pub use vmstat::{Vmstat, vmstat};
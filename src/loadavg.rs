@@ -1,12 +1,10 @@
 //! System load and task statistics from `/proc/loadavg`.
 
-use std::fs::File;
 use std::io::Result;
 
 use libc::pid_t;
-use nom::{line_ending, space};
 
-use parsers::{map_result, parse_f32, parse_i32, parse_u32, read_to_end};
+use parsers::{Fields, invalid_input, parse, parse_file, parse_line, read_to_end};
 
 /// System load and task statistics.
 ///
@@ -14,7 +12,8 @@ use parsers::{map_result, parse_f32, parse_i32, parse_u32, read_to_end};
 /// tasks on the system.
 ///
 /// See `man 5 proc` and `Linux/fs/proc/loadavg.c`.
-#[derive(Debug, Default, PartialEq)]
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "export", derive(Serialize))]
 pub struct LoadAvg {
     /// Load average over the last minute.
     pub load_avg_1_min: f32,
@@ -31,31 +30,46 @@ pub struct LoadAvg {
 }
 
 /// Parses the loadavg file format.
-named!(parse_loadavg<LoadAvg>,
-       chain!(load_avg_1_min:   parse_f32   ~ space ~
-              load_avg_5_min:   parse_f32   ~ space ~
-              load_avg_10_min:  parse_f32   ~ space ~
-              tasks_runnable:   parse_u32   ~ tag!("/") ~
-              tasks_total:      parse_u32   ~ space ~
-              last_created_pid: parse_i32   ~ line_ending,
-              || { LoadAvg { load_avg_1_min: load_avg_1_min,
-                             load_avg_5_min: load_avg_5_min,
-                             load_avg_10_min: load_avg_10_min,
-                             tasks_runnable: tasks_runnable,
-                             tasks_total: tasks_total,
-                             last_created_pid: last_created_pid } }));
+fn parse_loadavg(input: &[u8]) -> Result<LoadAvg> {
+    let mut fields = Fields::new(parse_line(input)?);
+    let load_avg_1_min = fields.parse()?;
+    let load_avg_5_min = fields.parse()?;
+    let load_avg_10_min = fields.parse()?;
+    let tasks = fields.next_str()?;
+    let (tasks_runnable, tasks_total) = tasks.split_once('/').ok_or_else(|| {
+        invalid_input(format!("invalid task counts: {:?}", tasks))
+    })?;
+    let last_created_pid = fields.parse()?;
+    fields.finish()?;
+
+    Ok(LoadAvg {
+        load_avg_1_min,
+        load_avg_5_min,
+        load_avg_10_min,
+        tasks_runnable: parse(tasks_runnable)?,
+        tasks_total: parse(tasks_total)?,
+        last_created_pid,
+    })
+}
+
+impl LoadAvg {
+    /// Parses the contents of a `/proc/loadavg` file.
+    pub fn parse(input: &[u8]) -> Result<LoadAvg> {
+        parse_loadavg(input)
+    }
+}
 
 /// Returns the system load average.
 pub fn loadavg() -> Result<LoadAvg> {
-    let mut buf = [0; 128]; // A typical loadavg file is about 32 bytes.
-    let mut file = try!(File::open("/proc/loadavg"));
-    map_result(parse_loadavg(try!(read_to_end(&mut file, &mut buf))))
+    parse_file("/proc/loadavg", |file| {
+        let mut buf = [0; 128]; // A typical loadavg file is about 32 bytes.
+        LoadAvg::parse(&read_to_end(file, &mut buf)?)
+    })
 }
 
 #[cfg(test)]
 mod tests {
-    use super::{loadavg, parse_loadavg};
-    use parsers::tests::unwrap;
+    use super::{LoadAvg, loadavg, parse_loadavg};
 
     /// Test that the system loadavg file can be parsed.
     #[test]
@@ -66,7 +80,7 @@ mod tests {
     #[test]
     fn test_parse_loadavg() {
         let loadavg_text = b"0.46 0.33 0.28 34/625 8435\n";
-        let loadavg = unwrap(parse_loadavg(loadavg_text));
+        let loadavg = parse_loadavg(loadavg_text).unwrap();
         assert_eq!(0.46, loadavg.load_avg_1_min);
         assert_eq!(0.33, loadavg.load_avg_5_min);
         assert_eq!(0.28, loadavg.load_avg_10_min);
@@ -74,26 +88,11 @@ mod tests {
         assert_eq!(625, loadavg.tasks_total);
         assert_eq!(8435, loadavg.last_created_pid);
     }
-}
-
-#[cfg(all(test, rustc_nightly))]
-mod benches {
-    extern crate test;
-
-    use std::fs::File;
-
-    use parsers::read_to_end;
-    use super::{loadavg, parse_loadavg};
-
-    #[bench]
-    fn bench_loadavg(b: &mut test::Bencher) {
-        b.iter(|| test::black_box(loadavg()));
-    }
 
-    #[bench]
-    fn bench_loadavg_parse(b: &mut test::Bencher) {
-        let mut buf = [0; 128];
-        let statm = read_to_end(&mut File::open("/proc/loadavg").unwrap(), &mut buf).unwrap();
-        b.iter(|| test::black_box(parse_loadavg(statm)));
+    /// Test that recorded loadavg files can be parsed, and trailing input is rejected.
+    #[test]
+    fn test_loadavg_parse() {
+        assert_eq!(8435, LoadAvg::parse(b"0.46 0.33 0.28 34/625 8435\n").unwrap().last_created_pid);
+        LoadAvg::parse(b"0.46 0.33 0.28 34/625 8435\nfoo").unwrap_err();
     }
 }
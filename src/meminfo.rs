@@ -0,0 +1,238 @@
+//! System memory usage statistics from `/proc/meminfo`.
+
+use std::fs::File;
+use std::io::{Error, ErrorKind, Read, Result};
+use std::path::Path;
+use std::str;
+
+use parsers::{with_line, with_path};
+
+/// System memory usage statistics.
+///
+/// Sizes are in kB, except for the `huge_pages_*` counts which are in pages. Fields which are not
+/// reported by the running kernel are zero.
+///
+/// See `man 5 proc` and `Linux/Documentation/filesystems/proc.rst`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "export", derive(Serialize))]
+pub struct Meminfo {
+    /// Total usable RAM (physical RAM less reserved bits and the kernel binary code).
+    pub mem_total: u64,
+    /// RAM left unused by the system.
+    pub mem_free: u64,
+    /// An estimate of the memory available for starting new applications without swapping (since
+    /// Linux 3.14).
+    pub mem_available: u64,
+    /// Relatively temporary storage for raw disk blocks.
+    pub buffers: u64,
+    /// In-memory cache for files read from the disk (the page cache), excluding `swap_cached`.
+    pub cached: u64,
+    /// Memory that once was swapped out, is swapped back in, but is still also in the swap file.
+    pub swap_cached: u64,
+    /// Memory that has been used more recently and usually not reclaimed unless absolutely
+    /// necessary.
+    pub active: u64,
+    /// Memory which has been less recently used, and is more eligible to be reclaimed.
+    pub inactive: u64,
+    /// Active anonymous memory.
+    pub active_anon: u64,
+    /// Inactive anonymous memory.
+    pub inactive_anon: u64,
+    /// Active file-backed memory.
+    pub active_file: u64,
+    /// Inactive file-backed memory.
+    pub inactive_file: u64,
+    /// Memory which can not be reclaimed.
+    pub unevictable: u64,
+    /// Memory locked with `mlock(2)`.
+    pub mlocked: u64,
+    /// Total amount of swap space available.
+    pub swap_total: u64,
+    /// Amount of swap space that is currently unused.
+    pub swap_free: u64,
+    /// Memory which is waiting to get written back to the disk.
+    pub dirty: u64,
+    /// Memory which is actively being written back to the disk.
+    pub writeback: u64,
+    /// Non-file backed pages mapped into user-space page tables.
+    pub anon_pages: u64,
+    /// Files which have been mapped into memory, such as libraries.
+    pub mapped: u64,
+    /// Amount of memory consumed in `tmpfs` filesystems and shared memory.
+    pub shmem: u64,
+    /// Kernel allocations that the kernel will attempt to reclaim under memory pressure (since
+    /// Linux 4.20).
+    pub kreclaimable: u64,
+    /// In-kernel data structures cache.
+    pub slab: u64,
+    /// Part of `slab` that might be reclaimed, such as caches.
+    pub sreclaimable: u64,
+    /// Part of `slab` that cannot be reclaimed under memory pressure.
+    pub sunreclaim: u64,
+    /// Amount of memory allocated to kernel stacks.
+    pub kernel_stack: u64,
+    /// Amount of memory dedicated to the lowest level of page tables.
+    pub page_tables: u64,
+    /// NFS pages sent to the server, but not yet committed to stable storage.
+    pub nfs_unstable: u64,
+    /// Memory used for block device bounce buffers.
+    pub bounce: u64,
+    /// Memory used by FUSE for temporary writeback buffers.
+    pub writeback_tmp: u64,
+    /// The total amount of memory currently available to be allocated on the system.
+    pub commit_limit: u64,
+    /// The amount of memory presently allocated on the system.
+    pub committed_as: u64,
+    /// Total size of vmalloc memory area.
+    pub vmalloc_total: u64,
+    /// Amount of vmalloc area which is used.
+    pub vmalloc_used: u64,
+    /// Largest contiguous block of vmalloc area which is free.
+    pub vmalloc_chunk: u64,
+    /// Memory which the kernel identified as corrupted.
+    pub hardware_corrupted: u64,
+    /// Non-file backed huge pages mapped into user-space page tables.
+    pub anon_huge_pages: u64,
+    /// Memory used by shared memory and `tmpfs` allocated with huge pages (since Linux 4.8).
+    pub shmem_huge_pages: u64,
+    /// Shared memory mapped into user space with huge pages (since Linux 4.8).
+    pub shmem_pmd_mapped: u64,
+    /// The size of the pool of huge pages, in pages.
+    pub huge_pages_total: u64,
+    /// The number of huge pages in the pool that are not yet allocated, in pages.
+    pub huge_pages_free: u64,
+    /// The number of huge pages for which a commitment to allocate from the pool has been made, but
+    /// no allocation has yet been made, in pages.
+    pub huge_pages_rsvd: u64,
+    /// The number of huge pages in the pool above the configured size, in pages.
+    pub huge_pages_surp: u64,
+    /// The size of huge pages.
+    pub hugepagesize: u64,
+    /// Total memory consumed by huge pages of all sizes (since Linux 4.16).
+    pub hugetlb: u64,
+    /// Memory mapped by the kernel with 4 kB pages.
+    pub direct_map_4k: u64,
+    /// Memory mapped by the kernel with 2 MB pages.
+    pub direct_map_2m: u64,
+    /// Memory mapped by the kernel with 1 GB pages.
+    pub direct_map_1g: u64,
+}
+
+impl Meminfo {
+    /// Parses the contents of a `/proc/meminfo` file.
+    ///
+    /// Lines are dispatched on their label, and lines with unrecognized labels are skipped.
+    pub fn parse(input: &[u8]) -> Result<Meminfo> {
+        let input = str::from_utf8(input)
+                        .map_err(|_| Error::new(ErrorKind::InvalidInput, "meminfo is not UTF-8"))?;
+        let mut meminfo = Meminfo::default();
+        for (i, line) in input.lines().enumerate() {
+            let mut parts = line.splitn(2, ':');
+            let label = parts.next().unwrap_or("");
+            let value = match parts.next() {
+                Some(value) => value.trim().trim_end_matches(" kB"),
+                None => continue,
+            };
+            let field = match label {
+                "MemTotal"            => &mut meminfo.mem_total,
+                "MemFree"             => &mut meminfo.mem_free,
+                "MemAvailable"        => &mut meminfo.mem_available,
+                "Buffers"             => &mut meminfo.buffers,
+                "Cached"              => &mut meminfo.cached,
+                "SwapCached"          => &mut meminfo.swap_cached,
+                "Active"              => &mut meminfo.active,
+                "Inactive"            => &mut meminfo.inactive,
+                "Active(anon)"        => &mut meminfo.active_anon,
+                "Inactive(anon)"      => &mut meminfo.inactive_anon,
+                "Active(file)"        => &mut meminfo.active_file,
+                "Inactive(file)"      => &mut meminfo.inactive_file,
+                "Unevictable"         => &mut meminfo.unevictable,
+                "Mlocked"             => &mut meminfo.mlocked,
+                "SwapTotal"           => &mut meminfo.swap_total,
+                "SwapFree"            => &mut meminfo.swap_free,
+                "Dirty"               => &mut meminfo.dirty,
+                "Writeback"           => &mut meminfo.writeback,
+                "AnonPages"           => &mut meminfo.anon_pages,
+                "Mapped"              => &mut meminfo.mapped,
+                "Shmem"               => &mut meminfo.shmem,
+                "KReclaimable"        => &mut meminfo.kreclaimable,
+                "Slab"                => &mut meminfo.slab,
+                "SReclaimable"        => &mut meminfo.sreclaimable,
+                "SUnreclaim"          => &mut meminfo.sunreclaim,
+                "KernelStack"         => &mut meminfo.kernel_stack,
+                "PageTables"          => &mut meminfo.page_tables,
+                "NFS_Unstable"        => &mut meminfo.nfs_unstable,
+                "Bounce"              => &mut meminfo.bounce,
+                "WritebackTmp"        => &mut meminfo.writeback_tmp,
+                "CommitLimit"         => &mut meminfo.commit_limit,
+                "Committed_AS"        => &mut meminfo.committed_as,
+                "VmallocTotal"        => &mut meminfo.vmalloc_total,
+                "VmallocUsed"         => &mut meminfo.vmalloc_used,
+                "VmallocChunk"        => &mut meminfo.vmalloc_chunk,
+                "HardwareCorrupted"   => &mut meminfo.hardware_corrupted,
+                "AnonHugePages"       => &mut meminfo.anon_huge_pages,
+                "ShmemHugePages"      => &mut meminfo.shmem_huge_pages,
+                "ShmemPmdMapped"      => &mut meminfo.shmem_pmd_mapped,
+                "HugePages_Total"     => &mut meminfo.huge_pages_total,
+                "HugePages_Free"      => &mut meminfo.huge_pages_free,
+                "HugePages_Rsvd"      => &mut meminfo.huge_pages_rsvd,
+                "HugePages_Surp"      => &mut meminfo.huge_pages_surp,
+                "Hugepagesize"        => &mut meminfo.hugepagesize,
+                "Hugetlb"             => &mut meminfo.hugetlb,
+                "DirectMap4k"         => &mut meminfo.direct_map_4k,
+                "DirectMap2M"         => &mut meminfo.direct_map_2m,
+                "DirectMap1G"         => &mut meminfo.direct_map_1g,
+                _ => continue,
+            };
+            *field = value.parse().map_err(|_| {
+                let error = Error::new(ErrorKind::InvalidInput,
+                                       format!("invalid meminfo line: {:?}", line));
+                with_line(error, i + 1)
+            })?;
+        }
+        Ok(meminfo)
+    }
+}
+
+/// Returns system memory usage statistics.
+pub fn meminfo() -> Result<Meminfo> {
+    let mut buf = Vec::new();
+    File::open("/proc/meminfo")?.read_to_end(&mut buf)?;
+    Meminfo::parse(&buf).map_err(|error| with_path(error, Path::new("/proc/meminfo")))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Meminfo, meminfo};
+
+    /// Test that the system meminfo file can be parsed.
+    #[test]
+    fn test_meminfo() {
+        let meminfo = meminfo().unwrap();
+        assert!(meminfo.mem_total > 0);
+    }
+
+    #[test]
+    fn test_parse_meminfo() {
+        let text = b"MemTotal:        6158152 kB\n\
+                     MemFree:         4167992 kB\n\
+                     MemAvailable:    5631028 kB\n\
+                     Active(anon):         32 kB\n\
+                     Zswap:                 0 kB\n\
+                     FutureField:      unknown\n\
+                     VmallocTotal:   34359738367 kB\n\
+                     HugePages_Total:       4\n\
+                     Hugepagesize:       2048 kB\n";
+        let meminfo = Meminfo::parse(text).unwrap();
+        assert_eq!(6158152, meminfo.mem_total);
+        assert_eq!(4167992, meminfo.mem_free);
+        assert_eq!(5631028, meminfo.mem_available);
+        assert_eq!(32, meminfo.active_anon);
+        assert_eq!(34359738367, meminfo.vmalloc_total);
+        assert_eq!(4, meminfo.huge_pages_total);
+        assert_eq!(2048, meminfo.hugepagesize);
+        assert_eq!(0, meminfo.swap_total);
+
+        Meminfo::parse(b"MemTotal:  lots kB\n").unwrap_err();
+    }
+}
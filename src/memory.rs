@@ -0,0 +1,133 @@
+//! A system memory summary in the style of `free(1)`, derived from `/proc/meminfo`.
+
+use std::io::Result;
+
+use meminfo::{Meminfo, meminfo};
+
+/// Usage of physical memory, as reported by the `Mem:` line of `free(1)`.
+///
+/// Sizes are in kB.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "export", derive(Serialize))]
+pub struct RamUsage {
+    /// Total usable memory.
+    pub total: u64,
+    /// Memory in use: `total` less `available`.
+    pub used: u64,
+    /// Unused memory.
+    pub free: u64,
+    /// Memory used by `tmpfs` filesystems and shared memory.
+    pub shared: u64,
+    /// Memory used by kernel buffers, the page cache, and reclaimable slabs.
+    pub buff_cache: u64,
+    /// An estimate of the memory available for starting new applications without swapping.
+    pub available: u64,
+}
+
+/// Usage of swap space, as reported by the `Swap:` line of `free(1)`.
+///
+/// Sizes are in kB.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "export", derive(Serialize))]
+pub struct SwapUsage {
+    /// Total swap space.
+    pub total: u64,
+    /// Swap space in use.
+    pub used: u64,
+    /// Unused swap space.
+    pub free: u64,
+}
+
+/// A system memory summary with the semantics of procps `free(1)`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "export", derive(Serialize))]
+pub struct Summary {
+    /// Usage of physical memory.
+    pub ram: RamUsage,
+    /// Usage of swap space.
+    pub swap: SwapUsage,
+}
+
+impl Summary {
+    /// Computes the summary from memory usage statistics.
+    ///
+    /// Like procps, reclaimable slab memory is counted as cache, and `used` is the memory which is
+    /// not available. When the kernel does not estimate the available memory (before Linux 3.14),
+    /// or the estimate exceeds the total, as happens in some containers, the free memory is used
+    /// instead.
+    pub fn from_meminfo(meminfo: &Meminfo) -> Summary {
+        let available = if meminfo.mem_available == 0 || meminfo.mem_available > meminfo.mem_total {
+            meminfo.mem_free
+        } else {
+            meminfo.mem_available
+        };
+        let ram = RamUsage {
+            total: meminfo.mem_total,
+            used: meminfo.mem_total.saturating_sub(available),
+            free: meminfo.mem_free,
+            shared: meminfo.shmem,
+            buff_cache: meminfo.buffers + meminfo.cached + meminfo.sreclaimable,
+            available,
+        };
+        let swap = SwapUsage {
+            total: meminfo.swap_total,
+            used: meminfo.swap_total.saturating_sub(meminfo.swap_free),
+            free: meminfo.swap_free,
+        };
+        Summary { ram, swap }
+    }
+}
+
+/// Returns a system memory summary with the semantics of procps `free(1)`.
+pub fn summary() -> Result<Summary> {
+    meminfo().map(|meminfo| Summary::from_meminfo(&meminfo))
+}
+
+#[cfg(test)]
+mod tests {
+    use meminfo::Meminfo;
+    use super::{Summary, summary};
+
+    /// Test that the system memory summary can be computed.
+    #[test]
+    fn test_summary() {
+        let summary = summary().unwrap();
+        assert!(summary.ram.total > 0);
+        assert!(summary.ram.used + summary.ram.available <= summary.ram.total);
+        assert_eq!(summary.swap.total, summary.swap.used + summary.swap.free);
+    }
+
+    #[test]
+    fn test_from_meminfo() {
+        let text = b"MemTotal:       16303428 kB\n\
+                     MemFree:         8613592 kB\n\
+                     MemAvailable:   12690360 kB\n\
+                     Buffers:          318200 kB\n\
+                     Cached:          3827964 kB\n\
+                     SwapTotal:       2097148 kB\n\
+                     SwapFree:        2000000 kB\n\
+                     Shmem:            612392 kB\n\
+                     SReclaimable:     280612 kB\n";
+        let meminfo = Meminfo::parse(text).unwrap();
+        let summary = Summary::from_meminfo(&meminfo);
+        assert_eq!(16303428, summary.ram.total);
+        assert_eq!(3613068, summary.ram.used);
+        assert_eq!(8613592, summary.ram.free);
+        assert_eq!(612392, summary.ram.shared);
+        assert_eq!(4426776, summary.ram.buff_cache);
+        assert_eq!(12690360, summary.ram.available);
+        assert_eq!(2097148, summary.swap.total);
+        assert_eq!(97148, summary.swap.used);
+        assert_eq!(2000000, summary.swap.free);
+
+        // Kernels before Linux 3.14 do not report the available memory.
+        let meminfo = Meminfo { mem_available: 0, ..meminfo };
+        let summary = Summary::from_meminfo(&meminfo);
+        assert_eq!(8613592, summary.ram.available);
+        assert_eq!(16303428 - 8613592, summary.ram.used);
+
+        // Containers may report more available memory than the total.
+        let meminfo = Meminfo { mem_available: 20000000, ..meminfo };
+        assert_eq!(8613592, Summary::from_meminfo(&meminfo).ram.available);
+    }
+}
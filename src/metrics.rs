@@ -0,0 +1,393 @@
+//! Conversion of parsed structures into Prometheus metric families.
+//!
+//! The metric names follow the conventions of the Prometheus client libraries for process
+//! metrics (`process_*`), and of the node exporter for system metrics (`node_*`), so that
+//! dashboards written against them work unchanged. Counters and times are converted to base units:
+//! bytes and seconds.
+//!
+//! The families can be returned from a `prometheus::core::Collector`, or encoded directly with a
+//! `prometheus::Encoder`.
+
+use std::io::Result;
+use std::time::UNIX_EPOCH;
+
+use prometheus::proto::{Counter, Gauge, LabelPair, Metric, MetricFamily, MetricType};
+
+use cpu::Cpu;
+use diskstats::DiskStat;
+use meminfo::Meminfo;
+use net::dev::DeviceStatus;
+use pid::{Io, Stat, Statm};
+
+/// The size of a sector in `/proc/diskstats`, which is always 512 bytes.
+const SECTOR_SIZE: f64 = 512.0;
+
+/// Creates a metric with the provided labels.
+fn metric(labels: &[(&str, &str)]) -> Metric {
+    let mut metric = Metric::default();
+    let labels = labels.iter().map(|&(name, value)| {
+        let mut label = LabelPair::default();
+        label.set_name(name.to_owned());
+        label.set_value(value.to_owned());
+        label
+    }).collect::<Vec<_>>();
+    // `into` converts to the collection types of both the protobuf and the plain data model of
+    // the prometheus crate, either of which may be enabled.
+    #[allow(clippy::useless_conversion)]
+    metric.set_label(labels.into());
+    metric
+}
+
+/// Creates a counter metric with the provided labels.
+fn counter(labels: &[(&str, &str)], value: f64) -> Metric {
+    let mut metric = metric(labels);
+    let mut counter = Counter::default();
+    counter.set_value(value);
+    metric.set_counter(counter);
+    metric
+}
+
+/// Creates a gauge metric with the provided labels.
+fn gauge(labels: &[(&str, &str)], value: f64) -> Metric {
+    let mut metric = metric(labels);
+    let mut gauge = Gauge::default();
+    gauge.set_value(value);
+    metric.set_gauge(gauge);
+    metric
+}
+
+/// Creates a metric family.
+fn family(name: &str, help: &str, metric_type: MetricType, metrics: Vec<Metric>) -> MetricFamily {
+    let mut family = MetricFamily::default();
+    family.set_name(name.to_owned());
+    family.set_help(help.to_owned());
+    family.set_field_type(metric_type);
+    #[allow(clippy::useless_conversion)]
+    family.set_metric(metrics.into());
+    family
+}
+
+/// Removes the families without metrics, which the text encoder rejects.
+fn non_empty(families: Vec<MetricFamily>) -> Vec<MetricFamily> {
+    families.into_iter().filter(|family| !family.get_metric().is_empty()).collect()
+}
+
+/// Returns the `process_cpu_seconds_total`, `process_threads`, and `process_start_time_seconds`
+/// metric families of a process.
+///
+/// Fails if the boot time, which the start time is relative to, can not be read.
+pub fn stat_metrics(stat: &Stat) -> Result<Vec<MetricFamily>> {
+    let cpu = stat.utime_duration() + stat.stime_duration();
+    let start_time = stat.started_at()?
+                         .duration_since(UNIX_EPOCH)
+                         .unwrap_or_default();
+    Ok(vec![
+        family("process_cpu_seconds_total", "Total user and system CPU time spent in seconds.",
+               MetricType::COUNTER, vec![counter(&[], cpu.as_secs_f64())]),
+        family("process_threads", "Number of OS threads in the process.",
+               MetricType::GAUGE, vec![gauge(&[], stat.num_threads as f64)]),
+        family("process_start_time_seconds",
+               "Start time of the process since unix epoch in seconds.",
+               MetricType::GAUGE, vec![gauge(&[], start_time.as_secs_f64())]),
+    ])
+}
+
+/// Returns the `process_virtual_memory_bytes` and `process_resident_memory_bytes` metric families
+/// of a process.
+pub fn statm_metrics(statm: &Statm) -> Vec<MetricFamily> {
+    vec![
+        family("process_virtual_memory_bytes", "Virtual memory size in bytes.",
+               MetricType::GAUGE, vec![gauge(&[], statm.size_bytes() as f64)]),
+        family("process_resident_memory_bytes", "Resident memory size in bytes.",
+               MetricType::GAUGE, vec![gauge(&[], statm.resident_bytes() as f64)]),
+    ]
+}
+
+/// Returns the `process_io_*` metric families of a process.
+pub fn io_metrics(io: &Io) -> Vec<MetricFamily> {
+    let counters = [
+        ("process_io_read_chars_total", "Bytes read by read system calls.", io.rchar),
+        ("process_io_write_chars_total", "Bytes written by write system calls.", io.wchar),
+        ("process_io_read_syscalls_total", "Read system calls.", io.syscr),
+        ("process_io_write_syscalls_total", "Write system calls.", io.syscw),
+        ("process_io_read_bytes_total", "Bytes read from storage.", io.read_bytes),
+        ("process_io_write_bytes_total", "Bytes written to storage.", io.write_bytes),
+        ("process_io_cancelled_write_bytes_total",
+         "Bytes whose write to storage was cancelled by truncation.", io.cancelled_write_bytes),
+    ];
+    counters.iter().map(|&(name, help, value)| {
+        family(name, help, MetricType::COUNTER, vec![counter(&[], value as f64)])
+    }).collect()
+}
+
+/// Returns the `node_cpu_seconds_total` and `node_cpu_guest_seconds_total` metric families of
+/// the CPUs, as returned by `cpu::cpus`.
+///
+/// The aggregate of all CPUs, which has no ID, is skipped.
+pub fn cpu_metrics(cpus: &[Cpu]) -> Vec<MetricFamily> {
+    let mut seconds = Vec::new();
+    let mut guest_seconds = Vec::new();
+    for cpu in cpus {
+        let id = match cpu.id {
+            Some(id) => id.to_string(),
+            None => continue,
+        };
+        let modes = [
+            ("user", cpu.user_duration()),
+            ("nice", cpu.nice_duration()),
+            ("system", cpu.system_duration()),
+            ("idle", cpu.idle_duration()),
+            ("iowait", cpu.iowait_duration()),
+            ("irq", cpu.irq_duration()),
+            ("softirq", cpu.softirq_duration()),
+            ("steal", cpu.steal_duration()),
+        ];
+        for &(mode, duration) in &modes {
+            seconds.push(counter(&[("cpu", &id), ("mode", mode)], duration.as_secs_f64()));
+        }
+        for &(mode, duration) in &[("user", cpu.guest_duration()),
+                                   ("nice", cpu.guest_nice_duration())] {
+            guest_seconds.push(counter(&[("cpu", &id), ("mode", mode)], duration.as_secs_f64()));
+        }
+    }
+    non_empty(vec![
+        family("node_cpu_seconds_total", "Seconds the CPUs spent in each mode.",
+               MetricType::COUNTER, seconds),
+        family("node_cpu_guest_seconds_total",
+               "Seconds the CPUs spent in guests (VMs) for each mode.",
+               MetricType::COUNTER, guest_seconds),
+    ])
+}
+
+/// Returns a `node_memory_*` gauge family per field of the memory statistics.
+///
+/// The families are named after the `/proc/meminfo` labels, with sizes converted to bytes and
+/// suffixed with `_bytes`, e.g. `node_memory_MemAvailable_bytes`. The huge page counts are not
+/// sizes, and are not suffixed.
+pub fn meminfo_metrics(meminfo: &Meminfo) -> Vec<MetricFamily> {
+    let sizes = [
+        ("MemTotal", meminfo.mem_total),
+        ("MemFree", meminfo.mem_free),
+        ("MemAvailable", meminfo.mem_available),
+        ("Buffers", meminfo.buffers),
+        ("Cached", meminfo.cached),
+        ("SwapCached", meminfo.swap_cached),
+        ("Active", meminfo.active),
+        ("Inactive", meminfo.inactive),
+        ("Active_anon", meminfo.active_anon),
+        ("Inactive_anon", meminfo.inactive_anon),
+        ("Active_file", meminfo.active_file),
+        ("Inactive_file", meminfo.inactive_file),
+        ("Unevictable", meminfo.unevictable),
+        ("Mlocked", meminfo.mlocked),
+        ("SwapTotal", meminfo.swap_total),
+        ("SwapFree", meminfo.swap_free),
+        ("Dirty", meminfo.dirty),
+        ("Writeback", meminfo.writeback),
+        ("AnonPages", meminfo.anon_pages),
+        ("Mapped", meminfo.mapped),
+        ("Shmem", meminfo.shmem),
+        ("KReclaimable", meminfo.kreclaimable),
+        ("Slab", meminfo.slab),
+        ("SReclaimable", meminfo.sreclaimable),
+        ("SUnreclaim", meminfo.sunreclaim),
+        ("KernelStack", meminfo.kernel_stack),
+        ("PageTables", meminfo.page_tables),
+        ("NFS_Unstable", meminfo.nfs_unstable),
+        ("Bounce", meminfo.bounce),
+        ("WritebackTmp", meminfo.writeback_tmp),
+        ("CommitLimit", meminfo.commit_limit),
+        ("Committed_AS", meminfo.committed_as),
+        ("VmallocTotal", meminfo.vmalloc_total),
+        ("VmallocUsed", meminfo.vmalloc_used),
+        ("VmallocChunk", meminfo.vmalloc_chunk),
+        ("HardwareCorrupted", meminfo.hardware_corrupted),
+        ("AnonHugePages", meminfo.anon_huge_pages),
+        ("ShmemHugePages", meminfo.shmem_huge_pages),
+        ("ShmemPmdMapped", meminfo.shmem_pmd_mapped),
+        ("Hugepagesize", meminfo.hugepagesize),
+        ("Hugetlb", meminfo.hugetlb),
+        ("DirectMap4k", meminfo.direct_map_4k),
+        ("DirectMap2M", meminfo.direct_map_2m),
+        ("DirectMap1G", meminfo.direct_map_1g),
+    ];
+    let counts = [
+        ("HugePages_Total", meminfo.huge_pages_total),
+        ("HugePages_Free", meminfo.huge_pages_free),
+        ("HugePages_Rsvd", meminfo.huge_pages_rsvd),
+        ("HugePages_Surp", meminfo.huge_pages_surp),
+    ];
+    let sizes = sizes.iter().map(|&(label, kb)| {
+        family(&format!("node_memory_{}_bytes", label),
+               &format!("Memory information field {}_bytes.", label),
+               MetricType::GAUGE, vec![gauge(&[], kb as f64 * 1024.0)])
+    });
+    let counts = counts.iter().map(|&(label, pages)| {
+        family(&format!("node_memory_{}", label),
+               &format!("Memory information field {}.", label),
+               MetricType::GAUGE, vec![gauge(&[], pages as f64)])
+    });
+    sizes.chain(counts).collect()
+}
+
+/// Creates a counter family with a metric per block device which has a value.
+fn disk_counter<F>(disks: &[DiskStat], name: &str, help: &str, value: F) -> MetricFamily
+where F: Fn(&DiskStat) -> Option<f64> {
+    let metrics = disks.iter()
+                       .filter_map(|disk| {
+                           value(disk).map(|v| counter(&[("device", &disk.name)], v))
+                       })
+                       .collect();
+    family(name, help, MetricType::COUNTER, metrics)
+}
+
+/// Returns the `node_disk_*` metric families of the block devices, labeled by device name.
+///
+/// Families of fields which the running kernel does not report are omitted.
+pub fn diskstats_metrics(disks: &[DiskStat]) -> Vec<MetricFamily> {
+    // The times in `/proc/diskstats` are in milliseconds.
+    let secs = |ms: u64| ms as f64 / 1000.0;
+    let bytes = |sectors: u64| sectors as f64 * SECTOR_SIZE;
+    let io_now = disks.iter()
+                      .map(|disk| gauge(&[("device", &disk.name)], disk.ios_in_progress as f64))
+                      .collect();
+    non_empty(vec![
+        disk_counter(disks, "node_disk_reads_completed_total",
+                     "The total number of reads completed successfully.",
+                     |d| Some(d.reads_completed as f64)),
+        disk_counter(disks, "node_disk_reads_merged_total", "The total number of reads merged.",
+                     |d| Some(d.reads_merged as f64)),
+        disk_counter(disks, "node_disk_read_bytes_total",
+                     "The total number of bytes read successfully.",
+                     |d| Some(bytes(d.sectors_read))),
+        disk_counter(disks, "node_disk_read_time_seconds_total",
+                     "The total number of seconds spent by all reads.",
+                     |d| Some(secs(d.time_reading))),
+        disk_counter(disks, "node_disk_writes_completed_total",
+                     "The total number of writes completed successfully.",
+                     |d| Some(d.writes_completed as f64)),
+        disk_counter(disks, "node_disk_writes_merged_total", "The number of writes merged.",
+                     |d| Some(d.writes_merged as f64)),
+        disk_counter(disks, "node_disk_written_bytes_total",
+                     "The total number of bytes written successfully.",
+                     |d| Some(bytes(d.sectors_written))),
+        disk_counter(disks, "node_disk_write_time_seconds_total",
+                     "This is the total number of seconds spent by all writes.",
+                     |d| Some(secs(d.time_writing))),
+        family("node_disk_io_now", "The number of I/Os currently in progress.",
+               MetricType::GAUGE, io_now),
+        disk_counter(disks, "node_disk_io_time_seconds_total", "Total seconds spent doing I/Os.",
+                     |d| Some(secs(d.time_io))),
+        disk_counter(disks, "node_disk_io_time_weighted_seconds_total",
+                     "The weighted number of seconds spent doing I/Os.",
+                     |d| Some(secs(d.weighted_time_io))),
+        disk_counter(disks, "node_disk_discards_completed_total",
+                     "The total number of discards completed successfully.",
+                     |d| d.discards_completed.map(|n| n as f64)),
+        disk_counter(disks, "node_disk_discards_merged_total",
+                     "The total number of discards merged.",
+                     |d| d.discards_merged.map(|n| n as f64)),
+        disk_counter(disks, "node_disk_discarded_sectors_total",
+                     "The total number of sectors discarded successfully.",
+                     |d| d.sectors_discarded.map(|n| n as f64)),
+        disk_counter(disks, "node_disk_discard_time_seconds_total",
+                     "This is the total number of seconds spent by all discards.",
+                     |d| d.time_discarding.map(secs)),
+        disk_counter(disks, "node_disk_flush_requests_total",
+                     "The total number of flush requests completed successfully.",
+                     |d| d.flush_requests_completed.map(|n| n as f64)),
+        disk_counter(disks, "node_disk_flush_requests_time_seconds_total",
+                     "This is the total number of seconds spent by all flush requests.",
+                     |d| d.time_flushing.map(secs)),
+    ])
+}
+
+/// Returns the `node_network_*` metric families of the network devices, labeled by device name.
+pub fn net_dev_metrics(devices: &[DeviceStatus]) -> Vec<MetricFamily> {
+    type Field = fn(&DeviceStatus) -> u64;
+    let counters: [(&str, Field); 16] = [
+        ("receive_bytes", |d| d.receive_bytes),
+        ("receive_packets", |d| d.receive_packets),
+        ("receive_errs", |d| d.receive_errs),
+        ("receive_drop", |d| d.receive_drop),
+        ("receive_fifo", |d| d.receive_fifo),
+        ("receive_frame", |d| d.receive_frame),
+        ("receive_compressed", |d| d.receive_compressed),
+        ("receive_multicast", |d| d.receive_multicast),
+        ("transmit_bytes", |d| d.transmit_bytes),
+        ("transmit_packets", |d| d.transmit_packets),
+        ("transmit_errs", |d| d.transmit_errs),
+        ("transmit_drop", |d| d.transmit_drop),
+        ("transmit_fifo", |d| d.transmit_fifo),
+        ("transmit_colls", |d| d.transmit_colls),
+        ("transmit_carrier", |d| d.transmit_carrier),
+        ("transmit_compressed", |d| d.transmit_compressed),
+    ];
+    non_empty(counters.iter().map(|&(field, value)| {
+        let metrics = devices.iter()
+                             .map(|device| {
+                                 counter(&[("device", &device.interface)], value(device) as f64)
+                             })
+                             .collect();
+        family(&format!("node_network_{}_total", field),
+               &format!("Network device statistic {}.", field),
+               MetricType::COUNTER, metrics)
+    }).collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use prometheus::{Encoder, TextEncoder};
+    use prometheus::proto::MetricFamily;
+
+    use cpu::cpus;
+    use diskstats::diskstats;
+    use meminfo::meminfo;
+    use net::dev::dev;
+    use pid::{io_self, stat_self, statm_self};
+    use super::{cpu_metrics, diskstats_metrics, io_metrics, meminfo_metrics, net_dev_metrics,
+                stat_metrics, statm_metrics};
+
+    /// Encodes the families in the text exposition format.
+    fn encode(families: &[MetricFamily]) -> String {
+        let mut buf = Vec::new();
+        TextEncoder::new().encode(families, &mut buf).unwrap();
+        String::from_utf8(buf).unwrap()
+    }
+
+    #[test]
+    fn test_process_metrics() {
+        let text = encode(&stat_metrics(&stat_self().unwrap()).unwrap());
+        assert!(text.contains("# TYPE process_cpu_seconds_total counter\n"));
+        assert!(text.contains("\nprocess_start_time_seconds "));
+        let text = encode(&statm_metrics(&statm_self().unwrap()));
+        assert!(text.contains("\nprocess_resident_memory_bytes "));
+        let text = encode(&io_metrics(&io_self().unwrap()));
+        assert!(text.contains("\nprocess_io_read_chars_total "));
+    }
+
+    #[test]
+    fn test_node_metrics() {
+        let text = encode(&cpu_metrics(&cpus().unwrap()));
+        assert!(text.contains("node_cpu_seconds_total{cpu=\"0\",mode=\"idle\"} "));
+        let text = encode(&meminfo_metrics(&meminfo().unwrap()));
+        assert!(text.contains("# TYPE node_memory_MemTotal_bytes gauge\n"));
+        assert!(text.contains("\nnode_memory_HugePages_Total "));
+        let devices = dev().unwrap();
+        let text = encode(&net_dev_metrics(&devices));
+        assert!(text.contains(&format!("node_network_receive_bytes_total{{device=\"{}\"}} ",
+                                       devices[0].interface)));
+        encode(&diskstats_metrics(&diskstats().unwrap()));
+    }
+
+    #[test]
+    fn test_diskstats_metrics() {
+        let disk = ::diskstats::DiskStat::parse_line(
+            b"   8       0 sda 100 2 800 30 50 1 400 20 0 40 50 0 0 0 0").unwrap();
+        let text = encode(&diskstats_metrics(&[disk]));
+        assert!(text.contains("node_disk_read_bytes_total{device=\"sda\"} 409600\n"));
+        assert!(text.contains("node_disk_read_time_seconds_total{device=\"sda\"} 0.03\n"));
+        assert!(text.contains("node_disk_discards_completed_total{device=\"sda\"} 0\n"));
+        assert!(!text.contains("node_disk_flush_requests_total{"));
+    }
+}
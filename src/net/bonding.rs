@@ -0,0 +1,414 @@
+//! Bonding interface status from `/proc/net/bonding/`.
+
+use std::fs::{self, File};
+use std::io::{ErrorKind, Read, Result};
+use std::path::Path;
+
+use parsers::{invalid_input, parse, parse_str, split_label, with_line, with_path};
+
+const NET_BONDING_DIR: &str = "/proc/net/bonding";
+
+/// The MII link status of a bond or slave.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "export", derive(Serialize))]
+pub enum MiiStatus {
+    /// The link is up.
+    Up,
+    /// The link is down.
+    Down,
+    /// The link went down, and the slave will be disabled once the down delay elapses.
+    GoingDown,
+    /// The link came back up, and the slave will be enabled once the up delay elapses.
+    GoingBack,
+}
+
+/// Parses an MII status.
+fn parse_mii_status(s: &str) -> Result<MiiStatus> {
+    match s {
+        "up" => Ok(MiiStatus::Up),
+        "down" => Ok(MiiStatus::Down),
+        "going down" => Ok(MiiStatus::GoingDown),
+        "going back" => Ok(MiiStatus::GoingBack),
+        _ => Err(invalid_input(format!("invalid MII status: {:?}", s))),
+    }
+}
+
+/// The 802.3ad (LACP) status of a bond.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "export", derive(Serialize))]
+pub struct LacpInfo {
+    /// Whether LACP frames are sent actively (`on`) or only in reply (`off`) (since Linux 5.19).
+    pub active: Option<bool>,
+    /// The rate at which the partner is asked to send LACP frames, `slow` or `fast`.
+    pub rate: Option<String>,
+    /// The minimum number of links which must be up for the bond to be up.
+    pub min_links: Option<u32>,
+    /// The aggregator selection policy, e.g. `stable`.
+    pub aggregator_selection: Option<String>,
+    /// The system priority (only shown to privileged users).
+    pub system_priority: Option<u32>,
+    /// The system MAC address (only shown to privileged users).
+    pub system_mac: Option<String>,
+    /// The ID of the active aggregator.
+    pub aggregator_id: Option<u32>,
+    /// The number of ports in the active aggregator.
+    pub number_of_ports: Option<u32>,
+    /// The actor key of the active aggregator (only shown to privileged users).
+    pub actor_key: Option<u32>,
+    /// The partner key of the active aggregator.
+    pub partner_key: Option<u32>,
+    /// The MAC address of the partner of the active aggregator.
+    pub partner_mac: Option<String>,
+}
+
+/// A slave interface of a bond.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "export", derive(Serialize))]
+pub struct BondSlave {
+    /// The interface name.
+    pub interface: String,
+    /// The MII link status.
+    pub mii_status: MiiStatus,
+    /// The link speed in Mbps, or `None` if unknown.
+    pub speed: Option<u32>,
+    /// The duplex mode, e.g. `full`, or `None` if unknown.
+    pub duplex: Option<String>,
+    /// The number of times the link has failed.
+    pub link_failure_count: u64,
+    /// The permanent hardware address of the interface.
+    pub permanent_hw_addr: Option<String>,
+    /// The transmit queue ID of the slave.
+    pub queue_id: Option<u16>,
+    /// The ID of the 802.3ad aggregator which the slave belongs to.
+    pub aggregator_id: Option<u32>,
+}
+
+/// The status of a bonding interface.
+///
+/// Fields which are only printed for some bonding modes are `None` for the other modes.
+///
+/// See `Linux/Documentation/networking/bonding.rst` and `Linux/drivers/net/bonding/bond_procfs.c`.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "export", derive(Serialize))]
+pub struct Bond {
+    /// The version of the bonding driver.
+    pub driver_version: Option<String>,
+    /// The bonding mode, as described by the kernel, e.g. `fault-tolerance (active-backup)`.
+    pub mode: String,
+    /// The transmit hash policy, for the balance-xor and 802.3ad modes.
+    pub transmit_hash_policy: Option<String>,
+    /// The primary slave, and its reselection policy, for the active-backup, balance-tlb, and
+    /// balance-alb modes.
+    pub primary_slave: Option<String>,
+    /// The currently active slave, for the active-backup, balance-tlb, and balance-alb modes.
+    pub active_slave: Option<String>,
+    /// The MII link status of the bond.
+    pub mii_status: MiiStatus,
+    /// The MII polling interval in milliseconds.
+    pub mii_polling_interval: Option<u32>,
+    /// The delay before enabling a slave after its link comes up, in milliseconds.
+    pub up_delay: Option<u32>,
+    /// The delay before disabling a slave after its link goes down, in milliseconds.
+    pub down_delay: Option<u32>,
+    /// The 802.3ad status, for the 802.3ad mode.
+    pub lacp: Option<LacpInfo>,
+    /// The slave interfaces.
+    pub slaves: Vec<BondSlave>,
+}
+
+/// Parses an optional value, which the kernel prints as `None` when absent.
+fn parse_name(s: &str) -> Option<String> {
+    match s {
+        "None" => None,
+        _ => Some(s.to_owned()),
+    }
+}
+
+/// The fields of a bond which are set as they are parsed.
+#[derive(Default)]
+struct BondBuilder {
+    driver_version: Option<String>,
+    mode: Option<String>,
+    transmit_hash_policy: Option<String>,
+    primary_slave: Option<String>,
+    active_slave: Option<String>,
+    mii_status: Option<MiiStatus>,
+    mii_polling_interval: Option<u32>,
+    up_delay: Option<u32>,
+    down_delay: Option<u32>,
+    lacp: Option<LacpInfo>,
+    slaves: Vec<BondSlave>,
+}
+
+/// Parses a `Label: value` line of a slave section into the slave.
+///
+/// Fields which are not known, such as the per-slave 802.3ad details, are ignored.
+fn parse_slave_field(slave: &mut BondSlave, label: &str, value: &str) -> Result<()> {
+    match label {
+        "MII Status" => slave.mii_status = parse_mii_status(value)?,
+        "Speed" => {
+            slave.speed = match value.strip_suffix(" Mbps") {
+                Some(speed) => Some(parse(speed)?),
+                None => None,
+            }
+        },
+        "Duplex" => slave.duplex = if value == "Unknown" { None } else { Some(value.to_owned()) },
+        "Link Failure Count" => slave.link_failure_count = parse(value)?,
+        "Permanent HW addr" => slave.permanent_hw_addr = Some(value.to_owned()),
+        "Slave queue ID" => slave.queue_id = Some(parse(value)?),
+        "Aggregator ID" => slave.aggregator_id = Some(parse(value)?),
+        _ => (),
+    }
+    Ok(())
+}
+
+/// Parses a `Label: value` line of the bond or 802.3ad sections into the bond.
+fn parse_bond_field(bond: &mut BondBuilder, label: &str, value: &str) -> Result<()> {
+    if let Some(ref mut lacp) = bond.lacp {
+        match label {
+            "LACP active" => lacp.active = Some(value == "on"),
+            "LACP rate" => lacp.rate = Some(value.to_owned()),
+            "Min links" => lacp.min_links = Some(parse(value)?),
+            "Aggregator selection policy (ad_select)" => {
+                lacp.aggregator_selection = Some(value.to_owned())
+            },
+            "System priority" => lacp.system_priority = Some(parse(value)?),
+            "System MAC address" => lacp.system_mac = Some(value.to_owned()),
+            "Aggregator ID" => lacp.aggregator_id = Some(parse(value)?),
+            "Number of ports" => lacp.number_of_ports = Some(parse(value)?),
+            "Actor Key" => lacp.actor_key = Some(parse(value)?),
+            "Partner Key" => lacp.partner_key = Some(parse(value)?),
+            "Partner Mac Address" => lacp.partner_mac = Some(value.to_owned()),
+            _ => (),
+        }
+        return Ok(());
+    }
+    match label {
+        "Bonding Mode" => bond.mode = Some(value.to_owned()),
+        "Transmit Hash Policy" => bond.transmit_hash_policy = Some(value.to_owned()),
+        "Primary Slave" => bond.primary_slave = parse_name(value),
+        "Currently Active Slave" => bond.active_slave = parse_name(value),
+        "MII Status" => bond.mii_status = Some(parse_mii_status(value)?),
+        "MII Polling Interval (ms)" => bond.mii_polling_interval = Some(parse(value)?),
+        "Up Delay (ms)" => bond.up_delay = Some(parse(value)?),
+        "Down Delay (ms)" => bond.down_delay = Some(parse(value)?),
+        _ => (),
+    }
+    Ok(())
+}
+
+/// Parses a line of a bonding file into the bond.
+fn parse_bond_line(bond: &mut BondBuilder, line: &str) -> Result<()> {
+    if let Some(version) = line.strip_prefix("Ethernet Channel Bonding Driver: ") {
+        bond.driver_version = Some(version.to_owned());
+        return Ok(());
+    }
+    if line == "802.3ad info" {
+        bond.lacp = Some(LacpInfo::default());
+        return Ok(());
+    }
+    let (label, value) = match split_label(line) {
+        Some((label, value)) => (label.trim(), value.trim()),
+        None => return Ok(()),
+    };
+    if label == "Slave Interface" {
+        bond.slaves.push(BondSlave {
+            interface: value.to_owned(),
+            mii_status: MiiStatus::Down,
+            speed: None,
+            duplex: None,
+            link_failure_count: 0,
+            permanent_hw_addr: None,
+            queue_id: None,
+            aggregator_id: None,
+        });
+        return Ok(());
+    }
+    match bond.slaves.last_mut() {
+        Some(slave) => parse_slave_field(slave, label, value),
+        None => parse_bond_field(bond, label, value),
+    }
+}
+
+impl Bond {
+    /// Parses the contents of a `/proc/net/bonding/<bond>` file.
+    pub fn parse(input: &[u8]) -> Result<Bond> {
+        let mut bond = BondBuilder::default();
+        for (i, line) in parse_str(input)?.lines().enumerate() {
+            parse_bond_line(&mut bond, line).map_err(|error| with_line(error, i + 1))?;
+        }
+        Ok(Bond {
+            driver_version: bond.driver_version,
+            mode: bond.mode.ok_or_else(|| invalid_input("missing bonding mode"))?,
+            transmit_hash_policy: bond.transmit_hash_policy,
+            primary_slave: bond.primary_slave,
+            active_slave: bond.active_slave,
+            mii_status: bond.mii_status.ok_or_else(|| invalid_input("missing MII status"))?,
+            mii_polling_interval: bond.mii_polling_interval,
+            up_delay: bond.up_delay,
+            down_delay: bond.down_delay,
+            lacp: bond.lacp,
+            slaves: bond.slaves,
+        })
+    }
+}
+
+/// Reads the status of the named bonding interface from the bonding directory.
+fn bond_file(dir: &str, name: &str) -> Result<Bond> {
+    let path = Path::new(dir).join(name);
+    let mut buf = Vec::new();
+    File::open(&path)?.read_to_end(&mut buf)?;
+    Bond::parse(&buf).map_err(|error| with_path(error, &path))
+}
+
+/// Reads the status of each bonding interface in the bonding directory at the path.
+pub(crate) fn bonding_dir(path: &str) -> Result<Vec<(String, Bond)>> {
+    let dir = match fs::read_dir(path) {
+        Ok(dir) => dir,
+        Err(ref error) if error.kind() == ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(error) => return Err(error),
+    };
+    let mut bonds = Vec::new();
+    for entry in dir {
+        let name = entry?.file_name().to_string_lossy().into_owned();
+        // Bonds may be deleted while the directory is being read.
+        match bond_file(path, &name) {
+            Ok(bond) => bonds.push((name, bond)),
+            Err(ref error) if error.kind() == ErrorKind::NotFound => continue,
+            Err(error) => return Err(error),
+        }
+    }
+    bonds.sort_by(|a, b| a.0.cmp(&b.0));
+    Ok(bonds)
+}
+
+/// Returns the status of the bonding interface with the provided name.
+pub fn bond(name: &str) -> Result<Bond> {
+    bond_file(NET_BONDING_DIR, name)
+}
+
+/// Returns the status of each bonding interface in the network namespace of the current process,
+/// by interface name.
+///
+/// Returns no interfaces if the bonding driver is not loaded.
+pub fn bonding() -> Result<Vec<(String, Bond)>> {
+    bonding_dir(NET_BONDING_DIR)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Bond, MiiStatus, bonding};
+
+    /// Test that the bonding interfaces can be read, if any.
+    #[test]
+    fn test_bonding() {
+        bonding().unwrap();
+    }
+
+    #[test]
+    fn test_parse_active_backup() {
+        let text = b"Ethernet Channel Bonding Driver: v3.7.1 (April 27, 2011)\n\
+                     \n\
+                     Bonding Mode: fault-tolerance (active-backup)\n\
+                     Primary Slave: eth0 (primary_reselect always)\n\
+                     Currently Active Slave: eth1\n\
+                     MII Status: up\n\
+                     MII Polling Interval (ms): 100\n\
+                     Up Delay (ms): 0\n\
+                     Down Delay (ms): 200\n\
+                     \n\
+                     Slave Interface: eth0\n\
+                     MII Status: down\n\
+                     Speed: Unknown\n\
+                     Duplex: Unknown\n\
+                     Link Failure Count: 3\n\
+                     Permanent HW addr: 52:54:00:12:34:56\n\
+                     Slave queue ID: 0\n\
+                     \n\
+                     Slave Interface: eth1\n\
+                     MII Status: up\n\
+                     Speed: 1000 Mbps\n\
+                     Duplex: full\n\
+                     Link Failure Count: 0\n\
+                     Permanent HW addr: 52:54:00:12:34:57\n\
+                     Slave queue ID: 0\n";
+        let bond = Bond::parse(text).unwrap();
+        assert_eq!(Some("v3.7.1 (April 27, 2011)"), bond.driver_version.as_deref());
+        assert_eq!("fault-tolerance (active-backup)", bond.mode);
+        assert_eq!(Some("eth0 (primary_reselect always)"), bond.primary_slave.as_deref());
+        assert_eq!(Some("eth1"), bond.active_slave.as_deref());
+        assert_eq!(MiiStatus::Up, bond.mii_status);
+        assert_eq!(Some(100), bond.mii_polling_interval);
+        assert_eq!(Some(200), bond.down_delay);
+        assert_eq!(None, bond.lacp);
+
+        assert_eq!(2, bond.slaves.len());
+        assert_eq!("eth0", bond.slaves[0].interface);
+        assert_eq!(MiiStatus::Down, bond.slaves[0].mii_status);
+        assert_eq!(None, bond.slaves[0].speed);
+        assert_eq!(None, bond.slaves[0].duplex);
+        assert_eq!(3, bond.slaves[0].link_failure_count);
+        assert_eq!(Some(1000), bond.slaves[1].speed);
+        assert_eq!(Some("full"), bond.slaves[1].duplex.as_deref());
+        assert_eq!(Some("52:54:00:12:34:57"), bond.slaves[1].permanent_hw_addr.as_deref());
+    }
+
+    #[test]
+    fn test_parse_lacp() {
+        let text = b"Ethernet Channel Bonding Driver: v5.15.0\n\
+                     \n\
+                     Bonding Mode: IEEE 802.3ad Dynamic link aggregation\n\
+                     Transmit Hash Policy: layer3+4 (1)\n\
+                     MII Status: up\n\
+                     MII Polling Interval (ms): 100\n\
+                     Up Delay (ms): 0\n\
+                     Down Delay (ms): 0\n\
+                     Peer Notification Delay (ms): 0\n\
+                     \n\
+                     802.3ad info\n\
+                     LACP active: on\n\
+                     LACP rate: fast\n\
+                     Min links: 0\n\
+                     Aggregator selection policy (ad_select): stable\n\
+                     System priority: 65535\n\
+                     System MAC address: 52:54:00:12:34:56\n\
+                     Active Aggregator Info:\n\
+                     \tAggregator ID: 1\n\
+                     \tNumber of ports: 2\n\
+                     \tActor Key: 15\n\
+                     \tPartner Key: 32773\n\
+                     \tPartner Mac Address: 00:1c:73:aa:bb:cc\n\
+                     \n\
+                     Slave Interface: eth0\n\
+                     MII Status: going back\n\
+                     Speed: 10000 Mbps\n\
+                     Duplex: full\n\
+                     Link Failure Count: 1\n\
+                     Permanent HW addr: 52:54:00:12:34:56\n\
+                     Slave queue ID: 0\n\
+                     Aggregator ID: 1\n\
+                     Actor Churn State: none\n\
+                     details actor lacp pdu:\n\
+                     \x20   system priority: 65535\n\
+                     \x20   port state: 63\n";
+        let bond = Bond::parse(text).unwrap();
+        assert_eq!(Some("layer3+4 (1)"), bond.transmit_hash_policy.as_deref());
+        let lacp = bond.lacp.unwrap();
+        assert_eq!(Some(true), lacp.active);
+        assert_eq!(Some("fast"), lacp.rate.as_deref());
+        assert_eq!(Some("stable"), lacp.aggregator_selection.as_deref());
+        assert_eq!(Some(65535), lacp.system_priority);
+        assert_eq!(Some(1), lacp.aggregator_id);
+        assert_eq!(Some(2), lacp.number_of_ports);
+        assert_eq!(Some(32773), lacp.partner_key);
+        assert_eq!(Some("00:1c:73:aa:bb:cc"), lacp.partner_mac.as_deref());
+
+        assert_eq!(1, bond.slaves.len());
+        assert_eq!(MiiStatus::GoingBack, bond.slaves[0].mii_status);
+        assert_eq!(Some(10000), bond.slaves[0].speed);
+        assert_eq!(Some(1), bond.slaves[0].aggregator_id);
+
+        Bond::parse(b"MII Status: up\n").unwrap_err();
+        Bond::parse(b"Bonding Mode: round-robin\nMII Status: sideways\n").unwrap_err();
+    }
+}
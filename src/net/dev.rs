@@ -2,18 +2,17 @@
 
 use std::fs::File;
 use std::io::{Read, Result};
+use std::path::Path;
 
-use nom::{space, line_ending};
-use parsers::{
-    map_result,
-    parse_u64,
-};
+use parsers::{Fields, invalid_input, parse_lines, parse_str, with_path};
 
-const NET_DEV_FILE: &'static str = "/proc/net/dev";
+const NET_DEV_FILE: &str = "/proc/net/dev";
 
 /// Network device status information.
 ///
 /// See `man 5 proc` and `Linux/net/core/net-procfs.c`
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "export", derive(Serialize))]
 pub struct DeviceStatus {
     /// Name of the interface representing this device.
     pub interface:           String,
@@ -53,76 +52,64 @@ pub struct DeviceStatus {
     pub transmit_compressed: u64,
 }
 
-named!(interface_stats<DeviceStatus>,
-    do_parse!(
-        opt!(space) >>
-        interface: take_until_and_consume!(":") >>
-        space >>
-        receive_bytes:       terminated!(parse_u64, space) >>
-        receive_packets:     terminated!(parse_u64, space) >>
-        receive_errs:        terminated!(parse_u64, space) >>
-        receive_drop:        terminated!(parse_u64, space) >>
-        receive_fifo:        terminated!(parse_u64, space) >>
-        receive_frame:       terminated!(parse_u64, space) >>
-        receive_compressed:  terminated!(parse_u64, space) >>
-        receive_multicast:   terminated!(parse_u64, space) >>
-        transmit_bytes:      terminated!(parse_u64, space) >>
-        transmit_packets:    terminated!(parse_u64, space) >>
-        transmit_errs:       terminated!(parse_u64, space) >>
-        transmit_drop:       terminated!(parse_u64, space) >>
-        transmit_fifo:       terminated!(parse_u64, space) >>
-        transmit_colls:      terminated!(parse_u64, space) >>
-        transmit_carrier:    terminated!(parse_u64, space) >>
-        transmit_compressed: parse_u64 >>
-        (DeviceStatus {
-            interface:           String::from_utf8_lossy(interface).to_string(),
-            receive_bytes:       receive_bytes,
-            receive_packets:     receive_packets,
-            receive_errs:        receive_errs,
-            receive_drop:        receive_drop,
-            receive_fifo:        receive_fifo,
-            receive_frame:       receive_frame,
-            receive_compressed:  receive_compressed,
-            receive_multicast:   receive_multicast,
-            transmit_bytes:      transmit_bytes,
-            transmit_packets:    transmit_packets,
-            transmit_errs:       transmit_errs,
-            transmit_drop:       transmit_drop,
-            transmit_fifo:       transmit_fifo,
-            transmit_colls:      transmit_colls,
-            transmit_carrier:    transmit_carrier,
-            transmit_compressed: transmit_compressed,
-        })));
-
-named!(interface_list< Vec<DeviceStatus> >,
-    do_parse!(
-        interfaces: separated_list!(line_ending, interface_stats) >>
-        line_ending >>
-        (interfaces)));
-
-named!(empty_list< Vec<DeviceStatus> >,
-    value!(Vec::new(), eof!()));
-
-named!(parse_dev< Vec<DeviceStatus> >,
-    do_parse!(
-        count!(take_until_and_consume!("\n"), 2) >>
-        interfaces: alt_complete!(interface_list | empty_list) >>
-        (interfaces)));
+impl DeviceStatus {
+    /// Parses a device line of a `/proc/net/dev` file, without the line terminator.
+    pub fn parse_line(input: &[u8]) -> Result<DeviceStatus> {
+        let line = parse_str(input)?;
+        let (interface, stats) = line.split_once(':').ok_or_else(|| {
+            invalid_input(format!("invalid network device line: {:?}", line))
+        })?;
+        let mut fields = Fields::new(stats);
+        let status = DeviceStatus {
+            interface:           interface.trim().to_owned(),
+            receive_bytes:       fields.parse()?,
+            receive_packets:     fields.parse()?,
+            receive_errs:        fields.parse()?,
+            receive_drop:        fields.parse()?,
+            receive_fifo:        fields.parse()?,
+            receive_frame:       fields.parse()?,
+            receive_compressed:  fields.parse()?,
+            receive_multicast:   fields.parse()?,
+            transmit_bytes:      fields.parse()?,
+            transmit_packets:    fields.parse()?,
+            transmit_errs:       fields.parse()?,
+            transmit_drop:       fields.parse()?,
+            transmit_fifo:       fields.parse()?,
+            transmit_colls:      fields.parse()?,
+            transmit_carrier:    fields.parse()?,
+            transmit_compressed: fields.parse()?,
+        };
+        fields.finish()?;
+        Ok(status)
+    }
+}
 
-/// Returns list of all network devices and information about their state.
-pub fn dev() -> Result<Vec<DeviceStatus>> {
-    let mut file = File::open(NET_DEV_FILE)?;
+/// Parses the net/dev file format, which has two header lines followed by a line per device.
+fn parse_dev(input: &[u8]) -> Result<Vec<DeviceStatus>> {
+    parse_lines(input, 2, DeviceStatus::parse_line)
+}
+
+/// Parses the provided net/dev file.
+pub(crate) fn dev_file(path: &str) -> Result<Vec<DeviceStatus>> {
+    let mut file = File::open(path)?;
 
     let mut buffer = vec![];
     file.read_to_end(&mut buffer)?;
 
-    map_result(parse_dev(buffer.as_slice()))
+    parse_dev(&buffer).map_err(|error| with_path(error, Path::new(path)))
+}
+
+/// Returns list of all network devices and information about their state.
+///
+/// The devices are those of the network namespace of the current process. See
+/// `pid::net::dev` for the devices of another process's network namespace.
+pub fn dev() -> Result<Vec<DeviceStatus>> {
+    dev_file(NET_DEV_FILE)
 }
 
 #[cfg(test)]
 mod test {
     use super::{dev, parse_dev};
-    use parsers::map_result;
 
     #[test]
     fn two_interfaces() {
@@ -131,7 +118,7 @@ mod test {
     lo:  206950    2701    0    0    0     0          0         0   206950    2701    0    0    0     0       0          0
 wlp58s0: 631994599  596110    0    1    0     0          0         0 47170335  384943    0    0    0     0       0          0
 "#;
-        let interfaces = map_result(parse_dev(file)).unwrap();
+        let interfaces = parse_dev(file).unwrap();
 
         assert!(interfaces.len() == 2);
 
@@ -175,8 +162,8 @@ wlp58s0: 631994599  596110    0    1    0     0          0         0 47170335  3
         let file = br#"Inter-|   Receive                                                |  Transmit
  face |bytes    packets errs drop fifo frame compressed multicast|bytes    packets errs drop fifo colls carrier compressed
 "#;
-        let interfaces = map_result(parse_dev(file)).unwrap();
-        assert!(interfaces.len() == 0);
+        let interfaces = parse_dev(file).unwrap();
+        assert!(interfaces.is_empty());
     }
 
     #[test]
@@ -0,0 +1,303 @@
+//! Multicast group memberships from `/proc/net/{igmp,igmp6}`.
+
+use std::fs::File;
+use std::io::{Read, Result};
+use std::net::{Ipv4Addr, Ipv6Addr};
+use std::path::Path;
+use std::time::Duration;
+
+use parsers::{Fields, invalid_input, parse_lines, parse_str, parse_u32_hex, parse_u64_hex,
+              with_line, with_path};
+use sysconf::ticks_to_duration;
+
+const NET_IGMP_FILE: &str = "/proc/net/igmp";
+const NET_IGMP6_FILE: &str = "/proc/net/igmp6";
+
+/// The IGMP version used by the querier of a network.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "export", derive(Serialize))]
+pub enum IgmpVersion {
+    /// IGMPv1, after an IGMPv1 query has been seen.
+    V1,
+    /// IGMPv2, after an IGMPv2 query has been seen.
+    V2,
+    /// IGMPv3.
+    V3,
+}
+
+/// Parses an IGMP querier version.
+fn parse_igmp_version(s: &str) -> Result<IgmpVersion> {
+    match s {
+        "V1" => Ok(IgmpVersion::V1),
+        "V2" => Ok(IgmpVersion::V2),
+        "V3" => Ok(IgmpVersion::V3),
+        _ => Err(invalid_input(format!("invalid IGMP version: {:?}", s))),
+    }
+}
+
+/// An IPv4 multicast group joined on an interface.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "export", derive(Serialize))]
+pub struct IgmpMembership {
+    /// The multicast group address.
+    pub group: Ipv4Addr,
+    /// The number of sockets and kernel users which joined the group.
+    pub users: u32,
+    /// The time until the next membership report is sent, if a report is pending.
+    pub timer: Option<Duration>,
+    /// Whether this host sent the last membership report for the group on the network.
+    pub reporter: bool,
+}
+
+/// The IPv4 multicast group memberships of a network interface.
+///
+/// See `Linux/net/ipv4/igmp.c`.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "export", derive(Serialize))]
+pub struct IgmpInterface {
+    /// The interface index.
+    pub index: u32,
+    /// The interface name.
+    pub device: String,
+    /// The IGMP version of the querier on the network.
+    pub querier: IgmpVersion,
+    /// The joined multicast groups.
+    pub groups: Vec<IgmpMembership>,
+}
+
+/// Parses a timer of the form `running:ticks`, where the ticks are in hexadecimal.
+fn parse_igmp_timer(s: &str) -> Result<Option<Duration>> {
+    let (running, ticks) = s.split_once(':')
+                            .ok_or_else(|| invalid_input(format!("invalid IGMP timer: {:?}", s)))?;
+    match running {
+        "0" => Ok(None),
+        "1" => Ok(Some(ticks_to_duration(parse_u64_hex(ticks)?))),
+        _ => Err(invalid_input(format!("invalid IGMP timer: {:?}", s))),
+    }
+}
+
+/// Parses an interface line, of the form `2\teth0      :     1      V3`.
+fn parse_igmp_interface(line: &str) -> Result<IgmpInterface> {
+    let (device, counts) = line.split_once(':').ok_or_else(|| {
+        invalid_input(format!("invalid IGMP interface line: {:?}", line))
+    })?;
+    let mut fields = Fields::new(device);
+    let index = fields.parse()?;
+    let device = fields.next_str()?.to_owned();
+    fields.finish()?;
+
+    let mut fields = Fields::new(counts);
+    let _count: usize = fields.parse()?;
+    let querier = parse_igmp_version(fields.next_str()?)?;
+    fields.finish()?;
+
+    Ok(IgmpInterface { index, device, querier, groups: Vec::new() })
+}
+
+/// Parses a group line, of the form `\t\t\t\t010000E0     1 0:00000000\t\t0`.
+///
+/// The group address is printed as a 32-bit word in native byte order, holding the address in
+/// network order.
+fn parse_igmp_membership(line: &str) -> Result<IgmpMembership> {
+    let mut fields = Fields::new(line);
+    let group = Ipv4Addr::from(parse_u32_hex(fields.next_str()?)?.to_ne_bytes());
+    let users = fields.parse()?;
+    let timer = parse_igmp_timer(fields.next_str()?)?;
+    let reporter = match fields.next_str()? {
+        "0" => false,
+        "1" => true,
+        reporter => return Err(invalid_input(format!("invalid IGMP reporter: {:?}", reporter))),
+    };
+    fields.finish()?;
+    Ok(IgmpMembership { group, users, timer, reporter })
+}
+
+/// Parses a line of the igmp file into the interfaces.
+fn parse_igmp_line(interfaces: &mut Vec<IgmpInterface>, line: &str) -> Result<()> {
+    if line.starts_with('\t') {
+        let interface = interfaces.last_mut()
+                                  .ok_or_else(|| invalid_input("IGMP group before interface"))?;
+        interface.groups.push(parse_igmp_membership(line)?);
+    } else {
+        interfaces.push(parse_igmp_interface(line)?);
+    }
+    Ok(())
+}
+
+/// Parses the igmp file, which has a header line followed by a line per interface, each followed
+/// by a line per joined group.
+fn parse_igmp(input: &[u8]) -> Result<Vec<IgmpInterface>> {
+    let mut interfaces = Vec::new();
+    for (i, line) in parse_str(input)?.lines().enumerate().skip(1) {
+        if !line.is_empty() {
+            parse_igmp_line(&mut interfaces, line).map_err(|error| with_line(error, i + 1))?;
+        }
+    }
+    Ok(interfaces)
+}
+
+/// An IPv6 multicast group joined on an interface.
+///
+/// See `Linux/net/ipv6/mcast.c`.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "export", derive(Serialize))]
+pub struct Igmp6Membership {
+    /// The interface index.
+    pub index: u32,
+    /// The interface name.
+    pub device: String,
+    /// The multicast group address.
+    pub group: Ipv6Addr,
+    /// The number of sockets and kernel users which joined the group.
+    pub users: u32,
+    /// The time until the next listener report is sent, if a report is pending.
+    pub timer: Option<Duration>,
+    /// Whether this host sent the last listener report for the group on the network.
+    pub reporter: bool,
+}
+
+/// The `MAF_TIMER_RUNNING` flag of an IPv6 multicast group.
+const MAF_TIMER_RUNNING: u32 = 0x01;
+/// The `MAF_LAST_REPORTER` flag of an IPv6 multicast group.
+const MAF_LAST_REPORTER: u32 = 0x02;
+
+impl Igmp6Membership {
+    /// Parses a line of a `/proc/net/igmp6` file, without the line terminator.
+    pub fn parse_line(input: &[u8]) -> Result<Igmp6Membership> {
+        let mut fields = Fields::new(parse_str(input)?);
+        let index = fields.parse()?;
+        let device = fields.next_str()?.to_owned();
+        let group = fields.next_str()?;
+        let users = fields.parse()?;
+        let flags = parse_u32_hex(fields.next_str()?)?;
+        let ticks: u64 = fields.parse()?;
+        fields.finish()?;
+
+        // The address is printed as 32 hexadecimal digits in network order.
+        if group.len() != 32 {
+            return Err(invalid_input(format!("invalid IPv6 group address: {:?}", group)));
+        }
+        let group = u128::from_str_radix(group, 16).map_err(|_| {
+            invalid_input(format!("invalid IPv6 group address: {:?}", group))
+        })?;
+
+        Ok(Igmp6Membership {
+            index,
+            device,
+            group: Ipv6Addr::from(group),
+            users,
+            timer: if flags & MAF_TIMER_RUNNING != 0 {
+                Some(ticks_to_duration(ticks))
+            } else {
+                None
+            },
+            reporter: flags & MAF_LAST_REPORTER != 0,
+        })
+    }
+}
+
+/// Parses the igmp6 file, which has a line per joined group.
+fn parse_igmp6(input: &[u8]) -> Result<Vec<Igmp6Membership>> {
+    parse_lines(input, 0, Igmp6Membership::parse_line)
+}
+
+/// Reads the igmp file at the path.
+pub(crate) fn igmp_file(path: &str) -> Result<Vec<IgmpInterface>> {
+    let mut buf = Vec::new();
+    File::open(path)?.read_to_end(&mut buf)?;
+    parse_igmp(&buf).map_err(|error| with_path(error, Path::new(path)))
+}
+
+/// Reads the igmp6 file at the path.
+pub(crate) fn igmp6_file(path: &str) -> Result<Vec<Igmp6Membership>> {
+    let mut buf = Vec::new();
+    File::open(path)?.read_to_end(&mut buf)?;
+    parse_igmp6(&buf).map_err(|error| with_path(error, Path::new(path)))
+}
+
+/// Returns the IPv4 multicast group memberships of each interface in the network namespace of the
+/// current process.
+pub fn igmp() -> Result<Vec<IgmpInterface>> {
+    igmp_file(NET_IGMP_FILE)
+}
+
+/// Returns the IPv6 multicast group memberships in the network namespace of the current process.
+pub fn igmp6() -> Result<Vec<Igmp6Membership>> {
+    igmp6_file(NET_IGMP6_FILE)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::ErrorKind;
+    use std::net::{Ipv4Addr, Ipv6Addr};
+    use std::time::Duration;
+
+    use sysconf::ticks_per_second;
+    use super::{Igmp6Membership, IgmpVersion, igmp, igmp6, parse_igmp, parse_igmp6};
+
+    /// Test that the system igmp files can be parsed, if IPv4 and IPv6 are enabled.
+    #[test]
+    fn test_igmp() {
+        match igmp() {
+            Ok(interfaces) => {
+                let all_hosts = Ipv4Addr::new(224, 0, 0, 1);
+                if let Some(lo) = interfaces.iter().find(|interface| interface.device == "lo") {
+                    assert!(lo.groups.iter().any(|group| group.group == all_hosts));
+                }
+            },
+            Err(error) => assert_eq!(ErrorKind::NotFound, error.kind()),
+        }
+        if let Err(error) = igmp6() {
+            assert_eq!(ErrorKind::NotFound, error.kind());
+        }
+    }
+
+    #[test]
+    fn test_parse_igmp() {
+        let text = b"Idx\tDevice    : Count Querier\tGroup    Users Timer\tReporter\n\
+                     1\tlo        :     1      V3\n\
+                     \t\t\t\t010000E0     1 0:00000000\t\t0\n\
+                     4\teth0      :     2      V2\n\
+                     \t\t\t\t010000E0     1 0:00000000\t\t0\n\
+                     \t\t\t\tFB0000E0     2 1:00000064\t\t1\n";
+        let interfaces = parse_igmp(text).unwrap();
+        assert_eq!(2, interfaces.len());
+        assert_eq!((1, "lo"), (interfaces[0].index, &*interfaces[0].device));
+        assert_eq!(IgmpVersion::V3, interfaces[0].querier);
+        assert_eq!(1, interfaces[0].groups.len());
+        assert_eq!(Ipv4Addr::new(224, 0, 0, 1), interfaces[0].groups[0].group);
+        assert_eq!(None, interfaces[0].groups[0].timer);
+
+        let mdns = &interfaces[1].groups[1];
+        assert_eq!(IgmpVersion::V2, interfaces[1].querier);
+        assert_eq!(Ipv4Addr::new(224, 0, 0, 251), mdns.group);
+        assert_eq!(2, mdns.users);
+        assert_eq!(Some(Duration::from_secs(100) / ticks_per_second() as u32), mdns.timer);
+        assert!(mdns.reporter);
+
+        parse_igmp(b"header\n\t\t\t\t010000E0     1 0:00000000\t\t0\n").unwrap_err();
+        parse_igmp(b"header\n1\tlo        :     1      V4\n").unwrap_err();
+    }
+
+    #[test]
+    fn test_parse_igmp6() {
+        let text = b"1    lo              ff020000000000000000000000000001     1 0000000C 0\n\
+                     4    eth0            ff0200000000000000000001ff000002     1 00000007 30\n";
+        let memberships = parse_igmp6(text).unwrap();
+        assert_eq!(2, memberships.len());
+        assert_eq!(Igmp6Membership {
+                       index: 1,
+                       device: "lo".to_owned(),
+                       group: "ff02::1".parse::<Ipv6Addr>().unwrap(),
+                       users: 1,
+                       timer: None,
+                       reporter: false,
+                   },
+                   memberships[0]);
+        assert_eq!("ff02::1:ff00:2".parse::<Ipv6Addr>().unwrap(), memberships[1].group);
+        assert!(memberships[1].timer.is_some());
+        assert!(memberships[1].reporter);
+
+        Igmp6Membership::parse_line(b"1 lo ff02::1 1 0000000C 0").unwrap_err();
+    }
+}
@@ -0,0 +1,274 @@
+//! TCP, UDP, and ICMP socket tables from `/proc/net/{tcp,tcp6,udp,udp6,icmp,icmp6}`.
+
+use std::fs::File;
+use std::io::{Read, Result};
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr};
+use std::path::Path;
+
+use parsers::{Fields, invalid_input, parse_lines, parse_str, parse_u32_hex, parse_u64_hex,
+              with_path};
+
+const NET_TCP_FILE: &str = "/proc/net/tcp";
+const NET_TCP6_FILE: &str = "/proc/net/tcp6";
+const NET_UDP_FILE: &str = "/proc/net/udp";
+const NET_UDP6_FILE: &str = "/proc/net/udp6";
+const NET_ICMP_FILE: &str = "/proc/net/icmp";
+const NET_ICMP6_FILE: &str = "/proc/net/icmp6";
+
+/// The state of a socket.
+///
+/// UDP and ICMP sockets use the TCP states: a connected socket is `Established`, and an unconnected
+/// socket is `Close`.
+///
+/// See `Linux/include/net/tcp_states.h`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "export", derive(Serialize))]
+pub enum SocketState {
+    /// The connection is established.
+    Established,
+    /// A connection request has been sent.
+    SynSent,
+    /// A connection request has been received.
+    SynRecv,
+    /// The socket is closed, and the connection is shutting down.
+    FinWait1,
+    /// The connection is closed, and the socket is waiting for a shutdown from the remote end.
+    FinWait2,
+    /// The socket is waiting after close to handle packets still in the network.
+    TimeWait,
+    /// The socket is not being used.
+    Close,
+    /// The remote end has shut down, and is waiting for the socket to close.
+    CloseWait,
+    /// The remote end has shut down and the socket is closed, and is waiting for acknowledgement.
+    LastAck,
+    /// The socket is listening for incoming connections.
+    Listen,
+    /// Both sockets are shut down but not all data has been sent.
+    Closing,
+    /// A connection request has been received by a listener (since Linux 4.4).
+    NewSynRecv,
+    /// The socket is bound but neither listening nor connected (since Linux 6.5).
+    BoundInactive,
+}
+
+/// Parses a hexadecimal socket state.
+fn parse_socket_state(s: &str) -> Result<SocketState> {
+    let state = match parse_u32_hex(s)? {
+        0x01 => SocketState::Established,
+        0x02 => SocketState::SynSent,
+        0x03 => SocketState::SynRecv,
+        0x04 => SocketState::FinWait1,
+        0x05 => SocketState::FinWait2,
+        0x06 => SocketState::TimeWait,
+        0x07 => SocketState::Close,
+        0x08 => SocketState::CloseWait,
+        0x09 => SocketState::LastAck,
+        0x0A => SocketState::Listen,
+        0x0B => SocketState::Closing,
+        0x0C => SocketState::NewSynRecv,
+        0x0D => SocketState::BoundInactive,
+        _ => return Err(invalid_input(format!("invalid socket state: {:?}", s))),
+    };
+    Ok(state)
+}
+
+/// Parses an `address:port` pair.
+///
+/// The address is printed as a sequence of 32-bit words in native byte order, each holding four
+/// bytes of the address in network order.
+fn parse_socket_addr(s: &str) -> Result<SocketAddr> {
+    let (addr, port) = s.split_once(':')
+                        .ok_or_else(|| invalid_input(format!("invalid socket address: {:?}", s)))?;
+    let port = u16::from_str_radix(port, 16)
+                   .map_err(|_| invalid_input(format!("invalid socket port: {:?}", s)))?;
+    let word = |i: usize| -> Result<[u8; 4]> {
+        Ok(parse_u32_hex(&addr[i * 8..i * 8 + 8])?.to_ne_bytes())
+    };
+    let ip = match addr.len() {
+        _ if !addr.is_ascii() => {
+            return Err(invalid_input(format!("invalid socket address: {:?}", s)))
+        },
+        8 => IpAddr::V4(Ipv4Addr::from(word(0)?)),
+        32 => {
+            let mut octets = [0; 16];
+            for i in 0..4 {
+                octets[i * 4..i * 4 + 4].copy_from_slice(&word(i)?);
+            }
+            IpAddr::V6(Ipv6Addr::from(octets))
+        },
+        _ => return Err(invalid_input(format!("invalid socket address: {:?}", s))),
+    };
+    Ok(SocketAddr::new(ip, port))
+}
+
+/// A TCP, UDP, or ICMP socket.
+///
+/// The port of an ICMP (ping) socket is its echo identifier.
+///
+/// See `man 5 proc`, `Linux/net/ipv4/tcp_ipv4.c`, and `Linux/net/ipv4/ping.c`.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "export", derive(Serialize))]
+pub struct InetSocket {
+    /// The local address and port.
+    pub local_address: SocketAddr,
+    /// The remote address and port, which is unspecified for unconnected sockets.
+    pub remote_address: SocketAddr,
+    /// The socket state.
+    pub state: SocketState,
+    /// The number of bytes in the send queue.
+    pub tx_queue: u64,
+    /// The number of bytes in the receive queue.
+    pub rx_queue: u64,
+    /// The effective user ID of the socket's creator.
+    pub uid: u32,
+    /// The inode of the socket.
+    pub inode: u64,
+}
+
+impl InetSocket {
+    /// Parses a socket line of a `/proc/net/{tcp,tcp6,udp,udp6,icmp,icmp6}` file, without the
+    /// line terminator.
+    pub fn parse_line(input: &[u8]) -> Result<InetSocket> {
+        let mut fields = Fields::new(parse_str(input)?);
+        let _sl = fields.next_str()?;
+        let local_address = parse_socket_addr(fields.next_str()?)?;
+        let remote_address = parse_socket_addr(fields.next_str()?)?;
+        let state = parse_socket_state(fields.next_str()?)?;
+        let queues = fields.next_str()?;
+        let (tx_queue, rx_queue) = queues.split_once(':').ok_or_else(|| {
+            invalid_input(format!("invalid socket queues: {:?}", queues))
+        })?;
+        let _timer = fields.next_str()?;
+        let _retransmits = fields.next_str()?;
+        let uid = fields.parse()?;
+        let _timeout = fields.next_str()?;
+        let inode = fields.parse()?;
+        // The remaining fields differ between protocols and kernel versions.
+        Ok(InetSocket {
+            local_address,
+            remote_address,
+            state,
+            tx_queue: parse_u64_hex(tx_queue)?,
+            rx_queue: parse_u64_hex(rx_queue)?,
+            uid,
+            inode,
+        })
+    }
+}
+
+/// Parses a socket table, which has a header line followed by a line per socket.
+fn parse_inet(input: &[u8]) -> Result<Vec<InetSocket>> {
+    parse_lines(input, 1, InetSocket::parse_line)
+}
+
+/// Parses the provided socket table file.
+pub(crate) fn inet_file(path: &str) -> Result<Vec<InetSocket>> {
+    let mut buf = Vec::new();
+    File::open(path)?.read_to_end(&mut buf)?;
+    parse_inet(&buf).map_err(|error| with_path(error, Path::new(path)))
+}
+
+/// Returns the IPv4 TCP sockets of the network namespace of the current process.
+pub fn tcp() -> Result<Vec<InetSocket>> {
+    inet_file(NET_TCP_FILE)
+}
+
+/// Returns the IPv6 TCP sockets of the network namespace of the current process.
+pub fn tcp6() -> Result<Vec<InetSocket>> {
+    inet_file(NET_TCP6_FILE)
+}
+
+/// Returns the IPv4 UDP sockets of the network namespace of the current process.
+pub fn udp() -> Result<Vec<InetSocket>> {
+    inet_file(NET_UDP_FILE)
+}
+
+/// Returns the IPv6 UDP sockets of the network namespace of the current process.
+pub fn udp6() -> Result<Vec<InetSocket>> {
+    inet_file(NET_UDP6_FILE)
+}
+
+/// Returns the IPv4 ICMP (ping) sockets of the network namespace of the current process.
+///
+/// Raw sockets are not included.
+pub fn icmp() -> Result<Vec<InetSocket>> {
+    inet_file(NET_ICMP_FILE)
+}
+
+/// Returns the IPv6 ICMP (ping) sockets of the network namespace of the current process.
+///
+/// Raw sockets are not included.
+pub fn icmp6() -> Result<Vec<InetSocket>> {
+    inet_file(NET_ICMP6_FILE)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::net::{Ipv6Addr, SocketAddr, TcpListener};
+
+    use super::{InetSocket, SocketState, icmp, icmp6, parse_inet, parse_socket_addr, tcp, tcp6, udp,
+                udp6};
+
+    /// Test that the system socket tables can be parsed.
+    #[test]
+    fn test_inet() {
+        udp().unwrap();
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        assert!(tcp().unwrap().iter().any(|socket| {
+            socket.local_address == addr && socket.state == SocketState::Listen
+        }));
+        // IPv6 may be disabled.
+        let _ = tcp6();
+        let _ = udp6();
+        icmp().unwrap();
+        let _ = icmp6();
+    }
+
+    #[test]
+    fn test_parse_socket_addr() {
+        if cfg!(target_endian = "little") {
+            assert_eq!("127.0.0.1:8080".parse::<SocketAddr>().unwrap(),
+                       parse_socket_addr("0100007F:1F90").unwrap());
+            assert_eq!("[::1]:22".parse::<SocketAddr>().unwrap(),
+                       parse_socket_addr("00000000000000000000000001000000:0016").unwrap());
+            assert_eq!(SocketAddr::new(Ipv6Addr::new(0xfe80, 0, 0, 0, 0, 0, 0, 1).into(), 1),
+                       parse_socket_addr("000080FE000000000000000001000000:0001").unwrap());
+        }
+        parse_socket_addr("0100007F").unwrap_err();
+        parse_socket_addr("0100007:0050").unwrap_err();
+    }
+
+    #[test]
+    fn test_parse_inet() {
+        let text = b"  sl  local_address rem_address   st tx_queue rx_queue tr tm->when retrnsmt   uid  timeout inode\n\
+   0: 0100007F:BC8F 00000000:0000 0A 00000000:00000000 00:00000000 00000000 65534        0 925 1 00000000ba03bd49 100 0 0 10 0\n\
+   1: 0100007F:CCF6 0100007F:BC8F 01 0000001A:00000002 02:00000672 00000000     0        0 40746 2 0000000081a260d9 20 4 0 14 8\n";
+        let sockets = parse_inet(text).unwrap();
+        assert_eq!(2, sockets.len());
+        assert_eq!(SocketState::Listen, sockets[0].state);
+        assert_eq!(65534, sockets[0].uid);
+        assert_eq!(925, sockets[0].inode);
+        assert_eq!(SocketState::Established, sockets[1].state);
+        assert_eq!(0x1a, sockets[1].tx_queue);
+        assert_eq!(2, sockets[1].rx_queue);
+        assert_eq!(40746, sockets[1].inode);
+        if cfg!(target_endian = "little") {
+            assert_eq!("127.0.0.1:48271".parse::<SocketAddr>().unwrap(),
+                       sockets[1].remote_address);
+        }
+
+        let text = b"  sl  local_address rem_address   st tx_queue rx_queue tr tm->when retrnsmt   uid  timeout inode ref pointer drops\n\
+   3: 0100007F:002A 00000000:0000 07 00000000:00000000 00:00000000 00000000  1000        0 51234 2 0000000000000000 0\n";
+        let sockets = parse_inet(text).unwrap();
+        assert_eq!(1, sockets.len());
+        assert_eq!(SocketState::Close, sockets[0].state);
+        assert_eq!(42, sockets[0].local_address.port());
+        assert_eq!(1000, sockets[0].uid);
+        assert_eq!(51234, sockets[0].inode);
+
+        let truncated = b"   0: 0100007F:BC8F 00000000:0000 0A 00000000:00000000";
+        InetSocket::parse_line(truncated).unwrap_err();
+    }
+}
@@ -1 +1,13 @@
+pub mod bonding;
 pub mod dev;
+pub mod igmp;
+pub mod inet;
+pub mod nfs;
+pub mod psched;
+pub mod stat;
+pub mod unix;
+pub mod vlan;
+
+mod owners;
+
+pub use net::owners::{SocketOwner, SocketOwners, socket_owners};
@@ -0,0 +1,379 @@
+//! NFS statistics from `/proc/net/rpc/`.
+
+use std::fs::File;
+use std::io::{Read, Result};
+use std::path::Path;
+
+use parsers::{Fields, invalid_input, parse_str, with_line, with_path};
+
+const NET_RPC_NFS_FILE: &str = "/proc/net/rpc/nfs";
+const NET_RPC_NFSD_FILE: &str = "/proc/net/rpc/nfsd";
+
+/// Network statistics of an RPC service.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "export", derive(Serialize))]
+pub struct RpcNetStats {
+    /// The number of packets received.
+    pub packets: u64,
+    /// The number of UDP packets received.
+    pub udp: u64,
+    /// The number of TCP packets received.
+    pub tcp: u64,
+    /// The number of TCP connections accepted.
+    pub tcp_connections: u64,
+}
+
+/// RPC call statistics of an RPC server.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "export", derive(Serialize))]
+pub struct RpcServerStats {
+    /// The number of calls received.
+    pub calls: u64,
+    /// The number of calls rejected, the sum of the following counters.
+    pub bad_calls: u64,
+    /// The number of calls rejected because they could not be decoded.
+    pub bad_format: u64,
+    /// The number of calls rejected because they failed authentication.
+    pub bad_auth: u64,
+    /// The number of calls rejected because the client was unknown.
+    pub bad_client: u64,
+}
+
+/// RPC call statistics of an RPC client.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "export", derive(Serialize))]
+pub struct RpcClientStats {
+    /// The number of calls sent.
+    pub calls: u64,
+    /// The number of calls retransmitted.
+    pub retransmissions: u64,
+    /// The number of times the credentials of a call had to be refreshed.
+    pub auth_refreshes: u64,
+}
+
+/// Reply cache statistics of the NFS server.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "export", derive(Serialize))]
+pub struct NfsdReplyCache {
+    /// The number of retransmitted requests answered from the cache.
+    pub hits: u64,
+    /// The number of cacheable requests which were not in the cache.
+    pub misses: u64,
+    /// The number of requests which are not cacheable, such as reads.
+    pub nocache: u64,
+}
+
+/// The per-version operation counters of an NFS client or server.
+///
+/// Each counter vector is indexed by procedure number, e.g. `proc3[3]` counts NFSv3 `LOOKUP`
+/// calls. Versions which are not enabled are `None`.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "export", derive(Serialize))]
+pub struct NfsProcs {
+    /// NFSv2 procedure counters.
+    pub proc2: Option<Vec<u64>>,
+    /// NFSv3 procedure counters.
+    pub proc3: Option<Vec<u64>>,
+    /// NFSv4 procedure counters. The server only has the `NULL` and `COMPOUND` procedures, and
+    /// counts the operations within compounds separately.
+    pub proc4: Option<Vec<u64>>,
+}
+
+/// NFS server statistics.
+///
+/// See `Linux/fs/nfsd/stats.c` and `Linux/net/sunrpc/stats.c`.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "export", derive(Serialize))]
+pub struct NfsdStats {
+    /// Reply cache statistics.
+    pub reply_cache: NfsdReplyCache,
+    /// The number of requests with a stale file handle.
+    pub stale_file_handles: u64,
+    /// The number of bytes read from disk on behalf of clients.
+    pub read_bytes: u64,
+    /// The number of bytes written to disk on behalf of clients.
+    pub write_bytes: u64,
+    /// The number of server threads.
+    pub threads: u64,
+    /// The number of times a request arrived while all server threads were busy. Only counted
+    /// before Linux 4.3, and zero since.
+    pub threads_busy: u64,
+    /// Network statistics.
+    pub net: RpcNetStats,
+    /// RPC call statistics.
+    pub rpc: RpcServerStats,
+    /// The per-version procedure counters.
+    pub procs: NfsProcs,
+    /// The NFSv4 operation counters, indexed by operation number.
+    pub proc4ops: Option<Vec<u64>>,
+}
+
+/// NFS client statistics.
+///
+/// The client counts network packets only when it uses a UDP transport, so the network statistics
+/// are usually zero. Per-mount statistics are in `/proc/self/mountstats`.
+///
+/// See `Linux/fs/nfs/inode.c` and `Linux/net/sunrpc/stats.c`.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "export", derive(Serialize))]
+pub struct NfsClientStats {
+    /// Network statistics.
+    pub net: RpcNetStats,
+    /// RPC call statistics.
+    pub rpc: RpcClientStats,
+    /// The per-version procedure counters.
+    pub procs: NfsProcs,
+}
+
+/// Parses a counter line of the form `proc3 22 0 1 2 ...`, where the first field is the number of
+/// counters which follow.
+fn parse_counters(mut fields: Fields) -> Result<Vec<u64>> {
+    let count: usize = fields.parse()?;
+    let counters = (0..count).map(|_| fields.parse()).collect::<Result<Vec<u64>>>()?;
+    fields.finish()?;
+    Ok(counters)
+}
+
+/// Parses a line of the form `net 42 0 42 3`.
+fn parse_net(mut fields: Fields) -> Result<RpcNetStats> {
+    let net = RpcNetStats {
+        packets: fields.parse()?,
+        udp: fields.parse()?,
+        tcp: fields.parse()?,
+        tcp_connections: fields.parse()?,
+    };
+    fields.finish()?;
+    Ok(net)
+}
+
+/// Parses a procedure counter line into the counters. Other lines are ignored.
+fn parse_procs(procs: &mut NfsProcs, label: &str, fields: Fields) -> Result<()> {
+    let counters = match label {
+        "proc2" => &mut procs.proc2,
+        "proc3" => &mut procs.proc3,
+        "proc4" => &mut procs.proc4,
+        _ => return Ok(()),
+    };
+    *counters = Some(parse_counters(fields)?);
+    Ok(())
+}
+
+/// Parses a line of the nfsd file into the statistics.
+///
+/// Lines which are not known, such as the obsolete `ra` line, are ignored.
+fn parse_nfsd_line(stats: &mut NfsdStats, seen: &mut [bool; 6], line: &str) -> Result<()> {
+    let mut fields = Fields::new(line);
+    let label = fields.next_str()?;
+    match label {
+        "rc" => {
+            stats.reply_cache = NfsdReplyCache {
+                hits: fields.parse()?,
+                misses: fields.parse()?,
+                nocache: fields.parse()?,
+            };
+            seen[0] = true;
+        },
+        "fh" => {
+            // The remaining file handle counters have not been counted since Linux 2.6.
+            stats.stale_file_handles = fields.parse()?;
+            seen[1] = true;
+        },
+        "io" => {
+            stats.read_bytes = fields.parse()?;
+            stats.write_bytes = fields.parse()?;
+            seen[2] = true;
+        },
+        "th" => {
+            // The thread usage histogram which follows has been zero since Linux 2.6.
+            stats.threads = fields.parse()?;
+            stats.threads_busy = fields.parse()?;
+            seen[3] = true;
+        },
+        "net" => {
+            stats.net = parse_net(fields)?;
+            seen[4] = true;
+        },
+        "rpc" => {
+            stats.rpc = RpcServerStats {
+                calls: fields.parse()?,
+                bad_calls: fields.parse()?,
+                bad_format: fields.parse()?,
+                bad_auth: fields.parse()?,
+                bad_client: fields.parse()?,
+            };
+            fields.finish()?;
+            seen[5] = true;
+        },
+        "proc4ops" => stats.proc4ops = Some(parse_counters(fields)?),
+        _ => parse_procs(&mut stats.procs, label, fields)?,
+    }
+    Ok(())
+}
+
+impl NfsdStats {
+    /// Parses the contents of a `/proc/net/rpc/nfsd` file.
+    pub fn parse(input: &[u8]) -> Result<NfsdStats> {
+        let mut stats = NfsdStats::default();
+        let mut seen = [false; 6];
+        for (i, line) in parse_str(input)?.lines().enumerate() {
+            if !line.is_empty() {
+                parse_nfsd_line(&mut stats, &mut seen, line)
+                    .map_err(|error| with_line(error, i + 1))?;
+            }
+        }
+        if seen.iter().any(|&seen| !seen) {
+            return Err(invalid_input("missing nfsd statistics line"));
+        }
+        Ok(stats)
+    }
+}
+
+/// Parses a line of the nfs file into the statistics.
+fn parse_nfs_client_line(stats: &mut NfsClientStats, seen: &mut [bool; 2], line: &str)
+                         -> Result<()> {
+    let mut fields = Fields::new(line);
+    let label = fields.next_str()?;
+    match label {
+        "net" => {
+            stats.net = parse_net(fields)?;
+            seen[0] = true;
+        },
+        "rpc" => {
+            stats.rpc = RpcClientStats {
+                calls: fields.parse()?,
+                retransmissions: fields.parse()?,
+                auth_refreshes: fields.parse()?,
+            };
+            fields.finish()?;
+            seen[1] = true;
+        },
+        _ => parse_procs(&mut stats.procs, label, fields)?,
+    }
+    Ok(())
+}
+
+impl NfsClientStats {
+    /// Parses the contents of a `/proc/net/rpc/nfs` file.
+    pub fn parse(input: &[u8]) -> Result<NfsClientStats> {
+        let mut stats = NfsClientStats::default();
+        let mut seen = [false; 2];
+        for (i, line) in parse_str(input)?.lines().enumerate() {
+            if !line.is_empty() {
+                parse_nfs_client_line(&mut stats, &mut seen, line)
+                    .map_err(|error| with_line(error, i + 1))?;
+            }
+        }
+        if seen.iter().any(|&seen| !seen) {
+            return Err(invalid_input("missing nfs statistics line"));
+        }
+        Ok(stats)
+    }
+}
+
+/// Reads the file at the path, and parses it with the provided function.
+fn read_file<T>(path: &str, parse: fn(&[u8]) -> Result<T>) -> Result<T> {
+    let mut buf = Vec::new();
+    File::open(path)?.read_to_end(&mut buf)?;
+    parse(&buf).map_err(|error| with_path(error, Path::new(path)))
+}
+
+/// Returns the NFS server statistics.
+///
+/// Fails with `NotFound` if the NFS server module is not loaded.
+pub fn nfsd_stats() -> Result<NfsdStats> {
+    read_file(NET_RPC_NFSD_FILE, NfsdStats::parse)
+}
+
+/// Returns the NFS client statistics.
+///
+/// Fails with `NotFound` if the NFS client module is not loaded.
+pub fn nfs_client_stats() -> Result<NfsClientStats> {
+    read_file(NET_RPC_NFS_FILE, NfsClientStats::parse)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::ErrorKind;
+
+    use super::{NfsClientStats, NfsdReplyCache, NfsdStats, RpcClientStats, RpcNetStats,
+                RpcServerStats, nfs_client_stats, nfsd_stats};
+
+    /// Test that the NFS server statistics can be parsed, if the server is loaded.
+    #[test]
+    fn test_nfsd_stats() {
+        match nfsd_stats() {
+            Ok(stats) => assert_eq!(stats.rpc.bad_calls,
+                                    stats.rpc.bad_format + stats.rpc.bad_auth
+                                        + stats.rpc.bad_client),
+            Err(error) => assert_eq!(ErrorKind::NotFound, error.kind()),
+        }
+    }
+
+    #[test]
+    fn test_parse_nfsd_stats() {
+        let text = b"rc 12 3456 789\n\
+                     fh 2 0 0 0 0\n\
+                     io 1048576 4096\n\
+                     th 8 0 0.000 0.000 0.000 0.000 0.000 0.000 0.000 0.000 0.000 0.000\n\
+                     ra 32 0 0 0 0 0 0 0 0 0 0 0\n\
+                     net 4258 0 4258 6\n\
+                     rpc 4257 1 0 1 0\n\
+                     proc3 22 2 10 0 35 30 0 5 4 0 0 0 0 0 0 0 0 0 3 1 1 0 0\n\
+                     proc4 2 3 4250\n\
+                     proc4ops 5 0 0 0 1 2\n\
+                     wdeleg_getattr 0\n";
+        let stats = NfsdStats::parse(text).unwrap();
+        assert_eq!(NfsdReplyCache { hits: 12, misses: 3456, nocache: 789 }, stats.reply_cache);
+        assert_eq!(2, stats.stale_file_handles);
+        assert_eq!((1_048_576, 4096), (stats.read_bytes, stats.write_bytes));
+        assert_eq!(8, stats.threads);
+        assert_eq!(RpcNetStats { packets: 4258, udp: 0, tcp: 4258, tcp_connections: 6 },
+                   stats.net);
+        assert_eq!(RpcServerStats {
+                       calls: 4257,
+                       bad_calls: 1,
+                       bad_format: 0,
+                       bad_auth: 1,
+                       bad_client: 0,
+                   },
+                   stats.rpc);
+        assert_eq!(None, stats.procs.proc2);
+        let proc3 = stats.procs.proc3.unwrap();
+        assert_eq!(22, proc3.len());
+        assert_eq!(35, proc3[3]);
+        assert_eq!(Some(vec![3, 4250]), stats.procs.proc4);
+        assert_eq!(Some(vec![0, 0, 0, 1, 2]), stats.proc4ops);
+
+        // Missing lines, and counter lines with fewer counters than announced.
+        NfsdStats::parse(b"rc 0 0 0\nfh 0 0 0 0 0\nio 0 0\nth 8 0\n").unwrap_err();
+        let text = b"rc 0 0 0\nfh 0 0 0 0 0\nio 0 0\nth 8 0\n\
+                     net 0 0 0 0\nrpc 0 0 0 0 0\nproc3 22 0\n";
+        NfsdStats::parse(text).unwrap_err();
+    }
+
+    /// Test that the NFS client statistics can be parsed, if the client is loaded.
+    #[test]
+    fn test_nfs_client_stats() {
+        if let Err(error) = nfs_client_stats() {
+            assert_eq!(ErrorKind::NotFound, error.kind());
+        }
+    }
+
+    #[test]
+    fn test_parse_nfs_client_stats() {
+        let text = b"net 0 0 0 0\n\
+                     rpc 31642 2 31640\n\
+                     proc3 22 0 10880 0 3421 2140 0 8923 412 31 0 0 0 12 0 8 0 0 1 24 12 6 0\n\
+                     proc4 3 0 1 2\n";
+        let stats = NfsClientStats::parse(text).unwrap();
+        assert_eq!(RpcNetStats::default(), stats.net);
+        assert_eq!(RpcClientStats { calls: 31642, retransmissions: 2, auth_refreshes: 31640 },
+                   stats.rpc);
+        assert_eq!(None, stats.procs.proc2);
+        assert_eq!(Some(10880), stats.procs.proc3.as_ref().map(|proc3| proc3[1]));
+        assert_eq!(Some(vec![0, 1, 2]), stats.procs.proc4);
+
+        NfsClientStats::parse(b"net 0 0 0 0\n").unwrap_err();
+        NfsClientStats::parse(b"net 0 0 0 0\nrpc 1 0\n").unwrap_err();
+    }
+}
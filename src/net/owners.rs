@@ -0,0 +1,124 @@
+//! Attribution of sockets to the processes which have them open, from `/proc/[pid]/fd`.
+
+use std::collections::HashMap;
+use std::io::{ErrorKind, Result};
+use std::os::unix::io::RawFd;
+
+use libc::pid_t;
+
+use pid::{is_exited, pids, socket_fds};
+
+/// A file descriptor through which a process has a socket open.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+#[cfg_attr(feature = "export", derive(Serialize))]
+pub struct SocketOwner {
+    /// The pid of the process.
+    pub pid: pid_t,
+    /// The file descriptor of the socket in the process.
+    pub fd: RawFd,
+}
+
+/// A map from socket inode to the processes which have the socket open.
+///
+/// The `inode` field of the entries of the socket tables, such as `net::inet::tcp`, is the key.
+/// A socket may be open in several processes, for example after a `fork(2)`, or through several
+/// file descriptors of one process.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct SocketOwners {
+    owners: HashMap<u64, Vec<SocketOwner>>,
+}
+
+impl SocketOwners {
+    /// Returns the owners of the socket with the provided inode, ordered by pid and file
+    /// descriptor, or an empty slice if no visible process has it open.
+    pub fn get(&self, inode: u64) -> &[SocketOwner] {
+        self.owners.get(&inode).map(Vec::as_slice).unwrap_or(&[])
+    }
+
+    /// Returns the number of distinct sockets with at least one owner.
+    pub fn len(&self) -> usize {
+        self.owners.len()
+    }
+
+    /// Returns `true` if no sockets have owners.
+    pub fn is_empty(&self) -> bool {
+        self.owners.is_empty()
+    }
+
+    /// Returns an iterator over the socket inodes and their owners, in no particular order.
+    pub fn iter(&self) -> impl Iterator<Item = (u64, &[SocketOwner])> {
+        self.owners.iter().map(|(&inode, owners)| (inode, owners.as_slice()))
+    }
+
+    /// Rescans the file descriptors of all processes, replacing the previous contents of the map.
+    ///
+    /// The allocations of the map are reused, so refreshing a long-lived map is cheaper than
+    /// building a new one.
+    pub fn refresh(&mut self) -> Result<()> {
+        for owners in self.owners.values_mut() {
+            owners.clear();
+        }
+        for pid in pids()? {
+            let pid = pid?;
+            let fds = match socket_fds(&format!("/proc/{}", pid)) {
+                Ok(fds) => fds,
+                Err(ref error) if is_exited(error) => continue,
+                Err(ref error) if error.kind() == ErrorKind::PermissionDenied => continue,
+                Err(error) => return Err(error),
+            };
+            for (fd, inode) in fds {
+                self.owners.entry(inode).or_default().push(SocketOwner { pid, fd });
+            }
+        }
+        self.owners.retain(|_, owners| !owners.is_empty());
+        for owners in self.owners.values_mut() {
+            owners.sort();
+        }
+        Ok(())
+    }
+}
+
+/// Returns a map from socket inode to the processes which have the socket open.
+///
+/// The file descriptors of every process are scanned once, so entries of the socket tables of any
+/// network namespace can then be attributed to processes with a lookup each. Reading the file
+/// descriptors of another process requires ptrace access to it, so without `CAP_SYS_PTRACE` only
+/// the sockets of the current user's processes are found. Processes which exit during the scan
+/// are skipped.
+pub fn socket_owners() -> Result<SocketOwners> {
+    let mut owners = SocketOwners::default();
+    owners.refresh()?;
+    Ok(owners)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::net::TcpListener;
+    use std::os::unix::io::AsRawFd;
+
+    use libc;
+
+    use net::inet::tcp;
+    use super::{SocketOwner, socket_owners};
+
+    #[test]
+    fn test_socket_owners() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = listener.local_addr().unwrap();
+        let inode = tcp().unwrap()
+                         .into_iter()
+                         .find(|socket| socket.local_address == address)
+                         .unwrap()
+                         .inode;
+
+        let mut owners = socket_owners().unwrap();
+        let owner = SocketOwner { pid: unsafe { libc::getpid() }, fd: listener.as_raw_fd() };
+        assert_eq!(&[owner], owners.get(inode));
+        assert!(owners.iter().any(|(i, _)| i == inode));
+        assert!(owners.get(0).is_empty());
+
+        drop(listener);
+        owners.refresh().unwrap();
+        assert!(owners.get(inode).is_empty());
+    }
+}
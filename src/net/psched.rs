@@ -0,0 +1,112 @@
+//! Packet scheduler clock parameters from `/proc/net/psched`.
+
+use std::fs::File;
+use std::io::Result;
+use std::time::Duration;
+
+use parsers::{Fields, parse_file, parse_line, parse_u32_hex, read_to_end};
+
+const NET_PSCHED_FILE: &str = "/proc/net/psched";
+
+/// The clock parameters of the packet scheduler, used by traffic control to convert between
+/// scheduler ticks and time.
+///
+/// See `Linux/net/sched/sch_api.c` and `tc_core_init` in iproute2.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "export", derive(Serialize))]
+pub struct Psched {
+    /// Nanoseconds per microsecond (`NSEC_PER_USEC`).
+    pub ns_per_us: u32,
+    /// Nanoseconds per scheduler tick (`PSCHED_TICKS2NS(1)`).
+    pub ns_per_tick: u32,
+    /// The number of time units per second in which traffic control rates are expressed (always
+    /// 1,000,000).
+    pub units_per_sec: u32,
+    /// The resolution of the scheduler clock, in ticks per second (`NSEC_PER_SEC` divided by the
+    /// high resolution timer resolution).
+    pub clock_res: u32,
+}
+
+impl Psched {
+    /// Parses the contents of a `/proc/net/psched` file.
+    pub fn parse(input: &[u8]) -> Result<Psched> {
+        let mut fields = Fields::new(parse_line(input)?);
+        let psched = Psched {
+            ns_per_us: parse_u32_hex(fields.next_str()?)?,
+            ns_per_tick: parse_u32_hex(fields.next_str()?)?,
+            units_per_sec: parse_u32_hex(fields.next_str()?)?,
+            clock_res: parse_u32_hex(fields.next_str()?)?,
+        };
+        fields.finish()?;
+        Ok(psched)
+    }
+
+    /// Returns the factor by which the clock resolution exceeds the traffic control time units,
+    /// as computed by `tc`.
+    pub fn clock_factor(&self) -> f64 {
+        f64::from(self.clock_res) / f64::from(self.units_per_sec)
+    }
+
+    /// Returns the number of traffic control ticks per microsecond, as computed by `tc`.
+    ///
+    /// Traffic control converts times in microseconds to ticks with this factor when configuring
+    /// qdiscs, and back when reporting them.
+    pub fn tick_in_usec(&self) -> f64 {
+        // With a high resolution clock, tc treats the tick conversion as the identity.
+        let t2us = if self.clock_res == 1_000_000_000 { self.ns_per_tick } else { self.ns_per_us };
+        f64::from(t2us) / f64::from(self.ns_per_tick) * self.clock_factor()
+    }
+
+    /// Converts a number of scheduler ticks to a `Duration`.
+    pub fn ticks_to_duration(&self, ticks: u64) -> Duration {
+        Duration::from_nanos(ticks.saturating_mul(u64::from(self.ns_per_tick)))
+    }
+}
+
+/// Parses the provided psched file.
+fn psched_file(file: &mut File) -> Result<Psched> {
+    let mut buf = [0; 64]; // A psched file is 36 bytes.
+    Psched::parse(&read_to_end(file, &mut buf)?)
+}
+
+/// Returns the packet scheduler clock parameters.
+pub fn psched() -> Result<Psched> {
+    parse_file(NET_PSCHED_FILE, psched_file)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::{Psched, psched};
+
+    /// Test that the system psched file can be parsed.
+    #[test]
+    fn test_psched() {
+        let psched = psched().unwrap();
+        assert_eq!(1000, psched.ns_per_us);
+        assert_eq!(1_000_000, psched.units_per_sec);
+    }
+
+    #[test]
+    fn test_parse_psched() {
+        let psched = Psched::parse(b"000003e8 00000040 000f4240 3b9aca00\n").unwrap();
+        assert_eq!(Psched {
+                       ns_per_us: 1000,
+                       ns_per_tick: 64,
+                       units_per_sec: 1_000_000,
+                       clock_res: 1_000_000_000,
+                   },
+                   psched);
+        assert_eq!(1000.0, psched.clock_factor());
+        assert_eq!(1000.0, psched.tick_in_usec());
+        assert_eq!(Duration::from_nanos(640), psched.ticks_to_duration(10));
+
+        // A kernel without high resolution timers reports its timer frequency, here 1000 Hz.
+        let psched = Psched::parse(b"000003e8 00000040 000f4240 000003e8\n").unwrap();
+        assert_eq!(0.015625, psched.tick_in_usec());
+
+        Psched::parse(b"000003e8 00000040 000f4240\n").unwrap_err();
+        Psched::parse(b"000003e8 00000040 000f4240 3b9aca00 0\n").unwrap_err();
+    }
+}
@@ -0,0 +1,169 @@
+//! Per-CPU network statistics tables from `/proc/net/stat/`.
+
+use std::fs::File;
+use std::io::{ErrorKind, Read, Result};
+use std::path::Path;
+
+use parsers::{invalid_input, parse_str, parse_u64_hex, with_line, with_path};
+
+const NET_STAT_DIR: &str = "/proc/net/stat";
+
+/// A table of per-CPU counters, such as `/proc/net/stat/arp_cache`.
+///
+/// The tables have a header line naming the columns, followed by a line of hexadecimal counters
+/// per possible CPU, in CPU order. The `entries` column is a system-wide gauge which is repeated on
+/// every line, rather than a per-CPU counter.
+///
+/// See `Linux/net/core/neighbour.c`, `Linux/net/ipv4/route.c`, and
+/// `Linux/net/netfilter/nf_conntrack_standalone.c`.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "export", derive(Serialize))]
+pub struct NetStatTable {
+    /// The column names, from the header line.
+    pub columns: Vec<String>,
+    /// The counters of each CPU, in column order.
+    pub cpus: Vec<Vec<u64>>,
+}
+
+impl NetStatTable {
+    /// Parses the contents of a `/proc/net/stat/` table.
+    pub fn parse(input: &[u8]) -> Result<NetStatTable> {
+        let mut lines = parse_str(input)?.lines();
+        let columns = lines.next()
+                           .ok_or_else(|| invalid_input("missing header line"))?
+                           .split_whitespace()
+                           .map(str::to_owned)
+                           .collect::<Vec<_>>();
+        let mut cpus = Vec::new();
+        for (i, line) in lines.enumerate().filter(|&(_, line)| !line.trim().is_empty()) {
+            let values = line.split_whitespace()
+                             .map(parse_u64_hex)
+                             .collect::<Result<Vec<_>>>()
+                             .map_err(|error| with_line(error, i + 2))?;
+            if values.len() != columns.len() {
+                let error = invalid_input(format!("expected {} columns, found {}",
+                                                  columns.len(), values.len()));
+                return Err(with_line(error, i + 2));
+            }
+            cpus.push(values);
+        }
+        Ok(NetStatTable { columns, cpus })
+    }
+
+    /// Returns the index of the named column.
+    fn index(&self, column: &str) -> Option<usize> {
+        self.columns.iter().position(|c| c == column)
+    }
+
+    /// Returns the counters of the named column for each CPU, or `None` if the table has no such
+    /// column.
+    pub fn column(&self, column: &str) -> Option<Vec<u64>> {
+        let index = self.index(column)?;
+        Some(self.cpus.iter().map(|cpu| cpu[index]).collect())
+    }
+
+    /// Returns the sum of the named column over all CPUs, or `None` if the table has no such
+    /// column.
+    ///
+    /// The `entries` column is not summed, since it holds the same system-wide value for every
+    /// CPU.
+    pub fn total(&self, column: &str) -> Option<u64> {
+        let index = self.index(column)?;
+        if column == "entries" {
+            return Some(self.cpus.first().map_or(0, |cpu| cpu[index]));
+        }
+        Some(self.cpus.iter().map(|cpu| cpu[index]).fold(0, u64::wrapping_add))
+    }
+}
+
+/// The per-CPU network statistics tables.
+///
+/// Tables which the running kernel does not provide, e.g. `nf_conntrack` when the connection
+/// tracking module is not loaded, or `ndisc_cache` when IPv6 is disabled, are `None`.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "export", derive(Serialize))]
+pub struct NetStat {
+    /// ARP (IPv4 neighbour) cache statistics.
+    pub arp_cache: Option<NetStatTable>,
+    /// NDISC (IPv6 neighbour) cache statistics.
+    pub ndisc_cache: Option<NetStatTable>,
+    /// IPv4 routing statistics. The route cache was removed in Linux 3.6, and most of its
+    /// counters have been zero since.
+    pub rt_cache: Option<NetStatTable>,
+    /// Connection tracking statistics.
+    pub nf_conntrack: Option<NetStatTable>,
+}
+
+/// Reads the named table from the stat directory.
+fn table_file(dir: &str, name: &str) -> Result<NetStatTable> {
+    let path = Path::new(dir).join(name);
+    let mut buf = Vec::new();
+    File::open(&path)?.read_to_end(&mut buf)?;
+    NetStatTable::parse(&buf).map_err(|error| with_path(error, &path))
+}
+
+/// Reads the named table from the stat directory, treating a missing table as absent.
+fn optional_table(dir: &str, name: &str) -> Result<Option<NetStatTable>> {
+    match table_file(dir, name) {
+        Ok(table) => Ok(Some(table)),
+        Err(ref error) if error.kind() == ErrorKind::NotFound => Ok(None),
+        Err(error) => Err(error),
+    }
+}
+
+/// Reads the tables of the stat directory at the path.
+pub(crate) fn stat_dir(dir: &str) -> Result<NetStat> {
+    Ok(NetStat {
+        arp_cache: optional_table(dir, "arp_cache")?,
+        ndisc_cache: optional_table(dir, "ndisc_cache")?,
+        rt_cache: optional_table(dir, "rt_cache")?,
+        nf_conntrack: optional_table(dir, "nf_conntrack")?,
+    })
+}
+
+/// Returns the named table from `/proc/net/stat/`, such as `arp_cache` or `synproxy`.
+pub fn stat_table(name: &str) -> Result<NetStatTable> {
+    table_file(NET_STAT_DIR, name)
+}
+
+/// Returns the per-CPU network statistics tables of the network namespace of the current process.
+pub fn stat() -> Result<NetStat> {
+    stat_dir(NET_STAT_DIR)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{NetStatTable, stat};
+
+    /// Test that the system network statistics tables can be parsed.
+    #[test]
+    fn test_stat() {
+        let stat = stat().unwrap();
+        if let Some(arp_cache) = stat.arp_cache {
+            assert_eq!(Some(0), arp_cache.index("entries"));
+            assert!(!arp_cache.cpus.is_empty());
+        }
+    }
+
+    #[test]
+    fn test_parse_net_stat_table() {
+        let text = b"entries  allocs   destroys hash_grows lookups  hits\n\
+                     00000002 00000002 00000000 00000000   0000000d 00000008\n\
+                     00000002 00000001 00000000 00000000   000000ff 00000001\n";
+        let table = NetStatTable::parse(text).unwrap();
+        assert_eq!(6, table.columns.len());
+        assert_eq!("hash_grows", table.columns[3]);
+        assert_eq!(2, table.cpus.len());
+        assert_eq!(Some(vec![0x0d, 0xff]), table.column("lookups"));
+        assert_eq!(Some(0x10c), table.total("lookups"));
+        assert_eq!(Some(2), table.total("entries"));
+        assert_eq!(None, table.total("missing"));
+
+        let text = b"entries\t\tsyn_received\tcookie_invalid\n00000000\t00000001\t0000000a\n";
+        assert_eq!(Some(10), NetStatTable::parse(text).unwrap().total("cookie_invalid"));
+
+        NetStatTable::parse(b"entries allocs\n00000002\n").unwrap_err();
+        NetStatTable::parse(b"entries allocs\n00000002 0000000g\n").unwrap_err();
+        NetStatTable::parse(b"").unwrap_err();
+    }
+}
@@ -0,0 +1,154 @@
+//! Unix domain socket table from `/proc/net/unix`.
+
+use std::ffi::OsStr;
+use std::fs::File;
+use std::io::{Read, Result};
+use std::os::unix::ffi::OsStrExt;
+use std::path::{Path, PathBuf};
+
+use parsers::{invalid_input, next_field, parse, parse_lines, parse_u32_hex, with_path};
+
+const NET_UNIX_FILE: &str = "/proc/net/unix";
+
+/// The type of a Unix domain socket.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "export", derive(Serialize))]
+pub enum UnixSocketType {
+    /// A stream socket (`SOCK_STREAM`).
+    Stream,
+    /// A datagram socket (`SOCK_DGRAM`).
+    Datagram,
+    /// A sequenced packet socket (`SOCK_SEQPACKET`).
+    SeqPacket,
+}
+
+/// The state of a Unix domain socket.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "export", derive(Serialize))]
+pub enum UnixSocketState {
+    /// The socket is not connected (`SS_UNCONNECTED`). Listening sockets are in this state.
+    Unconnected,
+    /// The socket is connecting (`SS_CONNECTING`).
+    Connecting,
+    /// The socket is connected (`SS_CONNECTED`).
+    Connected,
+    /// The socket is disconnecting (`SS_DISCONNECTING`).
+    Disconnecting,
+}
+
+/// A Unix domain socket.
+///
+/// See `man 5 proc` and `Linux/net/unix/af_unix.c`.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "export", derive(Serialize))]
+pub struct UnixSocket {
+    /// The number of users of the socket.
+    pub ref_count: u32,
+    /// The socket type.
+    pub socket_type: UnixSocketType,
+    /// The socket state.
+    pub state: UnixSocketState,
+    /// Whether the socket is listening for connections (`__SO_ACCEPTCON`).
+    pub is_listening: bool,
+    /// The inode of the socket.
+    pub inode: u64,
+    /// The path the socket is bound to, if any.
+    ///
+    /// Sockets in the abstract namespace have a path beginning with `@`.
+    pub path: Option<PathBuf>,
+}
+
+impl UnixSocket {
+    /// Parses a socket line of a `/proc/net/unix` file, without the line terminator.
+    pub fn parse_line(mut input: &[u8]) -> Result<UnixSocket> {
+        let _num = next_field(&mut input)?;
+        let ref_count = parse_u32_hex(next_field(&mut input)?)?;
+        let _protocol = next_field(&mut input)?;
+        let flags = parse_u32_hex(next_field(&mut input)?)?;
+        let socket_type = match parse_u32_hex(next_field(&mut input)?)? {
+            1 => UnixSocketType::Stream,
+            2 => UnixSocketType::Datagram,
+            5 => UnixSocketType::SeqPacket,
+            t => return Err(invalid_input(format!("invalid unix socket type: {}", t))),
+        };
+        let state = match parse_u32_hex(next_field(&mut input)?)? {
+            1 => UnixSocketState::Unconnected,
+            2 => UnixSocketState::Connecting,
+            3 => UnixSocketState::Connected,
+            4 => UnixSocketState::Disconnecting,
+            s => return Err(invalid_input(format!("invalid unix socket state: {}", s))),
+        };
+        let inode = parse(next_field(&mut input)?)?;
+        // The path may contain spaces and bytes which are not valid UTF-8.
+        let path = match input.strip_prefix(b" ") {
+            Some(path) if !path.is_empty() => Some(PathBuf::from(OsStr::from_bytes(path))),
+            _ => None,
+        };
+
+        Ok(UnixSocket {
+            ref_count,
+            socket_type,
+            state,
+            is_listening: flags & 0x0001_0000 != 0,
+            inode,
+            path,
+        })
+    }
+}
+
+/// Parses the socket table, which has a header line followed by a line per socket.
+fn parse_unix(input: &[u8]) -> Result<Vec<UnixSocket>> {
+    parse_lines(input, 1, UnixSocket::parse_line)
+}
+
+/// Parses the provided unix socket table file.
+pub(crate) fn unix_file(path: &str) -> Result<Vec<UnixSocket>> {
+    let mut buf = Vec::new();
+    File::open(path)?.read_to_end(&mut buf)?;
+    parse_unix(&buf).map_err(|error| with_path(error, Path::new(path)))
+}
+
+/// Returns the Unix domain sockets of the network namespace of the current process.
+pub fn unix() -> Result<Vec<UnixSocket>> {
+    unix_file(NET_UNIX_FILE)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use super::{UnixSocket, UnixSocketState, UnixSocketType, parse_unix, unix};
+
+    /// Test that the system unix socket table can be parsed.
+    #[test]
+    fn test_unix() {
+        unix().unwrap();
+    }
+
+    #[test]
+    fn test_parse_unix() {
+        let text = b"Num       RefCount Protocol Flags    Type St Inode Path\n\
+                     000000003d7d9727: 00000003 00000000 00000000 0001 03   923\n\
+                     00000000c68ae557: 00000002 00000000 00010000 0001 01 53092 /run/a b.sock\n\
+                     0000000082e39a65: 00000002 00000000 00000000 0002 01   659 @/tmp/.X11-unix\n";
+        let sockets = parse_unix(text).unwrap();
+        assert_eq!(3, sockets.len());
+        assert_eq!(UnixSocket {
+                       ref_count: 3,
+                       socket_type: UnixSocketType::Stream,
+                       state: UnixSocketState::Connected,
+                       is_listening: false,
+                       inode: 923,
+                       path: None,
+                   },
+                   sockets[0]);
+        assert!(sockets[1].is_listening);
+        assert_eq!(UnixSocketState::Unconnected, sockets[1].state);
+        assert_eq!(Some(PathBuf::from("/run/a b.sock")), sockets[1].path);
+        assert_eq!(UnixSocketType::Datagram, sockets[2].socket_type);
+        assert_eq!(Some(PathBuf::from("@/tmp/.X11-unix")), sockets[2].path);
+
+        UnixSocket::parse_line(b"000000003d7d9727: 00000003 00000000 00000000 0009 03 923")
+                   .unwrap_err();
+    }
+}
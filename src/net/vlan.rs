@@ -0,0 +1,103 @@
+//! VLAN devices from `/proc/net/vlan/config`.
+
+use std::fs::File;
+use std::io::{ErrorKind, Read, Result};
+use std::path::Path;
+
+use parsers::{invalid_input, parse, parse_lines, parse_str, with_path};
+
+const NET_VLAN_CONFIG_FILE: &str = "/proc/net/vlan/config";
+
+/// A configured 802.1Q VLAN device.
+///
+/// See `Linux/net/8021q/vlanproc.c`.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "export", derive(Serialize))]
+pub struct VlanDevice {
+    /// The name of the VLAN device, e.g. `eth0.100`.
+    pub device: String,
+    /// The VLAN ID.
+    pub id: u16,
+    /// The name of the parent interface which the VLAN device sends and receives through.
+    pub parent: String,
+}
+
+impl VlanDevice {
+    /// Parses a line of a `/proc/net/vlan/config` file, without the line terminator.
+    ///
+    /// The line is of the form `eth0.100       | 100  | eth0`.
+    pub fn parse_line(input: &[u8]) -> Result<VlanDevice> {
+        let line = parse_str(input)?;
+        let mut columns = line.split('|');
+        let device = columns.next().unwrap_or_default().trim();
+        let (id, parent) = match (columns.next(), columns.next(), columns.next()) {
+            (Some(id), Some(parent), None) => (id.trim(), parent.trim()),
+            _ => return Err(invalid_input(format!("invalid VLAN config line: {:?}", line))),
+        };
+        if device.is_empty() || parent.is_empty() {
+            return Err(invalid_input(format!("invalid VLAN config line: {:?}", line)));
+        }
+        Ok(VlanDevice { device: device.to_owned(), id: parse(id)?, parent: parent.to_owned() })
+    }
+}
+
+/// Parses the VLAN config file, which has two header lines followed by a line per device.
+fn parse_vlan_config(input: &[u8]) -> Result<Vec<VlanDevice>> {
+    parse_lines(input, 2, VlanDevice::parse_line)
+}
+
+/// Reads the VLAN config file at the path, treating a missing file as no devices.
+pub(crate) fn vlan_config_file(path: &str) -> Result<Vec<VlanDevice>> {
+    let mut buf = Vec::new();
+    match File::open(path) {
+        Ok(mut file) => file.read_to_end(&mut buf)?,
+        Err(ref error) if error.kind() == ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(error) => return Err(error),
+    };
+    parse_vlan_config(&buf).map_err(|error| with_path(error, Path::new(path)))
+}
+
+/// Returns the VLAN devices configured in the network namespace of the current process.
+///
+/// Returns no devices if the 802.1Q driver is not loaded.
+pub fn vlan_config() -> Result<Vec<VlanDevice>> {
+    vlan_config_file(NET_VLAN_CONFIG_FILE)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{VlanDevice, parse_vlan_config, vlan_config};
+
+    /// Test that the VLAN devices can be read, if any.
+    #[test]
+    fn test_vlan_config() {
+        for device in vlan_config().unwrap() {
+            assert!(!device.parent.is_empty());
+        }
+    }
+
+    #[test]
+    fn test_parse_vlan_config() {
+        let text = b"VLAN Dev name\t | VLAN ID\n\
+                     Name-Type: VLAN_NAME_TYPE_RAW_PLUS_VID_NO_PAD\n\
+                     eth0.100       | 100  | eth0\n\
+                     vlan4094       | 4094  | bond0\n";
+        let devices = parse_vlan_config(text).unwrap();
+        assert_eq!(vec![VlanDevice {
+                            device: "eth0.100".to_owned(),
+                            id: 100,
+                            parent: "eth0".to_owned(),
+                        },
+                        VlanDevice {
+                            device: "vlan4094".to_owned(),
+                            id: 4094,
+                            parent: "bond0".to_owned(),
+                        }],
+                   devices);
+
+        VlanDevice::parse_line(b"eth0.100       | 100").unwrap_err();
+        VlanDevice::parse_line(b"eth0.100       | 100  | eth0 | eth1").unwrap_err();
+        VlanDevice::parse_line(b"eth0.100       | x  | eth0").unwrap_err();
+        VlanDevice::parse_line(b"eth0.100       | 100  | ").unwrap_err();
+    }
+}
@@ -1,173 +1,290 @@
 //! Parsers and utility functions.
 
-use std::borrow::ToOwned;
+use std::borrow::Cow;
+use std::error;
+use std::fmt;
 use std::fs::File;
 use std::io::{Error, ErrorKind, Read, Result};
-use std::str::{self, FromStr};
+use std::path::{Path, PathBuf};
+use std::str::{self, FromStr, SplitWhitespace};
 
 use byteorder::{ByteOrder, LittleEndian};
-use libc::clock_t;
-use nom::{
-    alphanumeric,
-    digit,
-    Err,
-    IResult,
-    is_digit,
-    not_line_ending,
-    space
-};
-use nom::ErrorKind::Digit;
-
-/// Read all bytes in the file until EOF, placing them into `buf`.
+
+/// Read all bytes in the file until EOF.
 ///
-/// All bytes read from this source will be written to `buf`.  If `buf` is not large enough an
-/// underflow error will be returned. This function will continuously call `read` to append more
-/// data to `buf` until read returns either `Ok(0)`, or an error of non-`ErrorKind::Interrupted`
-/// kind.
+/// Bytes are read into `buf`, and if the file does not fit, the bytes read so far are moved into a
+/// heap allocated buffer which grows to hold the rest of the file. `buf` should be sized to hold
+/// the typical file, so that the common case does not allocate. This function will continuously
+/// call `read` to append more data until read returns either `Ok(0)`, or an error of
+/// non-`ErrorKind::Interrupted` kind.
 ///
-/// If successful, this function will return the slice of read bytes.
+/// If successful, this function will return the read bytes.
 ///
 /// # Errors
 ///
 /// If this function encounters an error of the kind `ErrorKind::Interrupted` then the error is
 /// ignored and the operation will continue.
 ///
-/// If any other read error is encountered then this function immediately returns.  Any bytes which
-/// have already been read will be written to `buf`.
-///
-/// If `buf` is not large enough to hold the file, an underflow error will be returned.
-pub fn read_to_end<'a>(file: &mut File, buf: &'a mut [u8]) -> Result<&'a mut [u8]> {
+/// If any other read error is encountered then this function immediately returns.
+pub fn read_to_end<'a>(file: &mut File, buf: &'a mut [u8]) -> Result<Cow<'a, [u8]>> {
     let mut from = 0;
 
-    loop {
-        if from == buf.len() {
-            return Err(Error::new(ErrorKind::Other, "read underflow"));
-        }
+    while from < buf.len() {
         match file.read(&mut buf[from..]) {
-            Ok(0) => return Ok(&mut buf[..from]),
+            Ok(0) => return Ok(Cow::Borrowed(&buf[..from])),
             Ok(n) => from += n,
             Err(ref e) if e.kind() == ErrorKind::Interrupted => {}
             Err(e) => return Err(e),
         }
     }
+
+    let mut vec = Vec::with_capacity(buf.len() * 2);
+    vec.extend_from_slice(buf);
+    file.read_to_end(&mut vec)?;
+    Ok(Cow::Owned(vec))
+}
+
+/// Returns an `InvalidInput` error with the provided message.
+pub fn invalid_input<E>(msg: E) -> Error
+where E: Into<Box<dyn error::Error + Send + Sync>> {
+    Error::new(ErrorKind::InvalidInput, msg)
 }
 
-/// Transforms a `nom` parse result into a io result.
+/// The maximum length of the unparsed remainder included in a `ParseError`.
+const REMAINDER_LEN: usize = 64;
+
+/// The context of an error parsing a file.
+///
+/// Parse errors are returned as `io::Error`s of kind `InvalidInput`, which carry a `ParseError`
+/// locating the error. It can be retrieved with `get_ref` and `downcast_ref`:
+///
+/// ```
+/// use procinfo::ParseError;
+/// use procinfo::pid::Statm;
 ///
-/// The parser must completely consume the input.
-pub fn map_result<T>(result: IResult<&[u8], T>) -> Result<T> {
-    match result {
-        IResult::Done(remaining, val) => {
-            if remaining.is_empty() {
-                Ok(val)
-            } else {
-                let remaining = str::from_utf8(remaining);
-                Err(Error::new(ErrorKind::InvalidInput,
-                               format!("unable to parse whole input, remaining: {:?}", remaining)))
-            }
+/// let error = Statm::parse(b"1006 2303 x 330 0 890 0\n").unwrap_err();
+/// let context = error.get_ref().and_then(|e| e.downcast_ref::<ParseError>()).unwrap();
+/// assert_eq!(Some(10), context.offset);
+/// assert_eq!(Some("x 330 0 890 0".to_owned()), context.remainder);
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct ParseError {
+    /// The path of the file, if known.
+    pub path: Option<PathBuf>,
+    /// The line number, starting at 1, if known.
+    pub line: Option<usize>,
+    /// The byte offset of the unparsed remainder within the line, if known.
+    pub offset: Option<usize>,
+    /// The start of the unparsed remainder of the line, truncated to 64 bytes.
+    pub remainder: Option<String>,
+    /// A description of the error.
+    pub message: String,
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if let Some(ref path) = self.path {
+            write!(f, "{}: ", path.display())?;
+        }
+        match (self.line, self.offset) {
+            (Some(line), Some(offset)) => write!(f, "line {}, offset {}: ", line, offset)?,
+            (Some(line), None) => write!(f, "line {}: ", line)?,
+            (None, Some(offset)) => write!(f, "offset {}: ", offset)?,
+            (None, None) => (),
         }
-        IResult::Error(err) => Err(Error::new(ErrorKind::InvalidInput,
-                                              format!("unable to parse input: {:?}", err))),
-        _ => Err(Error::new(ErrorKind::InvalidInput, "unable to parse input")),
+        f.write_str(&self.message)?;
+        if let Some(ref remainder) = self.remainder {
+            write!(f, " at {:?}", remainder)?;
+        }
+        Ok(())
     }
 }
 
+impl error::Error for ParseError {}
 
-/// Recognizes numerical characters: 0-9, and periods: '.'.
-fn fdigit(input: &[u8]) -> IResult<&[u8], &[u8]> {
-    for idx in 0..input.len() {
-        if (!is_digit(input[idx])) && ('.' as u8 != input[idx]) {
-            return IResult::Done(&input[idx..], &input[0..idx])
-        }
+/// Adds context to a parse error.
+///
+/// Errors of kinds other than `InvalidInput`, such as I/O errors, are returned unchanged.
+fn add_context<F>(error: Error, f: F) -> Error where F: FnOnce(&mut ParseError) {
+    if error.kind() != ErrorKind::InvalidInput {
+        return error;
     }
-    IResult::Done(b"", input)
+    let mut context = match error.get_ref().and_then(|e| e.downcast_ref::<ParseError>()) {
+        Some(context) => context.clone(),
+        None => ParseError {
+            path: None,
+            line: None,
+            offset: None,
+            remainder: None,
+            message: error.to_string(),
+        },
+    };
+    f(&mut context);
+    Error::new(ErrorKind::InvalidInput, context)
 }
 
-/// Recognizes numerical characters: 0-9, and an optional leading dash: '-'.
-pub fn sdigit(input:&[u8]) -> IResult<&[u8], &[u8]> {
-    if input.is_empty() {
-        return IResult::Done(b"", input)
-    }
+/// Adds the path of the file to a parse error.
+pub fn with_path(error: Error, path: &Path) -> Error {
+    add_context(error, |context| context.path = Some(path.to_owned()))
+}
 
-    let start = if input[0] == '-' as u8 { 1 } else { 0 };
-    for (idx, item) in input.iter().enumerate().skip(start) {
-        if !is_digit(*item) {
-            if idx == start {
-                return IResult::Error(Err::Position(Digit, input));
-            } else {
-                return IResult::Done(&input[idx..], &input[0..idx]);
-            }
-        }
+/// Adds the line number, starting at 1, to a parse error.
+pub fn with_line(error: Error, line: usize) -> Error {
+    add_context(error, |context| context.line = Some(line))
+}
+
+/// Adds the unparsed remainder of the line, starting at the byte offset, to a parse error.
+pub fn with_remainder(error: Error, line: &str, offset: usize) -> Error {
+    let mut end = line.len().min(offset + REMAINDER_LEN);
+    while !line.is_char_boundary(end) {
+        end -= 1;
     }
-    IResult::Done(b"", input)
+    add_context(error, |context| {
+        context.offset = Some(offset);
+        context.remainder = Some(line[offset..end].to_owned());
+    })
 }
 
-/// Parses a line to a string.
-named!(pub parse_line<String>,
-       map!(map_res!(not_line_ending, str::from_utf8), ToOwned::to_owned));
+/// Opens the file at the path and parses it, adding the path to parse errors.
+pub fn parse_file<P, T, F>(path: P, parse: F) -> Result<T>
+where P: AsRef<Path>, F: FnOnce(&mut File) -> Result<T> {
+    let path = path.as_ref();
+    parse(&mut File::open(path)?).map_err(|error| with_path(error, path))
+}
 
-/// Parses a clock_t in base-10 format.
-named!(pub parse_clock<clock_t>,
-       map_res!(map_res!(sdigit, str::from_utf8), FromStr::from_str));
+/// Parses each non-empty line following the header lines of the input, adding the line number
+/// to parse errors.
+pub fn parse_lines<T, F>(input: &[u8], header_lines: usize, mut parse_line: F) -> Result<Vec<T>>
+where F: FnMut(&[u8]) -> Result<T> {
+    input.split(|&b| b == b'\n')
+         .enumerate()
+         .skip(header_lines)
+         .filter(|&(_, line)| !line.is_empty())
+         .map(|(i, line)| parse_line(line).map_err(|error| with_line(error, i + 1)))
+         .collect()
+}
 
-/// Parses an i32 in base-10 format.
-named!(pub parse_i32<i32>,
-       map_res!(map_res!(sdigit, str::from_utf8), FromStr::from_str));
+/// A line which a lenient parser skipped because it could not be parsed.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "export", derive(Serialize))]
+pub struct ParseWarning {
+    /// The line number, starting at 1.
+    pub line: usize,
+    /// The contents of the line.
+    pub text: String,
+    /// A description of the parse error.
+    pub message: String,
+}
 
-/// Parses an i64 in base-10 format.
-named!(pub parse_i64<i64>,
-       map_res!(map_res!(sdigit, str::from_utf8), FromStr::from_str));
+/// Interprets the input as UTF-8.
+pub fn parse_str(input: &[u8]) -> Result<&str> {
+    str::from_utf8(input).map_err(|_| invalid_input("input is not valid UTF-8"))
+}
 
-/// Parses an isize in base-10 format.
-named!(pub parse_isize<isize>,
-       map_res!(map_res!(sdigit, str::from_utf8), FromStr::from_str));
+/// Interprets the input as a single line of UTF-8, stripping the line terminator.
+///
+/// Files containing a single value are parsed with this.
+pub fn parse_line(input: &[u8]) -> Result<&str> {
+    let line = parse_str(input)?;
+    let line = line.strip_suffix('\n').unwrap_or(line);
+    if line.contains('\n') {
+        return Err(invalid_input(format!("unable to parse whole input: {:?}", line)));
+    }
+    Ok(line)
+}
 
-/// Parses a u32 in base-10 format.
-named!(pub parse_u32<u32>,
-       map_res!(map_res!(digit, str::from_utf8), FromStr::from_str));
+/// Parses a value from its base-10 (or, for floats, decimal) representation.
+pub fn parse<T: FromStr>(s: &str) -> Result<T> {
+    s.parse().map_err(|_| invalid_input(format!("invalid value: {:?}", s)))
+}
 
-/// Parses a u64 in base-10 format.
-named!(pub parse_u64<u64>,
-       map_res!(map_res!(digit, str::from_utf8), FromStr::from_str));
+/// Splits a `label: value` line at the first colon.
+///
+/// The value is returned as is, including any leading whitespace.
+pub fn split_label(line: &str) -> Option<(&str, &str)> {
+    line.split_once(':')
+}
 
-/// Parses a usize in base-10 format.
-named!(pub parse_usize<usize>,
-       map_res!(map_res!(digit, str::from_utf8), FromStr::from_str));
+/// Splits the next space separated field off the front of the input.
+///
+/// Used for lines which end in a free-form column, such as a path, which may contain spaces or
+/// bytes which are not valid UTF-8. The input is left pointing at the space following the field.
+pub fn next_field<'a>(input: &mut &'a [u8]) -> Result<&'a str> {
+    let start = input.iter().position(|&c| c != b' ').unwrap_or(input.len());
+    let rest = &input[start..];
+    let end = rest.iter().position(|&c| c == b' ').unwrap_or(rest.len());
+    if end == 0 {
+        return Err(invalid_input("missing field"));
+    }
+    *input = &rest[end..];
+    parse_str(&rest[..end])
+}
 
-/// Parses a f32 in base-10 format.
-named!(pub parse_f32<f32>,
-      map_res!(map_res!(fdigit, str::from_utf8), FromStr::from_str));
+/// Parses a u32 in base-16 format.
+pub fn parse_u32_hex(s: &str) -> Result<u32> {
+    u32::from_str_radix(s, 16).map_err(|_| invalid_input(format!("invalid hex value: {:?}", s)))
+}
 
-/// Parses a sequence of whitespace seperated u32s.
-named!(pub parse_u32s<Vec<u32> >, separated_list!(space, complete!(parse_u32)));
+/// Parses a u32 in base-8 format.
+pub fn parse_u32_octal(s: &str) -> Result<u32> {
+    u32::from_str_radix(s, 8).map_err(|_| invalid_input(format!("invalid octal value: {:?}", s)))
+}
 
-/// Parses a sequence of whitespace seperated i32s.
-named!(pub parse_i32s<Vec<i32> >, separated_list!(space, parse_i32));
+/// Parses a u64 in base-16 format.
+pub fn parse_u64_hex(s: &str) -> Result<u64> {
+    u64::from_str_radix(s, 16).map_err(|_| invalid_input(format!("invalid hex value: {:?}", s)))
+}
 
-/// Parses a bit into a boolean
-named!(pub parse_bit<bool>, alt!(
-          char!('0') => { |_| false }
-        | char!('1') => { |_| true }
-));
+/// Parses a bit into a boolean.
+pub fn parse_bit(s: &str) -> Result<bool> {
+    match s {
+        "0" => Ok(false),
+        "1" => Ok(true),
+        _ => Err(invalid_input(format!("invalid bit: {:?}", s))),
+    }
+}
 
-/// Parses a usize followed by a kB unit tag.
-named!(pub parse_kb<usize>,
-       chain!(space ~ bytes: parse_usize ~ space ~ tag!("kB"), || { bytes }));
+/// Parses a u64 followed by a kB unit tag.
+pub fn parse_kb(s: &str) -> Result<u64> {
+    match s.trim().strip_suffix(" kB") {
+        Some(kb) => parse(kb.trim_end()),
+        None => Err(invalid_input(format!("missing kB unit: {:?}", s))),
+    }
+}
 
-/// Parses a u32 in base-16 format.
-named!(pub parse_u32_hex<u32>,
-       map_res!(map_res!(alphanumeric, str::from_utf8),
-                |s| u32::from_str_radix(s, 16)));
+/// Parses a sequence of whitespace separated values.
+pub fn parse_list<T: FromStr>(s: &str) -> Result<Vec<T>> {
+    s.split_whitespace().map(parse).collect()
+}
 
-/// Parses a u32 in base-8 format.
-named!(pub parse_u32_octal<u32>,
-       map_res!(map_res!(alphanumeric, str::from_utf8),
-                |s| u32::from_str_radix(s, 8)));
+/// The largest id accepted in a range list, which bounds the memory used by a parsed list.
+///
+/// The kernel supports at most 8192 CPUs and 1024 NUMA nodes.
+const MAX_RANGE_LIST_ID: usize = 1 << 20;
 
-/// Parses a u64 in base-16 format.
-named!(pub parse_u64_hex<u64>,
-       map_res!(map_res!(alphanumeric, str::from_utf8),
-                |s| u64::from_str_radix(s, 16)));
+/// Parses a range list, such as `0-3,5,7-9`, calling `range` with the inclusive bounds of each
+/// range. An empty list has no ranges.
+///
+/// See the "List format" section of cpuset(7) for the format being parsed.
+pub fn parse_range_list<F>(s: &str, mut range: F) -> Result<()> where F: FnMut(usize, usize) {
+    if s.is_empty() {
+        return Ok(());
+    }
+    for item in s.split(',') {
+        let (start, end) = match item.split_once('-') {
+            Some((start, end)) => (parse(start)?, parse(end)?),
+            None => {
+                let id = parse(item)?;
+                (id, id)
+            },
+        };
+        if start > end || end > MAX_RANGE_LIST_ID {
+            return Err(invalid_input(format!("invalid range: {:?}", item)));
+        }
+        range(start, end);
+    }
+    Ok(())
+}
 
 /// Reverses the bits in a byte.
 fn reverse(n: u8) -> u8 {
@@ -180,62 +297,106 @@ fn reverse(n: u8) -> u8 {
 /// Parses a list of u32 masks into an array of bytes in `BitVec` format.
 ///
 /// See cpuset(7) for the format being parsed.
-named!(pub parse_u32_mask_list<Box<[u8]> >,
-       map!(separated_nonempty_list!(tag!(","), parse_u32_hex), |mut ints: Vec<u32>| {
-           let mut bytes: Vec<u8> = Vec::with_capacity(ints.len() * 4);
-           let mut buf: [u8; 4] = [0; 4];
-           ints.reverse();
-           for int in ints {
-               LittleEndian::write_u32(&mut buf, int);
-               for b in buf.iter_mut() {
-                   *b = reverse(*b);
-               }
-               bytes.extend(&buf);
-           }
-           bytes.into_boxed_slice()
-       }));
-
-/// `take_until_right_and_consume!(tag) => &[T] -> IResult<&[T], &[T]>`
-/// generates a parser consuming bytes until the specified byte sequence is found, and consumes it.
-/// The sequence is searched for in the input in right to left order.
-macro_rules! take_until_right_and_consume(
-    ($i:expr, $inp:expr) => ({
-        #[inline(always)]
-        fn as_bytes<T: nom::AsBytes>(b: &T) -> &[u8] {
-            b.as_bytes()
+pub fn parse_u32_mask_list(s: &str) -> Result<Box<[u8]>> {
+    let ints = s.split(',').map(parse_u32_hex).collect::<Result<Vec<u32>>>()?;
+    let mut bytes: Vec<u8> = Vec::with_capacity(ints.len() * 4);
+    let mut buf: [u8; 4] = [0; 4];
+    for int in ints.into_iter().rev() {
+        LittleEndian::write_u32(&mut buf, int);
+        for b in buf.iter_mut() {
+            *b = reverse(*b);
         }
+        bytes.extend(&buf);
+    }
+    Ok(bytes.into_boxed_slice())
+}
+
+/// A cursor over the whitespace separated fields of a line.
+///
+/// Each accessor consumes one field, and fails if the field is missing or malformed.
+#[derive(Debug)]
+pub struct Fields<'a> {
+    line: &'a str,
+    fields: SplitWhitespace<'a>,
+}
 
-        let expected   = $inp;
-        let bytes      = as_bytes(&expected);
-        let mut index  = 0;
-        let mut parsed = false;
-        for idx in (0..(($i.len() + 1) - bytes.len())).rev() {
-            if &$i[idx..idx + bytes.len()] == bytes {
-                index = idx;
-                parsed = true;
-                break;
-            }
+impl<'a> Fields<'a> {
+    /// Creates a cursor over the fields of the line.
+    pub fn new(line: &'a str) -> Fields<'a> {
+        Fields { line, fields: line.split_whitespace() }
+    }
+
+    /// Returns the next field.
+    pub fn next_str(&mut self) -> Result<&'a str> {
+        let line = self.line;
+        self.fields.next().ok_or_else(|| {
+            with_remainder(invalid_input("missing field"), line, line.len())
+        })
+    }
+
+    /// Parses the next field.
+    pub fn parse<T: FromStr>(&mut self) -> Result<T> {
+        let field = self.next_str()?;
+        self.parse_field(field)
+    }
+
+    /// Parses the next field, if present.
+    ///
+    /// Fields which were added in later kernel versions are parsed with this.
+    pub fn parse_opt<T: FromStr>(&mut self) -> Result<Option<T>> {
+        match self.fields.next() {
+            Some(field) => self.parse_field(field).map(Some),
+            None => Ok(None),
         }
-        if parsed {
-            nom::IResult::Done(&$i[(index + bytes.len())..], &$i[0..index])
-        } else {
-            nom::IResult::Error(nom::Err::Position(nom::ErrorKind::TakeUntilAndConsume, $i))
+    }
+
+    /// Parses a field of the line, adding the unparsed remainder to parse errors.
+    fn parse_field<T: FromStr>(&self, field: &str) -> Result<T> {
+        parse(field).map_err(|error| with_remainder(error, self.line, self.offset(field)))
+    }
+
+    /// Returns the byte offset of a field within the line.
+    fn offset(&self, field: &str) -> usize {
+        field.as_ptr() as usize - self.line.as_ptr() as usize
+    }
+
+    /// Fails if any fields remain.
+    pub fn finish(mut self) -> Result<()> {
+        match self.fields.next() {
+            None => Ok(()),
+            Some(field) => Err(with_remainder(invalid_input("unable to parse whole input"),
+                                              self.line,
+                                              self.offset(field))),
         }
-    });
-);
+    }
+}
 
 #[cfg(test)]
 pub mod tests {
-    use std::u32;
+    use std::borrow::Cow;
+    use std::fs::{self, File};
 
-    use nom::IResult;
+    use std::io::{Error, ErrorKind};
+    use std::path::Path;
 
-    use super::{map_result, parse_f32, parse_i32, parse_i32s, parse_bit, parse_i64, parse_u32_hex,
-                parse_u32_mask_list, parse_u32s, reverse};
+    use super::{Fields, ParseError, invalid_input, parse, parse_bit, parse_kb, parse_line,
+                parse_lines, parse_list, parse_range_list, parse_str, parse_u32_hex,
+                parse_u32_mask_list, read_to_end, reverse, with_line, with_path, with_remainder};
 
-    /// Unwrap a complete parse result.
-    pub fn unwrap<T>(result: IResult<&[u8], T>) -> T {
-        map_result(result).unwrap()
+    /// Test that files larger than the provided buffer are read in full.
+    #[test]
+    fn test_read_to_end() {
+        let expected = fs::read("/proc/self/limits").unwrap();
+
+        let mut buf = [0; 16];
+        let read = read_to_end(&mut File::open("/proc/self/limits").unwrap(), &mut buf).unwrap();
+        assert!(matches!(read, Cow::Owned(_)));
+        assert_eq!(expected, &*read);
+
+        let mut buf = [0; 4096];
+        let read = read_to_end(&mut File::open("/proc/self/limits").unwrap(), &mut buf).unwrap();
+        assert!(matches!(read, Cow::Borrowed(_)));
+        assert_eq!(expected, &*read);
     }
 
     #[test]
@@ -249,78 +410,150 @@ pub mod tests {
 
     #[test]
     fn test_parse_u32_hex() {
-        assert_eq!(0, unwrap(parse_u32_hex(b"00000000")));
-        assert_eq!(1, unwrap(parse_u32_hex(b"00000001")));
-        assert_eq!(42, unwrap(parse_u32_hex(b"0000002a")));
-        assert_eq!(286331153, unwrap(parse_u32_hex(b"11111111")));
-        assert_eq!(u32::MAX, unwrap(parse_u32_hex(b"ffffffff")));
+        assert_eq!(0, parse_u32_hex("00000000").unwrap());
+        assert_eq!(1, parse_u32_hex("00000001").unwrap());
+        assert_eq!(42, parse_u32_hex("0000002a").unwrap());
+        assert_eq!(286331153, parse_u32_hex("11111111").unwrap());
+        assert_eq!(u32::MAX, parse_u32_hex("ffffffff").unwrap());
+        parse_u32_hex("fffffffff").unwrap_err();
     }
 
     #[test]
     fn test_u32_mask_list() {
         // Examples adapted from cpuset(7).
-        assert_eq!([0, 0, 0, 0], &*unwrap(parse_u32_mask_list(b"00000000")));
+        assert_eq!([0, 0, 0, 0], &*parse_u32_mask_list("00000000").unwrap());
 
-        assert_eq!([0x80, 0, 0, 0], &*unwrap(parse_u32_mask_list(b"00000001")));
+        assert_eq!([0x80, 0, 0, 0], &*parse_u32_mask_list("00000001").unwrap());
 
         assert_eq!([0, 0, 0, 0,
                     0, 0, 0, 0,
-                    0, 0, 0, 2], &*unwrap(parse_u32_mask_list(b"40000000,00000000,00000000")));
+                    0, 0, 0, 2], &*parse_u32_mask_list("40000000,00000000,00000000").unwrap());
 
         assert_eq!([0, 0, 0, 0,
                     0, 0, 0, 0,
-                    128, 0, 0, 0], &*unwrap(parse_u32_mask_list(b"00000001,00000000,00000000")));
+                    128, 0, 0, 0], &*parse_u32_mask_list("00000001,00000000,00000000").unwrap());
 
         assert_eq!([0, 0, 0, 0,
-                    0xff, 0, 0, 0], &*unwrap(parse_u32_mask_list(b"000000ff,00000000")));
+                    0xff, 0, 0, 0], &*parse_u32_mask_list("000000ff,00000000").unwrap());
 
         assert_eq!([0x46, 0x1c, 0x70, 0,
-                    0, 0, 0, 0], &*unwrap(parse_u32_mask_list(b"00000000,000e3862")));
+                    0, 0, 0, 0], &*parse_u32_mask_list("00000000,000e3862").unwrap());
+    }
+
+    #[test]
+    fn test_parse_range_list() {
+        let ranges = |s| {
+            let mut ranges = Vec::new();
+            parse_range_list(s, |start, end| ranges.push((start, end))).map(|()| ranges)
+        };
+        assert_eq!(vec![(0, 3), (5, 5), (7, 9)], ranges("0-3,5,7-9").unwrap());
+        assert_eq!(Vec::<(usize, usize)>::new(), ranges("").unwrap());
+        ranges("3-1").unwrap_err();
+        ranges("0-").unwrap_err();
+        ranges("0,,1").unwrap_err();
+        ranges("0-4294967295").unwrap_err();
     }
 
     #[test]
     fn test_parse_u32s() {
-        assert_eq!(Vec::<u32>::new(), &*unwrap(parse_u32s(b"")));
-        assert_eq!(vec![0u32], &*unwrap(parse_u32s(b"0")));
-        assert_eq!(vec![0u32, 1], &*unwrap(parse_u32s(b"0 1")));
-        assert_eq!(vec![99999u32, 32, 22, 888], &*unwrap(parse_u32s(b"99999 32 22 	888")));
+        assert_eq!(Vec::<u32>::new(), parse_list::<u32>("").unwrap());
+        assert_eq!(vec![0u32], parse_list::<u32>("0").unwrap());
+        assert_eq!(vec![0u32, 1], parse_list::<u32>("0 1").unwrap());
+        assert_eq!(vec![99999u32, 32, 22, 888], parse_list::<u32>("99999 32 22 	888").unwrap());
     }
 
     #[test]
     fn test_parse_i32s() {
-        assert_eq!(Vec::<i32>::new(), &*unwrap(parse_i32s(b"")));
-        assert_eq!(vec![0i32], &*unwrap(parse_i32s(b"0")));
-        assert_eq!(vec![0i32, 1], &*unwrap(parse_i32s(b"0 1")));
-        assert_eq!(vec![99999i32, 0, -22, 32, 888], &*unwrap(parse_i32s(b"99999 0 -22 32 888")));
+        assert_eq!(Vec::<i32>::new(), parse_list::<i32>("").unwrap());
+        assert_eq!(vec![0i32], parse_list::<i32>("0").unwrap());
+        assert_eq!(vec![0i32, 1], parse_list::<i32>("0 1").unwrap());
+        assert_eq!(vec![99999i32, 0, -22, 32, 888],
+                   parse_list::<i32>("99999 0 -22 32 888").unwrap());
     }
 
     #[test]
     fn test_parse_i32() {
-        assert_eq!(0i32, unwrap(parse_i32(b"0")));
-        assert_eq!(0i32, unwrap(parse_i32(b"-0")));
-        assert_eq!(32i32, unwrap(parse_i32(b"32")));
-        assert_eq!(-32i32, unwrap(parse_i32(b"-32")));
+        assert_eq!(0i32, parse::<i32>("0").unwrap());
+        assert_eq!(0i32, parse::<i32>("-0").unwrap());
+        assert_eq!(32i32, parse::<i32>("32").unwrap());
+        assert_eq!(-32i32, parse::<i32>("-32").unwrap());
+        parse::<i32>("32a").unwrap_err();
     }
 
     #[test]
-    fn test_parse_i64() {
-        assert_eq!(0i64, unwrap(parse_i64(b"0")));
-        assert_eq!(0i64, unwrap(parse_i64(b"-0")));
-        assert_eq!(32i64, unwrap(parse_i64(b"32")));
-        assert_eq!(-32i64, unwrap(parse_i64(b"-32")));
+    fn test_parse_f32() {
+        assert_eq!(0.0, parse::<f32>("0").unwrap());
+        assert_eq!(0.0, parse::<f32>("0.0").unwrap());
+        assert_eq!(2.0, parse::<f32>("2.0").unwrap());
+        assert_eq!(45.67, parse::<f32>("45.67").unwrap());
     }
 
     #[test]
-    fn test_parse_f32() {
-        assert_eq!(0.0, unwrap(parse_f32(b"0")));
-        assert_eq!(0.0, unwrap(parse_f32(b"0.0")));
-        assert_eq!(2.0, unwrap(parse_f32(b"2.0")));
-        assert_eq!(45.67, unwrap(parse_f32(b"45.67")));
+    fn test_parse_bit() {
+        assert!(parse_bit("1").unwrap());
+        assert!(!parse_bit("0").unwrap());
+        parse_bit("2").unwrap_err();
     }
 
     #[test]
-    fn test_parse_bit() {
-        assert_eq!(true, unwrap(parse_bit(b"1")));
-        assert_eq!(false, unwrap(parse_bit(b"0")));
+    fn test_parse_kb() {
+        assert_eq!(1024, parse_kb("\t    1024 kB").unwrap());
+        parse_kb("1024").unwrap_err();
+    }
+
+    #[test]
+    fn test_parse_line() {
+        assert_eq!("667", parse_line(b"667\n").unwrap());
+        assert_eq!("667", parse_line(b"667").unwrap());
+        parse_line(b"667\nfoo").unwrap_err();
+    }
+
+    #[test]
+    fn test_fields() {
+        let mut fields = Fields::new("1 -2  three\t4.5");
+        assert_eq!(1, fields.parse::<u32>().unwrap());
+        assert_eq!(-2, fields.parse::<i32>().unwrap());
+        assert_eq!("three", fields.next_str().unwrap());
+        assert_eq!(Some(4.5f32), fields.parse_opt().unwrap());
+        assert_eq!(None, fields.parse_opt::<u32>().unwrap());
+        fields.parse::<u32>().unwrap_err();
+        fields.finish().unwrap();
+
+        let mut fields = Fields::new("1 2");
+        fields.parse::<u32>().unwrap();
+        fields.finish().unwrap_err();
+    }
+
+    /// Returns the context of a parse error.
+    fn parse_error(error: Error) -> ParseError {
+        error.get_ref().and_then(|e| e.downcast_ref::<ParseError>()).unwrap().clone()
+    }
+
+    #[test]
+    fn test_parse_error() {
+        let mut fields = Fields::new("1 x 3");
+        fields.parse::<u32>().unwrap();
+        let error = fields.parse::<u32>().unwrap_err();
+        assert_eq!(ErrorKind::InvalidInput, error.kind());
+        let error = with_path(error, Path::new("/proc/self/statm"));
+        assert_eq!("/proc/self/statm: offset 2: invalid value: \"x\" at \"x 3\"",
+                   error.to_string());
+
+        let input = b"header\n1 2\n\n3 x\n";
+        let error = parse_lines(input, 1, |line| {
+            let mut fields = Fields::new(parse_str(line)?);
+            Ok((fields.parse::<u32>()?, fields.parse::<u32>()?))
+        }).unwrap_err();
+        let context = parse_error(error);
+        assert_eq!(Some(4), context.line);
+        assert_eq!(Some(2), context.offset);
+        assert_eq!("line 4, offset 2: invalid value: \"x\" at \"x\"", context.to_string());
+
+        let line = "€".repeat(30);
+        let error = with_remainder(invalid_input("invalid"), &line, 0);
+        assert_eq!(21, parse_error(error).remainder.unwrap().chars().count());
+
+        let error = with_line(Error::from(ErrorKind::NotFound), 1);
+        assert!(error.get_ref().is_none());
     }
 }
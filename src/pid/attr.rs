@@ -0,0 +1,70 @@
+//! Linux Security Module attributes from `/proc/[pid]/attr`.
+
+use std::fs::File;
+use std::io::{Read, Result};
+use std::str;
+
+use libc::{EINVAL, pid_t};
+
+use parsers::{invalid_input, parse_file};
+
+/// Parses the attr/current file format.
+///
+/// The security context is terminated by a NUL (SELinux) or a newline (AppArmor).
+fn parse_attr_current(input: &[u8]) -> Result<String> {
+    let mut end = input.len();
+    while end > 0 && (input[end - 1] == b'\0' || input[end - 1] == b'\n') {
+        end -= 1;
+    }
+    match str::from_utf8(&input[..end]) {
+        Ok(context) => Ok(context.to_owned()),
+        Err(_) => Err(invalid_input("security context is not valid UTF-8")),
+    }
+}
+
+/// Parses the provided attr/current file.
+pub(crate) fn attr_current_file(file: &mut File) -> Result<Option<String>> {
+    let mut buf = Vec::new();
+    match file.read_to_end(&mut buf) {
+        Ok(_) => parse_attr_current(&buf).map(Some),
+        Err(ref error) if error.raw_os_error() == Some(EINVAL) => Ok(None),
+        Err(error) => Err(error),
+    }
+}
+
+/// Returns the security context (e.g. the SELinux or AppArmor label) of the process with the
+/// provided pid.
+///
+/// Returns `None` if no Linux Security Module providing process labels is active.
+pub fn attr_current(pid: pid_t) -> Result<Option<String>> {
+    parse_file(format!("/proc/{}/attr/current", pid), attr_current_file)
+}
+
+/// Returns the security context of the current process.
+///
+/// Returns `None` if no Linux Security Module providing process labels is active.
+pub fn attr_current_self() -> Result<Option<String>> {
+    parse_file("/proc/self/attr/current", attr_current_file)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{attr_current, attr_current_self, parse_attr_current};
+
+    /// Test that the system attr/current files can be read.
+    #[test]
+    fn test_attr_current() {
+        attr_current_self().unwrap();
+        attr_current(1).unwrap();
+    }
+
+    #[test]
+    fn test_parse_attr_current() {
+        assert_eq!("unconfined_u:unconfined_r:unconfined_t:s0-s0:c0.c1023",
+                   parse_attr_current(b"unconfined_u:unconfined_r:unconfined_t:s0-s0:c0.c1023\0")
+                       .unwrap());
+        assert_eq!("unconfined", parse_attr_current(b"unconfined\n").unwrap());
+        assert_eq!("/usr/bin/foo (enforce)",
+                   parse_attr_current(b"/usr/bin/foo (enforce)\n").unwrap());
+    }
+}
@@ -0,0 +1,182 @@
+//! Information which zombie processes and kernel threads do not have.
+
+use std::ffi::OsString;
+use std::fs::File;
+use std::io::{ErrorKind, Result};
+
+use libc::pid_t;
+
+use pid::ProcessState;
+use pid::cmdline::cmdline_file;
+use pid::stat::{Stat, stat};
+
+/// Information about a process, such as its command line or executable, which is unavailable when
+/// the process is a zombie or a kernel thread.
+///
+/// Zombie processes have released their memory and files, and kernel threads have no user-space
+/// memory or executable, so the kernel reports empty command lines and missing links for both.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "export", derive(Serialize))]
+pub enum Availability<T> {
+    /// The information is available.
+    Available(T),
+    /// The process is a zombie which has not yet been reaped by its parent.
+    Zombie,
+    /// The process is a kernel thread.
+    KernelThread,
+}
+
+impl<T> Availability<T> {
+    /// Returns the information, or `None` if it is unavailable.
+    pub fn available(self) -> Option<T> {
+        match self {
+            Availability::Available(value) => Some(value),
+            _ => None,
+        }
+    }
+
+    /// Returns whether the information is available.
+    pub fn is_available(&self) -> bool {
+        matches!(*self, Availability::Available(_))
+    }
+}
+
+/// Returns why information is missing for the process, or `None` if it is neither a zombie nor a
+/// kernel thread.
+fn unavailable<T>(stat: &Stat) -> Option<Availability<T>> {
+    if stat.state == ProcessState::Zombie {
+        Some(Availability::Zombie)
+    } else if stat.is_kernel_thread() {
+        Some(Availability::KernelThread)
+    } else {
+        None
+    }
+}
+
+/// Classifies a command line, which is empty for zombies and kernel threads.
+///
+/// The stat of the process is only read when the command line is empty, since a process may also
+/// have been started without arguments.
+pub(crate) fn classify_cmdline<T, F>(args: Vec<T>, stat: F) -> Result<Availability<Vec<T>>>
+where F: FnOnce() -> Result<Stat> {
+    if !args.is_empty() {
+        return Ok(Availability::Available(args));
+    }
+    Ok(unavailable(&stat()?).unwrap_or(Availability::Available(args)))
+}
+
+/// Classifies the result of reading a link, such as `exe`, which is missing for zombies and kernel
+/// threads.
+///
+/// If the process has exited, or is neither a zombie nor a kernel thread, the original error is
+/// returned.
+pub(crate) fn classify_link<T, F>(link: Result<T>, stat: F) -> Result<Availability<T>>
+where F: FnOnce() -> Result<Stat> {
+    match link {
+        Ok(target) => Ok(Availability::Available(target)),
+        Err(error) => {
+            if error.kind() != ErrorKind::NotFound {
+                return Err(error);
+            }
+            match stat() {
+                Ok(ref stat) => unavailable(stat).ok_or(error),
+                Err(_) => Err(error),
+            }
+        },
+    }
+}
+
+/// Returns whether the process is a kernel thread.
+///
+/// Kernels before Linux 2.6.27 do not report `PF_KTHREAD`, so a process without it is also taken
+/// to be a kernel thread if it has no memory map and an empty command line, and is not a zombie.
+/// The command line is only read in that case.
+pub(crate) fn classify_kernel_thread<F>(stat: &Stat, cmdline: F) -> Result<bool>
+where F: FnOnce() -> Result<Vec<OsString>> {
+    if stat.is_kernel_thread() {
+        return Ok(true);
+    }
+    if stat.vsize != 0 || stat.state == ProcessState::Zombie {
+        return Ok(false);
+    }
+    Ok(cmdline()?.is_empty())
+}
+
+/// Returns whether the process with the provided pid is a kernel thread.
+pub fn is_kernel_thread(pid: pid_t) -> Result<bool> {
+    classify_kernel_thread(&stat(pid)?, || {
+        cmdline_file(&mut File::open(format!("/proc/{}/cmdline", pid))?)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::ffi::OsString;
+    use std::io::{Error, ErrorKind, Result};
+    use std::path::PathBuf;
+
+    use libc;
+
+    use pid::{ProcessState, Stat, TaskFlags, stat, stat_self};
+    use super::{Availability, classify_cmdline, classify_kernel_thread, classify_link,
+                is_kernel_thread};
+
+    fn zombie() -> Result<Stat> {
+        let mut stat = stat_self()?;
+        stat.state = ProcessState::Zombie;
+        Ok(stat)
+    }
+
+    fn kernel_thread() -> Result<Stat> {
+        let mut stat = stat_self()?;
+        stat.flags |= TaskFlags::KTHREAD.bits();
+        Ok(stat)
+    }
+
+    fn missing() -> Result<PathBuf> {
+        Err(Error::from(ErrorKind::NotFound))
+    }
+
+    #[test]
+    fn test_classify_cmdline() {
+        assert_eq!(Availability::Available(vec!["ls"]),
+                   classify_cmdline(vec!["ls"], || unreachable!()).unwrap());
+        assert_eq!(Availability::Zombie, classify_cmdline(Vec::<String>::new(), zombie).unwrap());
+        assert_eq!(Availability::KernelThread,
+                   classify_cmdline(Vec::<String>::new(), kernel_thread).unwrap());
+        assert_eq!(Availability::Available(Vec::<String>::new()),
+                   classify_cmdline(Vec::new(), stat_self).unwrap());
+    }
+
+    #[test]
+    fn test_classify_link() {
+        assert_eq!(Some(PathBuf::from("/")),
+                   classify_link(Ok(PathBuf::from("/")), || unreachable!()).unwrap().available());
+        assert_eq!(Availability::Zombie, classify_link(missing(), zombie).unwrap());
+        assert_eq!(Availability::KernelThread, classify_link(missing(), kernel_thread).unwrap());
+        assert_eq!(ErrorKind::NotFound, classify_link(missing(), stat_self).unwrap_err().kind());
+
+        // The process has exited.
+        let error = classify_link(missing(), || Err(Error::from(ErrorKind::NotFound))).unwrap_err();
+        assert_eq!(ErrorKind::NotFound, error.kind());
+    }
+
+    #[test]
+    fn test_is_kernel_thread() {
+        assert!(!is_kernel_thread(unsafe { libc::getpid() }).unwrap());
+        assert!(!is_kernel_thread(1).unwrap());
+        // kthreadd is pid 2, unless running in a pid namespace.
+        if stat(2).map(|stat| stat.command == "kthreadd").unwrap_or(false) {
+            assert!(is_kernel_thread(2).unwrap());
+        }
+
+        let empty = || Ok(Vec::<OsString>::new());
+        assert!(classify_kernel_thread(&kernel_thread().unwrap(), || unreachable!()).unwrap());
+        assert!(!classify_kernel_thread(&stat_self().unwrap(), || unreachable!()).unwrap());
+        let no_mm = Stat { vsize: 0, ..stat_self().unwrap() };
+        assert!(classify_kernel_thread(&no_mm, empty).unwrap());
+        assert!(!classify_kernel_thread(&no_mm, || Ok(vec![OsString::from("ls")])).unwrap());
+        let zombie = Stat { vsize: 0, ..zombie().unwrap() };
+        assert!(!classify_kernel_thread(&zombie, || unreachable!()).unwrap());
+    }
+}
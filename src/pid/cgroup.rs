@@ -0,0 +1,340 @@
+//! Process control groups from `/proc/[pid]/cgroup`, and cgroup v1 controller statistics.
+
+use std::collections::BTreeMap;
+use std::fs::File;
+use std::io::{ErrorKind, Read, Result};
+use std::path::PathBuf;
+use std::time::Duration;
+
+use libc::pid_t;
+
+use parsers::{Fields, invalid_input, parse, parse_file, parse_line, parse_lines, parse_str,
+              with_line, with_path};
+use pid::mountinfo::{Mountinfo, mountinfo_self};
+
+/// A control group which a process belongs to.
+///
+/// See `cgroups(7)`.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "export", derive(Serialize))]
+pub struct Cgroup {
+    /// The ID of the hierarchy, which is zero for the cgroup v2 hierarchy.
+    pub hierarchy_id: u32,
+    /// The controllers bound to the hierarchy, including named hierarchies such as
+    /// `name=systemd`. Empty for the cgroup v2 hierarchy.
+    pub controllers: Vec<String>,
+    /// The path of the control group, relative to the root of the hierarchy as seen from the
+    /// cgroup namespace of the reading process.
+    pub path: PathBuf,
+}
+
+impl Cgroup {
+    /// Parses a line of a `/proc/[pid]/cgroup` file, without the line terminator.
+    ///
+    /// The line is of the form `4:memory,hugetlb:/user.slice`.
+    pub fn parse_line(input: &[u8]) -> Result<Cgroup> {
+        let line = parse_str(input)?;
+        let mut fields = line.splitn(3, ':');
+        let fields = (fields.next(), fields.next(), fields.next());
+        let (hierarchy_id, controllers, path) = match fields {
+            (Some(id), Some(controllers), Some(path)) => (id, controllers, path),
+            _ => return Err(invalid_input(format!("invalid cgroup line: {:?}", line))),
+        };
+        Ok(Cgroup {
+            hierarchy_id: parse(hierarchy_id)?,
+            controllers: controllers.split(',')
+                                    .filter(|controller| !controller.is_empty())
+                                    .map(str::to_owned)
+                                    .collect(),
+            path: PathBuf::from(path),
+        })
+    }
+
+    /// Returns whether the control group belongs to the cgroup v2 hierarchy.
+    pub fn is_v2(&self) -> bool {
+        self.hierarchy_id == 0 && self.controllers.is_empty()
+    }
+}
+
+/// Parses the provided cgroup file.
+pub(crate) fn cgroup_file(file: &mut File) -> Result<Vec<Cgroup>> {
+    let mut buf = Vec::new();
+    file.read_to_end(&mut buf)?;
+    parse_lines(&buf, 0, Cgroup::parse_line)
+}
+
+/// Returns the control groups of the process with the provided pid.
+pub fn cgroup(pid: pid_t) -> Result<Vec<Cgroup>> {
+    parse_file(format!("/proc/{}/cgroup", pid), cgroup_file)
+}
+
+/// Returns the control groups of the current process.
+pub fn cgroup_self() -> Result<Vec<Cgroup>> {
+    parse_file("/proc/self/cgroup", cgroup_file)
+}
+
+/// The bytes transferred to and from a block device by a cgroup v1 blkio control group.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "export", derive(Serialize))]
+pub struct BlkioServiceBytes {
+    /// The major number of the device.
+    pub major: u32,
+    /// The minor number of the device.
+    pub minor: u32,
+    /// The number of bytes read.
+    pub read: u64,
+    /// The number of bytes written.
+    pub write: u64,
+    /// The number of bytes transferred by synchronous requests.
+    pub sync: u64,
+    /// The number of bytes transferred by asynchronous requests.
+    pub async_: u64,
+    /// The number of bytes discarded (since Linux 4.19).
+    pub discard: Option<u64>,
+    /// The total number of bytes transferred.
+    pub total: u64,
+}
+
+/// Parses a line of a blkio statistics file into the per-device statistics.
+///
+/// The line is of the form `8:0 Read 4096`. The final `Total 4096` line, which sums all devices,
+/// is ignored.
+fn parse_blkio_line(devices: &mut Vec<BlkioServiceBytes>, line: &str) -> Result<()> {
+    let mut fields = Fields::new(line);
+    let device = fields.next_str()?;
+    if device == "Total" {
+        return Ok(());
+    }
+    let (major, minor) = device.split_once(':').ok_or_else(|| {
+        invalid_input(format!("invalid blkio device: {:?}", device))
+    })?;
+    let (major, minor) = (parse(major)?, parse(minor)?);
+    let operation = fields.next_str()?;
+    let bytes = fields.parse()?;
+    fields.finish()?;
+
+    if devices.last().map(|last| (last.major, last.minor)) != Some((major, minor)) {
+        devices.push(BlkioServiceBytes { major, minor, ..BlkioServiceBytes::default() });
+    }
+    let stats = devices.last_mut().unwrap();
+    match operation {
+        "Read" => stats.read = bytes,
+        "Write" => stats.write = bytes,
+        "Sync" => stats.sync = bytes,
+        "Async" => stats.async_ = bytes,
+        "Discard" => stats.discard = Some(bytes),
+        "Total" => stats.total = bytes,
+        _ => return Err(invalid_input(format!("invalid blkio operation: {:?}", operation))),
+    }
+    Ok(())
+}
+
+/// Parses the contents of a blkio statistics file, such as `blkio.throttle.io_service_bytes`.
+fn parse_blkio_service_bytes(input: &[u8]) -> Result<Vec<BlkioServiceBytes>> {
+    let mut devices = Vec::new();
+    for (i, line) in parse_str(input)?.lines().enumerate() {
+        if !line.is_empty() {
+            parse_blkio_line(&mut devices, line).map_err(|error| with_line(error, i + 1))?;
+        }
+    }
+    Ok(devices)
+}
+
+/// Parses a line of a `memory.stat` file, of the form `rss 228552704`.
+fn parse_memory_stat_line(line: &str) -> Result<(String, u64)> {
+    let mut fields = Fields::new(line);
+    let name = fields.next_str()?.to_owned();
+    let value = fields.parse()?;
+    fields.finish()?;
+    Ok((name, value))
+}
+
+/// Parses the contents of a `memory.stat` file, which has a line per counter.
+fn parse_memory_stat(input: &[u8]) -> Result<BTreeMap<String, u64>> {
+    let mut stat = BTreeMap::new();
+    for (i, line) in parse_str(input)?.lines().enumerate() {
+        if !line.is_empty() {
+            let (name, value) = parse_memory_stat_line(line)
+                .map_err(|error| with_line(error, i + 1))?;
+            stat.insert(name, value);
+        }
+    }
+    Ok(stat)
+}
+
+/// Parses the contents of a single-value control file.
+fn parse_value(input: &[u8]) -> Result<u64> {
+    parse(parse_line(input)?)
+}
+
+/// Statistics of the cgroup v1 memory, cpuacct, and blkio controllers of a process.
+///
+/// Statistics of controllers which are not mounted as a cgroup v1 hierarchy, or whose hierarchy
+/// mount does not include the control group of the process, are `None`.
+///
+/// See `Linux/Documentation/admin-guide/cgroup-v1/`.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "export", derive(Serialize))]
+pub struct CgroupV1Stats {
+    /// The memory usage of the control group in bytes, from `memory.usage_in_bytes`.
+    pub memory_usage: Option<u64>,
+    /// The memory counters of the control group by name, from `memory.stat`.
+    pub memory_stat: Option<BTreeMap<String, u64>>,
+    /// The CPU time consumed by the control group, from `cpuacct.usage`.
+    pub cpuacct_usage: Option<Duration>,
+    /// The bytes transferred to and from each block device by the control group, from
+    /// `blkio.throttle.io_service_bytes`.
+    pub blkio_service_bytes: Option<Vec<BlkioServiceBytes>>,
+}
+
+/// Returns the directory of the control group of the controller, if its cgroup v1 hierarchy is
+/// mounted and the mount includes the control group.
+fn controller_dir(cgroups: &[Cgroup], mounts: &[Mountinfo], controller: &str) -> Option<PathBuf> {
+    let cgroup = cgroups.iter()
+                        .find(|cgroup| cgroup.controllers.iter().any(|c| c == controller))?;
+    mounts.iter()
+          .filter(|mount| mount.fs_type.0 == "cgroup")
+          .filter(|mount| mount.super_opts.iter().any(|opt| opt == controller))
+          .filter_map(|mount| {
+              let relative = cgroup.path.strip_prefix(&mount.root).ok()?;
+              Some(mount.mount_point.join(relative))
+          })
+          .next()
+}
+
+/// Reads the control file of the controller, treating a missing hierarchy or file as absent.
+fn read_control_file<T>(cgroups: &[Cgroup],
+                        mounts: &[Mountinfo],
+                        controller: &str,
+                        name: &str,
+                        parse: fn(&[u8]) -> Result<T>)
+                        -> Result<Option<T>> {
+    let path = match controller_dir(cgroups, mounts, controller) {
+        Some(dir) => dir.join(name),
+        None => return Ok(None),
+    };
+    let mut buf = Vec::new();
+    match File::open(&path).and_then(|mut file| file.read_to_end(&mut buf)) {
+        Ok(_) => parse(&buf).map(Some).map_err(|error| with_path(error, &path)),
+        Err(ref error) if error.kind() == ErrorKind::NotFound => Ok(None),
+        Err(error) => Err(error),
+    }
+}
+
+impl CgroupV1Stats {
+    /// Reads the statistics of the provided control groups, whose hierarchies are located through
+    /// the provided mounts.
+    pub fn read(cgroups: &[Cgroup], mounts: &[Mountinfo]) -> Result<CgroupV1Stats> {
+        Ok(CgroupV1Stats {
+            memory_usage: read_control_file(cgroups, mounts, "memory", "memory.usage_in_bytes",
+                                            parse_value)?,
+            memory_stat: read_control_file(cgroups, mounts, "memory", "memory.stat",
+                                           parse_memory_stat)?,
+            cpuacct_usage: read_control_file(cgroups, mounts, "cpuacct", "cpuacct.usage",
+                                             parse_value)?.map(Duration::from_nanos),
+            blkio_service_bytes: read_control_file(cgroups, mounts, "blkio",
+                                                   "blkio.throttle.io_service_bytes",
+                                                   parse_blkio_service_bytes)?,
+        })
+    }
+}
+
+/// Returns the cgroup v1 controller statistics of the process with the provided pid.
+///
+/// The hierarchies are located through the mounts of the current process.
+pub fn cgroup_v1_stats(pid: pid_t) -> Result<CgroupV1Stats> {
+    CgroupV1Stats::read(&cgroup(pid)?, &mountinfo_self()?)
+}
+
+/// Returns the cgroup v1 controller statistics of the current process.
+pub fn cgroup_v1_stats_self() -> Result<CgroupV1Stats> {
+    CgroupV1Stats::read(&cgroup_self()?, &mountinfo_self()?)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use super::{BlkioServiceBytes, Cgroup, cgroup_self, cgroup_v1_stats_self,
+                parse_blkio_service_bytes, parse_memory_stat};
+
+    /// Test that the control groups and cgroup v1 statistics of the current process can be read.
+    #[test]
+    fn test_cgroup() {
+        let cgroups = cgroup_self().unwrap();
+        assert!(!cgroups.is_empty());
+        assert!(cgroups.iter().all(|cgroup| cgroup.path.is_absolute()));
+
+        let stats = cgroup_v1_stats_self().unwrap();
+        if let (Some(usage), Some(stat)) = (stats.memory_usage, stats.memory_stat) {
+            assert!(usage > 0);
+            assert!(stat.contains_key("rss"));
+        }
+    }
+
+    #[test]
+    fn test_parse_cgroup_line() {
+        let cgroup = Cgroup::parse_line(b"4:memory,hugetlb:/user.slice").unwrap();
+        assert_eq!(4, cgroup.hierarchy_id);
+        assert_eq!(vec!["memory", "hugetlb"], cgroup.controllers);
+        assert_eq!(Path::new("/user.slice"), cgroup.path);
+        assert!(!cgroup.is_v2());
+
+        let cgroup = Cgroup::parse_line(b"0::/system.slice/a:b.service").unwrap();
+        assert!(cgroup.is_v2());
+        assert_eq!(Path::new("/system.slice/a:b.service"), cgroup.path);
+
+        Cgroup::parse_line(b"1:name=systemd").unwrap_err();
+        Cgroup::parse_line(b"x:cpu:/").unwrap_err();
+    }
+
+    #[test]
+    fn test_parse_memory_stat() {
+        let stat = parse_memory_stat(b"cache 4007211008\nrss 228552704\nrss_huge 0\n").unwrap();
+        assert_eq!(3, stat.len());
+        assert_eq!(Some(&228_552_704), stat.get("rss"));
+        parse_memory_stat(b"cache\n").unwrap_err();
+    }
+
+    #[test]
+    fn test_parse_blkio_service_bytes() {
+        let text = b"8:0 Read 4096\n\
+                     8:0 Write 8192\n\
+                     8:0 Sync 12288\n\
+                     8:0 Async 0\n\
+                     8:0 Discard 0\n\
+                     8:0 Total 12288\n\
+                     253:1 Read 512\n\
+                     253:1 Write 0\n\
+                     253:1 Sync 0\n\
+                     253:1 Async 512\n\
+                     253:1 Total 512\n\
+                     Total 12800\n";
+        let devices = parse_blkio_service_bytes(text).unwrap();
+        assert_eq!(vec![BlkioServiceBytes {
+                            major: 8,
+                            minor: 0,
+                            read: 4096,
+                            write: 8192,
+                            sync: 12288,
+                            async_: 0,
+                            discard: Some(0),
+                            total: 12288,
+                        },
+                        BlkioServiceBytes {
+                            major: 253,
+                            minor: 1,
+                            read: 512,
+                            write: 0,
+                            sync: 0,
+                            async_: 512,
+                            discard: None,
+                            total: 512,
+                        }],
+                   devices);
+        assert!(parse_blkio_service_bytes(b"Total 0\n").unwrap().is_empty());
+
+        parse_blkio_service_bytes(b"8:0 Seek 1\n").unwrap_err();
+        parse_blkio_service_bytes(b"8-0 Read 1\n").unwrap_err();
+    }
+}
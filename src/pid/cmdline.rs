@@ -0,0 +1,95 @@
+//! Process command line arguments from `/proc/[pid]/cmdline`.
+
+use std::ffi::{OsStr, OsString};
+use std::fs::File;
+use std::io::{Read, Result};
+use std::os::unix::ffi::OsStrExt;
+
+use libc::pid_t;
+
+use parsers::parse_file;
+use pid::availability::{Availability, classify_cmdline};
+use pid::stat::{stat, stat_task};
+
+/// Parses the cmdline file format.
+///
+/// Arguments are terminated by a NUL byte. A process which overwrites its argument area may omit
+/// the terminator of the final argument.
+pub fn parse_cmdline(input: &[u8]) -> Vec<OsString> {
+    let input = if input.last() == Some(&0) { &input[..input.len() - 1] } else { input };
+    if input.is_empty() {
+        return Vec::new();
+    }
+    input.split(|&b| b == 0).map(|arg| OsStr::from_bytes(arg).to_owned()).collect()
+}
+
+/// Parses the provided cmdline file.
+pub(crate) fn cmdline_file(file: &mut File) -> Result<Vec<OsString>> {
+    let mut buf = Vec::new();
+    file.read_to_end(&mut buf)?;
+    Ok(parse_cmdline(&buf))
+}
+
+/// Returns the command line arguments of the process with the provided pid.
+///
+/// Zombie processes and kernel threads have no arguments, and are reported as such instead of with
+/// an empty vector.
+pub fn cmdline(pid: pid_t) -> Result<Availability<Vec<OsString>>> {
+    let args = parse_file(format!("/proc/{}/cmdline", pid), cmdline_file)?;
+    classify_cmdline(args, || stat(pid))
+}
+
+/// Returns the command line arguments of the current process.
+pub fn cmdline_self() -> Result<Vec<OsString>> {
+    parse_file("/proc/self/cmdline", cmdline_file)
+}
+
+/// Returns the command line arguments from the thread with the provided parent process ID and
+/// thread ID.
+pub fn cmdline_task(process_id: pid_t, thread_id: pid_t)
+                    -> Result<Availability<Vec<OsString>>> {
+    let path = format!("/proc/{}/task/{}/cmdline", process_id, thread_id);
+    classify_cmdline(parse_file(path, cmdline_file)?, || stat_task(process_id, thread_id))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::env;
+    use std::ffi::OsString;
+
+    use libc;
+
+    use pid::{Availability, stat};
+    use super::{cmdline, cmdline_self, cmdline_task, parse_cmdline};
+
+    /// Test that the system cmdline files can be read.
+    #[test]
+    fn test_cmdline() {
+        assert_eq!(env::args_os().collect::<Vec<_>>(), cmdline_self().unwrap());
+        cmdline(1).unwrap();
+    }
+
+    /// Test that kthreadd, if visible, is reported as a kernel thread.
+    #[test]
+    fn test_cmdline_kernel_thread() {
+        if stat(2).map(|stat| stat.command == "kthreadd").unwrap_or(false) {
+            assert_eq!(Availability::KernelThread, cmdline(2).unwrap());
+        }
+    }
+
+    /// Test that the cmdline file of the main thread matches that of the process.
+    #[test]
+    fn test_cmdline_task() {
+        let pid = unsafe { libc::getpid() };
+        assert_eq!(Some(cmdline_self().unwrap()), cmdline_task(pid, pid).unwrap().available());
+    }
+
+    #[test]
+    fn test_parse_cmdline() {
+        assert_eq!(vec![OsString::from("ls"), OsString::from("-l"), OsString::from("")],
+                   parse_cmdline(b"ls\0-l\0\0"));
+        assert_eq!(vec![OsString::from("nginx: worker process")],
+                   parse_cmdline(b"nginx: worker process"));
+        assert!(parse_cmdline(b"").is_empty());
+    }
+}
@@ -0,0 +1,103 @@
+//! Core dump mapping filter from `/proc/[pid]/coredump_filter`.
+
+use std::fs::{File, OpenOptions};
+use std::io::{Result, Write};
+
+use libc::pid_t;
+
+use parsers::{parse_file, parse_line, parse_u32_hex, read_to_end};
+
+bitflags! {
+    /// The types of memory mappings which are written to a core dump.
+    ///
+    /// See `core(5)`.
+    #[derive(Default)]
+    #[cfg_attr(feature = "export", derive(Serialize))]
+    pub struct CoredumpFilter: u32 {
+        /// Anonymous private mappings.
+        const ANONYMOUS_PRIVATE = 1 << 0;
+        /// Anonymous shared mappings.
+        const ANONYMOUS_SHARED = 1 << 1;
+        /// File-backed private mappings.
+        const FILE_PRIVATE = 1 << 2;
+        /// File-backed shared mappings.
+        const FILE_SHARED = 1 << 3;
+        /// ELF headers (since Linux 2.6.24).
+        const ELF_HEADERS = 1 << 4;
+        /// Private huge pages (since Linux 2.6.28).
+        const HUGETLB_PRIVATE = 1 << 5;
+        /// Shared huge pages (since Linux 2.6.28).
+        const HUGETLB_SHARED = 1 << 6;
+        /// Private DAX pages (since Linux 4.4).
+        const DAX_PRIVATE = 1 << 7;
+        /// Shared DAX pages (since Linux 4.4).
+        const DAX_SHARED = 1 << 8;
+    }
+}
+
+/// Parses the coredump_filter file format.
+fn parse_coredump_filter(input: &[u8]) -> Result<CoredumpFilter> {
+    parse_u32_hex(parse_line(input)?).map(CoredumpFilter::from_bits_truncate)
+}
+
+impl CoredumpFilter {
+    /// Parses the contents of a `/proc/[pid]/coredump_filter` file.
+    pub fn parse(input: &[u8]) -> Result<CoredumpFilter> {
+        parse_coredump_filter(input)
+    }
+}
+
+/// Parses the provided coredump_filter file.
+pub(crate) fn coredump_filter_file(file: &mut File) -> Result<CoredumpFilter> {
+    let mut buf = [0; 32]; // A coredump_filter file is 9 bytes.
+    CoredumpFilter::parse(&read_to_end(file, &mut buf)?)
+}
+
+/// Returns the core dump filter of the process with the provided pid.
+pub fn coredump_filter(pid: pid_t) -> Result<CoredumpFilter> {
+    parse_file(format!("/proc/{}/coredump_filter", pid), coredump_filter_file)
+}
+
+/// Returns the core dump filter of the current process.
+pub fn coredump_filter_self() -> Result<CoredumpFilter> {
+    parse_file("/proc/self/coredump_filter", coredump_filter_file)
+}
+
+/// Sets the core dump filter of the current process.
+///
+/// The filter is inherited by children created with `fork(2)` and preserved across `execve(2)`.
+pub fn set_coredump_filter_self(filter: CoredumpFilter) -> Result<()> {
+    let mut file = OpenOptions::new().write(true).open("/proc/self/coredump_filter")?;
+    file.write_all(format!("{:#x}\n", filter.bits()).as_bytes())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{CoredumpFilter, coredump_filter, coredump_filter_self, parse_coredump_filter,
+                set_coredump_filter_self};
+
+    /// Test that the system coredump_filter files can be parsed.
+    #[test]
+    fn test_coredump_filter() {
+        coredump_filter_self().unwrap();
+        coredump_filter(1).unwrap();
+    }
+
+    /// Test that writing the current filter back is accepted.
+    #[test]
+    fn test_set_coredump_filter_self() {
+        let filter = coredump_filter_self().unwrap();
+        set_coredump_filter_self(filter).unwrap();
+        assert_eq!(filter, coredump_filter_self().unwrap());
+    }
+
+    #[test]
+    fn test_parse_coredump_filter() {
+        assert_eq!(CoredumpFilter::ANONYMOUS_PRIVATE
+                       | CoredumpFilter::ANONYMOUS_SHARED
+                       | CoredumpFilter::ELF_HEADERS
+                       | CoredumpFilter::HUGETLB_PRIVATE,
+                   parse_coredump_filter(b"00000033\n").unwrap());
+        assert_eq!(CoredumpFilter::empty(), parse_coredump_filter(b"00000000\n").unwrap());
+    }
+}
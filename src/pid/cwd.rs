@@ -7,10 +7,16 @@ use std::path::PathBuf;
 
 use libc::pid_t;
 
+use pid::availability::{Availability, classify_link};
+use pid::stat::stat;
+
 /// Gets path of current working directory for the process with the provided
 /// pid.
-pub fn cwd(pid: pid_t) -> Result<PathBuf> {
-    fs::read_link(format!("/proc/{}/cwd", pid))
+///
+/// Zombie processes and kernel threads have no working directory, and are
+/// reported as such instead of with a `NotFound` error.
+pub fn cwd(pid: pid_t) -> Result<Availability<PathBuf>> {
+    classify_link(fs::read_link(format!("/proc/{}/cwd", pid)), || stat(pid))
 }
 
 /// Gets path of current working directory for the current process.
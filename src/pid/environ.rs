@@ -0,0 +1,156 @@
+//! Process environment from `/proc/[pid]/environ`.
+
+use std::ffi::{OsStr, OsString};
+use std::fs::File;
+use std::io::{Read, Result};
+use std::os::unix::ffi::OsStrExt;
+use std::slice;
+use std::vec;
+
+use libc::pid_t;
+
+use parsers::parse_file;
+
+/// The environment of a process, as it was when the process was executed.
+///
+/// Variables are kept in the order in which they appear in the environment. Changes the process
+/// makes to its environment after starting (e.g. with `setenv(3)`) are not reflected.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "export", derive(Serialize))]
+pub struct Environ {
+    vars: Vec<(OsString, OsString)>,
+}
+
+impl Environ {
+    /// Parses the contents of a `/proc/[pid]/environ` file.
+    ///
+    /// Variables are terminated by a NUL byte, and are split into a key and value at the first
+    /// `=`. A variable without an `=` has an empty value.
+    pub fn parse(input: &[u8]) -> Environ {
+        let vars = input.split(|&b| b == 0).filter(|var| !var.is_empty()).map(|var| {
+            let (key, value) = match var.iter().position(|&b| b == b'=') {
+                Some(i) => (&var[..i], &var[i + 1..]),
+                None => (var, &[][..]),
+            };
+            (OsStr::from_bytes(key).to_owned(), OsStr::from_bytes(value).to_owned())
+        }).collect();
+        Environ { vars }
+    }
+
+    /// Returns the value of the variable with the provided key.
+    ///
+    /// If the key appears more than once, the first value is returned, matching `getenv(3)`.
+    pub fn get<K: AsRef<OsStr>>(&self, key: K) -> Option<&OsStr> {
+        let key = key.as_ref();
+        self.vars.iter().find(|(k, _)| k == key).map(|(_, v)| v.as_os_str())
+    }
+
+    /// Returns an iterator over the variables as key and value pairs.
+    pub fn iter(&self) -> EnvironIter<'_> {
+        EnvironIter { vars: self.vars.iter() }
+    }
+
+    /// Returns the number of variables.
+    pub fn len(&self) -> usize {
+        self.vars.len()
+    }
+
+    /// Returns `true` if the environment contains no variables.
+    pub fn is_empty(&self) -> bool {
+        self.vars.is_empty()
+    }
+}
+
+/// An iterator over the variables of an `Environ`.
+#[derive(Clone, Debug)]
+pub struct EnvironIter<'a> {
+    vars: slice::Iter<'a, (OsString, OsString)>,
+}
+
+impl<'a> Iterator for EnvironIter<'a> {
+    type Item = (&'a OsStr, &'a OsStr);
+
+    fn next(&mut self) -> Option<(&'a OsStr, &'a OsStr)> {
+        self.vars.next().map(|(k, v)| (k.as_os_str(), v.as_os_str()))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.vars.size_hint()
+    }
+}
+
+impl<'a> IntoIterator for &'a Environ {
+    type Item = (&'a OsStr, &'a OsStr);
+    type IntoIter = EnvironIter<'a>;
+
+    fn into_iter(self) -> EnvironIter<'a> {
+        self.iter()
+    }
+}
+
+impl IntoIterator for Environ {
+    type Item = (OsString, OsString);
+    type IntoIter = vec::IntoIter<(OsString, OsString)>;
+
+    fn into_iter(self) -> vec::IntoIter<(OsString, OsString)> {
+        self.vars.into_iter()
+    }
+}
+
+/// Parses the provided environ file.
+pub(crate) fn environ_file(file: &mut File) -> Result<Environ> {
+    let mut buf = Vec::new();
+    file.read_to_end(&mut buf)?;
+    Ok(Environ::parse(&buf))
+}
+
+/// Returns the environment of the process with the provided pid.
+///
+/// Reading the environment of another process requires ptrace access to it.
+pub fn environ(pid: pid_t) -> Result<Environ> {
+    parse_file(format!("/proc/{}/environ", pid), environ_file)
+}
+
+/// Returns the environment of the current process.
+pub fn environ_self() -> Result<Environ> {
+    parse_file("/proc/self/environ", environ_file)
+}
+
+/// Returns the environment from the thread with the provided parent process ID and thread ID.
+pub fn environ_task(process_id: pid_t, thread_id: pid_t) -> Result<Environ> {
+    parse_file(format!("/proc/{}/task/{}/environ", process_id, thread_id), environ_file)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::env;
+    use std::ffi::OsStr;
+
+    use super::{Environ, environ_self};
+
+    /// Test that the environment of the current process can be read.
+    #[test]
+    fn test_environ() {
+        let environ = environ_self().unwrap();
+        if let Some(path) = env::var_os("PATH") {
+            assert_eq!(Some(&*path), environ.get("PATH"));
+        }
+    }
+
+    #[test]
+    fn test_parse_environ() {
+        let environ = Environ::parse(b"HOME=/root\0A=b=c\0EMPTY=\0NOVALUE\0HOME=/tmp\0");
+        assert_eq!(5, environ.len());
+        assert_eq!(Some(OsStr::new("/root")), environ.get("HOME"));
+        assert_eq!(Some(OsStr::new("b=c")), environ.get(OsStr::new("A")));
+        assert_eq!(Some(OsStr::new("")), environ.get("EMPTY"));
+        assert_eq!(Some(OsStr::new("")), environ.get("NOVALUE"));
+        assert_eq!(None, environ.get("MISSING"));
+
+        let keys = environ.iter().map(|(k, _)| k).collect::<Vec<_>>();
+        assert_eq!(vec!["HOME", "A", "EMPTY", "NOVALUE", "HOME"], keys);
+        assert_eq!(5, (&environ).into_iter().count());
+
+        assert!(Environ::parse(b"").is_empty());
+    }
+}
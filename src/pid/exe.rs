@@ -0,0 +1,34 @@
+//! Concerning the executable of a process, from `/proc/[pid]/exe`.
+
+use std::fs;
+use std::io::Result;
+use std::path::PathBuf;
+
+use libc::pid_t;
+
+use pid::availability::{Availability, classify_link};
+use pid::stat::stat;
+
+/// Gets the path of the executable of the process with the provided pid.
+///
+/// Reading the executable of another process requires ptrace access to it. Zombie processes and
+/// kernel threads have no executable, and are reported as such instead of with a `NotFound` error.
+pub fn exe(pid: pid_t) -> Result<Availability<PathBuf>> {
+    classify_link(fs::read_link(format!("/proc/{}/exe", pid)), || stat(pid))
+}
+
+/// Gets the path of the executable of the current process.
+pub fn exe_self() -> Result<PathBuf> {
+    fs::read_link("/proc/self/exe")
+}
+
+#[cfg(test)]
+pub mod tests {
+    use super::exe_self;
+    use std::env;
+
+    #[test]
+    fn test_exe_self() {
+        assert_eq!(env::current_exe().unwrap(), exe_self().unwrap());
+    }
+}
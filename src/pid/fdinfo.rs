@@ -0,0 +1,491 @@
+//! Information about open file descriptors from `/proc/[pid]/fdinfo/[fd]`.
+
+use std::fs::File;
+use std::io::{Read, Result};
+use std::os::unix::io::RawFd;
+use std::str;
+use std::time::Duration;
+
+use libc::pid_t;
+
+use parsers::{invalid_input, parse, parse_file, parse_str, parse_u32_hex, parse_u32_octal,
+              parse_u64_hex, split_label, with_line};
+use pid::status::SignalSet;
+
+/// Information about an open file descriptor.
+///
+/// See `proc(5)` and `Linux/Documentation/filesystems/proc.rst`.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "export", derive(Serialize))]
+pub struct FdInfo {
+    /// The file offset.
+    pub pos: u64,
+    /// The file access mode and status flags, as passed to `open(2)`.
+    pub flags: u32,
+    /// The ID of the mount containing the file (since Linux 3.15).
+    pub mnt_id: Option<u32>,
+    /// The inode number of the file (since Linux 5.14).
+    pub ino: Option<u64>,
+    /// The information specific to the type of the file.
+    pub kind: FdInfoKind,
+}
+
+/// The information specific to the type of an open file.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "export", derive(Serialize))]
+pub enum FdInfoKind {
+    /// A timer created by `timerfd_create(2)` (since Linux 3.17).
+    Timerfd(Timerfd),
+    /// A file descriptor created by `signalfd(2)`, with the mask of signals it accepts.
+    Signalfd(SignalSet),
+    /// An inotify instance, with its watches.
+    Inotify(Vec<InotifyWatch>),
+    /// A fanotify group.
+    Fanotify(Fanotify),
+    /// A file with no type specific information, or information which is not parsed.
+    Other,
+}
+
+/// The state of a timer created by `timerfd_create(2)`.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "export", derive(Serialize))]
+pub struct Timerfd {
+    /// The clock of the timer, such as `CLOCK_MONOTONIC`.
+    pub clockid: i32,
+    /// The number of expirations which have not been read.
+    pub ticks: u64,
+    /// The flags of the last `timerfd_settime(2)` call, such as `TFD_TIMER_ABSTIME`.
+    pub settime_flags: u32,
+    /// The time until the next expiration, or zero if the timer is disarmed.
+    pub it_value: Duration,
+    /// The interval between expirations, or zero for a one-shot timer.
+    pub it_interval: Duration,
+}
+
+/// A file handle, as returned by `name_to_handle_at(2)`, which identifies a watched inode.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "export", derive(Serialize))]
+pub struct FileHandle {
+    /// The type of the handle, which depends on the filesystem.
+    pub handle_type: i32,
+    /// The opaque bytes of the handle.
+    pub bytes: Vec<u8>,
+}
+
+/// An inotify watch, as added by `inotify_add_watch(2)`.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "export", derive(Serialize))]
+pub struct InotifyWatch {
+    /// The watch descriptor.
+    pub wd: i32,
+    /// The inode number of the watched file.
+    pub ino: u64,
+    /// The device number of the watched file.
+    pub sdev: u32,
+    /// The events watched for, such as `IN_MODIFY`.
+    pub mask: u32,
+    /// The events ignored, which is always 0 for inotify.
+    pub ignored_mask: u32,
+    /// The file handle of the watched file, if the kernel was built with `CONFIG_EXPORTFS`.
+    pub handle: Option<FileHandle>,
+}
+
+/// A fanotify group, as created by `fanotify_init(2)`.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "export", derive(Serialize))]
+pub struct Fanotify {
+    /// The flags of the group, such as `FAN_CLASS_NOTIF`.
+    pub flags: u32,
+    /// The flags of the event file descriptors, such as `O_RDONLY`.
+    pub event_flags: u32,
+    /// The marks of the group.
+    pub marks: Vec<FanotifyMark>,
+}
+
+/// What a fanotify mark is placed on.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "export", derive(Serialize))]
+pub enum FanotifyTarget {
+    /// An inode.
+    Inode {
+        /// The inode number.
+        ino: u64,
+        /// The device number.
+        sdev: u32,
+        /// The file handle of the inode, if the kernel was built with `CONFIG_EXPORTFS`.
+        handle: Option<FileHandle>,
+    },
+    /// A mount, with the provided mount ID.
+    Mount(u32),
+    /// A filesystem, with the provided device number (since Linux 4.20).
+    Filesystem(u32),
+}
+
+/// A fanotify mark, as added by `fanotify_mark(2)`.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "export", derive(Serialize))]
+pub struct FanotifyMark {
+    /// What the mark is placed on.
+    pub target: FanotifyTarget,
+    /// The flags of the mark, such as `FAN_MARK_IGNORED_SURV_MODIFY`.
+    pub mflags: u32,
+    /// The events marked, such as `FAN_MODIFY`.
+    pub mask: u32,
+    /// The events ignored.
+    pub ignored_mask: u32,
+}
+
+/// The `key:value` fields of an inotify or fanotify line, such as
+/// `inotify wd:1 ino:7e9e sdev:800013 mask:800afce ignored_mask:0`.
+#[derive(Default)]
+struct MarkFields {
+    wd: Option<i32>,
+    ino: Option<u64>,
+    sdev: Option<u32>,
+    mnt_id: Option<u32>,
+    flags: u32,
+    event_flags: u32,
+    mflags: u32,
+    mask: u32,
+    ignored_mask: u32,
+    handle_type: Option<i32>,
+    handle: Option<Vec<u8>>,
+}
+
+impl MarkFields {
+    fn parse(line: &str) -> Result<MarkFields> {
+        let mut fields = MarkFields::default();
+        // The first word is `inotify` or `fanotify`.
+        for field in line.split_whitespace().skip(1) {
+            let (key, value) = field.split_once(':').ok_or_else(|| {
+                invalid_input(format!("invalid fdinfo mark field: {:?}", field))
+            })?;
+            match key {
+                "wd" => fields.wd = Some(parse_u32_hex(value)? as i32),
+                "ino" => fields.ino = Some(parse_u64_hex(value)?),
+                "sdev" => fields.sdev = Some(parse_u32_hex(value)?),
+                "mnt_id" => fields.mnt_id = Some(parse_u32_hex(value)?),
+                "flags" => fields.flags = parse_u32_hex(value)?,
+                "event-flags" => fields.event_flags = parse_u32_hex(value)?,
+                "mflags" => fields.mflags = parse_u32_hex(value)?,
+                "mask" => fields.mask = parse_u32_hex(value)?,
+                "ignored_mask" => fields.ignored_mask = parse_u32_hex(value)?,
+                "fhandle-type" => fields.handle_type = Some(parse_u32_hex(value)? as i32),
+                "f_handle" => fields.handle = Some(parse_hex_bytes(value)?),
+                _ => (),
+            }
+        }
+        Ok(fields)
+    }
+
+    fn handle(&mut self) -> Option<FileHandle> {
+        let handle_type = self.handle_type?;
+        self.handle.take().map(|bytes| FileHandle { handle_type, bytes })
+    }
+}
+
+/// Parses a string of hex digit pairs into bytes.
+fn parse_hex_bytes(s: &str) -> Result<Vec<u8>> {
+    s.as_bytes().chunks(2).map(|pair| {
+        str::from_utf8(pair).ok()
+                            .filter(|pair| pair.len() == 2)
+                            .and_then(|pair| u8::from_str_radix(pair, 16).ok())
+                            .ok_or_else(|| invalid_input(format!("invalid hex bytes: {:?}", s)))
+    }).collect()
+}
+
+/// Parses a timerfd time of the form `(seconds, nanoseconds)`.
+fn parse_timespec(s: &str) -> Result<Duration> {
+    let (secs, nanos) = s.strip_prefix('(')
+                         .and_then(|s| s.strip_suffix(')'))
+                         .and_then(|s| s.split_once(','))
+                         .ok_or_else(|| invalid_input(format!("invalid timerfd time: {:?}", s)))?;
+    Ok(Duration::new(parse(secs.trim())?, parse(nanos.trim())?))
+}
+
+/// Returns the timerfd information of the file, once the first timerfd line is parsed.
+///
+/// Fails if lines of another file type have been parsed.
+fn timerfd(kind: &mut FdInfoKind) -> Result<&mut Timerfd> {
+    if let FdInfoKind::Other = *kind {
+        *kind = FdInfoKind::Timerfd(Timerfd::default());
+    }
+    match *kind {
+        FdInfoKind::Timerfd(ref mut timerfd) => Ok(timerfd),
+        _ => Err(invalid_input("timerfd field in fdinfo of another file type")),
+    }
+}
+
+/// Parses a line of the fdinfo format into the info.
+///
+/// Lines which are not known, such as those of other file types, are ignored.
+fn parse_fdinfo_line(info: &mut FdInfo, line: &str) -> Result<()> {
+    if line.starts_with("inotify ") {
+        let mut fields = MarkFields::parse(line)?;
+        let watch = InotifyWatch {
+            wd: fields.wd.ok_or_else(|| invalid_input("missing inotify watch descriptor"))?,
+            ino: fields.ino.unwrap_or(0),
+            sdev: fields.sdev.unwrap_or(0),
+            mask: fields.mask,
+            ignored_mask: fields.ignored_mask,
+            handle: fields.handle(),
+        };
+        match info.kind {
+            FdInfoKind::Inotify(ref mut watches) => watches.push(watch),
+            _ => info.kind = FdInfoKind::Inotify(vec![watch]),
+        }
+        return Ok(());
+    }
+    if line.starts_with("fanotify ") {
+        let mut fields = MarkFields::parse(line)?;
+        let target = match (fields.ino, fields.mnt_id, fields.sdev) {
+            (Some(ino), _, sdev) => {
+                let handle = fields.handle();
+                FanotifyTarget::Inode { ino, sdev: sdev.unwrap_or(0), handle }
+            },
+            (None, Some(mnt_id), _) => FanotifyTarget::Mount(mnt_id),
+            (None, None, Some(sdev)) => FanotifyTarget::Filesystem(sdev),
+            // The group line, which precedes the mark lines.
+            (None, None, None) => {
+                info.kind = FdInfoKind::Fanotify(Fanotify {
+                    flags: fields.flags,
+                    event_flags: fields.event_flags,
+                    marks: Vec::new(),
+                });
+                return Ok(());
+            },
+        };
+        let mark = FanotifyMark {
+            target,
+            mflags: fields.mflags,
+            mask: fields.mask,
+            ignored_mask: fields.ignored_mask,
+        };
+        match info.kind {
+            FdInfoKind::Fanotify(ref mut fanotify) => fanotify.marks.push(mark),
+            _ => return Err(invalid_input("fanotify mark before group")),
+        }
+        return Ok(());
+    }
+
+    let (label, value) = match split_label(line) {
+        Some((label, value)) => (label, value.trim()),
+        None => return Ok(()),
+    };
+    match label {
+        "pos"    => info.pos    = parse(value)?,
+        "flags"  => info.flags  = parse_u32_octal(value)?,
+        "mnt_id" => info.mnt_id = Some(parse(value)?),
+        "ino"    => info.ino    = Some(parse(value)?),
+
+        "clockid"       => timerfd(&mut info.kind)?.clockid       = parse(value)?,
+        "ticks"         => timerfd(&mut info.kind)?.ticks         = parse(value)?,
+        "settime flags" => timerfd(&mut info.kind)?.settime_flags = parse_u32_octal(value)?,
+        "it_value"      => timerfd(&mut info.kind)?.it_value      = parse_timespec(value)?,
+        "it_interval"   => timerfd(&mut info.kind)?.it_interval   = parse_timespec(value)?,
+
+        "sigmask" => info.kind = FdInfoKind::Signalfd(SignalSet::from_bits(parse_u64_hex(value)?)),
+        _ => (),
+    }
+    Ok(())
+}
+
+/// Parses the fdinfo format.
+fn parse_fdinfo(input: &[u8]) -> Result<FdInfo> {
+    let mut info = FdInfo { pos: 0, flags: 0, mnt_id: None, ino: None, kind: FdInfoKind::Other };
+    for (i, line) in parse_str(input)?.lines().enumerate() {
+        parse_fdinfo_line(&mut info, line).map_err(|error| with_line(error, i + 1))?;
+    }
+    Ok(info)
+}
+
+/// Parses the provided fdinfo file.
+pub(crate) fn fdinfo_file(file: &mut File) -> Result<FdInfo> {
+    let mut buf = Vec::new();
+    file.read_to_end(&mut buf)?;
+    parse_fdinfo(&buf)
+}
+
+/// Returns information about the open file descriptor of the process with the provided pid.
+pub fn fdinfo(pid: pid_t, fd: RawFd) -> Result<FdInfo> {
+    parse_file(format!("/proc/{}/fdinfo/{}", pid, fd), fdinfo_file)
+}
+
+/// Returns information about the open file descriptor of the current process.
+pub fn fdinfo_self(fd: RawFd) -> Result<FdInfo> {
+    parse_file(format!("/proc/self/fdinfo/{}", fd), fdinfo_file)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::env;
+    use std::ffi::CString;
+    use std::fs::{self, File};
+    use std::mem;
+    use std::os::unix::ffi::OsStringExt;
+    use std::os::unix::io::{AsRawFd, FromRawFd};
+    use std::ptr;
+    use std::time::Duration;
+
+    use libc;
+
+    use pid::Signal;
+    use super::{FanotifyTarget, FdInfoKind, FileHandle, fdinfo_self, parse_fdinfo};
+
+    /// Test that the fdinfo of a regular file can be parsed.
+    #[test]
+    fn test_fdinfo_file() {
+        let file = File::open("/proc/self/stat").unwrap();
+        let info = fdinfo_self(file.as_raw_fd()).unwrap();
+        assert_eq!(0, info.pos);
+        assert_eq!(libc::O_RDONLY, info.flags as i32 & libc::O_ACCMODE);
+        assert_eq!(FdInfoKind::Other, info.kind);
+    }
+
+    #[test]
+    fn test_timerfd() {
+        let fd = unsafe { libc::timerfd_create(libc::CLOCK_MONOTONIC, libc::TFD_CLOEXEC) };
+        assert!(fd >= 0);
+        let timer = unsafe { File::from_raw_fd(fd) };
+        let spec = libc::itimerspec {
+            it_interval: libc::timespec { tv_sec: 7, tv_nsec: 0 },
+            it_value: libc::timespec { tv_sec: 3600, tv_nsec: 0 },
+        };
+        assert_eq!(0, unsafe { libc::timerfd_settime(fd, 0, &spec, ptr::null_mut()) });
+
+        let timerfd = match fdinfo_self(timer.as_raw_fd()).unwrap().kind {
+            FdInfoKind::Timerfd(timerfd) => timerfd,
+            kind => panic!("unexpected fdinfo kind: {:?}", kind),
+        };
+        assert_eq!(libc::CLOCK_MONOTONIC, timerfd.clockid);
+        assert_eq!(0, timerfd.ticks);
+        assert_eq!(Duration::from_secs(7), timerfd.it_interval);
+        assert!(timerfd.it_value > Duration::from_secs(3500));
+    }
+
+    #[test]
+    fn test_signalfd() {
+        let fd = unsafe {
+            let mut mask: libc::sigset_t = mem::zeroed();
+            libc::sigemptyset(&mut mask);
+            libc::sigaddset(&mut mask, libc::SIGUSR1);
+            libc::signalfd(-1, &mask, libc::SFD_CLOEXEC)
+        };
+        assert!(fd >= 0);
+        let signalfd = unsafe { File::from_raw_fd(fd) };
+        match fdinfo_self(signalfd.as_raw_fd()).unwrap().kind {
+            FdInfoKind::Signalfd(mask) => {
+                assert!(mask.contains(Signal::Usr1));
+                assert!(!mask.contains(Signal::Usr2));
+            },
+            kind => panic!("unexpected fdinfo kind: {:?}", kind),
+        }
+    }
+
+    #[test]
+    fn test_inotify() {
+        let fd = unsafe { libc::inotify_init1(libc::IN_CLOEXEC) };
+        assert!(fd >= 0);
+        let inotify = unsafe { File::from_raw_fd(fd) };
+        // Watch enough directories that the watch descriptors, which are written in hex, include
+        // letters.
+        let dir = env::temp_dir().join(format!("procinfo-inotify-{}", unsafe { libc::getpid() }));
+        let mut wds = Vec::new();
+        for i in 0..12 {
+            let path = dir.join(i.to_string());
+            fs::create_dir_all(&path).unwrap();
+            let path = CString::new(path.into_os_string().into_vec()).unwrap();
+            let wd = unsafe { libc::inotify_add_watch(fd, path.as_ptr(), libc::IN_CREATE) };
+            assert!(wd >= 0);
+            wds.push(wd);
+        }
+        let info = fdinfo_self(inotify.as_raw_fd());
+        fs::remove_dir_all(&dir).unwrap();
+        match info.unwrap().kind {
+            FdInfoKind::Inotify(watches) => {
+                let mut found = watches.iter().map(|watch| watch.wd).collect::<Vec<_>>();
+                found.sort();
+                assert_eq!(wds, found);
+                assert!(watches.iter().all(|watch| watch.mask == libc::IN_CREATE));
+            },
+            kind => panic!("unexpected fdinfo kind: {:?}", kind),
+        }
+    }
+
+    #[test]
+    fn test_parse_inotify() {
+        let text = b"pos:\t0\nflags:\t02000000\nmnt_id:\t15\n\
+                     inotify wd:a ino:7e9e sdev:800013 mask:100 ignored_mask:0\n";
+        match parse_fdinfo(text).unwrap().kind {
+            FdInfoKind::Inotify(watches) => assert_eq!(10, watches[0].wd),
+            kind => panic!("unexpected fdinfo kind: {:?}", kind),
+        }
+    }
+
+    #[test]
+    fn test_parse_fanotify() {
+        let text = b"pos:\t0\n\
+                     flags:\t02\n\
+                     mnt_id:\t15\n\
+                     ino:\t1057\n\
+                     fanotify flags:10 event-flags:0\n\
+                     fanotify mnt_id:12 mflags:40 mask:38 ignored_mask:40000003\n\
+                     fanotify ino:4f969 sdev:800013 mflags:0 mask:3b ignored_mask:40000000 \
+                     fhandle-bytes:8 fhandle-type:1 f_handle:69f90400c275b5b4\n\
+                     fanotify sdev:800013 mflags:0 mask:1 ignored_mask:0\n";
+        let info = parse_fdinfo(text).unwrap();
+        assert_eq!(0o2, info.flags);
+        assert_eq!(Some(15), info.mnt_id);
+        assert_eq!(Some(1057), info.ino);
+        let fanotify = match info.kind {
+            FdInfoKind::Fanotify(fanotify) => fanotify,
+            kind => panic!("unexpected fdinfo kind: {:?}", kind),
+        };
+        assert_eq!(0x10, fanotify.flags);
+        assert_eq!(3, fanotify.marks.len());
+        assert_eq!(FanotifyTarget::Mount(0x12), fanotify.marks[0].target);
+        assert_eq!(0x40, fanotify.marks[0].mflags);
+        assert_eq!(0x40000003, fanotify.marks[0].ignored_mask);
+        assert_eq!(FanotifyTarget::Inode {
+                       ino: 0x4f969,
+                       sdev: 0x800013,
+                       handle: Some(FileHandle {
+                           handle_type: 1,
+                           bytes: vec![0x69, 0xf9, 0x04, 0x00, 0xc2, 0x75, 0xb5, 0xb4],
+                       }),
+                   },
+                   fanotify.marks[1].target);
+        assert_eq!(0x3b, fanotify.marks[1].mask);
+        assert_eq!(FanotifyTarget::Filesystem(0x800013), fanotify.marks[2].target);
+
+        parse_fdinfo(b"fanotify flags:10 event-flags:0\n\
+                       fanotify ino:1 sdev:0 fhandle-type:1 f_handle:abc\n").unwrap_err();
+        parse_fdinfo(b"fanotify mnt_id:12 mflags:40 mask:38 ignored_mask:0\n").unwrap_err();
+        parse_fdinfo(b"fanotify flags:10 event-flags:0\nfanotify mnt_id:zz\n").unwrap_err();
+    }
+
+    #[test]
+    fn test_parse_timerfd() {
+        let text = b"pos:\t0\n\
+                     flags:\t02\n\
+                     mnt_id:\t15\n\
+                     clockid: 0\n\
+                     ticks: 3\n\
+                     settime flags: 01\n\
+                     it_value: (0, 49406829)\n\
+                     it_interval: (1, 0)\n";
+        let timerfd = match parse_fdinfo(text).unwrap().kind {
+            FdInfoKind::Timerfd(timerfd) => timerfd,
+            kind => panic!("unexpected fdinfo kind: {:?}", kind),
+        };
+        assert_eq!(0, timerfd.clockid);
+        assert_eq!(3, timerfd.ticks);
+        assert_eq!(1, timerfd.settime_flags);
+        assert_eq!(Duration::new(0, 49406829), timerfd.it_value);
+        assert_eq!(Duration::from_secs(1), timerfd.it_interval);
+
+        parse_fdinfo(b"sigmask:\t0000000000004000\nclockid: 0\n").unwrap_err();
+        parse_fdinfo(b"inotify wd:1 ino:7e9e sdev:800013 mask:2 ignored_mask:0\nticks: 3\n")
+            .unwrap_err();
+    }
+}
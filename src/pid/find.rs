@@ -0,0 +1,112 @@
+//! Finding processes by name or by an arbitrary predicate.
+
+use std::ffi::OsStr;
+use std::io::Result;
+use std::path::Path;
+
+use pid::availability::Availability;
+use pid::pids::{is_exited, pids};
+use pid::process::Process;
+
+/// The maximum length of a process's command name (`TASK_COMM_LEN` less the NUL terminator).
+const COMM_LEN: usize = 15;
+
+/// Returns handles to all processes for which the predicate returns `true`.
+///
+/// Each process is opened before the predicate is called, so the predicate and the returned handle
+/// always refer to the same process even if the pid is reused in the meantime. Processes which
+/// exit during the scan are skipped.
+pub fn find<F>(mut predicate: F) -> Result<Vec<Process>> where F: FnMut(&Process) -> bool {
+    let mut processes = Vec::new();
+    for pid in pids()? {
+        let process = match Process::new(pid?) {
+            Ok(process) => process,
+            Err(ref error) if is_exited(error) => continue,
+            Err(error) => return Err(error),
+        };
+        if predicate(&process) {
+            processes.push(process);
+        }
+    }
+    Ok(processes)
+}
+
+/// Returns handles to all processes with the provided name.
+///
+/// A process matches if its command name, the file name of its executable, or the file name of
+/// its first command line argument is equal to `name`. The command name is truncated by the kernel
+/// to 15 bytes, so it is only compared with names of up to 15 bytes. Executables which can not be
+/// read due to insufficient permissions are not matched.
+pub fn find_by_name(name: &str) -> Result<Vec<Process>> {
+    find(|process| matches_name(process, name))
+}
+
+fn matches_name(process: &Process, name: &str) -> bool {
+    if name.len() <= COMM_LEN
+        && process.stat().map(|stat| stat.command == name).unwrap_or(false) {
+        return true;
+    }
+    let name = OsStr::new(name);
+    let exe = process.exe().ok().and_then(Availability::available);
+    if exe.map(|exe| exe.file_name() == Some(name)).unwrap_or(false) {
+        return true;
+    }
+    process.cmdline()
+           .ok()
+           .and_then(Availability::available)
+           .and_then(|args| args.into_iter().next())
+           .map(|arg0| Path::new(&arg0).file_name() == Some(name))
+           .unwrap_or(false)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::env;
+    use std::process::Command;
+    use std::thread;
+    use std::time::Duration;
+
+    use libc;
+
+    use pid::process::Process;
+    use super::{find, find_by_name, matches_name};
+
+    #[test]
+    fn test_find() {
+        let pid = unsafe { libc::getpid() };
+        let processes = find(|process| process.pid() == pid).unwrap();
+        assert_eq!(1, processes.len());
+        assert_eq!(pid, processes[0].pid());
+    }
+
+    #[test]
+    fn test_find_by_name() {
+        let mut child = Command::new("sleep").arg("10").spawn().unwrap();
+        let pid = child.id() as libc::pid_t;
+        // The child may not have called exec yet, so retry for a while.
+        let found = (0..100).any(|_| {
+            let found = find_by_name("sleep").unwrap().iter().any(|process| process.pid() == pid);
+            if !found {
+                thread::sleep(Duration::from_millis(10));
+            }
+            found
+        });
+        child.kill().unwrap();
+        child.wait().unwrap();
+        assert!(found);
+
+        let exe = env::current_exe().unwrap();
+        let name = exe.file_name().unwrap().to_str().unwrap();
+        let pid = unsafe { libc::getpid() };
+        assert!(find_by_name(name).unwrap().iter().any(|process| process.pid() == pid));
+    }
+    /// Test that a name longer than the command name is not matched by its truncation.
+    #[test]
+    fn test_matches_name_truncated() {
+        let process = Process::myself().unwrap();
+        let comm = process.stat().unwrap().command;
+        assert!(matches_name(&process, &comm));
+        let name = format!("{:-<15}-x", comm);
+        assert!(!matches_name(&process, &name));
+    }
+}
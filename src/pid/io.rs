@@ -0,0 +1,163 @@
+//! Process I/O statistics from `/proc/[pid]/io`.
+
+use std::fs::File;
+use std::io::{ErrorKind, Result};
+
+use libc::pid_t;
+
+use parsers::{parse, parse_file, parse_str, read_to_end, split_label};
+
+/// Process I/O statistics.
+///
+/// Reading the I/O statistics of another process requires ptrace access to it.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "export", derive(Serialize))]
+pub struct Io {
+    /// The number of bytes read by `read(2)` and similar system calls, including from the page
+    /// cache and from pipes and terminals.
+    pub rchar: u64,
+    /// The number of bytes written by `write(2)` and similar system calls.
+    pub wchar: u64,
+    /// The number of read I/O operations (system calls).
+    pub syscr: u64,
+    /// The number of write I/O operations (system calls).
+    pub syscw: u64,
+    /// The number of bytes fetched from the storage layer.
+    pub read_bytes: u64,
+    /// The number of bytes sent to the storage layer.
+    pub write_bytes: u64,
+    /// The number of bytes of written page cache which was truncated before being sent to the
+    /// storage layer.
+    pub cancelled_write_bytes: u64,
+}
+
+/// Parses the io file format.
+///
+/// Lines are dispatched on their label, and lines with unrecognized labels are skipped.
+fn parse_io(input: &[u8]) -> Result<Io> {
+    let mut io: Io = Default::default();
+    for line in parse_str(input)?.lines() {
+        let (label, value) = match split_label(line) {
+            Some(split) => split,
+            None => continue,
+        };
+        let field = match label {
+            "rchar"                 => &mut io.rchar,
+            "wchar"                 => &mut io.wchar,
+            "syscr"                 => &mut io.syscr,
+            "syscw"                 => &mut io.syscw,
+            "read_bytes"            => &mut io.read_bytes,
+            "write_bytes"           => &mut io.write_bytes,
+            "cancelled_write_bytes" => &mut io.cancelled_write_bytes,
+            _ => continue,
+        };
+        *field = parse(value.trim())?;
+    }
+    Ok(io)
+}
+
+impl Io {
+    /// Parses the contents of a `/proc/[pid]/io` file.
+    pub fn parse(input: &[u8]) -> Result<Io> {
+        parse_io(input)
+    }
+}
+
+/// Parses the provided io file.
+pub(crate) fn io_file(file: &mut File) -> Result<Io> {
+    let mut buf = [0; 256]; // A typical io file is about 150 bytes.
+    Io::parse(&read_to_end(file, &mut buf)?)
+}
+
+/// Converts a `PermissionDenied` error into `None`.
+///
+/// The kernel fails reads of the io file with `EACCES` or `EPERM`, depending on the version, when
+/// the caller lacks ptrace access to the process. Both are reported as `PermissionDenied`.
+pub(crate) fn if_permitted(result: Result<Io>) -> Result<Option<Io>> {
+    match result {
+        Ok(io) => Ok(Some(io)),
+        Err(ref error) if error.kind() == ErrorKind::PermissionDenied => Ok(None),
+        Err(error) => Err(error),
+    }
+}
+
+/// Returns I/O statistics for the process with the provided pid.
+///
+/// Fails with `PermissionDenied` if the caller lacks ptrace access to the process, which is
+/// usually the case for processes of other users. See `io_if_permitted`.
+pub fn io(pid: pid_t) -> Result<Io> {
+    parse_file(format!("/proc/{}/io", pid), io_file)
+}
+
+/// Returns I/O statistics for the process with the provided pid, or `None` if the caller lacks
+/// ptrace access to the process.
+///
+/// This allows processes of other users to be skipped when scanning all processes.
+pub fn io_if_permitted(pid: pid_t) -> Result<Option<Io>> {
+    if_permitted(io(pid))
+}
+
+/// Returns I/O statistics for the current process.
+pub fn io_self() -> Result<Io> {
+    parse_file("/proc/self/io", io_file)
+}
+
+/// Returns I/O statistics from the thread with the provided parent process ID and thread ID.
+pub fn io_task(process_id: pid_t, thread_id: pid_t) -> Result<Io> {
+    parse_file(format!("/proc/{}/task/{}/io", process_id, thread_id), io_file)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::{Error, ErrorKind};
+
+    use libc;
+
+    use super::{Io, if_permitted, io_if_permitted, io_self, io_task, parse_io};
+
+    /// Test that the io file of the current process can be parsed.
+    #[test]
+    fn test_io() {
+        io_self().unwrap();
+    }
+
+    #[test]
+    fn test_io_if_permitted() {
+        let pid = unsafe { libc::getpid() };
+        assert!(io_if_permitted(pid).unwrap().is_some());
+
+        for &errno in &[libc::EACCES, libc::EPERM] {
+            assert_eq!(None, if_permitted(Err(Error::from_raw_os_error(errno))).unwrap());
+        }
+        let error = if_permitted(Err(Error::from_raw_os_error(libc::ENOENT))).unwrap_err();
+        assert_eq!(ErrorKind::NotFound, error.kind());
+    }
+
+    /// Test that the io file of the main thread can be parsed.
+    #[test]
+    fn test_io_task() {
+        let pid = unsafe { libc::getpid() };
+        io_task(pid, pid).unwrap();
+    }
+
+    #[test]
+    fn test_parse_io() {
+        let text = b"rchar: 323934931\n\
+                     wchar: 323929600\n\
+                     syscr: 632687\n\
+                     syscw: 632675\n\
+                     read_bytes: 4096\n\
+                     write_bytes: 323932160\n\
+                     cancelled_write_bytes: 12\n";
+        assert_eq!(Io {
+                       rchar: 323934931,
+                       wchar: 323929600,
+                       syscr: 632687,
+                       syscw: 632675,
+                       read_bytes: 4096,
+                       write_bytes: 323932160,
+                       cancelled_write_bytes: 12,
+                   },
+                   parse_io(text).unwrap());
+    }
+}
@@ -4,28 +4,26 @@ use std::fs::File;
 use std::io::Result;
 use std::time::Duration;
 
-use libc::pid_t;
-use nom::{
-    IResult,
-    space,
-};
-
-use parsers::{
-    map_result,
-    parse_u64,
-    parse_usize,
-    read_to_end
-};
-
-fn parse_limit<'a, P, T>(input: &'a [u8], value_parser: P) -> IResult<&'a [u8], Limit<T>>
-where P: Fn(&[u8]) -> IResult<&[u8], T> {
-    let parse_field = closure!(&'a [u8], alt!(
-         tag!("unlimited") => { |_| None }
-       | value_parser => { |value| Some(value) }
-    ));
-
-    map!(input, separated_pair!(parse_field, space, parse_field),
-         |(soft, hard)| Limit { soft: soft, hard: hard })
+use std::str::FromStr;
+
+use libc::{self, c_int, pid_t, rlim_t, rlimit};
+
+use parsers::{Fields, parse, parse_file, parse_str, read_to_end};
+
+/// Parses a limit value, which is either a number or `unlimited`.
+fn parse_limit_value<T: FromStr>(input: &str) -> Result<Option<T>> {
+    match input {
+        "unlimited" => Ok(None),
+        value => parse(value).map(Some),
+    }
+}
+
+/// Parses the soft and hard columns of a limit row.
+fn parse_limit<T: FromStr>(fields: &mut Fields) -> Result<Limit<T>> {
+    Ok(Limit {
+        soft: parse_limit_value(fields.next_str()?)?,
+        hard: parse_limit_value(fields.next_str()?)?,
+    })
 }
 
 fn duration_from_micros(micros: u64) -> Duration {
@@ -36,69 +34,68 @@ fn duration_from_micros(micros: u64) -> Duration {
     Duration::new(secs, nanos)
 }
 
-named!(parse_limit_usize( &[u8] ) -> Limit<usize>, apply!(parse_limit, parse_usize));
-named!(parse_limit_u64( &[u8] ) -> Limit<u64>, apply!(parse_limit, parse_u64));
-named!(parse_limit_seconds( &[u8] ) -> Limit<Duration>,
-       map!(apply!(parse_limit, parse_u64),
-            | Limit { soft, hard } | {
-                Limit {
-                    soft: soft.map(Duration::from_secs),
-                    hard: hard.map(Duration::from_secs),
-                }
-            }
-       ));
-named!(parse_limit_micros( &[u8] ) -> Limit<Duration>,
-       map!(apply!(parse_limit, parse_u64),
-            | Limit { soft, hard } | {
-                Limit {
-                    soft: soft.map(duration_from_micros),
-                    hard: hard.map(duration_from_micros),
-                }
-            }
-       ));
-
-named!(parse_limits( &[u8] ) -> Limits,
-    ws!(do_parse!(
-        tag!("Limit") >> tag!("Soft Limit") >> tag!("Hard Limit") >> tag!("Units") >>
-        tag!("Max cpu time")          >> max_cpu_time: parse_limit_seconds        >> tag!("seconds")    >>
-        tag!("Max file size")         >> max_file_size: parse_limit_u64           >> tag!("bytes")      >>
-        tag!("Max data size")         >> max_data_size: parse_limit_usize         >> tag!("bytes")      >>
-        tag!("Max stack size")        >> max_stack_size: parse_limit_usize        >> tag!("bytes")      >>
-        tag!("Max core file size")    >> max_core_file_size: parse_limit_usize    >> tag!("bytes")      >>
-        tag!("Max resident set")      >> max_resident_set: parse_limit_usize      >> tag!("bytes")      >>
-        tag!("Max processes")         >> max_processes: parse_limit_usize         >> tag!("processes")  >>
-        tag!("Max open files")        >> max_open_files: parse_limit_usize        >> tag!("files")      >>
-        tag!("Max locked memory")     >> max_locked_memory: parse_limit_usize     >> tag!("bytes")      >>
-        tag!("Max address space")     >> max_address_space: parse_limit_usize     >> tag!("bytes")      >>
-        tag!("Max file locks")        >> max_file_locks: parse_limit_usize        >> tag!("locks")      >>
-        tag!("Max pending signals")   >> max_pending_signals: parse_limit_usize   >> tag!("signals")    >>
-        tag!("Max msgqueue size")     >> max_msgqueue_size: parse_limit_usize     >> tag!("bytes")      >>
-        tag!("Max nice priority")     >> max_nice_priority: parse_limit_usize     >>
-        tag!("Max realtime priority") >> max_realtime_priority: parse_limit_usize >>
-        tag!("Max realtime timeout")  >> max_realtime_timeout: parse_limit_micros >> tag!("us")         >>
-        (Limits {
-            max_cpu_time: max_cpu_time,
-            max_file_size: max_file_size,
-            max_data_size: max_data_size,
-            max_stack_size: max_stack_size,
-            max_core_file_size: max_core_file_size,
-            max_resident_set: max_resident_set,
-            max_processes: max_processes,
-            max_open_files: max_open_files,
-            max_locked_memory: max_locked_memory,
-            max_address_space: max_address_space,
-            max_file_locks: max_file_locks,
-            max_pending_signals: max_pending_signals,
-            max_msgqueue_size: max_msgqueue_size,
-            max_nice_priority: max_nice_priority,
-            max_realtime_priority: max_realtime_priority,
-            max_realtime_timeout: max_realtime_timeout,
-        })
-    ))
-);
+fn parse_limit_seconds(fields: &mut Fields) -> Result<Limit<Duration>> {
+    let Limit { soft, hard } = parse_limit(fields)?;
+    Ok(Limit { soft: soft.map(Duration::from_secs), hard: hard.map(Duration::from_secs) })
+}
+
+fn parse_limit_micros(fields: &mut Fields) -> Result<Limit<Duration>> {
+    let Limit { soft, hard } = parse_limit(fields)?;
+    Ok(Limit { soft: soft.map(duration_from_micros), hard: hard.map(duration_from_micros) })
+}
+
+/// Splits a limit row into its label and the remaining soft, hard and units columns.
+///
+/// The label is the run of words preceding the first value, which is either a number or
+/// `unlimited`.
+fn split_limit_label(line: &str) -> (&str, &str) {
+    let mut offset = 0;
+    for word in line.split(' ') {
+        if word == "unlimited" || word.starts_with(|c: char| c.is_ascii_digit()) {
+            return (line[..offset].trim(), &line[offset..]);
+        }
+        offset += word.len() + 1;
+    }
+    (line.trim(), "")
+}
+
+/// Parses the limits file format.
+///
+/// Rows are matched by their label, so the order of the rows does not matter. Rows with
+/// unrecognized labels are skipped, and limits whose rows are missing are left as the default.
+fn parse_limits(input: &[u8]) -> Result<Limits> {
+    let mut limits = Limits::default();
+    for line in parse_str(input)?.lines() {
+        let (label, rest) = split_limit_label(line);
+        let fields = &mut Fields::new(rest);
+        match label {
+            "Max cpu time"          => limits.max_cpu_time          = parse_limit_seconds(fields)?,
+            "Max file size"         => limits.max_file_size         = parse_limit(fields)?,
+            "Max data size"         => limits.max_data_size         = parse_limit(fields)?,
+            "Max stack size"        => limits.max_stack_size        = parse_limit(fields)?,
+            "Max core file size"    => limits.max_core_file_size    = parse_limit(fields)?,
+            "Max resident set"      => limits.max_resident_set      = parse_limit(fields)?,
+            "Max processes"         => limits.max_processes         = parse_limit(fields)?,
+            "Max open files"        => limits.max_open_files        = parse_limit(fields)?,
+            "Max locked memory"     => limits.max_locked_memory     = parse_limit(fields)?,
+            "Max address space"     => limits.max_address_space     = parse_limit(fields)?,
+            "Max file locks"        => limits.max_file_locks        = parse_limit(fields)?,
+            "Max pending signals"   => limits.max_pending_signals   = parse_limit(fields)?,
+            "Max msgqueue size"     => limits.max_msgqueue_size     = parse_limit(fields)?,
+            "Max nice priority"     => limits.max_nice_priority     = parse_limit(fields)?,
+            "Max realtime priority" => limits.max_realtime_priority = parse_limit(fields)?,
+            "Max realtime timeout"  => limits.max_realtime_timeout  = parse_limit_micros(fields)?,
+            _ => (),
+        }
+    }
+    Ok(limits)
+}
 
 /// A resource limit, including a soft and hard bound.
-#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+///
+/// The default limit is unlimited.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "export", derive(Serialize))]
 pub struct Limit<T> {
     /// The soft resource limit.
     ///
@@ -114,68 +111,228 @@ pub struct Limit<T> {
 
 /// Process limits information
 /// See `man 2 getrlimit`.
-#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+///
+/// Limits which are not listed in the limits file are unlimited.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "export", derive(Serialize))]
 pub struct Limits {
     /// The maximum CPU time a process can use.
     pub max_cpu_time: Limit<Duration>,
     /// The maximum size of files that the process may create in bytes.
     pub max_file_size: Limit<u64>,
     /// The maximum size of the process's data segment in bytes.
-    pub max_data_size: Limit<usize>,
+    pub max_data_size: Limit<u64>,
     /// The maximum size of the process stack in bytes.
-    pub max_stack_size: Limit<usize>,
+    pub max_stack_size: Limit<u64>,
     /// Maximum size of a core file in bytes.
-    pub max_core_file_size: Limit<usize>,
+    pub max_core_file_size: Limit<u64>,
     /// Specifies the limit of the process's resident set in bytes.
-    pub max_resident_set: Limit<usize>,
+    pub max_resident_set: Limit<u64>,
     /// The maximum number of processes (or, more precisely on Linux, threads)
     /// that can be created for the real user ID of the calling process.
-    pub max_processes: Limit<usize>,
+    pub max_processes: Limit<u64>,
     /// Specifies a value one greater than the maximum file descriptor
     /// number that can be opened by this process.
-    pub max_open_files: Limit<usize>,
+    pub max_open_files: Limit<u64>,
     /// The maximum number of bytes of memory that may be locked into RAM.
-    pub max_locked_memory: Limit<usize>,
+    pub max_locked_memory: Limit<u64>,
     /// The maximum size of the process's virtual memory (address space) in bytes.
-    pub max_address_space: Limit<usize>,
+    pub max_address_space: Limit<u64>,
     /// A limit on the combined number of locks and leases that this process may
     /// establish.
-    pub max_file_locks: Limit<usize>,
+    pub max_file_locks: Limit<u64>,
     /// Specifies the limit on the number of signals that may be queued for the
     /// real user ID of the calling process.
-    pub max_pending_signals: Limit<usize>,
+    pub max_pending_signals: Limit<u64>,
     /// Specifies the limit on the number of bytes that can be allocated for
     /// POSIX message queues for the real user ID of the calling process.
-    pub max_msgqueue_size: Limit<usize>,
+    pub max_msgqueue_size: Limit<u64>,
     /// Specifies a ceiling to which the process's nice value can be raised.
-    pub max_nice_priority: Limit<usize>,
+    pub max_nice_priority: Limit<u64>,
     /// Specifies a limit on the amount of CPU time that a process scheduled
     /// under a real-time scheduling policy may consume without making a blocking
     /// system call.
-    pub max_realtime_priority: Limit<usize>,
+    pub max_realtime_priority: Limit<u64>,
     /// Specifies a ceiling on the real-time priority that may be set for this process.
     pub max_realtime_timeout: Limit<Duration>,
 }
 
+/// A resource with a limit.
+///
+/// See `man 2 getrlimit`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "export", derive(Serialize))]
+pub enum Resource {
+    /// CPU time, in seconds (`RLIMIT_CPU`).
+    CpuTime,
+    /// File size, in bytes (`RLIMIT_FSIZE`).
+    FileSize,
+    /// Data segment size, in bytes (`RLIMIT_DATA`).
+    DataSize,
+    /// Stack size, in bytes (`RLIMIT_STACK`).
+    StackSize,
+    /// Core file size, in bytes (`RLIMIT_CORE`).
+    CoreFileSize,
+    /// Resident set size, in bytes (`RLIMIT_RSS`).
+    ResidentSet,
+    /// Number of processes (`RLIMIT_NPROC`).
+    Processes,
+    /// Number of open files (`RLIMIT_NOFILE`).
+    OpenFiles,
+    /// Locked memory, in bytes (`RLIMIT_MEMLOCK`).
+    LockedMemory,
+    /// Address space size, in bytes (`RLIMIT_AS`).
+    AddressSpace,
+    /// Number of file locks (`RLIMIT_LOCKS`).
+    FileLocks,
+    /// Number of pending signals (`RLIMIT_SIGPENDING`).
+    PendingSignals,
+    /// Size of POSIX message queues, in bytes (`RLIMIT_MSGQUEUE`).
+    MsgqueueSize,
+    /// Nice priority ceiling (`RLIMIT_NICE`).
+    NicePriority,
+    /// Real-time priority ceiling (`RLIMIT_RTPRIO`).
+    RealtimePriority,
+    /// Real-time CPU time without a blocking system call, in microseconds (`RLIMIT_RTTIME`).
+    RealtimeTimeout,
+}
+
+impl Resource {
+    /// All resources, in the order they are listed in the limits file.
+    pub const ALL: [Resource; 16] = [
+        Resource::CpuTime,
+        Resource::FileSize,
+        Resource::DataSize,
+        Resource::StackSize,
+        Resource::CoreFileSize,
+        Resource::ResidentSet,
+        Resource::Processes,
+        Resource::OpenFiles,
+        Resource::LockedMemory,
+        Resource::AddressSpace,
+        Resource::FileLocks,
+        Resource::PendingSignals,
+        Resource::MsgqueueSize,
+        Resource::NicePriority,
+        Resource::RealtimePriority,
+        Resource::RealtimeTimeout,
+    ];
+
+    /// Returns the `RLIMIT_*` constant of the resource, for use with `getrlimit(2)` and
+    /// `setrlimit(2)`.
+    ///
+    /// The resource parameter type of those functions differs between C libraries, so the value
+    /// may need to be cast with `as _`.
+    pub fn as_raw(self) -> c_int {
+        (match self {
+            Resource::CpuTime          => libc::RLIMIT_CPU,
+            Resource::FileSize         => libc::RLIMIT_FSIZE,
+            Resource::DataSize         => libc::RLIMIT_DATA,
+            Resource::StackSize        => libc::RLIMIT_STACK,
+            Resource::CoreFileSize     => libc::RLIMIT_CORE,
+            Resource::ResidentSet      => libc::RLIMIT_RSS,
+            Resource::Processes        => libc::RLIMIT_NPROC,
+            Resource::OpenFiles        => libc::RLIMIT_NOFILE,
+            Resource::LockedMemory     => libc::RLIMIT_MEMLOCK,
+            Resource::AddressSpace     => libc::RLIMIT_AS,
+            Resource::FileLocks        => libc::RLIMIT_LOCKS,
+            Resource::PendingSignals   => libc::RLIMIT_SIGPENDING,
+            Resource::MsgqueueSize     => libc::RLIMIT_MSGQUEUE,
+            Resource::NicePriority     => libc::RLIMIT_NICE,
+            Resource::RealtimePriority => libc::RLIMIT_RTPRIO,
+            Resource::RealtimeTimeout  => libc::RLIMIT_RTTIME,
+        }) as c_int
+    }
+}
+
+impl<T> Limit<T> {
+    /// Converts the soft and hard bounds with the provided function.
+    fn map<U, F: Fn(T) -> U>(self, f: F) -> Limit<U> {
+        Limit { soft: self.soft.map(&f), hard: self.hard.map(&f) }
+    }
+}
+
+impl From<rlimit> for Limit<u64> {
+    /// Converts an `rlimit`, in which `RLIM_INFINITY` is unlimited.
+    #[allow(clippy::unnecessary_cast)] // rlim_t is 32 bits on some targets.
+    fn from(rlimit: rlimit) -> Limit<u64> {
+        let value = |v: rlim_t| if v == libc::RLIM_INFINITY { None } else { Some(v as u64) };
+        Limit { soft: value(rlimit.rlim_cur), hard: value(rlimit.rlim_max) }
+    }
+}
+
+impl From<Limit<u64>> for rlimit {
+    /// Converts to an `rlimit`, in which unlimited is `RLIM_INFINITY`.
+    fn from(limit: Limit<u64>) -> rlimit {
+        let value = |v: Option<u64>| v.map_or(libc::RLIM_INFINITY, |v| v as rlim_t);
+        rlimit { rlim_cur: value(limit.soft), rlim_max: value(limit.hard) }
+    }
+}
+
+impl Limits {
+    /// Parses the contents of a `/proc/[pid]/limits` file.
+    pub fn parse(input: &[u8]) -> Result<Limits> {
+        parse_limits(input)
+    }
+
+    /// Returns the soft and hard limits of a resource, in the units of `setrlimit(2)`.
+    ///
+    /// `None` is unlimited.
+    pub fn get(&self, resource: Resource) -> (Option<u64>, Option<u64>) {
+        let Limit { soft, hard } = self.limit(resource);
+        (soft, hard)
+    }
+
+    /// Returns the limit of a resource, in the units of `setrlimit(2)`.
+    pub fn limit(&self, resource: Resource) -> Limit<u64> {
+        let secs = |d: Duration| d.as_secs();
+        let micros = |d: Duration| d.as_micros() as u64;
+        match resource {
+            Resource::CpuTime          => self.max_cpu_time.map(secs),
+            Resource::FileSize         => self.max_file_size,
+            Resource::DataSize         => self.max_data_size,
+            Resource::StackSize        => self.max_stack_size,
+            Resource::CoreFileSize     => self.max_core_file_size,
+            Resource::ResidentSet      => self.max_resident_set,
+            Resource::Processes        => self.max_processes,
+            Resource::OpenFiles        => self.max_open_files,
+            Resource::LockedMemory     => self.max_locked_memory,
+            Resource::AddressSpace     => self.max_address_space,
+            Resource::FileLocks        => self.max_file_locks,
+            Resource::PendingSignals   => self.max_pending_signals,
+            Resource::MsgqueueSize     => self.max_msgqueue_size,
+            Resource::NicePriority     => self.max_nice_priority,
+            Resource::RealtimePriority => self.max_realtime_priority,
+            Resource::RealtimeTimeout  => self.max_realtime_timeout.map(micros),
+        }
+    }
+
+    /// Returns the limit of a resource as an `rlimit`, which can be applied with `setrlimit(2)`.
+    pub fn rlimit(&self, resource: Resource) -> rlimit {
+        self.limit(resource).into()
+    }
+}
+
 /// Parses the provided limits file.
-fn limits_file(file: &mut File) -> Result<Limits> {
+pub(crate) fn limits_file(file: &mut File) -> Result<Limits> {
     let mut buf = [0; 2048]; // A typical limits file is about 1350 bytes
-    map_result(parse_limits(try!(read_to_end(file, &mut buf))))
+    Limits::parse(&read_to_end(file, &mut buf)?)
 }
 
 /// Returns resource limit information from the process with the provided pid.
 pub fn limits(pid: pid_t) -> Result<Limits> {
-    limits_file(&mut try!(File::open(&format!("/proc/{}/limits", pid))))
+    parse_file(format!("/proc/{}/limits", pid), limits_file)
 }
 
 /// Returns resource limit information for the current process.
 pub fn limits_self() -> Result<Limits> {
-    limits_file(&mut try!(File::open("/proc/self/limits")))
+    parse_file("/proc/self/limits", limits_file)
 }
 
-/// Returns resource limit information from the thread with the provided parent process ID and thread ID.
+/// Returns resource limit information from the thread with the provided parent process ID and
+/// thread ID.
 pub fn limits_task(process_id: pid_t, thread_id: pid_t) -> Result<Limits> {
-    limits_file(&mut try!(File::open(&format!("/proc/{}/task/{}/limits", process_id, thread_id))))
+    parse_file(format!("/proc/{}/task/{}/limits", process_id, thread_id), limits_file)
 }
 
 #[cfg(test)]
@@ -183,8 +340,9 @@ pub mod tests {
 
     use std::time::Duration;
 
-    use parsers::tests::unwrap;
-    use super::{limits, limits_self, parse_limits};
+    use libc::{self, rlimit};
+
+    use super::{Limit, Resource, limits, limits_self, parse_limits};
 
     /// Test that the system limit file can be parsed.
     #[test]
@@ -193,6 +351,20 @@ pub mod tests {
         limits(1).unwrap();
     }
 
+    /// Test that the limits of the current process match `getrlimit(2)`.
+    #[test]
+    fn test_limits_rlimit() {
+        let limits = limits_self().unwrap();
+        for &resource in &Resource::ALL {
+            let mut expected = rlimit { rlim_cur: 0, rlim_max: 0 };
+            assert_eq!(0, unsafe { libc::getrlimit(resource.as_raw() as _, &mut expected) });
+            let actual = limits.rlimit(resource);
+            assert_eq!((expected.rlim_cur, expected.rlim_max), (actual.rlim_cur, actual.rlim_max),
+                       "{:?}", resource);
+            assert_eq!(Limit::from(expected), limits.limit(resource));
+        }
+    }
+
     #[test]
     fn test_parse_limits() {
         let text = b"Limit                     Soft Limit           Hard Limit           Units         \n
@@ -213,7 +385,7 @@ pub mod tests {
                      Max realtime priority     0                    0                                  \n
                      Max realtime timeout      500                  unlimited            us            \n";
 
-        let limits = unwrap(parse_limits(text));
+        let limits = parse_limits(text).unwrap();
 
         assert_eq!(Some(Duration::new(10, 0)), limits.max_cpu_time.soft);
         assert_eq!(Some(Duration::new(60, 0)), limits.max_cpu_time.hard);
@@ -262,27 +434,55 @@ pub mod tests {
 
         assert_eq!(Some(Duration::new(0, 500 * 1000)), limits.max_realtime_timeout.soft);
         assert_eq!(None, limits.max_realtime_timeout.hard);
-    }
-}
-
-#[cfg(all(test, rustc_nightly))]
-mod benches {
-    extern crate test;
 
-    use std::fs::File;
-
-    use parsers::read_to_end;
-    use super::*;
+        assert_eq!((Some(10), Some(60)), limits.get(Resource::CpuTime));
+        assert_eq!((Some(1024), Some(4096)), limits.get(Resource::OpenFiles));
+        assert_eq!((Some(500), None), limits.get(Resource::RealtimeTimeout));
+        assert_eq!((None, None), limits.get(Resource::FileSize));
+    }
 
-    #[bench]
-    fn bench_limits(b: &mut test::Bencher) {
-        b.iter(|| test::black_box(limits(1)));
+    /// Test that rows with unrecognized labels are skipped.
+    #[test]
+    fn test_parse_limits_unknown_rows() {
+        let text = b"Limit                     Soft Limit           Hard Limit           Units     \n\
+Max cpu time              unlimited            unlimited            seconds   \n\
+Max file size             unlimited            unlimited            bytes     \n\
+Max data size             unlimited            unlimited            bytes     \n\
+Max stack size            8388608              unlimited            bytes     \n\
+Max core file size        0                    unlimited            bytes     \n\
+Max resident set          unlimited            unlimited            bytes     \n\
+Max processes             63632                63632                processes \n\
+Max open files            1024                 524288               files     \n\
+Max locked memory         8388608              8388608              bytes     \n\
+Max address space         unlimited            unlimited            bytes     \n\
+Max file locks            unlimited            unlimited            locks     \n\
+Max pending signals       63632                63632                signals   \n\
+Max frobnicators          12                   unlimited            frobs     \n\
+Max msgqueue size         819200               819200               bytes     \n\
+Max nice priority         0                    0                              \n\
+Max realtime priority     0                    0                              \n\
+Max realtime timeout      unlimited            unlimited            us        \n";
+
+        let limits = parse_limits(text).unwrap();
+        assert_eq!(Some(1024), limits.max_open_files.soft);
+        assert_eq!(Some(524288), limits.max_open_files.hard);
+        assert_eq!(Some(819200), limits.max_msgqueue_size.soft);
+        assert_eq!(None, limits.max_realtime_timeout.soft);
     }
 
-    #[bench]
-    fn bench_limits_parse(b: &mut test::Bencher) {
-        let mut buf = [0; 2048];
-        let limits = read_to_end(&mut File::open("/proc/1/limits").unwrap(), &mut buf).unwrap();
-        b.iter(|| test::black_box(parse_limits(limits)));
+    /// Test that rows are matched by label regardless of order, and missing rows are unlimited.
+    #[test]
+    fn test_parse_limits_reordered_and_missing_rows() {
+        let text = b"Limit                     Soft Limit           Hard Limit           Units     \n\
+Max open files            1024                 524288               files     \n\
+Max cpu time              10                   unlimited            seconds   \n";
+
+        let limits = parse_limits(text).unwrap();
+        assert_eq!(Some(1024), limits.max_open_files.soft);
+        assert_eq!(Some(524288), limits.max_open_files.hard);
+        assert_eq!(Some(Duration::new(10, 0)), limits.max_cpu_time.soft);
+        assert_eq!(None, limits.max_cpu_time.hard);
+        assert_eq!(Limit::default(), limits.max_stack_size);
+        assert_eq!(Limit::default(), limits.max_realtime_timeout);
     }
 }
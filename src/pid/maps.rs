@@ -0,0 +1,619 @@
+//! Memory mappings from `/proc/[pid]/maps`.
+
+use std::collections::HashMap;
+use std::ffi::OsStr;
+use std::fmt;
+use std::fs::File;
+use std::io::{BufRead, BufReader, Error, Result};
+use std::ops::Range;
+use std::os::unix::ffi::OsStrExt;
+use std::path::PathBuf;
+use std::str;
+
+use libc::pid_t;
+
+use parsers::{invalid_input, next_field, parse, parse_file, parse_u32_hex, parse_u64_hex, with_line,
+              with_path};
+use unmangle::unmangled_path;
+
+/// A mapped region of memory of a process.
+///
+/// See `proc(5)` for format details.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "export", derive(Serialize))]
+pub struct MemoryMap {
+    /// The address range of the mapping.
+    pub range: Range<u64>,
+    /// The access permissions of the mapping, and whether it is shared.
+    pub permissions: Permissions,
+    /// The offset into the mapped file.
+    pub offset: u64,
+    /// Device major ID of the mapped file.
+    pub dev_major: u32,
+    /// Device minor ID of the mapped file.
+    pub dev_minor: u32,
+    /// Inode of the mapped file, or 0 if the mapping is not backed by a file.
+    pub inode: u64,
+    /// What the mapping contains.
+    pub kind: MemoryMapKind,
+}
+
+bitflags! {
+    /// The access permissions of a memory mapping, and whether it is shared.
+    ///
+    /// Displayed in the `rwxp` notation of the maps file, where a mapping without `SHARED` is
+    /// private (copy on write).
+    #[derive(Default)]
+    #[cfg_attr(feature = "export", derive(Serialize))]
+    pub struct Permissions: u8 {
+        /// The mapping is readable.
+        const READ = 0x1;
+        /// The mapping is writable.
+        const WRITE = 0x2;
+        /// The mapping is executable.
+        const EXEC = 0x4;
+        /// The mapping is shared, rather than private (copy on write).
+        const SHARED = 0x8;
+    }
+}
+
+impl fmt::Display for Permissions {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let flag = |flag, c| if self.contains(flag) { c } else { '-' };
+        write!(f, "{}{}{}{}",
+               flag(Permissions::READ, 'r'),
+               flag(Permissions::WRITE, 'w'),
+               flag(Permissions::EXEC, 'x'),
+               if self.contains(Permissions::SHARED) { 's' } else { 'p' })
+    }
+}
+
+/// The contents of a memory mapping.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "export", derive(Serialize))]
+pub enum MemoryMapKind {
+    /// An anonymous mapping.
+    Anonymous,
+    /// A mapped file.
+    File {
+        /// The path of the file.
+        path: PathBuf,
+        /// Whether the file has been deleted since it was mapped.
+        deleted: bool,
+    },
+    /// The process heap.
+    Heap,
+    /// The stack of the main thread, or of the thread with the provided thread ID.
+    ///
+    /// Before Linux 4.5, the stacks of other threads were shown as `[stack:tid]`. Newer kernels
+    /// show them as anonymous mappings.
+    Stack(Option<pid_t>),
+    /// The virtual dynamically linked shared object.
+    Vdso,
+    /// The variables of the vDSO.
+    Vvar,
+    /// The legacy vsyscall page.
+    Vsyscall,
+    /// An anonymous mapping named with `prctl(PR_SET_VMA_ANON_NAME)` (since Linux 5.17) or by
+    /// Android, such as `libc_malloc` for `[anon:libc_malloc]`.
+    ///
+    /// Named anonymous shared memory, shown as `[anon_shmem:name]` (since Linux 6.2), has the
+    /// `SHARED` permission.
+    NamedAnonymous(String),
+    /// A pseudo-path which is not otherwise recognized.
+    Unknown(String),
+}
+
+/// Parses the `rwxp` permissions of a mapping.
+fn parse_perms(perms: &str) -> Result<Permissions> {
+    let invalid = || invalid_input(format!("invalid maps permissions: {:?}", perms));
+    let bytes = perms.as_bytes();
+    if bytes.len() != 4 {
+        return Err(invalid());
+    }
+    let mut permissions = Permissions::empty();
+    for (&c, &(set, flag)) in bytes.iter().zip(&[(b'r', Permissions::READ),
+                                                 (b'w', Permissions::WRITE),
+                                                 (b'x', Permissions::EXEC)]) {
+        match c {
+            _ if c == set => permissions |= flag,
+            b'-' => (),
+            _ => return Err(invalid()),
+        }
+    }
+    match bytes[3] {
+        b's' => permissions |= Permissions::SHARED,
+        b'p' => (),
+        _ => return Err(invalid()),
+    }
+    Ok(permissions)
+}
+
+/// Classifies the pathname of a mapping.
+fn parse_kind(pathname: &[u8]) -> MemoryMapKind {
+    match pathname {
+        b"" => MemoryMapKind::Anonymous,
+        b"[heap]" => MemoryMapKind::Heap,
+        b"[stack]" => MemoryMapKind::Stack(None),
+        b"[vdso]" => MemoryMapKind::Vdso,
+        b"[vvar]" => MemoryMapKind::Vvar,
+        b"[vsyscall]" => MemoryMapKind::Vsyscall,
+        _ if pathname.starts_with(b"[stack:") && pathname.ends_with(b"]") => {
+            let tid = &pathname[b"[stack:".len()..pathname.len() - 1];
+            match str::from_utf8(tid).ok().and_then(|tid| tid.parse().ok()) {
+                Some(tid) => MemoryMapKind::Stack(Some(tid)),
+                None => MemoryMapKind::Unknown(String::from_utf8_lossy(pathname).into_owned()),
+            }
+        },
+        _ if pathname.ends_with(b"]") && (pathname.starts_with(b"[anon:")
+                                           || pathname.starts_with(b"[anon_shmem:")) => {
+            let start = pathname.iter().position(|&c| c == b':').unwrap() + 1;
+            let name = &pathname[start..pathname.len() - 1];
+            MemoryMapKind::NamedAnonymous(String::from_utf8_lossy(name).into_owned())
+        },
+        _ if pathname.starts_with(b"/") => {
+            let (path, deleted) = match pathname.strip_suffix(b" (deleted)") {
+                Some(path) => (path, true),
+                None => (pathname, false),
+            };
+            let path = unmangled_path(path, b"\n");
+            MemoryMapKind::File { path: PathBuf::from(OsStr::from_bytes(&path)), deleted }
+        },
+        _ => MemoryMapKind::Unknown(String::from_utf8_lossy(pathname).into_owned()),
+    }
+}
+
+/// A mapped region of memory of a process, which borrows the pathname from the parsed line.
+///
+/// Parsing a `MemoryMapRef` does not allocate, and skips classifying and unmangling the pathname
+/// until `kind` is called.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "export", derive(Serialize))]
+pub struct MemoryMapRef<'a> {
+    /// The address range of the mapping.
+    pub range: Range<u64>,
+    /// The access permissions of the mapping, and whether it is shared.
+    pub permissions: Permissions,
+    /// The offset into the mapped file.
+    pub offset: u64,
+    /// Device major ID of the mapped file.
+    pub dev_major: u32,
+    /// Device minor ID of the mapped file.
+    pub dev_minor: u32,
+    /// Inode of the mapped file, or 0 if the mapping is not backed by a file.
+    pub inode: u64,
+    /// The pathname as printed by the kernel, with newlines escaped as `\012` and a ` (deleted)`
+    /// suffix if the file has been deleted.
+    pub pathname: &'a [u8],
+}
+
+/// Parses a line of the maps file format.
+fn parse_maps_entry(mut input: &[u8]) -> Result<MemoryMapRef<'_>> {
+    let (start, end) = next_field(&mut input)?.split_once('-')
+                                              .ok_or_else(|| invalid_input("invalid maps range"))?;
+    let permissions = parse_perms(next_field(&mut input)?)?;
+    let offset = parse_u64_hex(next_field(&mut input)?)?;
+    let (dev_major, dev_minor) = next_field(&mut input)?
+                                     .split_once(':')
+                                     .ok_or_else(|| invalid_input("invalid maps device"))?;
+    let inode = parse(next_field(&mut input)?)?;
+
+    let start = parse_u64_hex(start)?;
+    let end = parse_u64_hex(end)?;
+
+    // The pathname is padded to a fixed column, and may itself contain spaces.
+    let pathname = match input.iter().position(|&c| c != b' ') {
+        Some(i) => &input[i..],
+        None => &[],
+    };
+
+    Ok(MemoryMapRef {
+        range: start..end,
+        permissions,
+        offset,
+        dev_major: parse_u32_hex(dev_major)?,
+        dev_minor: parse_u32_hex(dev_minor)?,
+        inode,
+        pathname,
+    })
+}
+
+impl<'a> MemoryMapRef<'a> {
+    /// Parses a line of a `/proc/[pid]/maps` file, without the line terminator, borrowing the
+    /// pathname from the line.
+    pub fn parse_line(input: &'a [u8]) -> Result<MemoryMapRef<'a>> {
+        parse_maps_entry(input)
+    }
+
+    /// Returns what the mapping contains.
+    pub fn kind(&self) -> MemoryMapKind {
+        parse_kind(self.pathname)
+    }
+
+    /// Classifies the pathname, returning an owned `MemoryMap`.
+    pub fn into_owned(self) -> MemoryMap {
+        MemoryMap {
+            kind: self.kind(),
+            range: self.range,
+            permissions: self.permissions,
+            offset: self.offset,
+            dev_major: self.dev_major,
+            dev_minor: self.dev_minor,
+            inode: self.inode,
+        }
+    }
+}
+
+impl MemoryMap {
+    /// Parses a line of a `/proc/[pid]/maps` file, without the line terminator.
+    pub fn parse_line(input: &[u8]) -> Result<MemoryMap> {
+        parse_maps_entry(input).map(MemoryMapRef::into_owned)
+    }
+}
+
+/// An iterator over the memory mappings of a process.
+///
+/// Mappings are parsed as they are read, so memory use does not grow with the number of mappings.
+/// Created by `maps_iter` and `maps_iter_self`.
+#[derive(Debug)]
+pub struct MemoryMaps {
+    reader: BufReader<File>,
+    line: Vec<u8>,
+    line_number: usize,
+    /// The path of the maps file, added to parse errors.
+    path: PathBuf,
+}
+
+impl MemoryMaps {
+    pub(crate) fn new(file: File, path: PathBuf) -> MemoryMaps {
+        MemoryMaps { reader: BufReader::new(file), line: Vec::new(), line_number: 0, path }
+    }
+
+    /// Opens the maps file at the provided path.
+    fn open(path: String) -> Result<MemoryMaps> {
+        Ok(MemoryMaps::new(File::open(&path)?, PathBuf::from(path)))
+    }
+
+    /// Adds the path and the line number to a parse error.
+    fn with_context(&self, error: Error) -> Error {
+        with_path(with_line(error, self.line_number), &self.path)
+    }
+
+    /// Reads the next mapping, borrowing its pathname from the iterator's line buffer.
+    ///
+    /// Unlike `next`, this does not allocate once the line buffer has grown to the longest line,
+    /// and the pathname is only classified if `kind` is called. This is faster for processes with
+    /// many mappings, when only the address ranges and permissions are needed.
+    pub fn next_ref(&mut self) -> Option<Result<MemoryMapRef<'_>>> {
+        self.line_number += 1;
+        match read_line(&mut self.reader, &mut self.line)? {
+            Ok(line) => match MemoryMapRef::parse_line(line) {
+                Ok(map) => Some(Ok(map)),
+                Err(error) => Some(Err(with_path(with_line(error, self.line_number), &self.path))),
+            },
+            Err(error) => Some(Err(error)),
+        }
+    }
+}
+
+/// Reads the next line from the reader into the provided line buffer, without the terminator.
+pub(crate) fn read_line<'a, R: BufRead>(reader: &mut R,
+                                         line: &'a mut Vec<u8>)
+                                         -> Option<Result<&'a [u8]>> {
+    line.clear();
+    match reader.read_until(b'\n', line) {
+        Ok(0) => None,
+        Ok(_) => {
+            if line.last() == Some(&b'\n') {
+                line.pop();
+            }
+            Some(Ok(line))
+        },
+        Err(e) => Some(Err(e)),
+    }
+}
+
+/// Reads and parses the next mapping from the reader, reusing the provided line buffer.
+fn read_map<R: BufRead>(reader: &mut R, line: &mut Vec<u8>) -> Option<Result<MemoryMap>> {
+    read_line(reader, line).map(|line| line.and_then(MemoryMap::parse_line))
+}
+
+impl Iterator for MemoryMaps {
+    type Item = Result<MemoryMap>;
+
+    fn next(&mut self) -> Option<Result<MemoryMap>> {
+        self.line_number += 1;
+        read_map(&mut self.reader, &mut self.line)
+            .map(|map| map.map_err(|error| self.with_context(error)))
+    }
+}
+
+/// A mapping whose permissions differ between two snapshots of the memory mappings.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "export", derive(Serialize))]
+pub struct MemoryMapChange {
+    /// The permissions of the mapping in the old snapshot.
+    pub old_permissions: Permissions,
+    /// The mapping in the new snapshot.
+    pub map: MemoryMap,
+}
+
+/// The differences between two snapshots of the memory mappings of a process.
+///
+/// Created by `maps_diff`.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "export", derive(Serialize))]
+pub struct MapsDiff {
+    /// Mappings which are only in the new snapshot.
+    pub added: Vec<MemoryMap>,
+    /// Mappings which are only in the old snapshot.
+    pub removed: Vec<MemoryMap>,
+    /// Mappings which are in both snapshots, with different permissions.
+    pub changed: Vec<MemoryMapChange>,
+}
+
+impl MapsDiff {
+    /// Returns `true` if the snapshots contain the same mappings with the same permissions.
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty() && self.changed.is_empty()
+    }
+}
+
+/// Compares two snapshots of the memory mappings of a process.
+///
+/// Mappings are matched by their address range, device and inode, so a mapping which is resized
+/// or replaced by another file appears as both removed and added. The mappings in each part of the
+/// result are in the order of the snapshot they are taken from.
+pub fn maps_diff(old: &[MemoryMap], new: &[MemoryMap]) -> MapsDiff {
+    let key = |map: &MemoryMap| (map.range.start, map.range.end, map.dev_major, map.dev_minor,
+                                 map.inode);
+    let mut old_maps: HashMap<_, &MemoryMap> = old.iter().map(|map| (key(map), map)).collect();
+    let mut diff = MapsDiff::default();
+    for map in new {
+        match old_maps.remove(&key(map)) {
+            Some(old_map) if old_map.permissions != map.permissions => {
+                diff.changed.push(MemoryMapChange {
+                    old_permissions: old_map.permissions,
+                    map: map.clone(),
+                });
+            },
+            Some(_) => (),
+            None => diff.added.push(map.clone()),
+        }
+    }
+    diff.removed = old.iter().filter(|map| old_maps.contains_key(&key(map))).cloned().collect();
+    diff
+}
+
+/// Parses the provided maps file.
+pub(crate) fn maps_file(file: &mut File) -> Result<Vec<MemoryMap>> {
+    let mut reader = BufReader::new(file);
+    let mut line = Vec::new();
+    let mut maps = Vec::new();
+    while let Some(map) = read_map(&mut reader, &mut line) {
+        maps.push(map?);
+    }
+    Ok(maps)
+}
+
+/// Returns the memory mappings of the process with the provided pid.
+pub fn maps(pid: pid_t) -> Result<Vec<MemoryMap>> {
+    parse_file(format!("/proc/{}/maps", pid), maps_file)
+}
+
+/// Returns the memory mappings of the current process.
+pub fn maps_self() -> Result<Vec<MemoryMap>> {
+    parse_file("/proc/self/maps", maps_file)
+}
+
+/// Returns the memory mappings from the thread with the provided parent process ID and thread ID.
+pub fn maps_task(process_id: pid_t, thread_id: pid_t) -> Result<Vec<MemoryMap>> {
+    parse_file(format!("/proc/{}/task/{}/maps", process_id, thread_id), maps_file)
+}
+
+/// Returns an iterator over the memory mappings of the process with the provided pid.
+pub fn maps_iter(pid: pid_t) -> Result<MemoryMaps> {
+    MemoryMaps::open(format!("/proc/{}/maps", pid))
+}
+
+/// Returns an iterator over the memory mappings of the current process.
+pub fn maps_iter_self() -> Result<MemoryMaps> {
+    MemoryMaps::open("/proc/self/maps".to_owned())
+}
+
+/// Returns an iterator over the memory mappings from the thread with the provided parent process ID
+/// and thread ID.
+pub fn maps_iter_task(process_id: pid_t, thread_id: pid_t) -> Result<MemoryMaps> {
+    MemoryMaps::open(format!("/proc/{}/task/{}/maps", process_id, thread_id))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::env;
+    use std::fs::{self, File};
+    use std::path::PathBuf;
+    use std::process;
+
+    use libc;
+
+    use super::{MemoryMap, MemoryMapChange, MemoryMapKind, MemoryMapRef, MemoryMaps, Permissions,
+                maps_diff, maps_iter_self, maps_iter_task, maps_self, maps_task, parse_perms};
+
+    /// Test that the maps file of the current process can be parsed.
+    #[test]
+    fn test_maps() {
+        let maps = maps_self().unwrap();
+        assert!(maps.iter().any(|map| map.kind == MemoryMapKind::Stack(None)));
+    }
+
+    /// Test that the maps file of the main thread can be parsed.
+    #[test]
+    fn test_maps_task() {
+        let pid = unsafe { libc::getpid() };
+        assert!(!maps_task(pid, pid).unwrap().is_empty());
+        assert!(maps_iter_task(pid, pid).unwrap().all(|map| map.is_ok()));
+    }
+
+    /// Test that the mapping containing a local variable can be found by iterating.
+    #[test]
+    fn test_maps_iter() {
+        let local = 0u8;
+        let addr = &local as *const u8 as u64;
+        let map = maps_iter_self().unwrap()
+                                  .map(Result::unwrap)
+                                  .find(|map| map.range.contains(&addr))
+                                  .unwrap();
+        assert!(map.permissions.contains(Permissions::READ | Permissions::WRITE));
+    }
+
+    /// Test that iterator parse errors are located by path and line number.
+    #[test]
+    fn test_maps_iter_error() {
+        let path = env::temp_dir().join(format!("procinfo-maps-{}", process::id()));
+        fs::write(&path, "00400000-00452000 r-xp 00000000 08:02 173521 /usr/bin/dbus-daemon\n\
+                          00651000-00652000 x\n").unwrap();
+        let mut maps = MemoryMaps::new(File::open(&path).unwrap(), path.clone());
+        assert!(maps.next().unwrap().is_ok());
+        let error = maps.next().unwrap().unwrap_err();
+        assert!(error.to_string().starts_with(&format!("{}: line 2", path.display())));
+        assert!(maps.next_ref().is_none());
+        fs::remove_file(&path).unwrap();
+    }
+
+    /// Test that borrowed mappings match the owned mappings.
+    #[test]
+    fn test_maps_next_ref() {
+        let mut iter = maps_iter_self().unwrap();
+        let mut maps = Vec::new();
+        while let Some(map) = iter.next_ref() {
+            let map = map.unwrap();
+            if map.pathname == b"[stack]" {
+                assert_eq!(MemoryMapKind::Stack(None), map.kind());
+            }
+            maps.push(map.into_owned());
+        }
+        assert!(maps.iter().any(|map| map.kind == MemoryMapKind::Stack(None)));
+    }
+
+    #[test]
+    fn test_parse_maps_entry() {
+        let line = b"00400000-00452000 r-xp 00000000 08:02 173521                             \
+                     /usr/bin/dbus-daemon";
+        assert_eq!(MemoryMap {
+                       range: 0x400000..0x452000,
+                       permissions: Permissions::READ | Permissions::EXEC,
+                       offset: 0,
+                       dev_major: 8,
+                       dev_minor: 2,
+                       inode: 173521,
+                       kind: MemoryMapKind::File {
+                           path: PathBuf::from("/usr/bin/dbus-daemon"),
+                           deleted: false,
+                       },
+                   },
+                   MemoryMap::parse_line(line).unwrap());
+
+        let map = MemoryMap::parse_line(b"7f2c1a000000-7f2c1a021000 rw-s 0001f000 fd:01 42  \
+                                          /dev/shm/a b\\012c (deleted)").unwrap();
+        assert_eq!(Permissions::READ | Permissions::WRITE | Permissions::SHARED, map.permissions);
+        assert_eq!(0x1f000, map.offset);
+        assert_eq!((0xfd, 1), (map.dev_major, map.dev_minor));
+        assert_eq!(MemoryMapKind::File { path: PathBuf::from("/dev/shm/a b\nc"), deleted: true },
+                   map.kind);
+
+        let map = MemoryMap::parse_line(b"7ffd6c3a2000-7ffd6c3a4000 r-xp 00000000 00:00 0 \
+                                          [vdso]").unwrap();
+        assert_eq!(MemoryMapKind::Vdso, map.kind);
+
+        let map = MemoryMap::parse_line(b"7f2c1a021000-7f2c1a022000 rw-p 00000000 00:00 0 ")
+                           .unwrap();
+        assert_eq!(MemoryMapKind::Anonymous, map.kind);
+        assert_eq!(0, map.inode);
+
+        let map = MemoryMap::parse_line(b"7f2c1a021000-7f2c1a022000 ---p 00000000 00:00 0  \
+                                          [anon:foo]").unwrap();
+        assert!(map.permissions.is_empty());
+        assert_eq!(MemoryMapKind::NamedAnonymous("foo".to_owned()), map.kind);
+
+        let map = MemoryMap::parse_line(b"7f2c19820000-7f2c1a020000 rw-p 00000000 00:00 0 \
+                                          [stack:31337]").unwrap();
+        assert_eq!(MemoryMapKind::Stack(Some(31337)), map.kind);
+
+        let map = MemoryMap::parse_line(b"7f2c19820000-7f2c1a020000 rw-p 00000000 00:00 0 \
+                                          [stack:main]").unwrap();
+        assert_eq!(MemoryMapKind::Unknown("[stack:main]".to_owned()), map.kind);
+
+        let map = MemoryMap::parse_line(b"7f2c1a021000-7f2c1a022000 rw-s 00000000 00:01 1045 \
+                                          [anon_shmem:ring buffer]").unwrap();
+        assert_eq!(MemoryMapKind::NamedAnonymous("ring buffer".to_owned()), map.kind);
+
+        MemoryMap::parse_line(b"00400000-00452000 r-xq 00000000 08:02 173521").unwrap_err();
+        MemoryMap::parse_line(b"00400000 r-xp 00000000 08:02 173521").unwrap_err();
+        MemoryMap::parse_line(b"00400000-00452000 r-xp 00000000 08:02").unwrap_err();
+    }
+
+    #[test]
+    fn test_parse_maps_entry_ref() {
+        let line = b"7f2c1a000000-7f2c1a021000 rw-s 0001f000 fd:01 42  \
+                     /dev/shm/a b\\012c (deleted)";
+        let map = MemoryMapRef::parse_line(line).unwrap();
+        assert_eq!(&b"/dev/shm/a b\\012c (deleted)"[..], map.pathname);
+        assert_eq!(0x7f2c1a000000..0x7f2c1a021000, map.range);
+        assert_eq!(MemoryMapKind::File { path: PathBuf::from("/dev/shm/a b\nc"), deleted: true },
+                   map.kind());
+        assert_eq!(MemoryMap::parse_line(line).unwrap(), map.into_owned());
+
+        let map = MemoryMapRef::parse_line(b"7f2c1a021000-7f2c1a022000 rw-p 00000000 00:00 0 ")
+                              .unwrap();
+        assert!(map.pathname.is_empty());
+        assert_eq!(MemoryMapKind::Anonymous, map.kind());
+    }
+
+    #[test]
+    fn test_parse_maps_entry_deleted() {
+        let line = b"7f2c1a000000-7f2c1a021000 rw-s 0001f000 fd:01 42  \
+                     /dev/shm/a b\\012c (deleted)";
+        let map = MemoryMapRef::parse_line(line).unwrap();
+        assert_eq!(&b"/dev/shm/a b\\012c (deleted)"[..], map.pathname);
+        assert_eq!(0x7f2c1a000000..0x7f2c1a021000, map.range);
+        assert_eq!(42, map.inode);
+        assert_eq!(MemoryMap::parse_line(line).unwrap().kind, map.kind());
+    }
+
+    #[test]
+    fn test_maps_diff() {
+        let heap = MemoryMap::parse_line(b"01a0f000-01a30000 rw-p 00000000 00:00 0  [heap]")
+                             .unwrap();
+        let lib = MemoryMap::parse_line(b"7f2c1a000000-7f2c1a021000 r-xp 00000000 fd:01 42  \
+                                          /usr/lib/libc.so.6").unwrap();
+        let jit = MemoryMap::parse_line(b"7f2c1b000000-7f2c1b001000 rw-p 00000000 00:00 0 ")
+                            .unwrap();
+        let mut jit_exec = jit.clone();
+        jit_exec.permissions = Permissions::READ | Permissions::EXEC;
+        let mut heap_grown = heap.clone();
+        heap_grown.range.end += 0x1000;
+
+        let diff = maps_diff(&[heap.clone(), lib.clone(), jit.clone()],
+                             &[heap_grown.clone(), jit_exec.clone()]);
+        assert_eq!(vec![heap_grown], diff.added);
+        assert_eq!(vec![heap.clone(), lib.clone()], diff.removed);
+        assert_eq!(vec![MemoryMapChange { old_permissions: jit.permissions, map: jit_exec }],
+                   diff.changed);
+        assert!(!diff.is_empty());
+
+        assert!(maps_diff(&[heap.clone(), lib.clone()], &[heap, lib]).is_empty());
+        let maps = maps_self().unwrap();
+        assert!(maps_diff(&maps, &maps).is_empty());
+    }
+
+    #[test]
+    fn test_permissions() {
+        for &perms in &["rwxp", "r--s", "---p", "-w-s"] {
+            assert_eq!(perms, parse_perms(perms).unwrap().to_string());
+        }
+        assert_eq!(Permissions::WRITE | Permissions::SHARED, parse_perms("-w-s").unwrap());
+        parse_perms("rwx").unwrap_err();
+        parse_perms("wrxp").unwrap_err();
+        parse_perms("rwxq").unwrap_err();
+    }
+}
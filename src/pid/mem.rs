@@ -0,0 +1,140 @@
+//! Reading the memory of a process through `/proc/[pid]/mem`.
+
+use std::fs::File;
+use std::io::{ErrorKind, Result, Seek, SeekFrom};
+use std::ops::Range;
+use std::os::unix::fs::FileExt;
+
+use libc::pid_t;
+
+use parsers::invalid_input;
+use pid::maps::{MemoryMap, maps_file};
+
+/// A handle to the memory of a process.
+///
+/// Reads are validated against a snapshot of the memory mappings of the process, and ranges which
+/// are not entirely mapped are refused instead of being read. The snapshot is taken when the
+/// handle is opened, and is updated by `refresh_maps`.
+///
+/// Opening the memory of another process requires ptrace attach access to it, see `ptrace(2)`.
+#[derive(Debug)]
+pub struct Mem {
+    mem: File,
+    maps_file: File,
+    maps: Vec<MemoryMap>,
+}
+
+impl Mem {
+    /// Creates a handle from the open mem and maps files of a process.
+    pub(crate) fn from_files(mem: File, mut maps_file: File) -> Result<Mem> {
+        let maps = maps_file_at_start(&mut maps_file)?;
+        Ok(Mem { mem, maps_file, maps })
+    }
+
+    /// Returns the snapshot of the memory mappings of the process.
+    pub fn maps(&self) -> &[MemoryMap] {
+        &self.maps
+    }
+
+    /// Updates the snapshot of the memory mappings of the process.
+    pub fn refresh_maps(&mut self) -> Result<()> {
+        self.maps = maps_file_at_start(&mut self.maps_file)?;
+        Ok(())
+    }
+
+    /// Returns whether every address in the range is mapped, according to the snapshot of the
+    /// memory mappings.
+    pub fn is_mapped(&self, range: Range<u64>) -> bool {
+        let mut start = range.start;
+        // The mappings are ordered by address and do not overlap.
+        for map in self.maps.iter().filter(|map| map.range.end > range.start) {
+            if start >= range.end {
+                break;
+            }
+            if map.range.start > start {
+                return false;
+            }
+            start = map.range.end;
+        }
+        start >= range.end
+    }
+
+    /// Reads memory of the process at the address into the buffer, returning the number of bytes
+    /// read.
+    ///
+    /// Fails with `InvalidInput` if the range to be read is not entirely mapped. Like `pread(2)`,
+    /// fewer bytes than requested may be read.
+    pub fn read_at(&self, addr: u64, buf: &mut [u8]) -> Result<usize> {
+        let end = addr.checked_add(buf.len() as u64)
+                      .ok_or_else(|| invalid_input("address range overflows"))?;
+        if !self.is_mapped(addr..end) {
+            return Err(invalid_input(format!("address range {:#x}..{:#x} is not mapped",
+                                             addr, end)));
+        }
+        self.mem.read_at(buf, addr)
+    }
+
+    /// Reads memory of the process at the address, filling the buffer.
+    ///
+    /// Fails with `InvalidInput` if the range to be read is not entirely mapped.
+    pub fn read_exact_at(&self, mut addr: u64, mut buf: &mut [u8]) -> Result<()> {
+        while !buf.is_empty() {
+            match self.read_at(addr, buf) {
+                Ok(0) => return Err(ErrorKind::UnexpectedEof.into()),
+                Ok(n) => {
+                    addr += n as u64;
+                    buf = &mut buf[n..];
+                },
+                Err(ref error) if error.kind() == ErrorKind::Interrupted => (),
+                Err(error) => return Err(error),
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Parses the maps file from its start, so that it can be read again.
+fn maps_file_at_start(file: &mut File) -> Result<Vec<MemoryMap>> {
+    file.seek(SeekFrom::Start(0))?;
+    maps_file(file)
+}
+
+/// Opens the memory of the process with the provided pid.
+pub fn mem(pid: pid_t) -> Result<Mem> {
+    Mem::from_files(File::open(format!("/proc/{}/mem", pid))?,
+                    File::open(format!("/proc/{}/maps", pid))?)
+}
+
+/// Opens the memory of the current process.
+pub fn mem_self() -> Result<Mem> {
+    Mem::from_files(File::open("/proc/self/mem")?, File::open("/proc/self/maps")?)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::ErrorKind;
+
+    use super::mem_self;
+
+    #[test]
+    fn test_mem_self() {
+        let value: u64 = 0x0123_4567_89ab_cdef;
+        let addr = &value as *const u64 as u64;
+
+        let mut mem = mem_self().unwrap();
+        assert!(mem.is_mapped(addr..addr + 8));
+        let mut buf = [0; 8];
+        mem.read_exact_at(addr, &mut buf).unwrap();
+        assert_eq!(value, u64::from_ne_bytes(buf));
+
+        // The first page is never mapped.
+        assert!(!mem.is_mapped(0..8));
+        assert_eq!(ErrorKind::InvalidInput, mem.read_at(0, &mut buf).unwrap_err().kind());
+        mem.read_at(u64::MAX, &mut buf).unwrap_err();
+
+        mem.refresh_maps().unwrap();
+        assert!(!mem.maps().is_empty());
+        let last = mem.maps().last().unwrap().range.end;
+        assert!(!mem.is_mapped(last - 1..last + 1));
+    }
+}
@@ -0,0 +1,159 @@
+//! A summary of the memory usage of a process, from the cheapest file which provides it.
+
+use std::io::{Error, ErrorKind, Result};
+
+use libc::{self, pid_t};
+
+use pid::smaps::{SmapsEntry, SmapsSummary, smaps, smaps_rollup, smaps_rollup_self, smaps_self};
+use pid::statm::{Statm, statm, statm_self};
+
+/// The file from which a `MemorySummary` was computed.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "export", derive(Serialize))]
+pub enum MemorySource {
+    /// `/proc/[pid]/smaps_rollup` (since Linux 4.14).
+    SmapsRollup,
+    /// `/proc/[pid]/smaps`, which is expensive to read for processes with many mappings.
+    Smaps,
+    /// `/proc/[pid]/statm`, which does not require ptrace access to the process, but does not
+    /// provide proportional or unique set sizes, or swap usage.
+    Statm,
+}
+
+/// A summary of the memory usage of a process.
+///
+/// Sizes are in kB.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "export", derive(Serialize))]
+pub struct MemorySummary {
+    /// The file from which the summary was computed.
+    pub source: MemorySource,
+    /// Resident set size.
+    pub rss: u64,
+    /// Proportional set size: the resident memory, with each page divided by the number of
+    /// processes sharing it. Not provided by `statm`.
+    pub pss: Option<u64>,
+    /// Unique set size: the resident memory which is private to the process. Not provided by
+    /// `statm`.
+    pub uss: Option<u64>,
+    /// Resident shared memory. For `statm`, this is the resident memory backed by files or shared
+    /// memory, whether or not it is mapped by other processes.
+    pub shared: u64,
+    /// Memory which has been swapped out. Not provided by `statm`.
+    pub swap: Option<u64>,
+}
+
+impl MemorySummary {
+    fn from_smaps(source: MemorySource, entries: &[SmapsEntry]) -> MemorySummary {
+        let total = SmapsSummary::from(entries).total;
+        MemorySummary {
+            source,
+            rss: total.rss,
+            pss: Some(total.pss),
+            uss: Some(total.uss),
+            shared: total.shared,
+            swap: Some(total.swap),
+        }
+    }
+
+    fn from_statm(statm: &Statm) -> MemorySummary {
+        MemorySummary {
+            source: MemorySource::Statm,
+            rss: statm.resident_bytes() / 1024,
+            pss: None,
+            uss: None,
+            shared: statm.share_bytes() / 1024,
+            swap: None,
+        }
+    }
+}
+
+/// Returns whether the error indicates that the file is unavailable, rather than that the process
+/// has exited or the file is invalid.
+///
+/// `smaps_rollup` and `smaps` are missing on kernels without `CONFIG_PROC_PAGE_MONITOR`, require
+/// ptrace access to the process, and `smaps_rollup` fails with `ESRCH` for kernel threads.
+fn is_unavailable(error: &Error) -> bool {
+    error.kind() == ErrorKind::NotFound
+        || error.kind() == ErrorKind::PermissionDenied
+        || error.raw_os_error() == Some(libc::ESRCH)
+}
+
+/// Computes the memory summary from the first available source.
+pub(crate) fn summarize<R, S, M>(rollup: R, smaps: S, statm: M) -> Result<MemorySummary>
+where R: FnOnce() -> Result<SmapsEntry>,
+      S: FnOnce() -> Result<Vec<SmapsEntry>>,
+      M: FnOnce() -> Result<Statm> {
+    match rollup() {
+        Ok(entry) => return Ok(MemorySummary::from_smaps(MemorySource::SmapsRollup, &[entry])),
+        Err(ref error) if is_unavailable(error) => (),
+        Err(error) => return Err(error),
+    }
+    match smaps() {
+        Ok(entries) => return Ok(MemorySummary::from_smaps(MemorySource::Smaps, &entries)),
+        Err(ref error) if is_unavailable(error) => (),
+        Err(error) => return Err(error),
+    }
+    statm().map(|statm| MemorySummary::from_statm(&statm))
+}
+
+/// Returns a summary of the memory usage of the process with the provided pid.
+///
+/// The summary is computed from `smaps_rollup` if it is available, else from `smaps`, else from
+/// `statm`.
+pub fn memory_summary(pid: pid_t) -> Result<MemorySummary> {
+    summarize(|| smaps_rollup(pid), || smaps(pid), || statm(pid))
+}
+
+/// Returns a summary of the memory usage of the current process.
+pub fn memory_summary_self() -> Result<MemorySummary> {
+    summarize(smaps_rollup_self, smaps_self, statm_self)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::{Error, ErrorKind, Result};
+
+    use libc;
+
+    use pid::{smaps_self, statm_self};
+    use super::{MemorySource, memory_summary, memory_summary_self, summarize};
+
+    fn unavailable<T>() -> Result<T> {
+        Err(Error::from(ErrorKind::NotFound))
+    }
+
+    /// Test that the memory summary of the current process can be read.
+    #[test]
+    fn test_memory_summary() {
+        let summary = memory_summary_self().unwrap();
+        assert!(summary.rss > 0);
+        assert!(summary.pss.unwrap_or(0) <= summary.rss);
+
+        let pid = unsafe { libc::getpid() };
+        assert_eq!(summary.source, memory_summary(pid).unwrap().source);
+    }
+
+    #[test]
+    fn test_summarize_fallback() {
+        let summary = summarize(unavailable, smaps_self, statm_self).unwrap();
+        assert_eq!(MemorySource::Smaps, summary.source);
+        assert!(summary.uss.unwrap() > 0);
+
+        let summary = summarize(unavailable, unavailable, statm_self).unwrap();
+        assert_eq!(MemorySource::Statm, summary.source);
+        assert!(summary.rss > 0);
+        assert_eq!(None, summary.pss);
+        assert_eq!(None, summary.swap);
+
+        let esrch = || Err(Error::from_raw_os_error(libc::ESRCH));
+        assert_eq!(MemorySource::Statm,
+                   summarize(esrch, unavailable, statm_self).unwrap().source);
+
+        let invalid = || Err(Error::new(ErrorKind::InvalidInput, "invalid smaps"));
+        assert_eq!(ErrorKind::InvalidInput,
+                   summarize(invalid, smaps_self, statm_self).unwrap_err().kind());
+        assert_eq!(ErrorKind::NotFound,
+                   summarize(unavailable, unavailable, unavailable).unwrap_err().kind());
+    }
+}
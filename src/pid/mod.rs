@@ -1,60 +1,95 @@
 //! Process-specific information from `/proc/[pid]/`.
 
+mod attr;
+mod availability;
+mod cgroup;
+mod cmdline;
+mod coredump_filter;
 mod cwd;
+mod environ;
+mod exe;
+mod fdinfo;
+mod find;
+mod io;
 mod limits;
+mod maps;
+mod mem;
+mod memory_summary;
 mod mountinfo;
+mod mounts;
+mod numa_maps;
+pub mod net;
+mod ns;
+mod oom;
+mod personality;
+mod pids;
+mod process;
+mod ps;
+mod smaps;
+mod sockets;
 mod stat;
+mod state;
 mod statm;
 mod status;
+mod thread_cpu;
+mod timerslack_ns;
+mod tree;
+mod zombies;
 
+pub use pid::attr::{attr_current, attr_current_self};
+pub use pid::availability::{Availability, is_kernel_thread};
+pub use pid::cgroup::{BlkioServiceBytes, Cgroup, CgroupV1Stats, cgroup, cgroup_self,
+                      cgroup_v1_stats, cgroup_v1_stats_self};
+pub use pid::cmdline::{cmdline, cmdline_self, cmdline_task, parse_cmdline};
+pub use pid::coredump_filter::{CoredumpFilter, coredump_filter, coredump_filter_self,
+                               set_coredump_filter_self};
 pub use pid::cwd::{cwd, cwd_self};
-pub use pid::limits::{Limit, Limits, limits, limits_self};
-pub use pid::mountinfo::{Mountinfo, mountinfo, mountinfo_self};
-pub use pid::statm::{Statm, statm, statm_self};
-pub use pid::status::{SeccompMode, Status, status, status_self};
-pub use pid::stat::{Stat, stat, stat_self};
+pub use pid::environ::{Environ, EnvironIter, environ, environ_self, environ_task};
+pub use pid::exe::{exe, exe_self};
+pub use pid::fdinfo::{Fanotify, FanotifyMark, FanotifyTarget, FdInfo, FdInfoKind, FileHandle,
+                      InotifyWatch, Timerfd, fdinfo, fdinfo_self};
+pub use pid::find::{find, find_by_name};
+pub use pid::io::{Io, io, io_if_permitted, io_self, io_task};
+pub use pid::limits::{Limit, Limits, Resource, limits, limits_self, limits_task};
+pub use pid::maps::{MapsDiff, MemoryMap, MemoryMapChange, MemoryMapKind, MemoryMapRef, MemoryMaps,
+                    Permissions, maps, maps_diff, maps_iter, maps_iter_self, maps_iter_task,
+                    maps_self, maps_task};
+pub use pid::mem::{Mem, mem, mem_self};
+pub use pid::memory_summary::{MemorySource, MemorySummary, memory_summary,
+                               memory_summary_self};
+pub use pid::mountinfo::{MountOption, Mountinfo, mountinfo, mountinfo_self, mountinfo_task};
+pub use pid::mounts::{Mount, mounts, mounts_self, mounts_task};
+pub use pid::numa_maps::{MemoryPolicy, MemoryPolicyFlags, MemoryPolicyMode, NumaMap, numa_maps,
+                          numa_maps_self};
+pub use pid::ns::{Namespace, Namespaces, namespace, namespaces, namespaces_self,
+                  shares_namespace};
+pub use pid::oom::{OOM_SCORE_ADJ_MAX, OOM_SCORE_ADJ_MIN, oom_adj, oom_score, oom_score_adj,
+                   oom_score_adj_self, oom_score_self, set_oom_score_adj,
+                   set_oom_score_adj_self};
+pub use pid::personality::{Personality, PersonalityFlags, personality, personality_self};
+pub use pid::pids::{Pids, pids};
+pub use pid::process::Process;
+pub use pid::ps::{PsRow, PsTable, format_cpu_time, ps, tty_name};
+pub use pid::smaps::{SmapsEntries, SmapsEntry, SmapsFields, SmapsSummary, SmapsUsage, VmFlags,
+                     smaps, smaps_iter, smaps_iter_self, smaps_iter_task, smaps_rollup,
+                     smaps_rollup_self, smaps_self, smaps_task};
+pub use pid::sockets::{ProcessSocket, Socket, sockets, sockets_self};
+pub use pid::state::ProcessState;
+pub use pid::statm::{Statm, statm, statm_self, statm_task};
+pub use pid::status::{Capabilities, SeccompMode, Signal, SignalSet, SignalSetIter,
+                      SpeculationIndirectBranch, SpeculationStoreBypass, Status, status,
+                      status_self, status_task};
+pub use pid::stat::{Stat, StatAll, StatRef, StatSelect, TaskFlags, stat, stat_all, stat_self,
+                    stat_task};
+pub use pid::thread_cpu::{ThreadCpu, ThreadCpuUsage, ThreadTimes, thread_cpu};
+pub use pid::timerslack_ns::{TimerSlack, set_timerslack_ns_self, timerslack_ns,
+                             timerslack_ns_self};
+pub use pid::tree::{Ancestors, ProcessTree, Subtree, tree};
+pub use pid::zombies::{Zombie, zombies};
 
-/// The state of a process.
-#[derive(Debug, PartialEq, Eq, Hash)]
-pub enum State {
-    /// Running.
-    Running,
-    /// Sleeping in an interruptible wait.
-    Sleeping,
-    /// Waiting in uninterruptible disk sleep.
-    Waiting,
-    /// Zombie.
-    Zombie,
-    /// Stopped (on a signal) or (before Linux 2.6.33) trace stopped.
-    Stopped,
-    /// trace stopped.
-    ///
-    /// Linux 2.6.33 onward.
-    TraceStopped,
-    /// Paging.
-    ///
-    /// Only before linux 2.6.0.
-    Paging,
-    /// Dead.
-    ///
-    /// Linux 2.6.33 to 3.13 only.
-    Dead,
-    /// Wakekill.
-    ///
-    /// Linux 2.6.33 to 3.13 only.
-    Wakekill,
-    /// Waking.
-    ///
-    /// Linux 2.6.33 to 3.13 only.
-    Waking,
-    /// Parked.
-    ///
-    /// Linux 3.9 to 3.13 only.
-    Parked,
-}
-
-impl Default for State {
-    fn default() -> State {
-        State::Running
-    }
-}
+pub(crate) use pid::pids::is_exited;
+pub(crate) use pid::sockets::socket_fds;
+#[cfg(feature = "async")]
+pub(crate) use pid::oom::{parse_oom_adj, parse_oom_score};
+#[cfg(feature = "async")]
+pub(crate) use pid::process::open_at;
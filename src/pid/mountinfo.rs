@@ -3,19 +3,16 @@
 use std::fs::File;
 use std::io::{BufRead, BufReader, Result};
 use std::path::PathBuf;
-use std::str::{self, FromStr};
-use std::io::{Error, ErrorKind};
 
 use libc::pid_t;
-use nom::{Err, IResult, Needed};
-use nom::ErrorKind::Tag;
 
-use parsers::{map_result, parse_isize, parse_usize};
+use parsers::{invalid_input, parse, parse_file, parse_str, with_line};
 
 /// Process mounts information.
 ///
 /// See `proc(5)` for format details.
 #[derive(Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "export", derive(Serialize))]
 pub struct Mountinfo {
     /// Unique ID for the mount.
     pub mount_id: isize,
@@ -45,6 +42,7 @@ pub struct Mountinfo {
 ///
 /// See `proc(5)` and `mount_namespace(7)` for more details.
 #[derive(Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "export", derive(Serialize))]
 pub enum OptionalField {
     /// A mount shared in peer group `ID`
     Shared(usize),
@@ -63,6 +61,7 @@ pub enum OptionalField {
 ///
 /// See `mount(8)` for more details.
 #[derive(Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "export", derive(Serialize))]
 pub enum MountOption {
     /// Do not update inode access time
     Noatime,
@@ -84,26 +83,9 @@ pub enum MountOption {
     Other(String),
 }
 
-/// Consumes a space, main fields separator and optional fields separator
-named!(space, tag!(" "));
-
-/// Consumes an hypen, the optional fields terminator
-named!(hypen, tag!("-"));
-
-/// Consumes a colon, the major-minor separator
-named!(colon, tag!(":"));
-
-/// Consumes a dot, the fs sub-type separator
-named!(dot, tag!("."));
-
-/// Parses a space-terminated string field in a mountinfo entry
-named!(parse_string_field<String>,
-       map_res!(map_res!(is_not!(" "), str::from_utf8), FromStr::from_str));
-
-
-/// Parses a string of optional fields.
-fn mount_options(opts: String) -> Vec<MountOption> {
-    opts.split(",").map(|o|
+/// Parses a comma-separated list of mount options.
+pub(crate) fn mount_options(opts: &str) -> Vec<MountOption> {
+    opts.split(',').map(|o|
         match o {
             "noatime"    => MountOption::Noatime,
             "nodev"      => MountOption::Nodev,
@@ -118,144 +100,109 @@ fn mount_options(opts: String) -> Vec<MountOption> {
     ).collect()
 }
 
-/// Parses a comma-separated list of mount options.
-named!(parse_mnt_options<Vec<MountOption> >,
-       do_parse!(token: parse_string_field >>
-                 (mount_options(token))
-       )
-);
-
-/// Parses a string of optional fields.
-fn opt_fields(fs: &str) -> Result<Vec<OptionalField>> {
-    let mut v = Vec::new();
+/// Parses a tag:value optional field.
+fn opt_field(field: &str) -> Result<OptionalField> {
+    let opt_field = match field.split_once(':') {
+        Some(("shared", id)) => OptionalField::Shared(parse(id)?),
+        Some(("master", id)) => OptionalField::Master(parse(id)?),
+        Some(("propagate_from", id)) => OptionalField::PropagateFrom(parse(id)?),
+        None if field == "unbindable" => OptionalField::Unbindable,
+        _ => return Err(invalid_input(format!("invalid optional field: {:?}", field))),
+    };
+    Ok(opt_field)
+}
 
-    for i in fs.split_terminator(' ') {
-        let t: Vec<&str> = i.split(':').collect();
-        if t.len() > 2 {
-            return Err(Error::new(ErrorKind::InvalidInput, "too many colons"));
+/// Parses a mountpoint entry according to mountinfo file format.
+fn parse_mountinfo_entry(input: &[u8]) -> Result<Mountinfo> {
+    let line = parse_str(input)?;
+    let mut fields = line.split(' ');
+    let mut next = || fields.next().ok_or_else(|| {
+        invalid_input(format!("missing mountinfo field: {:?}", line))
+    });
+
+    let mount_id = parse(next()?)?;
+    let parent_id = parse(next()?)?;
+    let (major, minor) = next()?.split_once(':')
+                                .ok_or_else(|| invalid_input("invalid mountinfo device"))?;
+    let root = next()?;
+    let mount_point = next()?;
+    let mount_options = mount_options(next()?);
+
+    let mut opt_fields = Vec::new();
+    loop {
+        match next()? {
+            "-" => break,
+            field => opt_fields.push(opt_field(field)?),
         }
-        match (t.get(0), t.get(1)) {
-            (Some(&"shared"), Some(x)) if usize::from_str(x).is_ok() =>
-                v.push(OptionalField::Shared(usize::from_str(x).unwrap())),
-            (Some(&"master"), Some(x)) if usize::from_str(x).is_ok() =>
-                v.push(OptionalField::Master(usize::from_str(x).unwrap())),
-            (Some(&"propagate_from"), Some(x)) if usize::from_str(x).is_ok() =>
-                v.push(OptionalField::PropagateFrom(usize::from_str(x).unwrap())),
-            (Some(&"unbindable"), None) =>
-                v.push(OptionalField::Unbindable),
-            (_, _) => return Err(Error::new(ErrorKind::InvalidInput, "invalid optional value")),
-        };
+    }
+    if opt_fields.is_empty() {
+        opt_fields.push(OptionalField::Private);
     }
 
-    if v.len() == 0 {
-        v.push(OptionalField::Private);
+    let fs_type = next()?;
+    let fs_type = match fs_type.split_once('.') {
+        Some((fs_type, sub_type)) => (fs_type.to_owned(), Some(sub_type.to_owned())),
+        None => (fs_type.to_owned(), None),
+    };
+    let mount_src = match next()? {
+        "none" => None,
+        src => Some(src.to_owned()),
+    };
+    let super_opts = next()?.split(',').map(ToOwned::to_owned).collect();
+    if next().is_ok() {
+        return Err(invalid_input(format!("unable to parse whole input: {:?}", line)));
     }
 
-    Ok(v)
+    Ok(Mountinfo {
+        mount_id,
+        parent_id,
+        major: parse(major)?,
+        minor: parse(minor)?,
+        root: root.into(),
+        mount_point: mount_point.into(),
+        mount_options,
+        opt_fields,
+        fs_type,
+        mount_src,
+        super_opts,
+    })
 }
 
-/// Parses a space-separated list of tag:value optional fields.
-fn parse_opt_fields(input: &[u8]) -> IResult<&[u8], Vec<OptionalField>> {
-    // look for the mandatory terminator (hypen)
-    let mut hypen = None;
-    for idx in 0..input.len() {
-        if '-' as u8 == input[idx] {
-            hypen = Some(idx);
-            break
-        }
-    }
-    if hypen.is_none() {
-        return IResult::Incomplete(Needed::Unknown);
-    }
-
-    // parse all optional fields
-    let term = hypen.unwrap();
-    let fs = str::from_utf8(&input[0..term]);
-    match fs {
-        Err(_) => IResult::Error(Err::Position(Tag, input)),
-        Ok(f) => match opt_fields(f) {
-            Err(_) => IResult::Error(Err::Position(Tag, input)),
-            Ok(r) => IResult::Done(&input[term..], r),
-        }
+impl Mountinfo {
+    /// Parses a line of a `/proc/[pid]/mountinfo` file, without the line terminator.
+    pub fn parse_line(input: &[u8]) -> Result<Mountinfo> {
+        parse_mountinfo_entry(input)
     }
 }
 
-/// Parses a fs type label, with optional dotted sub-type.
-named!(parse_fs_type<(String, Option<String>)>,
-       do_parse!(k: map_res!(map_res!(take_until_either!(" ."), str::from_utf8), FromStr::from_str) >>
-                 v: opt!(do_parse!(dot >> s: parse_string_field >> (s))) >>
-                 (k, v)
-       )
-);
-
-/// Parses a mount source.
-named!(parse_mount_src<Option<String> >,
-       do_parse!(src: parse_string_field >>
-                 (if src == "none" { None } else { Some(src) })
-       )
-);
-
-/// Parses a comma-separated list of options.
-named!(parse_options<Vec<String> >,
-       do_parse!(token: parse_string_field >>
-                 (token.split(",").map(|s| s.into()).collect())
-       )
-);
-
-/// Parses a mountpoint entry according to mountinfo file format.
-named!(parse_mountinfo_entry<Mountinfo>,
-    do_parse!(mount_id: parse_isize            >> space >>
-              parent_id: parse_isize           >> space >>
-              major: parse_usize               >> colon >>
-              minor: parse_usize               >> space >>
-              root: parse_string_field         >> space >>
-              mount_point: parse_string_field  >> space >>
-              mount_options: parse_mnt_options >> space >>
-              opt_fields: parse_opt_fields     >> hypen >> space >>
-              fs_type: parse_fs_type           >> space >>
-              mount_src: parse_mount_src       >> space >>
-              super_opts: parse_options        >>
-              ( Mountinfo {
-                            mount_id: mount_id,
-                            parent_id: parent_id,
-                            major: major,
-                            minor: minor,
-                            root: root.into(),
-                            mount_point: mount_point.into(),
-                            mount_options: mount_options,
-                            opt_fields: opt_fields,
-                            fs_type: fs_type,
-                            mount_src: mount_src,
-                            super_opts: super_opts,
-           } )));
-
 /// Parses the provided mountinfo file.
-fn mountinfo_file(file: &mut File) -> Result<Vec<Mountinfo>> {
+pub(crate) fn mountinfo_file(file: &mut File) -> Result<Vec<Mountinfo>> {
     let mut r = Vec::new();
-    for line in BufReader::new(file).lines() {
-        let mi = try!(map_result(parse_mountinfo_entry(try!(line).as_bytes())));
-        r.push(mi);
+    for (i, line) in BufReader::new(file).lines().enumerate() {
+        r.push(Mountinfo::parse_line(line?.as_bytes()).map_err(|error| with_line(error, i + 1))?);
     }
     Ok(r)
 }
 
 /// Returns mounts information for the process with the provided pid.
 pub fn mountinfo(pid: pid_t) -> Result<Vec<Mountinfo>> {
-    mountinfo_file(&mut try!(File::open(&format!("/proc/{}/mountinfo", pid))))
+    parse_file(format!("/proc/{}/mountinfo", pid), mountinfo_file)
 }
 
 /// Returns mounts information for the current process.
 pub fn mountinfo_self() -> Result<Vec<Mountinfo>> {
-    mountinfo_file(&mut try!(File::open("/proc/self/mountinfo")))
+    parse_file("/proc/self/mountinfo", mountinfo_file)
 }
 
 /// Returns mounts information from the thread with the provided parent process ID and thread ID.
 pub fn mountinfo_task(process_id: pid_t, thread_id: pid_t) -> Result<Vec<Mountinfo>> {
-    mountinfo_file(&mut try!(File::open(&format!("/proc/{}/task/{}/mountinfo", process_id, thread_id))))
+    parse_file(format!("/proc/{}/task/{}/mountinfo", process_id, thread_id), mountinfo_file)
 }
 
 #[cfg(test)]
 pub mod tests {
+    use std::path::Path;
+
     use super::{Mountinfo, MountOption, OptionalField, mountinfo, mountinfo_self, parse_mountinfo_entry};
 
     /// Test parsing a single mountinfo entry (positive check).
@@ -263,7 +210,7 @@ pub mod tests {
     fn test_parse_mountinfo_entry() {
         let entry =
             b"19 23 0:4 / /proc rw,nosuid,foo shared:13 master:20 - proc.sys proc rw,nosuid";
-        let got_mi = parse_mountinfo_entry(entry).unwrap().1;
+        let got_mi = parse_mountinfo_entry(entry).unwrap();
         let want_mi = Mountinfo {
             mount_id: 19,
             parent_id: 23,
@@ -282,7 +229,7 @@ pub mod tests {
             ],
             fs_type: ("proc".to_string(), Some("sys".to_string())),
             mount_src: Some("proc".to_string()),
-            super_opts: vec!["rw","nosuid"].iter().map(|&s| s.into()).collect(),
+            super_opts: ["rw", "nosuid"].iter().map(|&s| s.into()).collect(),
         };
         assert_eq!(got_mi, want_mi);
     }
@@ -294,6 +241,17 @@ pub mod tests {
         parse_mountinfo_entry(entry).unwrap_err();
     }
 
+    #[test]
+    fn test_mountinfo_parse_line() {
+        let entry = b"19 23 0:4 / /proc rw,nosuid shared:13 - proc proc rw";
+        assert_eq!(Path::new("/proc"), Mountinfo::parse_line(entry).unwrap().mount_point);
+        let entry = b"40 25 0:35 / /mnt/remote rw - fuse.sshfs host:/ rw";
+        let mountinfo = Mountinfo::parse_line(entry).unwrap();
+        assert_eq!(vec![OptionalField::Private], mountinfo.opt_fields);
+        assert_eq!(("fuse".to_owned(), Some("sshfs".to_owned())), mountinfo.fs_type);
+        Mountinfo::parse_line(b"19 23 0:4 / /proc").unwrap_err();
+    }
+
     /// Test that the system mountinfo files can be parsed.
     #[test]
     fn test_mountinfo() {
@@ -0,0 +1,139 @@
+//! The mount table of a process from `/proc/[pid]/mounts`.
+//!
+//! The table lists the mounts of the mount namespace of the process, with mount points relative
+//! to the root directory of the process. Reading it for a process in a container gives the mounts
+//! as seen from inside the container, unlike the global `/proc/mounts`, which is the mount table
+//! of the reading process.
+
+use std::ffi::OsStr;
+use std::fs::File;
+use std::io::{BufRead, BufReader, Result};
+use std::os::unix::ffi::OsStrExt;
+use std::path::PathBuf;
+
+use libc::pid_t;
+
+use parsers::{invalid_input, parse, parse_file, parse_str, with_line};
+use pid::mountinfo::{MountOption, mount_options};
+use unmangle::unmangled_path;
+
+/// The characters escaped in the fields of the mounts file.
+const ESCAPED: &[u8] = b" \t\n\\";
+
+/// An entry of the mount table of a process.
+///
+/// See `fstab(5)` for format details.
+#[derive(Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "export", derive(Serialize))]
+pub struct Mount {
+    /// The mounted device, or filesystem specific information.
+    pub source: Option<String>,
+    /// Mount pathname relative to the process's root.
+    pub mount_point: PathBuf,
+    /// Filesystem type (main type with optional sub-type).
+    pub fs_type: (String, Option<String>),
+    /// Mount and superblock options.
+    pub options: Vec<MountOption>,
+    /// Whether the filesystem should be dumped by `dump(8)`, which is always 0.
+    pub dump: u32,
+    /// The order in which `fsck(8)` checks the filesystem, which is always 0.
+    pub pass: u32,
+}
+
+impl Mount {
+    /// Parses a line of a `/proc/[pid]/mounts` file, without the line terminator.
+    pub fn parse_line(input: &[u8]) -> Result<Mount> {
+        let mut fields = input.split(|&c| c == b' ');
+        let mut next = || fields.next().ok_or_else(|| {
+            invalid_input(format!("missing mounts field: {:?}", String::from_utf8_lossy(input)))
+        });
+
+        let source = String::from_utf8_lossy(&unmangled_path(next()?, ESCAPED)).into_owned();
+        let mount_point = unmangled_path(next()?, ESCAPED);
+        let fs_type = parse_str(next()?)?;
+        let fs_type = match fs_type.split_once('.') {
+            Some((fs_type, sub_type)) => (fs_type.to_owned(), Some(sub_type.to_owned())),
+            None => (fs_type.to_owned(), None),
+        };
+        let options = mount_options(&String::from_utf8_lossy(&unmangled_path(next()?, ESCAPED)));
+        let dump = parse(parse_str(next()?)?)?;
+        let pass = parse(parse_str(next()?)?)?;
+        if next().is_ok() {
+            return Err(invalid_input(format!("unable to parse whole input: {:?}",
+                                             String::from_utf8_lossy(input))));
+        }
+
+        Ok(Mount {
+            source: if source == "none" { None } else { Some(source) },
+            mount_point: PathBuf::from(OsStr::from_bytes(&mount_point)),
+            fs_type,
+            options,
+            dump,
+            pass,
+        })
+    }
+}
+
+/// Parses the provided mounts file.
+pub(crate) fn mounts_file(file: &mut File) -> Result<Vec<Mount>> {
+    let mut r = Vec::new();
+    for (i, line) in BufReader::new(file).split(b'\n').enumerate() {
+        r.push(Mount::parse_line(&line?).map_err(|error| with_line(error, i + 1))?);
+    }
+    Ok(r)
+}
+
+/// Returns the mount table of the process with the provided pid, as seen from its mount
+/// namespace.
+pub fn mounts(pid: pid_t) -> Result<Vec<Mount>> {
+    parse_file(format!("/proc/{}/mounts", pid), mounts_file)
+}
+
+/// Returns the mount table of the current process.
+pub fn mounts_self() -> Result<Vec<Mount>> {
+    parse_file("/proc/self/mounts", mounts_file)
+}
+
+/// Returns the mount table from the thread with the provided parent process ID and thread ID.
+pub fn mounts_task(process_id: pid_t, thread_id: pid_t) -> Result<Vec<Mount>> {
+    parse_file(format!("/proc/{}/task/{}/mounts", process_id, thread_id), mounts_file)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use libc;
+
+    use pid::mountinfo::{MountOption, mountinfo_self};
+    use super::{Mount, mounts, mounts_self};
+
+    #[test]
+    fn test_parse_line() {
+        let mount = Mount::parse_line(b"/dev/sda1 /mnt/my\\040disk fuse.sshfs \
+                                        rw,nosuid,user_id=0 0 0").unwrap();
+        assert_eq!(Some("/dev/sda1".to_owned()), mount.source);
+        assert_eq!(Path::new("/mnt/my disk"), mount.mount_point);
+        assert_eq!(("fuse".to_owned(), Some("sshfs".to_owned())), mount.fs_type);
+        assert_eq!(vec![MountOption::Rw,
+                        MountOption::Nosuid,
+                        MountOption::Other("user_id=0".to_owned())],
+                   mount.options);
+        assert_eq!((0, 0), (mount.dump, mount.pass));
+
+        let mount = Mount::parse_line(b"none /sys/fs/bpf bpf rw 0 0").unwrap();
+        assert_eq!(None, mount.source);
+
+        Mount::parse_line(b"proc /proc proc rw 0").unwrap_err();
+        Mount::parse_line(b"proc /proc proc rw 0 0 0").unwrap_err();
+    }
+
+    /// Test that the mount table agrees with the mountinfo file of the current process.
+    #[test]
+    fn test_mounts() {
+        let table = mounts_self().unwrap();
+        assert_eq!(mountinfo_self().unwrap().len(), table.len());
+        assert!(table.iter().any(|mount| mount.mount_point == Path::new("/proc")));
+        assert_eq!(table, mounts(unsafe { libc::getpid() }).unwrap());
+    }
+}
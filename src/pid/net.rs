@@ -0,0 +1,226 @@
+//! Network information from `/proc/[pid]/net`.
+//!
+//! The files in `/proc/[pid]/net` describe the network namespace of the process, which may differ
+//! from the network namespace of the current process read by the `net` module. `namespaces` and
+//! `namespace_stats` cover every network namespace on the host, such as those of containers.
+
+use std::collections::BTreeMap;
+use std::io::{ErrorKind, Result};
+
+use libc::pid_t;
+
+use net::bonding::{Bond, bonding_dir};
+use net::dev::{DeviceStatus, dev_file};
+use net::igmp::{Igmp6Membership, IgmpInterface, igmp6_file, igmp_file};
+use net::inet::{InetSocket, inet_file};
+use net::stat::{NetStat, stat_dir};
+use net::unix::{UnixSocket, unix_file};
+use net::vlan::{VlanDevice, vlan_config_file};
+use pid::ns::{Namespace, namespace};
+use pid::pids::{is_exited, pids};
+
+/// A network namespace, with a process which is a member of it.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "export", derive(Serialize))]
+pub struct NetNamespace {
+    /// The inode of the namespace, which identifies it.
+    pub inode: u64,
+    /// The lowest pid of the processes in the namespace which were visible when it was found.
+    pub pid: pid_t,
+}
+
+/// The network devices and socket tables of a network namespace.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "export", derive(Serialize))]
+pub struct NetNamespaceStats {
+    /// The namespace.
+    pub namespace: NetNamespace,
+    /// The network devices of the namespace.
+    pub dev: Vec<DeviceStatus>,
+    /// The IPv4 TCP sockets of the namespace.
+    pub tcp: Vec<InetSocket>,
+    /// The IPv6 TCP sockets of the namespace, empty if IPv6 is disabled.
+    pub tcp6: Vec<InetSocket>,
+    /// The IPv4 UDP sockets of the namespace.
+    pub udp: Vec<InetSocket>,
+    /// The IPv6 UDP sockets of the namespace, empty if IPv6 is disabled.
+    pub udp6: Vec<InetSocket>,
+    /// The Unix domain sockets of the namespace.
+    pub unix: Vec<UnixSocket>,
+}
+
+/// Returns the distinct network namespaces of all processes, ordered by inode.
+///
+/// Reading the namespace of another process requires ptrace access to it, so without
+/// `CAP_SYS_PTRACE` the namespaces which only contain processes of other users are not found.
+/// Processes which exit during the scan are skipped.
+pub fn namespaces() -> Result<Vec<NetNamespace>> {
+    let mut namespaces = BTreeMap::new();
+    for pid in pids()? {
+        let pid = pid?;
+        let inode = match namespace(pid, Namespace::Net) {
+            Ok(inode) => inode,
+            Err(ref error) if is_exited(error) => continue,
+            Err(ref error) if error.kind() == ErrorKind::PermissionDenied => continue,
+            Err(error) => return Err(error),
+        };
+        let representative = namespaces.entry(inode).or_insert(pid);
+        if pid < *representative {
+            *representative = pid;
+        }
+    }
+    Ok(namespaces.into_iter().map(|(inode, pid)| NetNamespace { inode, pid }).collect())
+}
+
+/// Reads an IPv6 socket table, which is missing when IPv6 is disabled.
+fn inet6_file(path: &str) -> Result<Vec<InetSocket>> {
+    match inet_file(path) {
+        Err(ref error) if error.kind() == ErrorKind::NotFound => Ok(Vec::new()),
+        result => result,
+    }
+}
+
+/// Returns the network devices and socket tables of the provided network namespace.
+fn namespace_stats_of(namespace: NetNamespace) -> Result<NetNamespaceStats> {
+    let pid = namespace.pid;
+    Ok(NetNamespaceStats {
+        namespace,
+        dev: dev(pid)?,
+        tcp: tcp(pid)?,
+        tcp6: inet6_file(&format!("/proc/{}/net/tcp6", pid))?,
+        udp: udp(pid)?,
+        udp6: inet6_file(&format!("/proc/{}/net/udp6", pid))?,
+        unix: unix(pid)?,
+    })
+}
+
+/// Returns the network devices and socket tables of each network namespace, ordered by inode.
+///
+/// Since the files in `/proc/net` only describe the network namespace of the reader, summing them
+/// across processes counts shared namespaces more than once, and reading them from a single
+/// process misses the other namespaces. This reads each namespace found by `namespaces` exactly
+/// once, through one of its processes. A namespace is skipped if that process exits before its
+/// files are read.
+pub fn namespace_stats() -> Result<Vec<NetNamespaceStats>> {
+    let mut stats = Vec::new();
+    for namespace in namespaces()? {
+        match namespace_stats_of(namespace) {
+            Ok(namespace_stats) => stats.push(namespace_stats),
+            Err(ref error) if is_exited(error) => continue,
+            Err(error) => return Err(error),
+        }
+    }
+    Ok(stats)
+}
+
+/// Returns the status of each bonding interface in the network namespace of the process with the
+/// provided pid, by interface name.
+pub fn bonding(pid: pid_t) -> Result<Vec<(String, Bond)>> {
+    bonding_dir(&format!("/proc/{}/net/bonding", pid))
+}
+
+/// Returns list of all network devices in the network namespace of the process with the provided
+/// pid, and information about their state.
+pub fn dev(pid: pid_t) -> Result<Vec<DeviceStatus>> {
+    dev_file(&format!("/proc/{}/net/dev", pid))
+}
+
+/// Returns the IPv4 ICMP (ping) sockets of the network namespace of the process with the provided
+/// pid.
+pub fn icmp(pid: pid_t) -> Result<Vec<InetSocket>> {
+    inet_file(&format!("/proc/{}/net/icmp", pid))
+}
+
+/// Returns the IPv6 ICMP (ping) sockets of the network namespace of the process with the provided
+/// pid.
+pub fn icmp6(pid: pid_t) -> Result<Vec<InetSocket>> {
+    inet_file(&format!("/proc/{}/net/icmp6", pid))
+}
+
+/// Returns the IPv4 multicast group memberships of each interface in the network namespace of the
+/// process with the provided pid.
+pub fn igmp(pid: pid_t) -> Result<Vec<IgmpInterface>> {
+    igmp_file(&format!("/proc/{}/net/igmp", pid))
+}
+
+/// Returns the IPv6 multicast group memberships in the network namespace of the process with the
+/// provided pid.
+pub fn igmp6(pid: pid_t) -> Result<Vec<Igmp6Membership>> {
+    igmp6_file(&format!("/proc/{}/net/igmp6", pid))
+}
+
+/// Returns the per-CPU network statistics tables of the network namespace of the process with the
+/// provided pid.
+pub fn stat(pid: pid_t) -> Result<NetStat> {
+    stat_dir(&format!("/proc/{}/net/stat", pid))
+}
+
+/// Returns the IPv4 TCP sockets of the network namespace of the process with the provided pid.
+pub fn tcp(pid: pid_t) -> Result<Vec<InetSocket>> {
+    inet_file(&format!("/proc/{}/net/tcp", pid))
+}
+
+/// Returns the IPv6 TCP sockets of the network namespace of the process with the provided pid.
+pub fn tcp6(pid: pid_t) -> Result<Vec<InetSocket>> {
+    inet_file(&format!("/proc/{}/net/tcp6", pid))
+}
+
+/// Returns the IPv4 UDP sockets of the network namespace of the process with the provided pid.
+pub fn udp(pid: pid_t) -> Result<Vec<InetSocket>> {
+    inet_file(&format!("/proc/{}/net/udp", pid))
+}
+
+/// Returns the IPv6 UDP sockets of the network namespace of the process with the provided pid.
+pub fn udp6(pid: pid_t) -> Result<Vec<InetSocket>> {
+    inet_file(&format!("/proc/{}/net/udp6", pid))
+}
+
+/// Returns the Unix domain sockets of the network namespace of the process with the provided pid.
+pub fn unix(pid: pid_t) -> Result<Vec<UnixSocket>> {
+    unix_file(&format!("/proc/{}/net/unix", pid))
+}
+
+/// Returns the VLAN devices configured in the network namespace of the process with the provided
+/// pid.
+pub fn vlan_config(pid: pid_t) -> Result<Vec<VlanDevice>> {
+    vlan_config_file(&format!("/proc/{}/net/vlan/config", pid))
+}
+
+#[cfg(test)]
+mod tests {
+    use libc;
+
+    use pid::ns::{Namespace, namespace};
+    use super::{dev, namespace_stats, namespaces, tcp, udp, unix};
+
+    /// Test that the system net/dev files can be parsed.
+    #[test]
+    fn test_dev() {
+        dev(1).unwrap();
+    }
+
+    /// Test that the system socket tables can be parsed.
+    #[test]
+    fn test_sockets() {
+        tcp(1).unwrap();
+        udp(1).unwrap();
+        unix(1).unwrap();
+    }
+
+    /// Test that the network namespaces can be enumerated and read.
+    #[test]
+    fn test_namespaces() {
+        let pid = unsafe { libc::getpid() };
+        let inode = namespace(pid, Namespace::Net).unwrap();
+        let namespaces = namespaces().unwrap();
+        let own = namespaces.iter().find(|namespace| namespace.inode == inode).unwrap();
+        assert!(own.pid <= pid);
+        assert!(namespaces.windows(2).all(|pair| pair[0].inode < pair[1].inode));
+
+        let stats = namespace_stats().unwrap();
+        assert!(stats.iter().any(|stats| {
+            stats.namespace.inode == inode
+                && stats.dev.iter().any(|device| device.interface == "lo")
+        }));
+    }
+}
@@ -0,0 +1,205 @@
+//! Namespace membership of a process, from `/proc/[pid]/ns`.
+
+use std::fs;
+use std::io::{ErrorKind, Result};
+use std::path::{Path, PathBuf};
+
+use libc::pid_t;
+
+use parsers::{invalid_input, with_path};
+
+/// A Linux namespace type.
+///
+/// See `namespaces(7)`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "export", derive(Serialize))]
+pub enum Namespace {
+    /// Cgroup root directory (since Linux 4.6).
+    Cgroup,
+    /// System V IPC and POSIX message queues.
+    Ipc,
+    /// Mount points.
+    Mnt,
+    /// Network devices, stacks, and ports.
+    Net,
+    /// Process IDs.
+    Pid,
+    /// Boot and monotonic clocks (since Linux 5.6).
+    Time,
+    /// User and group IDs.
+    User,
+    /// Hostname and NIS domain name.
+    Uts,
+}
+
+impl Namespace {
+    /// Returns the name of the namespace's entry in `/proc/[pid]/ns`.
+    pub fn name(self) -> &'static str {
+        match self {
+            Namespace::Cgroup => "cgroup",
+            Namespace::Ipc => "ipc",
+            Namespace::Mnt => "mnt",
+            Namespace::Net => "net",
+            Namespace::Pid => "pid",
+            Namespace::Time => "time",
+            Namespace::User => "user",
+            Namespace::Uts => "uts",
+        }
+    }
+}
+
+/// The namespaces of a process, identified by inode number.
+///
+/// Two processes are in the same namespace if the inode numbers of the namespace are equal. A
+/// namespace is `None` if the kernel does not support it.
+///
+/// See `namespaces(7)`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "export", derive(Serialize))]
+pub struct Namespaces {
+    /// Cgroup namespace inode.
+    pub cgroup: Option<u64>,
+    /// IPC namespace inode.
+    pub ipc: Option<u64>,
+    /// Mount namespace inode.
+    pub mnt: Option<u64>,
+    /// Network namespace inode.
+    pub net: Option<u64>,
+    /// PID namespace inode.
+    pub pid: Option<u64>,
+    /// Time namespace inode.
+    pub time: Option<u64>,
+    /// User namespace inode.
+    pub user: Option<u64>,
+    /// UTS namespace inode.
+    pub uts: Option<u64>,
+}
+
+impl Namespaces {
+    /// Returns the inode of the provided namespace type, if supported.
+    pub fn get(&self, namespace: Namespace) -> Option<u64> {
+        match namespace {
+            Namespace::Cgroup => self.cgroup,
+            Namespace::Ipc => self.ipc,
+            Namespace::Mnt => self.mnt,
+            Namespace::Net => self.net,
+            Namespace::Pid => self.pid,
+            Namespace::Time => self.time,
+            Namespace::User => self.user,
+            Namespace::Uts => self.uts,
+        }
+    }
+}
+
+/// Parses a namespace link target of the form `type:[inode]`.
+fn parse_ns_link(namespace: Namespace, link: &str) -> Result<u64> {
+    let name = namespace.name();
+    if link.len() > name.len() + 3
+        && link.starts_with(name)
+        && link[name.len()..].starts_with(":[")
+        && link.ends_with(']') {
+        if let Ok(inode) = link[name.len() + 2..link.len() - 1].parse() {
+            return Ok(inode);
+        }
+    }
+    Err(invalid_input(format!("unable to parse namespace link: {:?}", link)))
+}
+
+/// Reads the inode of the provided namespace in the `ns` directory at the provided path.
+///
+/// `read_link` reads the link with the provided name in the directory.
+fn ns_link<F>(dir: &Path, read_link: &F, namespace: Namespace) -> Result<u64>
+where F: Fn(&str) -> Result<PathBuf> {
+    let link = read_link(namespace.name())?;
+    let inode = match link.to_str() {
+        Some(link) => parse_ns_link(namespace, link),
+        None => Err(invalid_input("namespace link is not valid UTF-8")),
+    };
+    inode.map_err(|error| with_path(error, &dir.join(namespace.name())))
+}
+
+/// Reads the inode of the provided namespace, or `None` if the kernel does not support it.
+///
+/// `dir_exists` returns whether the `ns` directory exists, which distinguishes an unsupported
+/// namespace from a process which has exited.
+fn ns_link_opt<F, E>(dir: &Path, read_link: &F, dir_exists: &E, namespace: Namespace)
+                     -> Result<Option<u64>>
+where F: Fn(&str) -> Result<PathBuf>, E: Fn() -> bool {
+    match ns_link(dir, read_link, namespace) {
+        Ok(inode) => Ok(Some(inode)),
+        Err(ref error) if error.kind() == ErrorKind::NotFound && dir_exists() => Ok(None),
+        Err(error) => Err(error),
+    }
+}
+
+/// Reads all namespaces from the `ns` directory at the provided path.
+///
+/// `read_link` reads the link with the provided name in the directory, and `dir_exists` returns
+/// whether the directory exists.
+pub(crate) fn namespaces_dir<F, E>(dir: &Path, read_link: F, dir_exists: E) -> Result<Namespaces>
+where F: Fn(&str) -> Result<PathBuf>, E: Fn() -> bool {
+    let link = |namespace| ns_link_opt(dir, &read_link, &dir_exists, namespace);
+    Ok(Namespaces {
+        cgroup: link(Namespace::Cgroup)?,
+        ipc: link(Namespace::Ipc)?,
+        mnt: link(Namespace::Mnt)?,
+        net: link(Namespace::Net)?,
+        pid: link(Namespace::Pid)?,
+        time: link(Namespace::Time)?,
+        user: link(Namespace::User)?,
+        uts: link(Namespace::Uts)?,
+    })
+}
+
+/// Reads all namespaces from the `ns` directory at the provided path.
+fn namespaces_path(dir: &Path) -> Result<Namespaces> {
+    namespaces_dir(dir, |name| fs::read_link(dir.join(name)), || dir.exists())
+}
+
+/// Returns the namespaces of the process with the provided pid.
+///
+/// Reading the namespaces of another process requires ptrace access to it.
+pub fn namespaces(pid: pid_t) -> Result<Namespaces> {
+    namespaces_path(Path::new(&format!("/proc/{}/ns", pid)))
+}
+
+/// Returns the namespaces of the current process.
+pub fn namespaces_self() -> Result<Namespaces> {
+    namespaces_path(Path::new("/proc/self/ns"))
+}
+
+/// Returns the inode of a single namespace of the process with the provided pid.
+pub fn namespace(pid: pid_t, namespace: Namespace) -> Result<u64> {
+    let dir = PathBuf::from(format!("/proc/{}/ns", pid));
+    ns_link(&dir, &|name| fs::read_link(dir.join(name)), namespace)
+}
+
+/// Returns whether the two processes with the provided pids share the provided namespace.
+pub fn shares_namespace(pid_a: pid_t, pid_b: pid_t, ns: Namespace) -> Result<bool> {
+    Ok(namespace(pid_a, ns)? == namespace(pid_b, ns)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use libc::getpid;
+
+    use super::{Namespace, namespace, namespaces_self, parse_ns_link, shares_namespace};
+
+    /// Test that the namespaces of the current process can be read.
+    #[test]
+    fn test_namespaces() {
+        let namespaces = namespaces_self().unwrap();
+        let pid = unsafe { getpid() };
+        assert_eq!(namespaces.net, Some(namespace(pid, Namespace::Net).unwrap()));
+        assert!(shares_namespace(pid, pid, Namespace::Mnt).unwrap());
+    }
+
+    #[test]
+    fn test_parse_ns_link() {
+        assert_eq!(4026531992, parse_ns_link(Namespace::Net, "net:[4026531992]").unwrap());
+        assert_eq!(4026531835, parse_ns_link(Namespace::Cgroup, "cgroup:[4026531835]").unwrap());
+        parse_ns_link(Namespace::Net, "mnt:[4026531992]").unwrap_err();
+        parse_ns_link(Namespace::Net, "net:[]").unwrap_err();
+        parse_ns_link(Namespace::Net, "net:4026531992").unwrap_err();
+    }
+}
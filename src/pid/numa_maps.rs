@@ -0,0 +1,300 @@
+//! NUMA memory policy and node placement of memory mappings from `/proc/[pid]/numa_maps`.
+
+use std::ffi::OsStr;
+use std::fs::File;
+use std::io::{BufRead, BufReader, Result};
+use std::os::unix::ffi::OsStrExt;
+use std::path::PathBuf;
+
+use libc::pid_t;
+
+use cpuset::NodeSet;
+use parsers::{invalid_input, next_field, parse, parse_file, parse_str, parse_u64_hex, with_line};
+use unmangle::unmangled_path;
+
+/// The mode of a NUMA memory policy, see `set_mempolicy(2)`.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "export", derive(Serialize))]
+pub enum MemoryPolicyMode {
+    /// Allocate according to the policy of the process, or on the local node.
+    Default,
+    /// Allocate on the preferred node, falling back to other nodes.
+    Preferred,
+    /// Allocate on the preferred nodes, falling back to other nodes (since Linux 5.15).
+    PreferredMany,
+    /// Allocate only on the policy nodes.
+    Bind,
+    /// Interleave allocations across the policy nodes.
+    Interleave,
+    /// Interleave allocations across the policy nodes in proportion to their weights (since
+    /// Linux 6.9).
+    WeightedInterleave,
+    /// Allocate on the node of the CPU which triggered the allocation (since Linux 3.8).
+    Local,
+    /// A mode which is not otherwise recognized.
+    Other(String),
+}
+
+bitflags! {
+    /// The mode flags of a NUMA memory policy, see `set_mempolicy(2)`.
+    #[derive(Default)]
+    #[cfg_attr(feature = "export", derive(Serialize))]
+    pub struct MemoryPolicyFlags: u8 {
+        /// The policy nodes are not remapped when the allowed nodes of the process change
+        /// (`MPOL_F_STATIC_NODES`).
+        const STATIC_NODES = 0x1;
+        /// The policy nodes are relative to the allowed nodes of the process
+        /// (`MPOL_F_RELATIVE_NODES`).
+        const RELATIVE_NODES = 0x2;
+        /// Pages are migrated by NUMA balancing (`MPOL_F_NUMA_BALANCING`, since Linux 5.12).
+        const NUMA_BALANCING = 0x4;
+    }
+}
+
+/// A NUMA memory policy.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "export", derive(Serialize))]
+pub struct MemoryPolicy {
+    /// The mode of the policy.
+    pub mode: MemoryPolicyMode,
+    /// The mode flags of the policy.
+    pub flags: MemoryPolicyFlags,
+    /// The nodes of the policy, which are empty for the default and local modes.
+    pub nodes: NodeSet,
+}
+
+/// The NUMA memory policy and node placement of a memory mapping of a process.
+///
+/// Page counts only include pages which are present in memory. Counts which the kernel omits
+/// because they are zero are 0.
+///
+/// See `numa(7)` for format details.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "export", derive(Serialize))]
+pub struct NumaMap {
+    /// The start address of the mapping.
+    pub start: u64,
+    /// The memory policy of the mapping.
+    pub policy: MemoryPolicy,
+    /// The mapped file, if the mapping is backed by a file.
+    pub file: Option<PathBuf>,
+    /// Whether the mapping is the process heap.
+    pub heap: bool,
+    /// Whether the mapping is the stack of the main thread.
+    pub stack: bool,
+    /// Whether the mapping is backed by huge pages.
+    pub huge: bool,
+    /// Number of anonymous pages.
+    pub anon: u64,
+    /// Number of dirty pages.
+    pub dirty: u64,
+    /// Number of pages mapped by the process, when it differs from the number of anonymous and
+    /// dirty pages.
+    pub mapped: u64,
+    /// The largest number of processes mapping a single page.
+    pub mapmax: u64,
+    /// Number of pages with an associated swap entry.
+    pub swapcache: u64,
+    /// Number of pages on the active list, if fewer than all pages.
+    pub active: Option<u64>,
+    /// Number of pages under writeback.
+    pub writeback: u64,
+    /// Number of pages on each node, in node order.
+    pub node_pages: Vec<(usize, u64)>,
+    /// Size of the pages used by the kernel to back the mapping, in kB.
+    pub kernel_page_size: u64,
+}
+
+impl NumaMap {
+    /// Returns the nodes with pages of the mapping.
+    pub fn nodes(&self) -> NodeSet {
+        self.node_pages.iter().map(|&(node, _)| node).collect()
+    }
+
+    /// Returns the number of pages of the mapping present in memory, on any node.
+    pub fn pages(&self) -> u64 {
+        self.node_pages.iter().map(|&(_, pages)| pages).sum()
+    }
+
+    /// Parses a line of a `/proc/[pid]/numa_maps` file, without the line terminator.
+    pub fn parse_line(mut input: &[u8]) -> Result<NumaMap> {
+        let start = parse_u64_hex(next_field(&mut input)?)?;
+
+        // Policy modes added in Linux 5.15 and 6.9 contain a space.
+        let input = input.strip_prefix(b" ").unwrap_or(input);
+        let mode_len = [&b"prefer (many)"[..], b"weighted interleave"]
+            .iter()
+            .find(|mode| input.starts_with(mode))
+            .map_or(0, |mode| mode.len());
+        let policy_len = input[mode_len..].iter()
+                                          .position(|&c| c == b' ')
+                                          .map_or(input.len(), |i| mode_len + i);
+        let policy = parse_policy(parse_str(&input[..policy_len])?)?;
+
+        let mut map = NumaMap {
+            start,
+            policy,
+            file: None,
+            heap: false,
+            stack: false,
+            huge: false,
+            anon: 0,
+            dirty: 0,
+            mapped: 0,
+            mapmax: 0,
+            swapcache: 0,
+            active: None,
+            writeback: 0,
+            node_pages: Vec::new(),
+            kernel_page_size: 0,
+        };
+        for field in input[policy_len..].split(|&c| c == b' ').filter(|field| !field.is_empty()) {
+            if let Some(path) = field.strip_prefix(b"file=") {
+                let path = unmangled_path(path, b"\n\t= ");
+                map.file = Some(PathBuf::from(OsStr::from_bytes(&path)));
+                continue;
+            }
+            let field = parse_str(field)?;
+            let (key, value) = match field.split_once('=') {
+                Some(pair) => pair,
+                None => {
+                    match field {
+                        "heap" => map.heap = true,
+                        "stack" => map.stack = true,
+                        "huge" => map.huge = true,
+                        _ => (),
+                    }
+                    continue;
+                },
+            };
+            match key {
+                "anon" => map.anon = parse(value)?,
+                "dirty" => map.dirty = parse(value)?,
+                "mapped" => map.mapped = parse(value)?,
+                "mapmax" => map.mapmax = parse(value)?,
+                "swapcache" => map.swapcache = parse(value)?,
+                "active" => map.active = Some(parse(value)?),
+                "writeback" => map.writeback = parse(value)?,
+                "kernelpagesize_kB" => map.kernel_page_size = parse(value)?,
+                _ => if let Some(node) = key.strip_prefix('N') {
+                    map.node_pages.push((parse(node)?, parse(value)?));
+                },
+            }
+        }
+        Ok(map)
+    }
+}
+
+/// Parses a memory policy in the `mode[=flags][:nodes]` format of the kernel's `mpol_to_str`.
+fn parse_policy(policy: &str) -> Result<MemoryPolicy> {
+    let (mode_flags, nodes) = match policy.split_once(':') {
+        Some((mode_flags, nodes)) => (mode_flags, NodeSet::parse_list(nodes)?),
+        None => (policy, NodeSet::new()),
+    };
+    let (mode, flags) = mode_flags.split_once('=').unwrap_or((mode_flags, ""));
+    let mode = match mode {
+        "" => return Err(invalid_input(format!("invalid memory policy: {:?}", policy))),
+        "default" => MemoryPolicyMode::Default,
+        "prefer" => MemoryPolicyMode::Preferred,
+        "prefer (many)" => MemoryPolicyMode::PreferredMany,
+        "bind" => MemoryPolicyMode::Bind,
+        "interleave" => MemoryPolicyMode::Interleave,
+        "weighted interleave" => MemoryPolicyMode::WeightedInterleave,
+        "local" => MemoryPolicyMode::Local,
+        other => MemoryPolicyMode::Other(other.to_owned()),
+    };
+    // Unrecognized flags are ignored.
+    let flags = flags.split('|').fold(MemoryPolicyFlags::empty(), |flags, flag| {
+        flags | match flag {
+            "static" => MemoryPolicyFlags::STATIC_NODES,
+            "relative" => MemoryPolicyFlags::RELATIVE_NODES,
+            "balancing" => MemoryPolicyFlags::NUMA_BALANCING,
+            _ => MemoryPolicyFlags::empty(),
+        }
+    });
+    Ok(MemoryPolicy { mode, flags, nodes })
+}
+
+/// Parses the provided numa_maps file.
+pub(crate) fn numa_maps_file(file: &mut File) -> Result<Vec<NumaMap>> {
+    let mut maps = Vec::new();
+    for (i, line) in BufReader::new(file).split(b'\n').enumerate() {
+        maps.push(NumaMap::parse_line(&line?).map_err(|error| with_line(error, i + 1))?);
+    }
+    Ok(maps)
+}
+
+/// Returns the NUMA memory policy and node placement of the memory mappings of the process with
+/// the provided pid.
+///
+/// The file only exists on kernels built with `CONFIG_NUMA`.
+pub fn numa_maps(pid: pid_t) -> Result<Vec<NumaMap>> {
+    parse_file(format!("/proc/{}/numa_maps", pid), numa_maps_file)
+}
+
+/// Returns the NUMA memory policy and node placement of the memory mappings of the current
+/// process.
+pub fn numa_maps_self() -> Result<Vec<NumaMap>> {
+    parse_file("/proc/self/numa_maps", numa_maps_file)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::ErrorKind;
+    use std::path::PathBuf;
+
+    use cpuset::NodeSet;
+    use pid::maps_self;
+    use super::{MemoryPolicyFlags, MemoryPolicyMode, NumaMap, numa_maps_self};
+
+    /// Test that the numa_maps file of the current process can be parsed.
+    #[test]
+    fn test_numa_maps_self() {
+        let numa_maps = match numa_maps_self() {
+            Err(ref error) if error.kind() == ErrorKind::NotFound => return,
+            numa_maps => numa_maps.unwrap(),
+        };
+        let maps = maps_self().unwrap();
+        assert!(numa_maps.iter().all(|numa_map| {
+            maps.iter().any(|map| map.range.start == numa_map.start)
+        }));
+        assert!(numa_maps.iter().any(|map| map.heap || map.stack));
+    }
+
+    #[test]
+    fn test_parse_line() {
+        let map = NumaMap::parse_line(b"56177fa34000 default file=/usr/bin/my\\040cat anon=1 \
+                                        dirty=1 active=0 N0=1 kernelpagesize_kB=4").unwrap();
+        assert_eq!(0x56177fa34000, map.start);
+        assert_eq!(MemoryPolicyMode::Default, map.policy.mode);
+        assert!(map.policy.nodes.is_empty());
+        assert_eq!(Some(PathBuf::from("/usr/bin/my cat")), map.file);
+        assert_eq!((1, 1, 0), (map.anon, map.dirty, map.mapped));
+        assert_eq!(Some(0), map.active);
+        assert_eq!(vec![(0, 1)], map.node_pages);
+        assert_eq!(4, map.kernel_page_size);
+
+        let map = NumaMap::parse_line(b"7ffd1e0ca000 bind=static|balancing:0-1 stack anon=6 \
+                                        dirty=6 N0=2 N1=4 kernelpagesize_kB=4").unwrap();
+        assert_eq!(MemoryPolicyMode::Bind, map.policy.mode);
+        assert_eq!(MemoryPolicyFlags::STATIC_NODES | MemoryPolicyFlags::NUMA_BALANCING,
+                   map.policy.flags);
+        assert_eq!(NodeSet::parse_list("0-1").unwrap(), map.policy.nodes);
+        assert!(map.stack && !map.heap);
+        assert_eq!(None, map.file);
+        assert_eq!(6, map.pages());
+        assert_eq!(map.policy.nodes, map.nodes());
+
+        let map = NumaMap::parse_line(b"7f0000000000 prefer (many):1,3 huge N3=1 \
+                                        kernelpagesize_kB=2048").unwrap();
+        assert_eq!(MemoryPolicyMode::PreferredMany, map.policy.mode);
+        assert_eq!("1,3", map.policy.nodes.to_string());
+        assert!(map.huge);
+
+        let map = NumaMap::parse_line(b"7f0000000000 weighted interleave:0-3").unwrap();
+        assert_eq!(MemoryPolicyMode::WeightedInterleave, map.policy.mode);
+        assert_eq!(4, map.policy.nodes.len());
+
+        NumaMap::parse_line(b"7f0000000000 bind:1-0").unwrap_err();
+    }
+}
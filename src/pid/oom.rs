@@ -0,0 +1,123 @@
+//! Out-of-memory killer scoring from `/proc/[pid]/oom_score`, `/proc/[pid]/oom_score_adj`, and
+//! `/proc/[pid]/oom_adj`.
+
+use std::fs::{File, OpenOptions};
+use std::io::{Result, Write};
+
+use libc::pid_t;
+
+use parsers::{parse, parse_file, parse_line, read_to_end};
+
+/// The minimum value of `oom_score_adj`, which disables OOM killing for the process.
+pub const OOM_SCORE_ADJ_MIN: i32 = -1000;
+
+/// The maximum value of `oom_score_adj`, which makes the process the preferred OOM victim.
+pub const OOM_SCORE_ADJ_MAX: i32 = 1000;
+
+/// Parses the oom_score file format.
+pub(crate) fn parse_oom_score(input: &[u8]) -> Result<u32> {
+    parse(parse_line(input)?)
+}
+
+/// Parses the oom_score_adj and oom_adj file format.
+pub(crate) fn parse_oom_adj(input: &[u8]) -> Result<i32> {
+    parse(parse_line(input)?)
+}
+
+/// Parses the provided oom_score file.
+pub(crate) fn oom_score_file(file: &mut File) -> Result<u32> {
+    let mut buf = [0; 32]; // An oom_score file is at most 5 bytes.
+    parse_oom_score(&read_to_end(file, &mut buf)?)
+}
+
+/// Parses the provided oom_score_adj or oom_adj file.
+pub(crate) fn oom_adj_file(file: &mut File) -> Result<i32> {
+    let mut buf = [0; 32]; // An oom_score_adj file is at most 6 bytes.
+    parse_oom_adj(&read_to_end(file, &mut buf)?)
+}
+
+/// Returns the current OOM killer badness score of the process with the provided pid.
+///
+/// The process with the highest score is selected by the OOM killer when the system runs out of
+/// memory. The score includes the `oom_score_adj` adjustment.
+pub fn oom_score(pid: pid_t) -> Result<u32> {
+    parse_file(format!("/proc/{}/oom_score", pid), oom_score_file)
+}
+
+/// Returns the current OOM killer badness score of the current process.
+pub fn oom_score_self() -> Result<u32> {
+    parse_file("/proc/self/oom_score", oom_score_file)
+}
+
+/// Returns the OOM score adjustment of the process with the provided pid.
+///
+/// The value is in the range `OOM_SCORE_ADJ_MIN` to `OOM_SCORE_ADJ_MAX` (since Linux 2.6.36).
+pub fn oom_score_adj(pid: pid_t) -> Result<i32> {
+    parse_file(format!("/proc/{}/oom_score_adj", pid), oom_adj_file)
+}
+
+/// Returns the OOM score adjustment of the current process.
+pub fn oom_score_adj_self() -> Result<i32> {
+    parse_file("/proc/self/oom_score_adj", oom_adj_file)
+}
+
+/// Returns the legacy OOM adjustment of the process with the provided pid.
+///
+/// The value is in the range -17 to 15, where -17 disables OOM killing. Deprecated in favor of
+/// `oom_score_adj` since Linux 2.6.36.
+pub fn oom_adj(pid: pid_t) -> Result<i32> {
+    parse_file(format!("/proc/{}/oom_adj", pid), oom_adj_file)
+}
+
+/// Writes the provided adjustment to an oom_score_adj file, clamping it to the valid range.
+fn set_oom_score_adj_path(path: &str, value: i32) -> Result<()> {
+    let value = value.clamp(OOM_SCORE_ADJ_MIN, OOM_SCORE_ADJ_MAX);
+    let mut file = OpenOptions::new().write(true).open(path)?;
+    file.write_all(format!("{}\n", value).as_bytes())
+}
+
+/// Sets the OOM score adjustment of the process with the provided pid.
+///
+/// The value is clamped to the range `OOM_SCORE_ADJ_MIN` to `OOM_SCORE_ADJ_MAX`. Lowering the
+/// adjustment below its previous minimum requires `CAP_SYS_RESOURCE`.
+pub fn set_oom_score_adj(pid: pid_t, value: i32) -> Result<()> {
+    set_oom_score_adj_path(&format!("/proc/{}/oom_score_adj", pid), value)
+}
+
+/// Sets the OOM score adjustment of the current process.
+///
+/// See `set_oom_score_adj`.
+pub fn set_oom_score_adj_self(value: i32) -> Result<()> {
+    set_oom_score_adj_path("/proc/self/oom_score_adj", value)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{oom_adj, oom_score, oom_score_adj, oom_score_adj_self, oom_score_self,
+                parse_oom_adj, parse_oom_score, set_oom_score_adj_self};
+
+    /// Test that the system oom files can be parsed.
+    #[test]
+    fn test_oom() {
+        oom_score_self().unwrap();
+        oom_score(1).unwrap();
+        oom_score_adj(1).unwrap();
+        oom_adj(1).unwrap();
+    }
+
+    /// Test that writing the current adjustment back is accepted.
+    #[test]
+    fn test_set_oom_score_adj_self() {
+        let adj = oom_score_adj_self().unwrap();
+        set_oom_score_adj_self(adj).unwrap();
+        assert_eq!(adj, oom_score_adj_self().unwrap());
+    }
+
+    #[test]
+    fn test_parse_oom() {
+        assert_eq!(667, parse_oom_score(b"667\n").unwrap());
+        assert_eq!(-1000, parse_oom_adj(b"-1000\n").unwrap());
+        assert_eq!(0, parse_oom_adj(b"0\n").unwrap());
+        assert_eq!(-17, parse_oom_adj(b"-17\n").unwrap());
+    }
+}
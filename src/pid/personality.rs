@@ -0,0 +1,112 @@
+//! Process execution domain from `/proc/[pid]/personality`.
+
+use std::fs::File;
+use std::io::Result;
+
+use libc::pid_t;
+
+use parsers::{parse_file, parse_line, parse_u32_hex, read_to_end};
+
+/// Mask of the execution domain portion of a personality value.
+const PER_MASK: u32 = 0xff;
+
+bitflags! {
+    /// Personality flags which modify the behavior of the execution domain.
+    ///
+    /// See `personality(2)` and `Linux/include/uapi/linux/personality.h`.
+    #[derive(Default)]
+    #[cfg_attr(feature = "export", derive(Serialize))]
+    pub struct PersonalityFlags: u32 {
+        /// Report a 2.6.40+ kernel version as 2.6.(40 + x) (since Linux 3.1).
+        const UNAME26 = 0x0020000;
+        /// Disable address-space-layout randomization (since Linux 2.6.12).
+        const ADDR_NO_RANDOMIZE = 0x0040000;
+        /// Function pointers point to descriptors (since Linux 2.6.11).
+        const FDPIC_FUNCPTRS = 0x0080000;
+        /// Map page 0 as read-only (since Linux 2.4.0).
+        const MMAP_PAGE_ZERO = 0x0100000;
+        /// Provide legacy virtual address space layout (since Linux 2.6.9).
+        const ADDR_COMPAT_LAYOUT = 0x0200000;
+        /// `PROT_READ` implies `PROT_EXEC` for `mmap(2)` (since Linux 2.6.8).
+        const READ_IMPLIES_EXEC = 0x0400000;
+        /// Limit the address space to 32 bits.
+        const ADDR_LIMIT_32BIT = 0x0800000;
+        /// No effect.
+        const SHORT_INODE = 0x1000000;
+        /// No effect.
+        const WHOLE_SECONDS = 0x2000000;
+        /// `select(2)` and similar calls do not modify the returned timeout.
+        const STICKY_TIMEOUTS = 0x4000000;
+        /// Limit the address space to 3 GB (since Linux 2.4.0).
+        const ADDR_LIMIT_3GB = 0x8000000;
+    }
+}
+
+/// Process execution domain information.
+///
+/// See `personality(2)`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "export", derive(Serialize))]
+pub struct Personality {
+    /// The execution domain, e.g. `PER_LINUX` (0) or `PER_LINUX32` (8).
+    pub domain: u32,
+    /// The personality flags.
+    pub flags: PersonalityFlags,
+}
+
+/// Parses the personality file format.
+fn parse_personality(input: &[u8]) -> Result<Personality> {
+    let value = parse_u32_hex(parse_line(input)?)?;
+    Ok(Personality {
+        domain: value & PER_MASK,
+        flags: PersonalityFlags::from_bits_truncate(value),
+    })
+}
+
+impl Personality {
+    /// Parses the contents of a `/proc/[pid]/personality` file.
+    pub fn parse(input: &[u8]) -> Result<Personality> {
+        parse_personality(input)
+    }
+}
+
+/// Parses the provided personality file.
+pub(crate) fn personality_file(file: &mut File) -> Result<Personality> {
+    let mut buf = [0; 32]; // A personality file is 9 bytes.
+    Personality::parse(&read_to_end(file, &mut buf)?)
+}
+
+/// Returns the execution domain of the process with the provided pid.
+///
+/// Reading the personality of another process requires ptrace access to it.
+pub fn personality(pid: pid_t) -> Result<Personality> {
+    parse_file(format!("/proc/{}/personality", pid), personality_file)
+}
+
+/// Returns the execution domain of the current process.
+pub fn personality_self() -> Result<Personality> {
+    parse_file("/proc/self/personality", personality_file)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{PersonalityFlags, parse_personality, personality_self};
+
+    /// Test that the personality file of the current process can be parsed.
+    #[test]
+    fn test_personality() {
+        personality_self().unwrap();
+    }
+
+    #[test]
+    fn test_parse_personality() {
+        let personality = parse_personality(b"00000000\n").unwrap();
+        assert_eq!(0, personality.domain);
+        assert!(personality.flags.is_empty());
+
+        let personality = parse_personality(b"00440008\n").unwrap();
+        assert_eq!(8, personality.domain);
+        assert_eq!(PersonalityFlags::ADDR_NO_RANDOMIZE | PersonalityFlags::READ_IMPLIES_EXEC,
+                   personality.flags);
+    }
+}
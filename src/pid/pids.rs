@@ -0,0 +1,57 @@
+//! Enumeration of the processes in `/proc`.
+
+use std::fs::{self, ReadDir};
+use std::io::{Error, ErrorKind, Result};
+
+use libc::{ESRCH, pid_t};
+
+/// An iterator over the pids of all processes, in no particular order.
+///
+/// Processes may start or exit while the iterator is in use; a process which exits after its pid
+/// is returned is not detected.
+#[derive(Debug)]
+pub struct Pids {
+    dir: ReadDir,
+}
+
+impl Iterator for Pids {
+    type Item = Result<pid_t>;
+
+    fn next(&mut self) -> Option<Result<pid_t>> {
+        for entry in &mut self.dir {
+            let entry = match entry {
+                Ok(entry) => entry,
+                Err(error) => return Some(Err(error)),
+            };
+            if let Some(pid) = entry.file_name().to_str().and_then(|name| name.parse().ok()) {
+                return Some(Ok(pid));
+            }
+        }
+        None
+    }
+}
+
+/// Returns an iterator over the pids of all processes.
+pub fn pids() -> Result<Pids> {
+    Ok(Pids { dir: fs::read_dir("/proc")? })
+}
+
+/// Returns `true` if the error from reading a process's files indicates that the process has
+/// exited.
+pub(crate) fn is_exited(error: &Error) -> bool {
+    error.kind() == ErrorKind::NotFound || error.raw_os_error() == Some(ESRCH)
+}
+
+#[cfg(test)]
+mod tests {
+    use libc;
+
+    use super::pids;
+
+    #[test]
+    fn test_pids() {
+        let pids = pids().unwrap().collect::<Result<Vec<_>, _>>().unwrap();
+        assert!(pids.contains(&1));
+        assert!(pids.contains(&unsafe { libc::getpid() }));
+    }
+}
@@ -0,0 +1,344 @@
+//! A handle to a single process, from `/proc/[pid]`.
+
+use std::ffi::{CString, OsStr, OsString};
+use std::fs::{File, OpenOptions};
+use std::io::{Error, ErrorKind, Result};
+use std::os::unix::ffi::OsStrExt;
+use std::os::unix::fs::OpenOptionsExt;
+use std::os::unix::io::{AsRawFd, FromRawFd, RawFd};
+use std::path::PathBuf;
+use std::time::Duration;
+
+use libc::{self, O_CLOEXEC, O_DIRECTORY, O_RDONLY, PATH_MAX, c_char, pid_t};
+
+use parsers::with_path;
+use pid::attr::attr_current_file;
+use pid::availability::{Availability, classify_cmdline, classify_kernel_thread,
+                         classify_link};
+use pid::cgroup::{Cgroup, cgroup_file};
+use pid::cmdline::cmdline_file;
+use pid::coredump_filter::{CoredumpFilter, coredump_filter_file};
+use pid::environ::{Environ, environ_file};
+use pid::fdinfo::{FdInfo, fdinfo_file};
+use pid::io::{Io, if_permitted, io_file};
+use pid::limits::{Limits, limits_file};
+use pid::maps::{MemoryMap, MemoryMaps, maps_file};
+use pid::mem::Mem;
+use pid::memory_summary::{MemorySummary, summarize};
+use pid::mountinfo::{Mountinfo, mountinfo_file};
+use pid::mounts::{Mount, mounts_file};
+use pid::ns::{Namespaces, namespaces_dir};
+use pid::oom::{oom_adj_file, oom_score_file};
+use pid::personality::{Personality, personality_file};
+use pid::ps::PsRow;
+use pid::smaps::{SmapsEntries, SmapsEntry, smaps_file, smaps_rollup_file};
+use pid::stat::{Stat, stat_file};
+use pid::statm::{Statm, statm_file};
+use pid::status::{Status, status_file};
+use pid::timerslack_ns::timerslack_ns_file;
+
+/// Opens the file with the provided name relative to the provided directory.
+pub(crate) fn open_at(dir: &File, name: &str) -> Result<File> {
+    let name = CString::new(name).map_err(|e| Error::new(ErrorKind::InvalidInput, e))?;
+    let fd = unsafe { libc::openat(dir.as_raw_fd(), name.as_ptr(), O_RDONLY | O_CLOEXEC) };
+    if fd < 0 {
+        return Err(Error::last_os_error());
+    }
+    Ok(unsafe { File::from_raw_fd(fd) })
+}
+
+/// A handle to a process.
+///
+/// The handle holds the `/proc/[pid]` directory of the process open, and all files are opened
+/// relative to it with `openat(2)`. Once the process exits the accessors fail instead of reading
+/// the files of an unrelated process which has been assigned the same pid.
+#[derive(Debug)]
+pub struct Process {
+    pid: pid_t,
+    dir: File,
+}
+
+impl Process {
+    /// Opens a handle to the process with the provided pid.
+    pub fn new(pid: pid_t) -> Result<Process> {
+        Process::open_dir(pid, &format!("/proc/{}", pid))
+    }
+
+    /// Opens a handle to the current process.
+    pub fn myself() -> Result<Process> {
+        Process::open_dir(unsafe { libc::getpid() }, "/proc/self")
+    }
+
+    fn open_dir(pid: pid_t, path: &str) -> Result<Process> {
+        let dir = OpenOptions::new().read(true)
+                                    .custom_flags(O_DIRECTORY | O_CLOEXEC)
+                                    .open(path)?;
+        Ok(Process { pid, dir })
+    }
+
+    /// Returns the pid of the process.
+    pub fn pid(&self) -> pid_t {
+        self.pid
+    }
+
+    /// Opens the file with the provided name relative to the process directory.
+    fn open(&self, name: &str) -> Result<File> {
+        open_at(&self.dir, name)
+    }
+
+    /// Returns the path of the file with the provided name in the process directory, for error
+    /// messages.
+    pub(crate) fn path(&self, name: &str) -> PathBuf {
+        PathBuf::from(format!("/proc/{}/{}", self.pid, name))
+    }
+
+    /// Parses the file with the provided name relative to the process directory, adding the path
+    /// of the file to parse errors.
+    fn parse<T, F>(&self, name: &str, parse: F) -> Result<T>
+    where F: FnOnce(&mut File) -> Result<T> {
+        parse(&mut self.open(name)?).map_err(|error| with_path(error, &self.path(name)))
+    }
+
+    /// Returns a duplicate of the process directory file descriptor, for reading files of the
+    /// process on another thread.
+    #[cfg(feature = "async")]
+    pub(crate) fn try_clone_dir(&self) -> Result<File> {
+        self.dir.try_clone()
+    }
+
+    /// Reads the symbolic link with the provided name relative to the process directory.
+    fn read_link(&self, name: &str) -> Result<PathBuf> {
+        let name = CString::new(name).map_err(|e| Error::new(ErrorKind::InvalidInput, e))?;
+        let mut buf = vec![0u8; PATH_MAX as usize];
+        let len = unsafe {
+            libc::readlinkat(self.dir.as_raw_fd(),
+                             name.as_ptr(),
+                             buf.as_mut_ptr() as *mut c_char,
+                             buf.len())
+        };
+        if len < 0 {
+            return Err(Error::last_os_error());
+        }
+        buf.truncate(len as usize);
+        Ok(PathBuf::from(OsStr::from_bytes(&buf)))
+    }
+
+    /// Returns the security context (e.g. the SELinux or AppArmor label) of the process.
+    ///
+    /// Returns `None` if no Linux Security Module providing process labels is active.
+    pub fn attr_current(&self) -> Result<Option<String>> {
+        self.parse("attr/current", attr_current_file)
+    }
+
+    /// Returns the control groups of the process.
+    pub fn cgroup(&self) -> Result<Vec<Cgroup>> {
+        self.parse("cgroup", cgroup_file)
+    }
+
+    /// Returns the command line arguments of the process.
+    pub fn cmdline(&self) -> Result<Availability<Vec<OsString>>> {
+        classify_cmdline(cmdline_file(&mut self.open("cmdline")?)?, || self.stat())
+    }
+
+    /// Returns the core dump filter of the process.
+    pub fn coredump_filter(&self) -> Result<CoredumpFilter> {
+        self.parse("coredump_filter", coredump_filter_file)
+    }
+
+    /// Returns the path of the current working directory of the process.
+    pub fn cwd(&self) -> Result<Availability<PathBuf>> {
+        classify_link(self.read_link("cwd"), || self.stat())
+    }
+
+    /// Returns the environment of the process.
+    pub fn environ(&self) -> Result<Environ> {
+        environ_file(&mut self.open("environ")?)
+    }
+
+    /// Returns the path of the executable of the process.
+    pub fn exe(&self) -> Result<Availability<PathBuf>> {
+        classify_link(self.read_link("exe"), || self.stat())
+    }
+
+    /// Returns information about the open file descriptor of the process.
+    pub fn fdinfo(&self, fd: RawFd) -> Result<FdInfo> {
+        self.parse(&format!("fdinfo/{}", fd), fdinfo_file)
+    }
+
+    /// Returns I/O statistics of the process.
+    pub fn io(&self) -> Result<Io> {
+        self.parse("io", io_file)
+    }
+
+    /// Returns I/O statistics of the process, or `None` if the caller lacks ptrace access to it.
+    pub fn io_if_permitted(&self) -> Result<Option<Io>> {
+        if_permitted(self.io())
+    }
+
+    /// Returns whether the process is a kernel thread.
+    pub fn is_kernel_thread(&self) -> Result<bool> {
+        classify_kernel_thread(&self.stat()?, || cmdline_file(&mut self.open("cmdline")?))
+    }
+
+    /// Returns resource limit information of the process.
+    pub fn limits(&self) -> Result<Limits> {
+        self.parse("limits", limits_file)
+    }
+
+    /// Returns the memory mappings of the process.
+    pub fn maps(&self) -> Result<Vec<MemoryMap>> {
+        self.parse("maps", maps_file)
+    }
+
+    /// Returns an iterator over the memory mappings of the process.
+    pub fn maps_iter(&self) -> Result<MemoryMaps> {
+        Ok(MemoryMaps::new(self.open("maps")?, self.path("maps")))
+    }
+
+    /// Opens the memory of the process.
+    pub fn mem(&self) -> Result<Mem> {
+        Mem::from_files(self.open("mem")?, self.open("maps")?)
+    }
+
+    /// Returns a summary of the memory usage of the process, from the cheapest available source.
+    pub fn memory_summary(&self) -> Result<MemorySummary> {
+        summarize(|| self.smaps_rollup(), || self.smaps(), || self.statm())
+    }
+
+    /// Returns mounts information of the process.
+    pub fn mountinfo(&self) -> Result<Vec<Mountinfo>> {
+        self.parse("mountinfo", mountinfo_file)
+    }
+
+    /// Returns the mount table of the process, as seen from its mount namespace.
+    pub fn mounts(&self) -> Result<Vec<Mount>> {
+        self.parse("mounts", mounts_file)
+    }
+
+    /// Returns the namespaces of the process.
+    ///
+    /// Reading the namespaces of another process requires ptrace access to it.
+    pub fn namespaces(&self) -> Result<Namespaces> {
+        namespaces_dir(&self.path("ns"),
+                       |name| self.read_link(&format!("ns/{}", name)),
+                       || self.open("ns").is_ok())
+    }
+
+    /// Returns the current OOM killer badness score of the process.
+    pub fn oom_score(&self) -> Result<u32> {
+        self.parse("oom_score", oom_score_file)
+    }
+
+    /// Returns the OOM score adjustment of the process.
+    pub fn oom_score_adj(&self) -> Result<i32> {
+        self.parse("oom_score_adj", oom_adj_file)
+    }
+
+    /// Returns the execution domain of the process.
+    pub fn personality(&self) -> Result<Personality> {
+        self.parse("personality", personality_file)
+    }
+
+    /// Returns the `ps(1)` style row of the process.
+    pub fn ps_row(&self) -> Result<PsRow> {
+        Ok(PsRow::new(self.stat()?, Some(self.status()?)))
+    }
+
+    /// Returns the memory usage of each memory mapping of the process.
+    pub fn smaps(&self) -> Result<Vec<SmapsEntry>> {
+        self.parse("smaps", smaps_file)
+    }
+
+    /// Returns an iterator over the memory usage of each memory mapping of the process.
+    pub fn smaps_iter(&self) -> Result<SmapsEntries> {
+        Ok(SmapsEntries::new(self.open("smaps")?, self.path("smaps")))
+    }
+
+    /// Returns the memory usage of all memory mappings of the process, summed by the kernel
+    /// (since Linux 4.14).
+    pub fn smaps_rollup(&self) -> Result<SmapsEntry> {
+        self.parse("smaps_rollup", smaps_rollup_file)
+    }
+
+    /// Returns status information of the process.
+    pub fn stat(&self) -> Result<Stat> {
+        self.parse("stat", stat_file)
+    }
+
+    /// Returns memory status information of the process.
+    pub fn statm(&self) -> Result<Statm> {
+        self.parse("statm", statm_file)
+    }
+
+    /// Returns detailed status information of the process.
+    pub fn status(&self) -> Result<Status> {
+        self.parse("status", status_file)
+    }
+
+    /// Returns the current timer slack of the process (since Linux 4.6).
+    pub fn timerslack_ns(&self) -> Result<Duration> {
+        self.parse("timerslack_ns", timerslack_ns_file)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::env;
+    use std::fs::File;
+    use std::os::unix::io::AsRawFd;
+    use std::process::Command;
+
+    use libc;
+
+    use pid::{attr_current_self, fdinfo_self, namespaces_self};
+    use super::Process;
+
+    /// Test that the accessors of the current process can be read.
+    #[test]
+    fn test_myself() {
+        let process = Process::myself().unwrap();
+        assert_eq!(unsafe { libc::getpid() }, process.pid());
+        assert_eq!(process.pid(), process.stat().unwrap().pid);
+        assert_eq!(process.pid(), process.status().unwrap().pid);
+        assert_eq!(Some(env::current_dir().unwrap()), process.cwd().unwrap().available());
+        assert_eq!(Some(env::current_exe().unwrap()), process.exe().unwrap().available());
+        let file = File::open("/proc/self/stat").unwrap();
+        assert_eq!(process.fdinfo(file.as_raw_fd()).unwrap(),
+                   fdinfo_self(file.as_raw_fd()).unwrap());
+        assert_eq!(Some(env::args_os().collect::<Vec<_>>()),
+                   process.cmdline().unwrap().available());
+        process.statm().unwrap();
+        process.io().unwrap();
+        assert!(process.io_if_permitted().unwrap().is_some());
+        assert!(!process.is_kernel_thread().unwrap());
+        process.limits().unwrap();
+        process.mountinfo().unwrap();
+        process.mounts().unwrap();
+        assert_eq!(namespaces_self().unwrap(), process.namespaces().unwrap());
+        assert_eq!(attr_current_self().unwrap(), process.attr_current().unwrap());
+        process.oom_score().unwrap();
+        process.oom_score_adj().unwrap();
+        process.personality().unwrap();
+        assert_eq!(process.stat().unwrap().pid, process.ps_row().unwrap().stat.pid);
+        process.coredump_filter().unwrap();
+        process.smaps().unwrap();
+        assert!(process.smaps_iter().unwrap().all(|entry| entry.is_ok()));
+        process.smaps_rollup().unwrap();
+        process.memory_summary().unwrap();
+    }
+
+    #[test]
+    fn test_new() {
+        let process = Process::new(1).unwrap();
+        assert_eq!(1, process.stat().unwrap().pid);
+    }
+
+    /// Test that a handle does not read the files of another process after its process exits.
+    #[test]
+    fn test_exited() {
+        let mut child = Command::new("true").spawn().unwrap();
+        let process = Process::new(child.id() as libc::pid_t).unwrap();
+        child.wait().unwrap();
+        assert!(process.stat().is_err());
+    }
+}
@@ -0,0 +1,227 @@
+//! `ps(1)` style formatting of processes, for debugging output.
+
+use std::fmt;
+use std::io::Result;
+use std::time::Duration;
+
+use libc::pid_t;
+
+use pid::ProcessState;
+use pid::pids::{is_exited, pids};
+use pid::process::Process;
+use pid::stat::Stat;
+use pid::status::Status;
+use sysconf::{page_size, ticks_to_duration};
+
+/// The header of the rows of a `PsTable`.
+const HEADER: &str = "    PID    PPID   UID STAT TT           TIME      RSS COMMAND";
+
+/// A process formatted as a `ps(1)` style row by its `Display` implementation.
+///
+/// The columns are the pid, the parent pid, the effective user ID, the state with the BSD style
+/// flags of `ps`, the controlling terminal, the CPU time, the resident set size in kB, and the
+/// command name. Columns which need the status file are shown as `-` without it.
+#[derive(Debug, PartialEq, Eq, Hash)]
+pub struct PsRow {
+    /// The stat file of the process.
+    pub stat: Stat,
+    /// The status file of the process, if read.
+    pub status: Option<Status>,
+}
+
+impl PsRow {
+    /// Returns a row formatting the provided stat and status files of a process.
+    pub fn new(stat: Stat, status: Option<Status>) -> PsRow {
+        PsRow { stat, status }
+    }
+
+    /// Reads the row of the process with the provided pid.
+    ///
+    /// Both files are read through a `Process` handle, so that they belong to the same process
+    /// even if the pid is reused in the meantime.
+    pub fn read(pid: pid_t) -> Result<PsRow> {
+        Process::new(pid)?.ps_row()
+    }
+
+    /// Returns the state of the process in the `STAT` format of `ps`, such as `Ss+`.
+    ///
+    /// The state letter is followed by `<` for a raised priority, `N` for a lowered priority, `L`
+    /// if pages are locked in memory, `s` for a session leader, `l` if multi-threaded, and `+`
+    /// if in the foreground process group of its terminal.
+    pub fn state(&self) -> String {
+        let stat = &self.stat;
+        let mut state = stat.state.as_char().to_string();
+        if stat.nice < 0 {
+            state.push('<');
+        } else if stat.nice > 0 {
+            state.push('N');
+        }
+        if self.status.as_ref().is_some_and(|status| status.vm_locked > 0) {
+            state.push('L');
+        }
+        if stat.session == stat.pid {
+            state.push('s');
+        }
+        if stat.num_threads > 1 {
+            state.push('l');
+        }
+        if stat.tty_pgrp == stat.pgrp {
+            state.push('+');
+        }
+        state
+    }
+}
+
+impl fmt::Display for PsRow {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let stat = &self.stat;
+        let uid = match self.status {
+            Some(ref status) => status.uid_effective.to_string(),
+            None => "-".to_owned(),
+        };
+        let tty = tty_name(stat.tty_nr).unwrap_or_else(|| "?".to_owned());
+        let time = format_cpu_time(ticks_to_duration(stat.utime + stat.stime));
+        let rss = stat.rss * page_size() as u64 / 1024;
+        write!(f, "{:>7} {:>7} {:>5} {:<4} {:<8} {:>8} {:>8} ",
+               stat.pid, stat.ppid, uid, self.state(), tty, time, rss)?;
+        if stat.is_kernel_thread() {
+            write!(f, "[{}]", stat.command)
+        } else if stat.state == ProcessState::Zombie {
+            write!(f, "{} <defunct>", stat.command)
+        } else {
+            f.write_str(&stat.command)
+        }
+    }
+}
+
+/// A table of processes formatted by its `Display` implementation as a header row followed by a
+/// `PsRow` per process.
+#[derive(Debug, Default, PartialEq, Eq, Hash)]
+pub struct PsTable {
+    /// The rows of the table.
+    pub rows: Vec<PsRow>,
+}
+
+impl fmt::Display for PsTable {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "{}", HEADER)?;
+        for row in &self.rows {
+            writeln!(f, "{}", row)?;
+        }
+        Ok(())
+    }
+}
+
+/// Returns a table of all processes, in pid order.
+///
+/// Processes which exit while the table is read are left out.
+pub fn ps() -> Result<PsTable> {
+    let mut rows = Vec::new();
+    for pid in pids()? {
+        match Process::new(pid?).and_then(|process| process.ps_row()) {
+            Ok(row) => rows.push(row),
+            Err(ref error) if is_exited(error) => continue,
+            Err(error) => return Err(error),
+        }
+    }
+    rows.sort_by_key(|row| row.stat.pid);
+    Ok(PsTable { rows })
+}
+
+/// Returns the name of the terminal with the provided device number, relative to `/dev`, such as
+/// `pts/3`, or `None` if the device number is 0.
+///
+/// Terminals which are not virtual consoles, serial ports or pseudoterminals are named by their
+/// device numbers, such as `204:64`.
+pub fn tty_name(tty_nr: pid_t) -> Option<String> {
+    if tty_nr == 0 {
+        return None;
+    }
+    let tty_nr = tty_nr as u32;
+    let major = (tty_nr >> 8) & 0xfff;
+    let minor = (tty_nr & 0xff) | ((tty_nr >> 12) & 0xfff00);
+    let name = match major {
+        4 if minor < 64 => format!("tty{}", minor),
+        4 => format!("ttyS{}", minor - 64),
+        5 if minor == 1 => "console".to_owned(),
+        136..=143 => format!("pts/{}", (major - 136) * 256 + minor),
+        _ => format!("{}:{}", major, minor),
+    };
+    Some(name)
+}
+
+/// Formats a CPU time in the `[DD-]HH:MM:SS` format of the `TIME` column of `ps`.
+pub fn format_cpu_time(time: Duration) -> String {
+    let secs = time.as_secs();
+    let (days, hours, minutes, secs) = (secs / 86400, secs / 3600 % 24, secs / 60 % 60, secs % 60);
+    if days > 0 {
+        format!("{}-{:02}:{:02}:{:02}", days, hours, minutes, secs)
+    } else {
+        format!("{:02}:{:02}:{:02}", hours, minutes, secs)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use libc;
+
+    use pid::{ProcessState, Stat};
+    use super::{HEADER, PsRow, format_cpu_time, ps, tty_name};
+
+    #[test]
+    fn test_tty_name() {
+        assert_eq!(None, tty_name(0));
+        assert_eq!(Some("pts/3".to_owned()), tty_name(34819));
+        assert_eq!(Some("pts/300".to_owned()), tty_name((137 << 8) | 44));
+        assert_eq!(Some("tty1".to_owned()), tty_name(1025));
+        assert_eq!(Some("ttyS0".to_owned()), tty_name(1088));
+        assert_eq!(Some("204:64".to_owned()), tty_name((204 << 8) | 64));
+        // Minor numbers above 255 are split around the major number.
+        assert_eq!(Some("pts/300".to_owned()), tty_name((136 << 8) | 44 | (1 << 20)));
+    }
+
+    #[test]
+    fn test_format_cpu_time() {
+        assert_eq!("00:00:00", format_cpu_time(Duration::from_millis(900)));
+        assert_eq!("01:02:03", format_cpu_time(Duration::from_secs(3723)));
+        assert_eq!("2-00:00:05", format_cpu_time(Duration::from_secs(2 * 86400 + 5)));
+    }
+
+    #[test]
+    fn test_row() {
+        let stat = Stat {
+            pid: 42,
+            command: "bash".to_owned(),
+            state: ProcessState::Sleeping,
+            ppid: 1,
+            pgrp: 42,
+            session: 42,
+            tty_nr: 34819,
+            tty_pgrp: 42,
+            num_threads: 1,
+            ..Default::default()
+        };
+        let row = PsRow::new(stat, None);
+        assert_eq!("Ss+", row.state());
+        let line = row.to_string();
+        assert!(line.ends_with(" bash"));
+        assert_eq!(vec!["42", "1", "-", "Ss+", "pts/3", "00:00:00", "0", "bash"],
+                   line.split_whitespace().collect::<Vec<_>>());
+        // The columns line up with the header.
+        assert_eq!(HEADER.find("COMMAND"), line.find("bash"));
+        assert_eq!(HEADER.find("TT"), line.find("pts/3"));
+    }
+
+    #[test]
+    fn test_ps() {
+        let table = ps().unwrap();
+        let pid = unsafe { libc::getpid() };
+        assert!(table.rows.windows(2).all(|pair| pair[0].stat.pid < pair[1].stat.pid));
+        assert!(table.rows.iter().any(|row| row.stat.pid == pid));
+        let output = table.to_string();
+        assert_eq!(Some(HEADER), output.lines().next());
+        assert_eq!(table.rows.len() + 1, output.lines().count());
+    }
+}
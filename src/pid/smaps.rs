@@ -0,0 +1,842 @@
+//! Memory usage of memory mappings from `/proc/[pid]/smaps`.
+
+use std::fmt;
+use std::iter::FromIterator;
+use std::fs::File;
+use std::io::{BufReader, Read, Result};
+use std::mem;
+use std::path::PathBuf;
+
+use libc::pid_t;
+
+use parsers::{invalid_input, parse, parse_bit, parse_file, parse_kb, parse_str, split_label,
+              with_line, with_path};
+use pid::maps::{MemoryMap, MemoryMapKind, read_line};
+
+bitflags! {
+    /// The kernel flags of a memory mapping, from the `VmFlags` line of `/proc/[pid]/smaps`.
+    ///
+    /// See `proc(5)` and `Linux/fs/proc/task_mmu.c`.
+    #[derive(Default)]
+    #[cfg_attr(feature = "export", derive(Serialize))]
+    pub struct VmFlags: u64 {
+        /// Readable (`rd`).
+        const READ = 1 << 0;
+        /// Writable (`wr`).
+        const WRITE = 1 << 1;
+        /// Executable (`ex`).
+        const EXEC = 1 << 2;
+        /// Shared (`sh`).
+        const SHARED = 1 << 3;
+        /// May be made readable (`mr`).
+        const MAY_READ = 1 << 4;
+        /// May be made writable (`mw`).
+        const MAY_WRITE = 1 << 5;
+        /// May be made executable (`me`).
+        const MAY_EXEC = 1 << 6;
+        /// May be made shared (`ms`).
+        const MAY_SHARE = 1 << 7;
+        /// A stack segment which grows down (`gd`).
+        const GROWS_DOWN = 1 << 8;
+        /// A pure PFN range (`pf`).
+        const PFN_MAP = 1 << 9;
+        /// Writes to the mapped file are disabled (`dw`, before Linux 6.1).
+        const DENY_WRITE = 1 << 10;
+        /// Pages are locked in memory (`lo`).
+        const LOCKED = 1 << 11;
+        /// A memory mapped I/O area (`io`).
+        const IO = 1 << 12;
+        /// Sequential read access is advised (`sr`).
+        const SEQ_READ = 1 << 13;
+        /// Random read access is advised (`rr`).
+        const RAND_READ = 1 << 14;
+        /// Not copied on fork (`dc`).
+        const DONT_COPY = 1 << 15;
+        /// Not expanded on remap (`de`).
+        const DONT_EXPAND = 1 << 16;
+        /// Accounted against the commit limit (`ac`).
+        const ACCOUNT = 1 << 17;
+        /// Swap space is not reserved (`nr`).
+        const NO_RESERVE = 1 << 18;
+        /// Backed by huge TLB pages (`ht`).
+        const HUGETLB = 1 << 19;
+        /// Synchronous page faults (`sf`).
+        const SYNC = 1 << 20;
+        /// An architecture specific flag (`ar`).
+        const ARCH_1 = 1 << 21;
+        /// Wiped on fork (`wf`).
+        const WIPE_ON_FORK = 1 << 22;
+        /// Not included in core dumps (`dd`).
+        const DONT_DUMP = 1 << 23;
+        /// Soft dirty (`sd`).
+        const SOFT_DIRTY = 1 << 24;
+        /// A mixed map of PFNs and pages (`mm`).
+        const MIXED_MAP = 1 << 25;
+        /// Huge pages are advised (`hg`).
+        const HUGEPAGE = 1 << 26;
+        /// Huge pages are advised against (`nh`).
+        const NO_HUGEPAGE = 1 << 27;
+        /// Merging of identical pages is advised (`mg`).
+        const MERGEABLE = 1 << 28;
+        /// Guarded by arm64 branch target identification (`bt`).
+        const ARM64_BTI = 1 << 29;
+        /// Has arm64 memory tagging extension allocation tags (`mt`).
+        const ARM64_MTE = 1 << 30;
+        /// Missing pages are tracked by userfaultfd (`um`).
+        const UFFD_MISSING = 1 << 31;
+        /// Write protection is tracked by userfaultfd (`uw`).
+        const UFFD_WP = 1 << 32;
+        /// Minor faults are tracked by userfaultfd (`ui`).
+        const UFFD_MINOR = 1 << 33;
+        /// A shadow stack (`ss`).
+        const SHADOW_STACK = 1 << 34;
+        /// Sealed against modification (`sl`).
+        const SEALED = 1 << 35;
+        /// Pages are locked in memory once faulted in (`lf`).
+        const LOCK_ON_FAULT = 1 << 36;
+        /// Always lazily freeable (`dp`).
+        const DROPPABLE = 1 << 37;
+        /// Has guard regions (`gu`).
+        const GUARD_REGIONS = 1 << 38;
+    }
+}
+
+/// The mnemonics of the VM flags.
+const MNEMONICS: &[(&str, VmFlags)] = &[
+    ("rd", VmFlags::READ),
+    ("wr", VmFlags::WRITE),
+    ("ex", VmFlags::EXEC),
+    ("sh", VmFlags::SHARED),
+    ("mr", VmFlags::MAY_READ),
+    ("mw", VmFlags::MAY_WRITE),
+    ("me", VmFlags::MAY_EXEC),
+    ("ms", VmFlags::MAY_SHARE),
+    ("gd", VmFlags::GROWS_DOWN),
+    ("pf", VmFlags::PFN_MAP),
+    ("dw", VmFlags::DENY_WRITE),
+    ("lo", VmFlags::LOCKED),
+    ("io", VmFlags::IO),
+    ("sr", VmFlags::SEQ_READ),
+    ("rr", VmFlags::RAND_READ),
+    ("dc", VmFlags::DONT_COPY),
+    ("de", VmFlags::DONT_EXPAND),
+    ("ac", VmFlags::ACCOUNT),
+    ("nr", VmFlags::NO_RESERVE),
+    ("ht", VmFlags::HUGETLB),
+    ("sf", VmFlags::SYNC),
+    ("ar", VmFlags::ARCH_1),
+    ("wf", VmFlags::WIPE_ON_FORK),
+    ("dd", VmFlags::DONT_DUMP),
+    ("bt", VmFlags::ARM64_BTI),
+    ("mt", VmFlags::ARM64_MTE),
+    ("um", VmFlags::UFFD_MISSING),
+    ("uw", VmFlags::UFFD_WP),
+    ("ui", VmFlags::UFFD_MINOR),
+    ("ss", VmFlags::SHADOW_STACK),
+    ("sl", VmFlags::SEALED),
+    ("lf", VmFlags::LOCK_ON_FAULT),
+    ("dp", VmFlags::DROPPABLE),
+    ("gu", VmFlags::GUARD_REGIONS),
+    ("sd", VmFlags::SOFT_DIRTY),
+    ("mm", VmFlags::MIXED_MAP),
+    ("hg", VmFlags::HUGEPAGE),
+    ("nh", VmFlags::NO_HUGEPAGE),
+    ("mg", VmFlags::MERGEABLE),
+];
+
+impl VmFlags {
+    /// Parses a space separated list of VM flag mnemonics, such as `rd wr mr mw me ac`.
+    ///
+    /// Mnemonics which are not known, such as those added by later kernels, are ignored.
+    pub fn parse(s: &str) -> VmFlags {
+        s.split_whitespace()
+         .filter_map(|mnemonic| {
+             MNEMONICS.iter().find(|&&(m, _)| m == mnemonic).map(|&(_, flag)| flag)
+         })
+         .fold(VmFlags::empty(), |flags, flag| flags | flag)
+    }
+}
+
+impl fmt::Display for VmFlags {
+    /// Formats the flags as a space separated list of mnemonics.
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let mut first = true;
+        for &(mnemonic, flag) in MNEMONICS {
+            if self.contains(flag) {
+                if !first {
+                    f.write_str(" ")?;
+                }
+                f.write_str(mnemonic)?;
+                first = false;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// The memory usage of a memory mapping of a process.
+///
+/// Sizes are in kB. Fields which were added in later kernel versions are `None` when the running
+/// kernel does not provide them.
+///
+/// See `proc(5)` and `Linux/Documentation/filesystems/proc.rst`.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "export", derive(Serialize))]
+pub struct SmapsEntry {
+    /// The mapping.
+    pub map: MemoryMap,
+    /// Size of the mapping.
+    pub size: u64,
+    /// Size of the pages used by the kernel to back the mapping.
+    pub kernel_page_size: u64,
+    /// Size of the pages used by the MMU to back the mapping.
+    pub mmu_page_size: u64,
+    /// Resident set size of the mapping.
+    pub rss: u64,
+    /// Proportional set size of the mapping: the resident memory, with each page divided by the
+    /// number of processes sharing it.
+    pub pss: u64,
+    /// Dirty proportional set size (since Linux 6.0).
+    pub pss_dirty: Option<u64>,
+    /// Clean resident memory shared with other processes.
+    pub shared_clean: u64,
+    /// Dirty resident memory shared with other processes.
+    pub shared_dirty: u64,
+    /// Clean resident memory private to the process.
+    pub private_clean: u64,
+    /// Dirty resident memory private to the process.
+    pub private_dirty: u64,
+    /// Memory currently marked as referenced or accessed.
+    pub referenced: u64,
+    /// Memory which does not belong to any file.
+    pub anonymous: u64,
+    /// Memory shared by kernel same-page merging (since Linux 6.7).
+    pub ksm: Option<u64>,
+    /// Memory marked by `MADV_FREE`, which will be freed under memory pressure (since Linux 4.12).
+    pub lazy_free: Option<u64>,
+    /// Anonymous memory backed by transparent huge pages.
+    pub anon_huge_pages: u64,
+    /// Shared memory backed by huge pages (since Linux 4.10).
+    pub shmem_pmd_mapped: Option<u64>,
+    /// File memory backed by huge pages (since Linux 5.4).
+    pub file_pmd_mapped: Option<u64>,
+    /// Shared memory backed by hugetlbfs pages (since Linux 4.4).
+    pub shared_hugetlb: Option<u64>,
+    /// Private memory backed by hugetlbfs pages (since Linux 4.4).
+    pub private_hugetlb: Option<u64>,
+    /// Memory which has been swapped out.
+    pub swap: u64,
+    /// Proportional swap usage (since Linux 4.3).
+    pub swap_pss: Option<u64>,
+    /// Memory locked in memory.
+    pub locked: u64,
+    /// Whether the mapping is eligible for transparent huge pages (since Linux 5.0).
+    pub thp_eligible: Option<bool>,
+    /// The memory protection key of the mapping, on systems which support them (since Linux 4.9).
+    pub protection_key: Option<u32>,
+    /// The kernel flags of the mapping (since Linux 3.8).
+    pub vm_flags: VmFlags,
+}
+
+impl SmapsEntry {
+    /// Creates an entry for the mapping with no memory usage.
+    fn new(map: MemoryMap) -> SmapsEntry {
+        SmapsEntry {
+            map,
+            size: 0,
+            kernel_page_size: 0,
+            mmu_page_size: 0,
+            rss: 0,
+            pss: 0,
+            pss_dirty: None,
+            shared_clean: 0,
+            shared_dirty: 0,
+            private_clean: 0,
+            private_dirty: 0,
+            referenced: 0,
+            anonymous: 0,
+            ksm: None,
+            lazy_free: None,
+            anon_huge_pages: 0,
+            shmem_pmd_mapped: None,
+            file_pmd_mapped: None,
+            shared_hugetlb: None,
+            private_hugetlb: None,
+            swap: 0,
+            swap_pss: None,
+            locked: 0,
+            thp_eligible: None,
+            protection_key: None,
+            vm_flags: VmFlags::empty(),
+        }
+    }
+}
+
+bitflags! {
+    /// A selection of the fields of `SmapsEntry`, used to parse only the fields which are needed.
+    ///
+    /// The fields of `SmapsEntry` which are not selected are left 0, `None` or empty.
+    #[derive(Default)]
+    #[cfg_attr(feature = "export", derive(Serialize))]
+    pub struct SmapsFields: u32 {
+        /// `SmapsEntry::size`.
+        const SIZE = 1 << 0;
+        /// `SmapsEntry::kernel_page_size`.
+        const KERNEL_PAGE_SIZE = 1 << 1;
+        /// `SmapsEntry::mmu_page_size`.
+        const MMU_PAGE_SIZE = 1 << 2;
+        /// `SmapsEntry::rss`.
+        const RSS = 1 << 3;
+        /// `SmapsEntry::pss`.
+        const PSS = 1 << 4;
+        /// `SmapsEntry::pss_dirty`.
+        const PSS_DIRTY = 1 << 5;
+        /// `SmapsEntry::shared_clean`.
+        const SHARED_CLEAN = 1 << 6;
+        /// `SmapsEntry::shared_dirty`.
+        const SHARED_DIRTY = 1 << 7;
+        /// `SmapsEntry::private_clean`.
+        const PRIVATE_CLEAN = 1 << 8;
+        /// `SmapsEntry::private_dirty`.
+        const PRIVATE_DIRTY = 1 << 9;
+        /// `SmapsEntry::referenced`.
+        const REFERENCED = 1 << 10;
+        /// `SmapsEntry::anonymous`.
+        const ANONYMOUS = 1 << 11;
+        /// `SmapsEntry::ksm`.
+        const KSM = 1 << 12;
+        /// `SmapsEntry::lazy_free`.
+        const LAZY_FREE = 1 << 13;
+        /// `SmapsEntry::anon_huge_pages`.
+        const ANON_HUGE_PAGES = 1 << 14;
+        /// `SmapsEntry::shmem_pmd_mapped`.
+        const SHMEM_PMD_MAPPED = 1 << 15;
+        /// `SmapsEntry::file_pmd_mapped`.
+        const FILE_PMD_MAPPED = 1 << 16;
+        /// `SmapsEntry::shared_hugetlb`.
+        const SHARED_HUGETLB = 1 << 17;
+        /// `SmapsEntry::private_hugetlb`.
+        const PRIVATE_HUGETLB = 1 << 18;
+        /// `SmapsEntry::swap`.
+        const SWAP = 1 << 19;
+        /// `SmapsEntry::swap_pss`.
+        const SWAP_PSS = 1 << 20;
+        /// `SmapsEntry::locked`.
+        const LOCKED = 1 << 21;
+        /// `SmapsEntry::thp_eligible`.
+        const THP_ELIGIBLE = 1 << 22;
+        /// `SmapsEntry::protection_key`.
+        const PROTECTION_KEY = 1 << 23;
+        /// `SmapsEntry::vm_flags`.
+        const VM_FLAGS = 1 << 24;
+        /// The fields used by `SmapsSummary`.
+        const SUMMARY = Self::SIZE.bits | Self::RSS.bits | Self::PSS.bits
+                      | Self::SHARED_CLEAN.bits | Self::SHARED_DIRTY.bits
+                      | Self::PRIVATE_CLEAN.bits | Self::PRIVATE_DIRTY.bits
+                      | Self::SWAP.bits | Self::SWAP_PSS.bits;
+    }
+}
+
+/// Memory usage totals of a set of memory mappings.
+///
+/// Sizes are in kB.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "export", derive(Serialize))]
+pub struct SmapsUsage {
+    /// Total size of the mappings.
+    pub size: u64,
+    /// Resident set size.
+    pub rss: u64,
+    /// Proportional set size: the resident memory, with each page divided by the number of
+    /// processes sharing it.
+    pub pss: u64,
+    /// Unique set size: the resident memory which is private to the process, and which would be
+    /// freed if it exited.
+    pub uss: u64,
+    /// Resident memory shared with other processes.
+    pub shared: u64,
+    /// Memory which has been swapped out.
+    pub swap: u64,
+    /// Proportional swap usage, or `None` if the kernel does not report it (before Linux 4.3).
+    pub swap_pss: Option<u64>,
+}
+
+impl SmapsUsage {
+    /// Adds the usage of the entry to the totals.
+    fn add(&mut self, entry: &SmapsEntry) {
+        self.size += entry.size;
+        self.rss += entry.rss;
+        self.pss += entry.pss;
+        self.uss += entry.private_clean + entry.private_dirty;
+        self.shared += entry.shared_clean + entry.shared_dirty;
+        self.swap += entry.swap;
+        self.swap_pss = self.swap_pss.and_then(|total| entry.swap_pss.map(|pss| total + pss));
+    }
+}
+
+/// Memory usage totals of a process, computed from its smaps entries, overall and by what the
+/// mappings contain.
+///
+/// Sizes are in kB.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "export", derive(Serialize))]
+pub struct SmapsSummary {
+    /// Usage of all mappings.
+    pub total: SmapsUsage,
+    /// Usage of mapped files.
+    pub file: SmapsUsage,
+    /// Usage of anonymous mappings, named or not, other than the heap and stack.
+    pub anonymous: SmapsUsage,
+    /// Usage of the process heap.
+    pub heap: SmapsUsage,
+    /// Usage of the stacks of the main thread, and of other threads before Linux 4.5.
+    pub stack: SmapsUsage,
+    /// Usage of other mappings, such as the vDSO.
+    pub other: SmapsUsage,
+}
+
+impl Default for SmapsSummary {
+    fn default() -> SmapsSummary {
+        let usage = SmapsUsage { swap_pss: Some(0), ..SmapsUsage::default() };
+        SmapsSummary {
+            total: usage,
+            file: usage,
+            anonymous: usage,
+            heap: usage,
+            stack: usage,
+            other: usage,
+        }
+    }
+}
+
+impl<'a> FromIterator<&'a SmapsEntry> for SmapsSummary {
+    fn from_iter<I>(entries: I) -> SmapsSummary where I: IntoIterator<Item = &'a SmapsEntry> {
+        let mut summary = SmapsSummary::default();
+        for entry in entries {
+            summary.total.add(entry);
+            let kind = match entry.map.kind {
+                MemoryMapKind::File { .. } => &mut summary.file,
+                MemoryMapKind::Anonymous | MemoryMapKind::NamedAnonymous(_) => {
+                    &mut summary.anonymous
+                },
+                MemoryMapKind::Heap => &mut summary.heap,
+                MemoryMapKind::Stack(_) => &mut summary.stack,
+                _ => &mut summary.other,
+            };
+            kind.add(entry);
+        }
+        summary
+    }
+}
+
+impl<'a> From<&'a [SmapsEntry]> for SmapsSummary {
+    fn from(entries: &'a [SmapsEntry]) -> SmapsSummary {
+        entries.iter().collect()
+    }
+}
+
+/// Returns `true` if the line is a `Label: value` field, rather than the header of a mapping.
+fn is_field(line: &[u8]) -> bool {
+    let end = line.iter().position(|&c| c == b' ' || c == b'\t').unwrap_or(line.len());
+    line[..end].ends_with(b":")
+}
+
+/// Returns the field of `SmapsEntry` parsed from the line with the label, or no fields if the label
+/// is not known.
+fn smaps_field(label: &str) -> SmapsFields {
+    match label {
+        "Size"            => SmapsFields::SIZE,
+        "KernelPageSize"  => SmapsFields::KERNEL_PAGE_SIZE,
+        "MMUPageSize"     => SmapsFields::MMU_PAGE_SIZE,
+        "Rss"             => SmapsFields::RSS,
+        "Pss"             => SmapsFields::PSS,
+        "Pss_Dirty"       => SmapsFields::PSS_DIRTY,
+        "Shared_Clean"    => SmapsFields::SHARED_CLEAN,
+        "Shared_Dirty"    => SmapsFields::SHARED_DIRTY,
+        "Private_Clean"   => SmapsFields::PRIVATE_CLEAN,
+        "Private_Dirty"   => SmapsFields::PRIVATE_DIRTY,
+        "Referenced"      => SmapsFields::REFERENCED,
+        "Anonymous"       => SmapsFields::ANONYMOUS,
+        "KSM"             => SmapsFields::KSM,
+        "LazyFree"        => SmapsFields::LAZY_FREE,
+        "AnonHugePages"   => SmapsFields::ANON_HUGE_PAGES,
+        "ShmemPmdMapped"  => SmapsFields::SHMEM_PMD_MAPPED,
+        "FilePmdMapped"   => SmapsFields::FILE_PMD_MAPPED,
+        "Shared_Hugetlb"  => SmapsFields::SHARED_HUGETLB,
+        "Private_Hugetlb" => SmapsFields::PRIVATE_HUGETLB,
+        "Swap"            => SmapsFields::SWAP,
+        "SwapPss"         => SmapsFields::SWAP_PSS,
+        "Locked"          => SmapsFields::LOCKED,
+        "THPeligible"     => SmapsFields::THP_ELIGIBLE,
+        "ProtectionKey"   => SmapsFields::PROTECTION_KEY,
+        "VmFlags"         => SmapsFields::VM_FLAGS,
+        _ => SmapsFields::empty(),
+    }
+}
+
+/// Parses a field line into the entry, if it is one of the selected fields.
+///
+/// Fields which are not known, such as those added by later kernels, are ignored.
+fn parse_smaps_field(entry: &mut SmapsEntry, line: &str, fields: SmapsFields) -> Result<()> {
+    let (label, value) = match split_label(line) {
+        Some(split) => split,
+        None => return Ok(()),
+    };
+    if !fields.contains(smaps_field(label)) {
+        return Ok(());
+    }
+    let value = value.trim();
+    match label {
+        "Size"            => entry.size             = parse_kb(value)?,
+        "KernelPageSize"  => entry.kernel_page_size = parse_kb(value)?,
+        "MMUPageSize"     => entry.mmu_page_size    = parse_kb(value)?,
+        "Rss"             => entry.rss              = parse_kb(value)?,
+        "Pss"             => entry.pss              = parse_kb(value)?,
+        "Pss_Dirty"       => entry.pss_dirty        = Some(parse_kb(value)?),
+        "Shared_Clean"    => entry.shared_clean     = parse_kb(value)?,
+        "Shared_Dirty"    => entry.shared_dirty     = parse_kb(value)?,
+        "Private_Clean"   => entry.private_clean    = parse_kb(value)?,
+        "Private_Dirty"   => entry.private_dirty    = parse_kb(value)?,
+        "Referenced"      => entry.referenced       = parse_kb(value)?,
+        "Anonymous"       => entry.anonymous        = parse_kb(value)?,
+        "KSM"             => entry.ksm              = Some(parse_kb(value)?),
+        "LazyFree"        => entry.lazy_free        = Some(parse_kb(value)?),
+        "AnonHugePages"   => entry.anon_huge_pages  = parse_kb(value)?,
+        "ShmemPmdMapped"  => entry.shmem_pmd_mapped = Some(parse_kb(value)?),
+        "FilePmdMapped"   => entry.file_pmd_mapped  = Some(parse_kb(value)?),
+        "Shared_Hugetlb"  => entry.shared_hugetlb   = Some(parse_kb(value)?),
+        "Private_Hugetlb" => entry.private_hugetlb  = Some(parse_kb(value)?),
+        "Swap"            => entry.swap             = parse_kb(value)?,
+        "SwapPss"         => entry.swap_pss         = Some(parse_kb(value)?),
+        "Locked"          => entry.locked           = parse_kb(value)?,
+        "THPeligible"     => entry.thp_eligible     = Some(parse_bit(value)?),
+        "ProtectionKey"   => entry.protection_key   = Some(parse(value)?),
+        "VmFlags"         => entry.vm_flags         = VmFlags::parse(value),
+        _ => (),
+    }
+    Ok(())
+}
+
+/// Parses a line of the smaps file format into the entries.
+fn parse_smaps_line(entries: &mut Vec<SmapsEntry>, line: &[u8]) -> Result<()> {
+    if is_field(line) {
+        let entry = entries.last_mut().ok_or_else(|| invalid_input("smaps field before mapping"))?;
+        parse_smaps_field(entry, parse_str(line)?, SmapsFields::all())
+    } else {
+        entries.push(SmapsEntry::new(MemoryMap::parse_line(line)?));
+        Ok(())
+    }
+}
+
+/// Parses the smaps file format, which has a header line per mapping followed by its fields.
+fn parse_smaps(input: &[u8]) -> Result<Vec<SmapsEntry>> {
+    let mut entries = Vec::new();
+    for (i, line) in input.split(|&c| c == b'\n').enumerate() {
+        if !line.is_empty() {
+            parse_smaps_line(&mut entries, line).map_err(|error| with_line(error, i + 1))?;
+        }
+    }
+    Ok(entries)
+}
+
+/// Parses the provided smaps file.
+pub(crate) fn smaps_file(file: &mut File) -> Result<Vec<SmapsEntry>> {
+    let mut buf = Vec::new();
+    file.read_to_end(&mut buf)?;
+    parse_smaps(&buf)
+}
+
+/// An iterator over the memory usage of the memory mappings of a process.
+///
+/// Entries are parsed as they are read, so memory use does not grow with the number of mappings.
+/// Created by `smaps_iter` and `smaps_iter_self`.
+#[derive(Debug)]
+pub struct SmapsEntries {
+    reader: BufReader<File>,
+    line: Vec<u8>,
+    line_number: usize,
+    /// The path of the smaps file, added to parse errors.
+    path: PathBuf,
+    /// The entry whose fields are being read.
+    entry: Option<SmapsEntry>,
+    fields: SmapsFields,
+}
+
+impl SmapsEntries {
+    pub(crate) fn new(file: File, path: PathBuf) -> SmapsEntries {
+        SmapsEntries {
+            reader: BufReader::new(file),
+            line: Vec::new(),
+            line_number: 0,
+            path,
+            entry: None,
+            fields: SmapsFields::all(),
+        }
+    }
+
+    /// Opens the smaps file at the provided path.
+    fn open(path: String) -> Result<SmapsEntries> {
+        Ok(SmapsEntries::new(File::open(&path)?, PathBuf::from(path)))
+    }
+
+    /// Parses only the selected fields of each entry, skipping the others.
+    ///
+    /// For example, selecting `SmapsFields::PSS` is enough to sum the proportional set size of a
+    /// process.
+    pub fn fields(mut self, fields: SmapsFields) -> SmapsEntries {
+        self.fields = fields;
+        self
+    }
+
+    /// Parses a line into the current entry, and returns the previous entry if the line starts a
+    /// new one.
+    fn parse_line(&mut self, line: &[u8]) -> Result<Option<SmapsEntry>> {
+        if is_field(line) {
+            let entry = self.entry.as_mut()
+                                  .ok_or_else(|| invalid_input("smaps field before mapping"))?;
+            parse_smaps_field(entry, parse_str(line)?, self.fields)?;
+            Ok(None)
+        } else {
+            let entry = SmapsEntry::new(MemoryMap::parse_line(line)?);
+            Ok(self.entry.replace(entry))
+        }
+    }
+}
+
+impl Iterator for SmapsEntries {
+    type Item = Result<SmapsEntry>;
+
+    fn next(&mut self) -> Option<Result<SmapsEntry>> {
+        loop {
+            let mut line = mem::take(&mut self.line);
+            let result = match read_line(&mut self.reader, &mut line) {
+                None => return self.entry.take().map(Ok),
+                Some(Ok(&[])) => Ok(None),
+                Some(Ok(line)) => self.parse_line(line),
+                Some(Err(error)) => Err(error),
+            };
+            self.line = line;
+            self.line_number += 1;
+            match result {
+                Ok(None) => (),
+                Ok(Some(entry)) => return Some(Ok(entry)),
+                Err(error) => {
+                    return Some(Err(with_path(with_line(error, self.line_number), &self.path)));
+                },
+            }
+        }
+    }
+}
+
+/// Returns the memory usage of each memory mapping of the process with the provided pid.
+pub fn smaps(pid: pid_t) -> Result<Vec<SmapsEntry>> {
+    parse_file(format!("/proc/{}/smaps", pid), smaps_file)
+}
+
+/// Returns the memory usage of each memory mapping of the current process.
+pub fn smaps_self() -> Result<Vec<SmapsEntry>> {
+    parse_file("/proc/self/smaps", smaps_file)
+}
+
+/// Returns the memory usage of each memory mapping from the thread with the provided parent
+/// process ID and thread ID.
+pub fn smaps_task(process_id: pid_t, thread_id: pid_t) -> Result<Vec<SmapsEntry>> {
+    parse_file(format!("/proc/{}/task/{}/smaps", process_id, thread_id), smaps_file)
+}
+
+/// Returns an iterator over the memory usage of each memory mapping of the process with the
+/// provided pid.
+pub fn smaps_iter(pid: pid_t) -> Result<SmapsEntries> {
+    SmapsEntries::open(format!("/proc/{}/smaps", pid))
+}
+
+/// Returns an iterator over the memory usage of each memory mapping of the current process.
+pub fn smaps_iter_self() -> Result<SmapsEntries> {
+    SmapsEntries::open("/proc/self/smaps".to_owned())
+}
+
+/// Returns an iterator over the memory usage of each memory mapping from the thread with the
+/// provided parent process ID and thread ID.
+pub fn smaps_iter_task(process_id: pid_t, thread_id: pid_t) -> Result<SmapsEntries> {
+    SmapsEntries::open(format!("/proc/{}/task/{}/smaps", process_id, thread_id))
+}
+
+/// Parses the provided smaps_rollup file, which has a single entry spanning all mappings.
+pub(crate) fn smaps_rollup_file(file: &mut File) -> Result<SmapsEntry> {
+    let mut entries = smaps_file(file)?;
+    match entries.pop() {
+        Some(entry) if entries.is_empty() => Ok(entry),
+        _ => Err(invalid_input("smaps_rollup must contain a single entry")),
+    }
+}
+
+/// Returns the memory usage of all memory mappings of the process with the provided pid, summed
+/// by the kernel (since Linux 4.14).
+///
+/// This is much cheaper than summing the entries of `smaps`. The mapping of the entry spans the
+/// address ranges of all mappings, and its pathname is `[rollup]`.
+pub fn smaps_rollup(pid: pid_t) -> Result<SmapsEntry> {
+    parse_file(format!("/proc/{}/smaps_rollup", pid), smaps_rollup_file)
+}
+
+/// Returns the memory usage of all memory mappings of the current process, summed by the kernel
+/// (since Linux 4.14).
+pub fn smaps_rollup_self() -> Result<SmapsEntry> {
+    parse_file("/proc/self/smaps_rollup", smaps_rollup_file)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Result;
+
+    use pid::{MemoryMapKind, Permissions};
+    use super::{SmapsFields, SmapsSummary, VmFlags, parse_smaps, smaps_iter_self, smaps_rollup_self,
+                smaps_self};
+
+    /// Test that the smaps file of the current process can be parsed.
+    #[test]
+    fn test_smaps() {
+        let smaps = smaps_self().unwrap();
+        let stack = smaps.iter()
+                         .find(|entry| entry.map.kind == MemoryMapKind::Stack(None))
+                         .unwrap();
+        assert!(stack.rss > 0);
+        assert!(stack.vm_flags.contains(VmFlags::READ | VmFlags::WRITE | VmFlags::GROWS_DOWN));
+    }
+
+    /// Test that iterating over the smaps file agrees with parsing it at once.
+    #[test]
+    fn test_smaps_iter() {
+        let smaps = smaps_self().unwrap();
+        let entries = smaps_iter_self().unwrap().collect::<Result<Vec<_>>>().unwrap();
+        assert_eq!(smaps.len(), entries.len());
+        assert!(smaps.iter().zip(&entries).all(|(a, b)| a.map.range == b.map.range));
+
+        let stack = smaps_iter_self().unwrap()
+                                     .fields(SmapsFields::PSS)
+                                     .map(Result::unwrap)
+                                     .find(|entry| entry.map.kind == MemoryMapKind::Stack(None))
+                                     .unwrap();
+        assert!(stack.pss > 0);
+        assert_eq!(0, stack.rss);
+        assert_eq!(0, stack.size);
+        assert!(stack.vm_flags.is_empty());
+    }
+
+    /// Test that the smaps_rollup file of the current process can be parsed.
+    #[test]
+    fn test_smaps_rollup() {
+        let rollup = smaps_rollup_self().unwrap();
+        assert_eq!(MemoryMapKind::Unknown("[rollup]".to_owned()), rollup.map.kind);
+        assert!(rollup.rss > 0);
+    }
+
+    #[test]
+    fn test_parse_smaps() {
+        let text = b"00400000-00452000 r-xp 00000000 08:02 173521      /usr/bin/dbus-daemon\n\
+                     Size:                328 kB\n\
+                     KernelPageSize:        4 kB\n\
+                     MMUPageSize:           4 kB\n\
+                     Rss:                 300 kB\n\
+                     Pss:                 150 kB\n\
+                     Shared_Clean:        300 kB\n\
+                     Shared_Dirty:          0 kB\n\
+                     Private_Clean:         0 kB\n\
+                     Private_Dirty:         0 kB\n\
+                     Referenced:          300 kB\n\
+                     Anonymous:             0 kB\n\
+                     AnonHugePages:         0 kB\n\
+                     Swap:                  0 kB\n\
+                     Locked:              300 kB\n\
+                     VmFlags: rd ex mr mw me dw lo zz \n\
+                     7ffd6c3a2000-7ffd6c3a4000 rw-p 00000000 00:00 0                  [stack]\n\
+                     Size:                132 kB\n\
+                     Rss:                  12 kB\n\
+                     Pss_Dirty:            12 kB\n\
+                     Swap:                  4 kB\n\
+                     SwapPss:               4 kB\n\
+                     THPeligible:           1\n\
+                     ProtectionKey:         0\n\
+                     VmFlags: rd wr mr mw me gd ac ht\n";
+        let smaps = parse_smaps(text).unwrap();
+        assert_eq!(2, smaps.len());
+
+        assert_eq!(Permissions::READ | Permissions::EXEC, smaps[0].map.permissions);
+        assert_eq!(328, smaps[0].size);
+        assert_eq!(300, smaps[0].rss);
+        assert_eq!(150, smaps[0].pss);
+        assert_eq!(300, smaps[0].locked);
+        assert_eq!(None, smaps[0].pss_dirty);
+        assert_eq!(None, smaps[0].thp_eligible);
+        assert_eq!(VmFlags::READ | VmFlags::EXEC | VmFlags::MAY_READ | VmFlags::MAY_WRITE
+                   | VmFlags::MAY_EXEC | VmFlags::DENY_WRITE | VmFlags::LOCKED,
+                   smaps[0].vm_flags);
+
+        assert_eq!(MemoryMapKind::Stack(None), smaps[1].map.kind);
+        assert_eq!(Some(12), smaps[1].pss_dirty);
+        assert_eq!(4, smaps[1].swap);
+        assert_eq!(Some(true), smaps[1].thp_eligible);
+        assert_eq!(Some(0), smaps[1].protection_key);
+        assert!(smaps[1].vm_flags.contains(VmFlags::HUGETLB | VmFlags::GROWS_DOWN));
+
+        parse_smaps(b"Size: 4 kB\n").unwrap_err();
+        parse_smaps(b"00400000-00452000 r-xp 00000000 08:02 1\nRss: 4\n").unwrap_err();
+    }
+
+    #[test]
+    fn test_smaps_summary() {
+        let text = b"00400000-00452000 r-xp 00000000 08:02 173521      /usr/bin/dbus-daemon\n\
+                     Size:                328 kB\n\
+                     Rss:                 300 kB\n\
+                     Pss:                 150 kB\n\
+                     Shared_Clean:        280 kB\n\
+                     Private_Clean:        20 kB\n\
+                     SwapPss:               0 kB\n\
+                     01a0f000-01a30000 rw-p 00000000 00:00 0          [heap]\n\
+                     Size:                132 kB\n\
+                     Rss:                 100 kB\n\
+                     Pss:                 100 kB\n\
+                     Private_Dirty:       100 kB\n\
+                     Swap:                 16 kB\n\
+                     SwapPss:              16 kB\n\
+                     7f2c1a021000-7f2c1a022000 rw-p 00000000 00:00 0 \n\
+                     Size:                  4 kB\n\
+                     Rss:                   4 kB\n\
+                     Pss:                   2 kB\n\
+                     Shared_Dirty:          4 kB\n\
+                     SwapPss:               0 kB\n";
+        let mut entries = parse_smaps(text).unwrap();
+        let summary = SmapsSummary::from(&entries[..]);
+        assert_eq!(464, summary.total.size);
+        assert_eq!(404, summary.total.rss);
+        assert_eq!(252, summary.total.pss);
+        assert_eq!(120, summary.total.uss);
+        assert_eq!(284, summary.total.shared);
+        assert_eq!(16, summary.total.swap);
+        assert_eq!(Some(16), summary.total.swap_pss);
+        assert_eq!(20, summary.file.uss);
+        assert_eq!(100, summary.heap.rss);
+        assert_eq!(4, summary.anonymous.shared);
+        assert_eq!(0, summary.stack.rss);
+        assert_eq!(0, summary.other.size);
+
+        // Proportional swap usage is only summed if every entry reports it.
+        entries[2].swap_pss = None;
+        let summary = SmapsSummary::from(&entries[..]);
+        assert_eq!(None, summary.total.swap_pss);
+        assert_eq!(Some(16), summary.heap.swap_pss);
+
+        let summary: SmapsSummary = smaps_self().unwrap().iter().collect();
+        assert!(summary.total.rss > 0);
+        assert!(summary.total.pss <= summary.total.rss);
+        assert!(summary.stack.rss > 0);
+    }
+
+    #[test]
+    fn test_vm_flags() {
+        let flags = VmFlags::parse("rd wr mr mw me ac sd");
+        assert_eq!("rd wr mr mw me ac sd", flags.to_string());
+        assert_eq!("", VmFlags::parse("").to_string());
+        assert_eq!(VmFlags::LOCKED | VmFlags::HUGETLB, VmFlags::parse("ht lo xx"));
+    }
+}
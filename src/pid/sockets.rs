@@ -0,0 +1,177 @@
+//! Sockets open by a process, from `/proc/[pid]/fd` and `/proc/[pid]/net`.
+
+use std::collections::HashMap;
+use std::fs;
+use std::io::{ErrorKind, Result};
+use std::os::unix::io::RawFd;
+use std::path::Path;
+
+use libc::pid_t;
+
+use net::inet::{InetSocket, inet_file};
+use net::unix::{UnixSocket, unix_file};
+use pid::pids::is_exited;
+
+/// A socket, as described by the socket tables of a network namespace.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "export", derive(Serialize))]
+pub enum Socket {
+    /// An IPv4 or IPv6 TCP socket.
+    Tcp(InetSocket),
+    /// An IPv4 or IPv6 UDP socket.
+    Udp(InetSocket),
+    /// An IPv4 or IPv6 ICMP (ping) socket.
+    Icmp(InetSocket),
+    /// A Unix domain socket.
+    Unix(UnixSocket),
+    /// A socket of another family, such as a netlink or raw socket, which is not described by the
+    /// socket tables.
+    Other,
+}
+
+/// A socket open by a process.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "export", derive(Serialize))]
+pub struct ProcessSocket {
+    /// The file descriptor of the socket in the process.
+    pub fd: RawFd,
+    /// The inode of the socket.
+    pub inode: u64,
+    /// The socket.
+    pub socket: Socket,
+}
+
+/// Parses the inode from a `socket:[inode]` file descriptor link target.
+fn parse_socket_inode(target: &Path) -> Option<u64> {
+    target.to_str()?
+          .strip_prefix("socket:[")?
+          .strip_suffix(']')?
+          .parse()
+          .ok()
+}
+
+/// Reads a socket table, treating a missing table (e.g. with IPv6 disabled) as empty.
+fn read_table<T>(table: Result<Vec<T>>) -> Result<Vec<T>> {
+    match table {
+        Err(ref error) if error.kind() == ErrorKind::NotFound => Ok(Vec::new()),
+        table => table,
+    }
+}
+
+/// Returns the file descriptors and inodes of the sockets open by the process with the provided
+/// `/proc` directory, in directory order.
+pub(crate) fn socket_fds(dir: &str) -> Result<Vec<(RawFd, u64)>> {
+    let mut inodes = Vec::new();
+    for entry in fs::read_dir(format!("{}/fd", dir))? {
+        let entry = entry?;
+        let fd = match entry.file_name().to_str().and_then(|name| name.parse().ok()) {
+            Some(fd) => fd,
+            None => continue,
+        };
+        // File descriptors may be closed while the directory is being read.
+        let target = match fs::read_link(entry.path()) {
+            Ok(target) => target,
+            Err(ref error) if is_exited(error) => continue,
+            Err(error) => return Err(error),
+        };
+        if let Some(inode) = parse_socket_inode(&target) {
+            inodes.push((fd, inode));
+        }
+    }
+    Ok(inodes)
+}
+
+/// Returns the sockets open by the process with the provided `/proc` directory.
+fn sockets_dir(dir: &str) -> Result<Vec<ProcessSocket>> {
+    let inodes = socket_fds(dir)?;
+    if inodes.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let mut table = HashMap::new();
+    for path in &["tcp", "tcp6"] {
+        for socket in read_table(inet_file(&format!("{}/net/{}", dir, path)))? {
+            table.insert(socket.inode, Socket::Tcp(socket));
+        }
+    }
+    for path in &["udp", "udp6"] {
+        for socket in read_table(inet_file(&format!("{}/net/{}", dir, path)))? {
+            table.insert(socket.inode, Socket::Udp(socket));
+        }
+    }
+    for path in &["icmp", "icmp6"] {
+        for socket in read_table(inet_file(&format!("{}/net/{}", dir, path)))? {
+            table.insert(socket.inode, Socket::Icmp(socket));
+        }
+    }
+    for socket in read_table(unix_file(&format!("{}/net/unix", dir)))? {
+        table.insert(socket.inode, Socket::Unix(socket));
+    }
+
+    Ok(inodes.into_iter().map(|(fd, inode)| {
+        let socket = table.get(&inode).cloned().unwrap_or(Socket::Other);
+        ProcessSocket { fd, inode, socket }
+    }).collect())
+}
+
+/// Returns the sockets open by the process with the provided pid, in file descriptor order.
+///
+/// Each socket is joined with its entry in the TCP, UDP, ICMP, and Unix domain socket tables of the
+/// process's network namespace. Reading the file descriptors of another process requires ptrace
+/// access to it.
+pub fn sockets(pid: pid_t) -> Result<Vec<ProcessSocket>> {
+    let mut sockets = sockets_dir(&format!("/proc/{}", pid))?;
+    sockets.sort_by_key(|socket| socket.fd);
+    Ok(sockets)
+}
+
+/// Returns the sockets open by the current process, in file descriptor order.
+pub fn sockets_self() -> Result<Vec<ProcessSocket>> {
+    let mut sockets = sockets_dir("/proc/self")?;
+    sockets.sort_by_key(|socket| socket.fd);
+    Ok(sockets)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::net::{TcpListener, UdpSocket};
+    use std::os::unix::io::AsRawFd;
+    use std::os::unix::net::UnixDatagram;
+    use std::path::Path;
+
+    use net::inet::SocketState;
+    use super::{Socket, parse_socket_inode, sockets_self};
+
+    #[test]
+    fn test_sockets() {
+        let tcp = TcpListener::bind("127.0.0.1:0").unwrap();
+        let udp = UdpSocket::bind("127.0.0.1:0").unwrap();
+        let (unix, _) = UnixDatagram::pair().unwrap();
+
+        let sockets = sockets_self().unwrap();
+        let find = |fd| sockets.iter().find(|socket| socket.fd == fd).unwrap();
+
+        match find(tcp.as_raw_fd()).socket {
+            Socket::Tcp(ref socket) => {
+                assert_eq!(tcp.local_addr().unwrap(), socket.local_address);
+                assert_eq!(SocketState::Listen, socket.state);
+            },
+            ref socket => panic!("expected a TCP socket: {:?}", socket),
+        }
+        match find(udp.as_raw_fd()).socket {
+            Socket::Udp(ref socket) => assert_eq!(udp.local_addr().unwrap(), socket.local_address),
+            ref socket => panic!("expected a UDP socket: {:?}", socket),
+        }
+        match find(unix.as_raw_fd()).socket {
+            Socket::Unix(ref socket) => assert_eq!(None, socket.path),
+            ref socket => panic!("expected a Unix socket: {:?}", socket),
+        }
+    }
+
+    #[test]
+    fn test_parse_socket_inode() {
+        assert_eq!(Some(40746), parse_socket_inode(Path::new("socket:[40746]")));
+        assert_eq!(None, parse_socket_inode(Path::new("pipe:[40746]")));
+        assert_eq!(None, parse_socket_inode(Path::new("/dev/null")));
+    }
+}
@@ -1,34 +1,94 @@
 //! Process status information from `/proc/[pid]/stat`.
 
+use std::fmt::Write;
 use std::fs::File;
-use std::io::Result;
-use std::str::{self, FromStr};
-
-use libc::{clock_t, pid_t};
-use nom::{self, IResult, line_ending, space};
-use pid::State;
-
-use parsers::{
-    map_result,
-    parse_clock,
-    parse_i32,
-    parse_u32,
-    parse_u64,
-    parse_usize,
-    read_to_end
-};
+use std::io::{Read, Result};
+use std::path::Path;
+use std::str;
+use std::time::{Duration, SystemTime};
+
+use libc::pid_t;
+
+use boot_time::boot_time;
+use parsers::{Fields, invalid_input, parse, parse_file, parse_line, read_to_end, with_path};
+use pid::ProcessState;
+use pid::pids::{Pids, is_exited, pids};
+use sysconf::{page_size, ticks_to_duration};
+
+bitflags! {
+    /// Kernel flags of a process, from the `flags` field of `Stat`.
+    ///
+    /// Each flag corresponds to the `PF_*` define of the same name in
+    /// `Linux/include/linux/sched.h`. Flags whose values have changed between kernel versions
+    /// document the version from which they are valid; on earlier kernels the bit has a different
+    /// meaning.
+    #[derive(Default)]
+    #[cfg_attr(feature = "export", derive(Serialize))]
+    pub struct TaskFlags: u32 {
+        /// The process is exiting.
+        const EXITING = 0x0000_0004;
+        /// The process is a workqueue worker.
+        const WQ_WORKER = 0x0000_0020;
+        /// The process has forked but not executed.
+        const FORKNOEXEC = 0x0000_0040;
+        /// The process is subject to a machine check error policy.
+        const MCE_PROCESS = 0x0000_0080;
+        /// The process used super-user privileges.
+        const SUPERPRIV = 0x0000_0100;
+        /// The process dumped core.
+        const DUMPCORE = 0x0000_0200;
+        /// The process was killed by a signal.
+        const SIGNALED = 0x0000_0400;
+        /// The process is allocating memory to free memory.
+        const MEMALLOC = 0x0000_0800;
+        /// `set_user` noticed that `RLIMIT_NPROC` was exceeded.
+        const NPROC_EXCEEDED = 0x0000_1000;
+        /// The process used the FPU.
+        const USED_MATH = 0x0000_2000;
+        /// The process should not be frozen.
+        const NOFREEZE = 0x0000_8000;
+        /// The process is the `kswapd` kernel thread.
+        ///
+        /// Linux 4.12 onward. Earlier kernels use this bit for `PF_FSTRANS`, and flag `kswapd`
+        /// with the bit of `MEMALLOC_NOFS`.
+        const KSWAPD = 0x0002_0000;
+        /// All allocations of the process inherit `GFP_NOFS`.
+        ///
+        /// Linux 4.12 onward. Earlier kernels use this bit for `PF_KSWAPD`.
+        const MEMALLOC_NOFS = 0x0004_0000;
+        /// All allocations of the process inherit `GFP_NOIO`.
+        const MEMALLOC_NOIO = 0x0008_0000;
+        /// The process is a kernel thread.
+        const KTHREAD = 0x0020_0000;
+        /// The address space of the process is randomized.
+        const RANDOMIZE = 0x0040_0000;
+        /// Userspace may not change the CPU affinity of the process.
+        const NO_SETAFFINITY = 0x0400_0000;
+        /// Machine check errors are killed early.
+        const MCE_EARLY = 0x0800_0000;
+        /// The process is being suspended by the freezer.
+        const SUSPEND_TASK = 0x8000_0000;
+    }
+}
 
 /// Process status information.
 ///
+/// Fields which were added in later kernel versions are `None` when the running kernel does not
+/// provide them.
+///
 /// See `man 5 proc` and `Linux/fs/proc/array.c`.
+///
+/// The command is a `String` by default. A `StatRef` borrows the command from the parsed input
+/// instead, so that it can be parsed without allocating.
 #[derive(Debug, Default, PartialEq, Eq, Hash)]
-pub struct Stat {
+#[cfg_attr(feature = "export", derive(Serialize))]
+pub struct Stat<S = String> {
     /// Process ID (i.e., Thread Group ID).
     pub pid: pid_t,
     /// Filename of the executable.
-    pub command: String,
+    pub command: S,
     /// Current state of the process.
-    pub state: State,
+    pub state: ProcessState,
     /// Process ID of parent process.
     pub ppid: pid_t,
     /// Process group ID of the process.
@@ -41,33 +101,34 @@ pub struct Stat {
     /// Process group ID of the controlling terminal of the process.
     pub tty_pgrp: pid_t,
     /// The kernel flags word of the process. For bit meanings, see the `PF_*` defines in the Linux
-    /// kernel source file `include/linux/sched.h`. Details depend on the kernel version.
+    /// kernel source file `include/linux/sched.h`. Details depend on the kernel version. See
+    /// `task_flags` for the decoded flags.
     pub flags: u32,
     /// The number of minor faults the process has made which have not required loading a memory
     /// page from disk.
-    pub minflt: usize,
+    pub minflt: u64,
     /// The number of minor faults that the process's waited-for children have made.
-    pub cminflt: usize,
+    pub cminflt: u64,
     /// The number of major faults the process has made which have required loading a memory page
     /// from disk.
-    pub majflt: usize,
+    pub majflt: u64,
     /// The number of major faults that the process's waited-for children have made.
-    pub cmajflt: usize,
+    pub cmajflt: u64,
     /// Amount of time that this process has been scheduled in user mode, measured in clock ticks
     /// (divide by `sysconf(_SC_CLK_TCK)`). This includes guest time, `guest_time` (time spent
     /// running a virtual CPU, see below), so that applications that are not aware of the guest
     /// time field do not lose that time from their calculations.
-    pub utime: clock_t,
+    pub utime: u64,
     /// Amount of time that this process has been scheduled in kernel mode, measured in clock ticks
     /// (divide by `sysconf(_SC_CLK_TCK)`).
-    pub stime: clock_t,
+    pub stime: u64,
     /// Amount of time that this process's waited-for children have been scheduled in user mode,
     /// measured in clock ticks (divide by `sysconf(_SC_CLK_TCK)`). (See also `times(2)`.)  This
     /// includes guest time, `cguest_time` (time spent running a virtual CPU, see below).
-    pub cutime: clock_t,
+    pub cutime: i64,
     /// Amount of time that this process's waited-for children have been scheduled in kernel mode,
     /// measured in clock ticks (divide by `sysconf(_SC_CLK_TCK)`).
-    pub cstime: clock_t,
+    pub cstime: i64,
     /// For processes running a real-time scheduling policy (policy below; see
     /// `sched_setscheduler(2)`), this is the negated scheduling priority, minus one; that is, a
     /// number in the range -2 to -100, corresponding to real-time priorities 1 to 99. For
@@ -85,40 +146,40 @@ pub struct Stat {
     /// `sysconf(_SC_CLK_TCK)`).
     pub start_time: u64,
     /// Virtual memory size in bytes.
-    pub vsize: usize,
+    pub vsize: u64,
     /// Resident Set Size: number of pages the process has in real memory. This is just the pages
     /// which count toward text, data, or stack space. This does not include pages which have not
-    /// been demand-loaded in, or which are swapped out.
-    pub rss: usize,
+    /// been demand-loaded in, or which are swapped out. See `rss_bytes` for the size in bytes.
+    pub rss: u64,
     /// Current soft limit in bytes on the rss of the process; see the description of `RLIMIT_RSS`
     /// in `getrlimit(2)`.
-    pub rsslim: usize,
+    pub rsslim: u64,
     /// The address above which program text can run.
-    pub start_code: usize,
+    pub start_code: u64,
     /// The address below which program text can run.
-    pub end_code: usize,
+    pub end_code: u64,
     /// The address of the start (i.e., bottom) of the stack.
-    pub startstack: usize,
+    pub startstack: u64,
     /// The current value of ESP (stack pointer), as found in the kernel stack page for the process.
-    pub kstkeep: usize,
+    pub kstkeep: u64,
     /// The current EIP (instruction pointer).
-    pub kstkeip: usize,
+    pub kstkeip: u64,
     /// The bitmap of pending signals. Obsolete, because it does not provide information on
     /// real-time signals; use `/proc/[pid]/status` instead.
-    pub signal: usize,
+    pub signal: u64,
     /// The bitmap of blocked signals. Obsolete, because it does not provide information on
     /// real-time signals; use `/proc/[pid]/status` instead.
-    pub blocked: usize,
+    pub blocked: u64,
     /// The bitmap of ignored signals. Obsolete, because it does not provide information on
     /// real-time signals; use `/proc/[pid]/status` instead.
-    pub sigignore: usize,
+    pub sigignore: u64,
     /// The bitmap of caught signals. Obsolete, because it does not provide information on
     /// real-time signals; use /proc/[pid]/status instead.
-    pub sigcatch: usize,
+    pub sigcatch: u64,
     /// This is the "channel" in which the process is waiting. It is the address of a location in
     /// the kernel where the process is sleeping. The corresponding symbolic name can be found in
     /// `/proc/[pid]/wchan`.
-    pub wchan: usize,
+    pub wchan: u64,
     /// Signal to be sent to parent when we die.
     pub exit_signal: i32,
     /// CPU number last executed on.
@@ -130,239 +191,534 @@ pub struct Stat {
     /// `linux/sched.h`.
     pub policy: u32,
     /// Aggregated block I/O delays, measured in clock ticks (centiseconds). Since Linux 2.6.18.
-    pub delayacct_blkio_ticks: u64,
+    pub delayacct_blkio_ticks: Option<u64>,
     /// Guest time of the process (time spent running a virtual CPU for a guest operating system),
     /// measured in clock ticks (divide by `sysconf(_SC_CLK_TCK)`). Since Linux 2.6.24.
-    pub guest_time: clock_t,
+    pub guest_time: Option<u64>,
     /// Guest time of the process's children, measured in clock ticks (divide by
     /// `sysconf(_SC_CLK_TCK)`). Since linux 2.6.24.
-    pub cguest_time: clock_t,
+    pub cguest_time: Option<i64>,
     /// Address above which program initialized and uninitialized (BSS) data are placed. Since
     /// Linux 3.3.
-    pub start_data: usize,
+    pub start_data: Option<u64>,
     /// Address below which program initialized and uninitialized (BSS) data are placed. Since
     /// Linux 3.3.
-    pub end_data: usize,
+    pub end_data: Option<u64>,
     /// Address above which program heap can be expanded with `brk(2)`. Since Linux 3.3.
-    pub start_brk: usize,
+    pub start_brk: Option<u64>,
     /// Address above which program command-line arguments (argv) are placed. Since Linux 3.5.
-    pub arg_start: usize,
+    pub arg_start: Option<u64>,
     /// Address below program command-line arguments (argv) are placed. Since Linux 3.5.
-    pub arg_end: usize,
+    pub arg_end: Option<u64>,
     /// Address above which program environment is placed. Since Linux 3.5.
-    pub env_start: usize,
+    pub env_start: Option<u64>,
     /// Address below which program environment is placed. Since Linux 3.5.
-    pub env_end: usize,
+    pub env_end: Option<u64>,
     /// The thread's exit status in the form reported by `waitpid(2)`. Since Linux 3.5.
-    pub exit_code: i32,
+    pub exit_code: Option<i32>,
 }
 
-named!(parse_command<String>,
-       map_res!(map_res!(preceded!(char!('('),
-                                   take_until_right_and_consume!(")")),
-                         str::from_utf8),
-                FromStr::from_str));
+/// Process status information which borrows the command from the parsed input.
+pub type StatRef<'a> = Stat<&'a str>;
 
-/// Parse the stat state format.
-named!(parse_stat_state<State>,
-       alt!(tag!("R") => { |_| State::Running  }
-          | tag!("S") => { |_| State::Sleeping }
-          | tag!("D") => { |_| State::Waiting }
-          | tag!("Z") => { |_| State::Zombie }
-          | tag!("T") => { |_| State::Stopped }
-          | tag!("t") => { |_| State::TraceStopped }
-          | tag!("W") => { |_| State::Paging }
-          | tag!("X") => { |_| State::Dead }
-          | tag!("x") => { |_| State::Dead }
-          | tag!("K") => { |_| State::Wakekill }
-          | tag!("W") => { |_| State::Waking }
-          | tag!("P") => { |_| State::Parked }));
-
-// Note: this is implemented as a function insted of via `chain!` to reduce the
-// stack depth in rustc by limiting the generated AST's depth. This is a work
-// around for
-//   https://github.com/rust-lang/rust/issues/35408
-// where rustc overflows its stack. The bug affects at least rustc 1.12.
-fn parse_stat(input: &[u8]) -> IResult<&[u8], Stat> {
-    /// Helper macro for space terminated parser.
-    macro_rules! s {
-        ($i:expr, $f:expr) => (terminated!($i, call!($f), space))
+/// Splits the parenthesized command from the rest of the line.
+///
+/// The command may itself contain parentheses and spaces, so it extends to the last closing
+/// parenthesis in the line.
+fn parse_command(input: &str) -> Result<(&str, &str)> {
+    match (input.strip_prefix('('), input.rfind(')')) {
+        (Some(_), Some(end)) => Ok((&input[1..end], &input[end + 1..])),
+        _ => Err(invalid_input(format!("invalid command: {:?}", input))),
     }
-    /// Helper macro for line-ending terminated parser.
-    macro_rules! l {
-        ($i:expr, $f:expr) => (terminated!($i, call!($f), line_ending))
+}
+
+/// Parse the stat state format.
+fn parse_stat_state(input: &str) -> Result<ProcessState> {
+    let mut chars = input.chars();
+    match (chars.next(), chars.next()) {
+        (Some(c), None) => Ok(ProcessState::from_char(c)),
+        _ => Err(invalid_input(format!("invalid state: {:?}", input))),
     }
+}
+
+/// Parse the stat format.
+fn parse_stat(input: &[u8]) -> Result<StatRef<'_>> {
+    let line = parse_line(input)?;
+    let (pid, rest) = line.split_once(' ')
+                          .ok_or_else(|| invalid_input(format!("invalid stat: {:?}", line)))?;
+    let (command, rest) = parse_command(rest)?;
+    let mut fields = Fields::new(rest);
+
+    let state                 = parse_stat_state(fields.next_str()?)?;
+    let ppid                  = fields.parse()?;
+    let pgrp                  = fields.parse()?;
+    let session               = fields.parse()?;
+    let tty_nr                = fields.parse()?;
+    let tty_pgrp              = fields.parse()?;
+    let flags                 = fields.parse()?;
+    let minflt                = fields.parse()?;
+    let cminflt               = fields.parse()?;
+    let majflt                = fields.parse()?;
+    let cmajflt               = fields.parse()?;
+    let utime                 = fields.parse()?;
+    let stime                 = fields.parse()?;
+    let cutime                = fields.parse()?;
+    let cstime                = fields.parse()?;
+    let priority              = fields.parse()?;
+    let nice                  = fields.parse()?;
+    let num_threads           = fields.parse()?;
+    let _itrealvalue          = fields.parse::<i32>()?;
+    let start_time            = fields.parse()?;
+    let vsize                 = fields.parse()?;
+    let rss                   = fields.parse()?;
+    let rsslim                = fields.parse()?;
+    let start_code            = fields.parse()?;
+    let end_code              = fields.parse()?;
+    let startstack            = fields.parse()?;
+    let kstkeep               = fields.parse()?;
+    let kstkeip               = fields.parse()?;
+    let signal                = fields.parse()?;
+    let blocked               = fields.parse()?;
+    let sigignore             = fields.parse()?;
+    let sigcatch              = fields.parse()?;
+    let wchan                 = fields.parse()?;
+    let _nswap                = fields.parse::<usize>()?;
+    let _cnswap               = fields.parse::<usize>()?;
+    let exit_signal           = fields.parse()?;
+    let processor             = fields.parse()?;
+    let rt_priority           = fields.parse()?;
+    let policy                = fields.parse()?;
+    let delayacct_blkio_ticks = fields.parse_opt()?;
+    let guest_time            = fields.parse_opt()?;
+    let cguest_time           = fields.parse_opt()?;
+    let start_data            = fields.parse_opt()?;
+    let end_data              = fields.parse_opt()?;
+    let start_brk             = fields.parse_opt()?;
+    let arg_start             = fields.parse_opt()?;
+    let arg_end               = fields.parse_opt()?;
+    let env_start             = fields.parse_opt()?;
+    let env_end               = fields.parse_opt()?;
+    let exit_code             = fields.parse_opt()?;
+    fields.finish()?;
 
-    let rest = input;
-
-    let (rest, pid)                   = try_parse!(rest, s!(parse_i32        ));
-    let (rest, command)               = try_parse!(rest, s!(parse_command    ));
-    let (rest, state)                 = try_parse!(rest, s!(parse_stat_state ));
-    let (rest, ppid)                  = try_parse!(rest, s!(parse_i32        ));
-    let (rest, pgrp)                  = try_parse!(rest, s!(parse_i32        ));
-    let (rest, session)               = try_parse!(rest, s!(parse_i32        ));
-    let (rest, tty_nr)                = try_parse!(rest, s!(parse_i32        ));
-    let (rest, tty_pgrp)              = try_parse!(rest, s!(parse_i32        ));
-    let (rest, flags)                 = try_parse!(rest, s!(parse_u32        ));
-    let (rest, minflt)                = try_parse!(rest, s!(parse_usize      ));
-    let (rest, cminflt)               = try_parse!(rest, s!(parse_usize      ));
-    let (rest, majflt)                = try_parse!(rest, s!(parse_usize      ));
-    let (rest, cmajflt)               = try_parse!(rest, s!(parse_usize      ));
-    let (rest, utime)                 = try_parse!(rest, s!(parse_clock      ));
-    let (rest, stime)                 = try_parse!(rest, s!(parse_clock      ));
-    let (rest, cutime)                = try_parse!(rest, s!(parse_clock      ));
-    let (rest, cstime)                = try_parse!(rest, s!(parse_clock      ));
-    let (rest, priority)              = try_parse!(rest, s!(parse_i32        ));
-    let (rest, nice)                  = try_parse!(rest, s!(parse_i32        ));
-    let (rest, num_threads)           = try_parse!(rest, s!(parse_i32        ));
-    let (rest, _itrealvalue)          = try_parse!(rest, s!(parse_i32        ));
-    let (rest, start_time)            = try_parse!(rest, s!(parse_u64        ));
-    let (rest, vsize)                 = try_parse!(rest, s!(parse_usize      ));
-    let (rest, rss)                   = try_parse!(rest, s!(parse_usize      ));
-    let (rest, rsslim)                = try_parse!(rest, s!(parse_usize      ));
-    let (rest, start_code)            = try_parse!(rest, s!(parse_usize      ));
-    let (rest, end_code)              = try_parse!(rest, s!(parse_usize      ));
-    let (rest, startstack)            = try_parse!(rest, s!(parse_usize      ));
-    let (rest, kstkeep)               = try_parse!(rest, s!(parse_usize      ));
-    let (rest, kstkeip)               = try_parse!(rest, s!(parse_usize      ));
-    let (rest, signal)                = try_parse!(rest, s!(parse_usize      ));
-    let (rest, blocked)               = try_parse!(rest, s!(parse_usize      ));
-    let (rest, sigignore)             = try_parse!(rest, s!(parse_usize      ));
-    let (rest, sigcatch)              = try_parse!(rest, s!(parse_usize      ));
-    let (rest, wchan)                 = try_parse!(rest, s!(parse_usize      ));
-    let (rest, _nswap)                = try_parse!(rest, s!(parse_usize      ));
-    let (rest, _cnswap)               = try_parse!(rest, s!(parse_usize      ));
-    let (rest, exit_signal)           = try_parse!(rest, s!(parse_i32        ));
-    let (rest, processor)             = try_parse!(rest, s!(parse_u32        ));
-    let (rest, rt_priority)           = try_parse!(rest, s!(parse_u32        ));
-    let (rest, policy)                = try_parse!(rest, s!(parse_u32        ));
-    let (rest, delayacct_blkio_ticks) = try_parse!(rest, s!(parse_u64        ));
-    let (rest, guest_time)            = try_parse!(rest, s!(parse_clock      ));
-    let (rest, cguest_time)           = try_parse!(rest, s!(parse_clock      ));
-    let (rest, start_data)            = try_parse!(rest, s!(parse_usize      ));
-    let (rest, end_data)              = try_parse!(rest, s!(parse_usize      ));
-    let (rest, start_brk)             = try_parse!(rest, s!(parse_usize      ));
-    let (rest, arg_start)             = try_parse!(rest, s!(parse_usize      ));
-    let (rest, arg_end)               = try_parse!(rest, s!(parse_usize      ));
-    let (rest, env_start)             = try_parse!(rest, s!(parse_usize      ));
-    let (rest, env_end)               = try_parse!(rest, s!(parse_usize      ));
-    let (rest, exit_code)             = try_parse!(rest, l!(parse_i32        ));
-
-    IResult::Done(rest, Stat {
-        pid                   : pid,
-        command               : command,
-        state                 : state,
-        ppid                  : ppid,
-        pgrp                  : pgrp,
-        session               : session,
-        tty_nr                : tty_nr,
-        tty_pgrp              : tty_pgrp,
-        flags                 : flags,
-        minflt                : minflt,
-        cminflt               : cminflt,
-        majflt                : majflt,
-        cmajflt               : cmajflt,
-        utime                 : utime,
-        stime                 : stime,
-        cutime                : cutime,
-        cstime                : cstime,
-        priority              : priority,
-        nice                  : nice,
-        num_threads           : num_threads,
-        start_time            : start_time,
-        vsize                 : vsize,
-        rss                   : rss,
-        rsslim                : rsslim,
-        start_code            : start_code,
-        end_code              : end_code,
-        startstack            : startstack,
-        kstkeep               : kstkeep,
-        kstkeip               : kstkeip,
-        signal                : signal,
-        blocked               : blocked,
-        sigignore             : sigignore,
-        sigcatch              : sigcatch,
-        wchan                 : wchan,
-        exit_signal           : exit_signal,
-        processor             : processor,
-        rt_priority           : rt_priority,
-        policy                : policy,
-        delayacct_blkio_ticks : delayacct_blkio_ticks,
-        guest_time            : guest_time,
-        cguest_time           : cguest_time,
-        start_data            : start_data,
-        end_data              : end_data,
-        start_brk             : start_brk,
-        arg_start             : arg_start,
-        arg_end               : arg_end,
-        env_start             : env_start,
-        env_end               : env_end,
-        exit_code             : exit_code,
+    Ok(Stat {
+        pid: parse(pid)?,
+        command,
+        state,
+        ppid,
+        pgrp,
+        session,
+        tty_nr,
+        tty_pgrp,
+        flags,
+        minflt,
+        cminflt,
+        majflt,
+        cmajflt,
+        utime,
+        stime,
+        cutime,
+        cstime,
+        priority,
+        nice,
+        num_threads,
+        start_time,
+        vsize,
+        rss,
+        rsslim,
+        start_code,
+        end_code,
+        startstack,
+        kstkeep,
+        kstkeip,
+        signal,
+        blocked,
+        sigignore,
+        sigcatch,
+        wchan,
+        exit_signal,
+        processor,
+        rt_priority,
+        policy,
+        delayacct_blkio_ticks,
+        guest_time,
+        cguest_time,
+        start_data,
+        end_data,
+        start_brk,
+        arg_start,
+        arg_end,
+        env_start,
+        env_end,
+        exit_code,
     })
 }
 
+/// Converts a signed clock tick count to a `Duration`.
+fn clock_duration(ticks: i64) -> Duration {
+    ticks_to_duration(ticks.max(0) as u64)
+}
+
+impl Stat {
+    /// Parses the contents of a `/proc/[pid]/stat` file.
+    pub fn parse(input: &[u8]) -> Result<Stat> {
+        parse_stat(input).map(|stat| stat.into_owned())
+    }
+}
+
+impl<'a> StatRef<'a> {
+    /// Parses the contents of a `/proc/[pid]/stat` file, borrowing the command from the input.
+    pub fn parse_ref(input: &'a [u8]) -> Result<StatRef<'a>> {
+        parse_stat(input)
+    }
+
+    /// Copies the command, returning an owned `Stat`.
+    pub fn into_owned(self) -> Stat {
+        self.map_command(str::to_owned)
+    }
+}
+
+impl<S> Stat<S> {
+    /// Returns the status information with the command converted by the provided function.
+    fn map_command<T, F>(self, map: F) -> Stat<T> where F: FnOnce(S) -> T {
+        Stat {
+            pid: self.pid,
+            command: map(self.command),
+            state: self.state,
+            ppid: self.ppid,
+            pgrp: self.pgrp,
+            session: self.session,
+            tty_nr: self.tty_nr,
+            tty_pgrp: self.tty_pgrp,
+            flags: self.flags,
+            minflt: self.minflt,
+            cminflt: self.cminflt,
+            majflt: self.majflt,
+            cmajflt: self.cmajflt,
+            utime: self.utime,
+            stime: self.stime,
+            cutime: self.cutime,
+            cstime: self.cstime,
+            priority: self.priority,
+            nice: self.nice,
+            num_threads: self.num_threads,
+            start_time: self.start_time,
+            vsize: self.vsize,
+            rss: self.rss,
+            rsslim: self.rsslim,
+            start_code: self.start_code,
+            end_code: self.end_code,
+            startstack: self.startstack,
+            kstkeep: self.kstkeep,
+            kstkeip: self.kstkeip,
+            signal: self.signal,
+            blocked: self.blocked,
+            sigignore: self.sigignore,
+            sigcatch: self.sigcatch,
+            wchan: self.wchan,
+            exit_signal: self.exit_signal,
+            processor: self.processor,
+            rt_priority: self.rt_priority,
+            policy: self.policy,
+            delayacct_blkio_ticks: self.delayacct_blkio_ticks,
+            guest_time: self.guest_time,
+            cguest_time: self.cguest_time,
+            start_data: self.start_data,
+            end_data: self.end_data,
+            start_brk: self.start_brk,
+            arg_start: self.arg_start,
+            arg_end: self.arg_end,
+            env_start: self.env_start,
+            env_end: self.env_end,
+            exit_code: self.exit_code,
+        }
+    }
+
+    /// Returns the decoded kernel flags of the process.
+    ///
+    /// Flags which are unknown to `TaskFlags` are not retained; they remain available in `flags`.
+    pub fn task_flags(&self) -> TaskFlags {
+        TaskFlags::from_bits_truncate(self.flags)
+    }
+
+    /// Returns whether the process is a kernel thread, according to its `PF_KTHREAD` flag.
+    pub fn is_kernel_thread(&self) -> bool {
+        self.task_flags().contains(TaskFlags::KTHREAD)
+    }
+
+    /// Returns the time the process has been scheduled in user mode, including guest time.
+    pub fn utime_duration(&self) -> Duration {
+        ticks_to_duration(self.utime)
+    }
+
+    /// Returns the time the process has been scheduled in kernel mode.
+    pub fn stime_duration(&self) -> Duration {
+        ticks_to_duration(self.stime)
+    }
+
+    /// Returns the time the process's waited-for children have been scheduled in user mode.
+    pub fn cutime_duration(&self) -> Duration {
+        clock_duration(self.cutime)
+    }
+
+    /// Returns the time the process's waited-for children have been scheduled in kernel mode.
+    pub fn cstime_duration(&self) -> Duration {
+        clock_duration(self.cstime)
+    }
+
+    /// Returns the time spent running a virtual CPU for a guest operating system (since Linux
+    /// 2.6.24).
+    pub fn guest_time_duration(&self) -> Option<Duration> {
+        self.guest_time.map(ticks_to_duration)
+    }
+
+    /// Returns the time the process's children have spent running a virtual CPU for a guest
+    /// operating system (since Linux 2.6.24).
+    pub fn cguest_time_duration(&self) -> Option<Duration> {
+        self.cguest_time.map(clock_duration)
+    }
+
+    /// Returns the time the process started after system boot.
+    pub fn start_time_duration(&self) -> Duration {
+        ticks_to_duration(self.start_time)
+    }
+
+    /// Returns the wall clock time at which the process started.
+    ///
+    /// This is derived from the system boot time, and has a resolution of one second.
+    pub fn started_at(&self) -> Result<SystemTime> {
+        Ok(boot_time()? + self.start_time_duration())
+    }
+
+    /// Returns the resident set size in bytes.
+    pub fn rss_bytes(&self) -> u64 {
+        self.rss * page_size() as u64
+    }
+}
+
 /// Parses the provided stat file.
-fn stat_file(file: &mut File) -> Result<Stat> {
+pub(crate) fn stat_file(file: &mut File) -> Result<Stat> {
     let mut buf = [0; 1024]; // A typical statm file is about 300 bytes
-    map_result(parse_stat(try!(read_to_end(file, &mut buf))))
+    Stat::parse(&read_to_end(file, &mut buf)?)
 }
 
 /// Returns status information for the process with the provided pid.
 pub fn stat(pid: pid_t) -> Result<Stat> {
-    stat_file(&mut try!(File::open(&format!("/proc/{}/stat", pid))))
+    parse_file(format!("/proc/{}/stat", pid), stat_file)
 }
 
 /// Returns status information for the current process.
 pub fn stat_self() -> Result<Stat> {
-    stat_file(&mut try!(File::open("/proc/self/stat")))
+    parse_file("/proc/self/stat", stat_file)
 }
 
 /// Returns status information from the thread with the provided parent process ID and thread ID.
 pub fn stat_task(process_id: pid_t, thread_id: pid_t) -> Result<Stat> {
-    stat_file(&mut try!(File::open(&format!("/proc/{}/task/{}/stat", process_id, thread_id))))
+    parse_file(format!("/proc/{}/task/{}/stat", process_id, thread_id), stat_file)
+}
+
+/// A scan of the stat files of all processes, in no particular order.
+///
+/// A single buffer is reused for every process, so `next_ref` and `select` do not allocate per
+/// process. Processes which exit during the scan are skipped, as are kernel threads if the scan
+/// was created with `without_kernel_threads`. Created by `stat_all`.
+#[derive(Debug)]
+pub struct StatAll {
+    pids: Pids,
+    path: String,
+    buf: Vec<u8>,
+    skip_kernel_threads: bool,
+}
+
+impl StatAll {
+    /// Reads the next process's stat file into the buffer.
+    fn read_next(&mut self) -> Option<Result<()>> {
+        let StatAll { ref mut pids, ref mut path, ref mut buf, .. } = *self;
+        for pid in pids {
+            let pid = match pid {
+                Ok(pid) => pid,
+                Err(error) => return Some(Err(error)),
+            };
+            path.clear();
+            write!(path, "/proc/{}/stat", pid).unwrap();
+            buf.clear();
+            match File::open(&*path).and_then(|mut file| file.read_to_end(buf)) {
+                Ok(_) => return Some(Ok(())),
+                Err(ref error) if is_exited(error) => continue,
+                Err(error) => return Some(Err(error)),
+            }
+        }
+        None
+    }
+
+    /// Returns the status information of the next process, borrowing the command from the scan's
+    /// buffer.
+    pub fn next_ref(&mut self) -> Option<Result<StatRef<'_>>> {
+        loop {
+            if let Err(error) = self.read_next()? {
+                return Some(Err(error));
+            }
+            // The command is converted to its range in the buffer, so that the returned status
+            // information does not borrow the buffer while the loop reads the next process.
+            let stat = match parse_stat(&self.buf) {
+                Ok(ref stat) if self.skip_kernel_threads && stat.is_kernel_thread() => continue,
+                Ok(stat) => {
+                    let start = stat.command.as_ptr() as usize - self.buf.as_ptr() as usize;
+                    stat.map_command(|command| start..start + command.len())
+                },
+                Err(error) => return Some(Err(with_path(error, Path::new(&self.path)))),
+            };
+            let command = match str::from_utf8(&self.buf[stat.command.clone()]) {
+                Ok(command) => command,
+                Err(error) => return Some(Err(invalid_input(error))),
+            };
+            return Some(Ok(stat.map_command(|_| command)));
+        }
+    }
+
+    /// Returns the scan with kernel threads skipped.
+    ///
+    /// ```no_run
+    /// use procinfo::pid::stat_all;
+    ///
+    /// for stat in stat_all().unwrap().without_kernel_threads() {
+    ///     println!("{}", stat.unwrap().command);
+    /// }
+    /// ```
+    pub fn without_kernel_threads(mut self) -> StatAll {
+        self.skip_kernel_threads = true;
+        self
+    }
+
+    /// Returns an iterator over the fields selected from the status information of each process.
+    ///
+    /// ```no_run
+    /// use procinfo::pid::stat_all;
+    ///
+    /// for process in stat_all().unwrap().select(|stat| (stat.pid, stat.utime + stat.stime)) {
+    ///     let (pid, ticks) = process.unwrap();
+    ///     println!("{} {}", pid, ticks);
+    /// }
+    /// ```
+    pub fn select<T, F>(self, select: F) -> StatSelect<F> where F: FnMut(&StatRef) -> T {
+        StatSelect { scan: self, select }
+    }
+}
+
+impl Iterator for StatAll {
+    type Item = Result<Stat>;
+
+    fn next(&mut self) -> Option<Result<Stat>> {
+        self.next_ref().map(|stat| stat.map(StatRef::into_owned))
+    }
+}
+
+/// An iterator over fields selected from the status information of all processes.
+///
+/// Created by `StatAll::select`.
+#[derive(Debug)]
+pub struct StatSelect<F> {
+    scan: StatAll,
+    select: F,
+}
+
+impl<T, F> Iterator for StatSelect<F> where F: FnMut(&StatRef) -> T {
+    type Item = Result<T>;
+
+    fn next(&mut self) -> Option<Result<T>> {
+        let select = &mut self.select;
+        self.scan.next_ref().map(|stat| stat.map(|stat| select(&stat)))
+    }
+}
+
+/// Returns a scan of the status information of all processes.
+pub fn stat_all() -> Result<StatAll> {
+    Ok(StatAll {
+        pids: pids()?,
+        path: String::new(),
+        buf: Vec::with_capacity(1024),
+        skip_kernel_threads: false,
+    })
 }
 
 #[cfg(test)]
 pub mod tests {
-    use parsers::tests::unwrap;
-    use pid::State;
+    use std::time::SystemTime;
+
+    use libc;
+
+    use pid::ProcessState;
+    use sysconf::{page_size, ticks_per_second};
     use super::{
+        Stat,
+        StatRef,
+        TaskFlags,
         parse_command,
         parse_stat,
         stat,
+        stat_all,
         stat_self
     };
 
     #[test]
     fn test_parse_command() {
-        assert_eq!("cat", &unwrap(parse_command(b"(cat)")));
-        assert_eq!("cat )  (( )) ", &unwrap(parse_command(b"(cat )  (( )) )")));
+        assert_eq!(("cat", ""), parse_command("(cat)").unwrap());
+        assert_eq!(("cat )  (( )) ", " R"), parse_command("(cat )  (( )) ) R").unwrap());
+        parse_command("cat)").unwrap_err();
     }
 
     /// Test that the system stat files can be parsed.
     #[test]
     fn test_stat() {
-        stat_self().unwrap();
+        assert!(stat_self().unwrap().started_at().unwrap() <= SystemTime::now());
         stat(1).unwrap();
     }
 
+    #[test]
+    fn test_stat_all() {
+        let pid = unsafe { libc::getpid() };
+        let mut scan = stat_all().unwrap();
+        let mut found = false;
+        while let Some(stat) = scan.next_ref() {
+            found |= stat.unwrap().pid == pid;
+        }
+        assert!(found);
+
+        let stats = stat_all().unwrap().collect::<Result<Vec<_>, _>>().unwrap();
+        assert!(stats.iter().any(|stat| stat.pid == 1));
+
+        let processes = stat_all().unwrap()
+                                  .select(|stat| (stat.pid, stat.ppid))
+                                  .collect::<Result<Vec<_>, _>>()
+                                  .unwrap();
+        assert!(processes.contains(&(1, 0)));
+
+        let stats = stat_all().unwrap()
+                              .without_kernel_threads()
+                              .collect::<Result<Vec<_>, _>>()
+                              .unwrap();
+        assert!(stats.iter().any(|stat| stat.pid == pid));
+        assert!(!stats.iter().any(|stat| stat.is_kernel_thread()));
+    }
+
     #[test]
     fn test_parse_stat() {
         let text = b"19853 (cat) R 19435 19853 19435 34819 19853 4218880 98 0 0 0 0 0 0 0 20 0 1 0 \
                      279674171 112295936 180 18446744073709551615 4194304 4238772 140736513999744 \
                      140736513999080 139957028908944 0 0 0 0 0 0 0 17 15 0 0 0 0 0 6339648 6341408 \
                      17817600 140736514006312 140736514006332 140736514006332 140736514007019 0\n";
-        let stat = unwrap(parse_stat(text));
+        let stat = parse_stat(text).unwrap();
 
         assert_eq!(19853, stat.pid);
-        assert_eq!("cat", &stat.command);
-        assert_eq!(State::Running, stat.state);
+        assert_eq!("cat", stat.command);
+        assert_eq!(ProcessState::Running, stat.state);
         assert_eq!(19435, stat.ppid);
         assert_eq!(19853, stat.pgrp);
         assert_eq!(19435, stat.session);
         assert_eq!(34819, stat.tty_nr);
         assert_eq!(19853, stat.tty_pgrp);
         assert_eq!(4218880, stat.flags);
+        assert_eq!(TaskFlags::RANDOMIZE | TaskFlags::USED_MATH, stat.task_flags());
         assert_eq!(98, stat.minflt);
         assert_eq!(0, stat.cminflt);
         assert_eq!(0, stat.majflt);
@@ -375,8 +731,10 @@ pub mod tests {
         assert_eq!(0, stat.nice);
         assert_eq!(1, stat.num_threads);
         assert_eq!(279674171, stat.start_time);
+        assert_eq!(279674171 / ticks_per_second(), stat.start_time_duration().as_secs());
         assert_eq!(112295936, stat.vsize);
         assert_eq!(180, stat.rss);
+        assert_eq!(180 * page_size() as u64, stat.rss_bytes());
         assert_eq!(18446744073709551615, stat.rsslim);
         assert_eq!(4194304, stat.start_code);
         assert_eq!(4238772, stat.end_code);
@@ -392,38 +750,46 @@ pub mod tests {
         assert_eq!(15, stat.processor);
         assert_eq!(0, stat.rt_priority);
         assert_eq!(0, stat.policy);
-        assert_eq!(0, stat.delayacct_blkio_ticks);
-        assert_eq!(0, stat.guest_time);
-        assert_eq!(0, stat.cguest_time);
-        assert_eq!(6339648, stat.start_data);
-        assert_eq!(6341408, stat.end_data);
-        assert_eq!(17817600, stat.start_brk);
-        assert_eq!(140736514006312, stat.arg_start);
-        assert_eq!(140736514006332, stat.arg_end);
-        assert_eq!(140736514006332, stat.env_start);
-        assert_eq!(140736514007019, stat.env_end);
-        assert_eq!(0, stat.exit_code);
+        assert_eq!(Some(0), stat.delayacct_blkio_ticks);
+        assert_eq!(Some(0), stat.guest_time);
+        assert_eq!(Some(0), stat.cguest_time);
+        assert_eq!(Some(6339648), stat.start_data);
+        assert_eq!(Some(6341408), stat.end_data);
+        assert_eq!(Some(17817600), stat.start_brk);
+        assert_eq!(Some(140736514006312), stat.arg_start);
+        assert_eq!(Some(140736514006332), stat.arg_end);
+        assert_eq!(Some(140736514006332), stat.env_start);
+        assert_eq!(Some(140736514007019), stat.env_end);
+        assert_eq!(Some(0), stat.exit_code);
     }
-}
 
-#[cfg(all(test, rustc_nightly))]
-mod benches {
-    extern crate test;
-
-    use std::fs::File;
-
-    use parsers::read_to_end;
-    use super::{parse_stat, stat};
+    /// Test parsing a stat file from a kernel which predates the trailing fields (2.6.32).
+    #[test]
+    fn test_parse_stat_truncated() {
+        let text = b"1 (init) S 0 1 1 0 -1 4202752 3318 2352883 26 1393 4 124 19837 4862 20 0 1 0 \
+                     2 19750912 387 18446744073709551615 1 1 0 0 0 0 0 4096 536962595 \
+                     18446744073709551615 0 0 17 0 0 0 0 0 0\n";
+        let stat = parse_stat(text).unwrap();
 
-    #[bench]
-    fn bench_stat(b: &mut test::Bencher) {
-        b.iter(|| test::black_box(stat(1)));
+        assert_eq!(1, stat.pid);
+        assert_eq!("init", stat.command);
+        assert_eq!(0, stat.policy);
+        assert_eq!(Some(0), stat.delayacct_blkio_ticks);
+        assert_eq!(Some(0), stat.guest_time);
+        assert_eq!(Some(0), stat.cguest_time);
+        assert_eq!(None, stat.start_data);
+        assert_eq!(None, stat.exit_code);
     }
 
-    #[bench]
-    fn bench_stat_parse(b: &mut test::Bencher) {
-        let mut buf = [0; 256];
-        let stat = read_to_end(&mut File::open("/proc/1/stat").unwrap(), &mut buf).unwrap();
-        b.iter(|| test::black_box(parse_stat(stat)));
+    /// Test that borrowed parsing matches owned parsing.
+    #[test]
+    fn test_parse_stat_ref() {
+        let text = b"1 (a (b) c) S 0 1 1 0 -1 4202752 3318 2352883 26 1393 4 124 19837 4862 20 0 1 \
+                     0 2 19750912 387 18446744073709551615 1 1 0 0 0 0 0 4096 536962595 \
+                     18446744073709551615 0 0 17 0 0 0 0 0 0\n";
+        let stat = StatRef::parse_ref(text).unwrap();
+        assert_eq!("a (b) c", stat.command);
+        assert_eq!(387, stat.rss);
+        assert_eq!(Stat::parse(text).unwrap(), stat.into_owned());
     }
 }
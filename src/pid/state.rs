@@ -0,0 +1,106 @@
+//! The scheduling state of a process, shared by `/proc/[pid]/stat` and `/proc/[pid]/status`.
+
+/// The state of a process.
+///
+/// See `man 5 proc` and `task_state_array` in `Linux/fs/proc/array.c`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "export", derive(Serialize))]
+pub enum ProcessState {
+    /// Running.
+    #[default]
+    Running,
+    /// Sleeping in an interruptible wait.
+    Sleeping,
+    /// Waiting in uninterruptible disk sleep.
+    DiskSleep,
+    /// Zombie.
+    Zombie,
+    /// Stopped (on a signal) or (before Linux 2.6.33) trace stopped.
+    Stopped,
+    /// Stopped by a tracer.
+    ///
+    /// Linux 2.6.33 onward.
+    TracingStop,
+    /// Paging.
+    ///
+    /// Only before linux 2.6.0.
+    Paging,
+    /// Dead.
+    ///
+    /// Linux 2.6.33 to 3.13 only.
+    Dead,
+    /// Wakekill.
+    ///
+    /// Linux 2.6.33 to 3.13 only.
+    Wakekill,
+    /// Waking.
+    ///
+    /// Linux 2.6.33 to 3.13 only.
+    Waking,
+    /// Parked.
+    ///
+    /// Linux 3.9 to 3.13 only.
+    Parked,
+    /// Idle kernel thread.
+    ///
+    /// Linux 4.14 onward.
+    Idle,
+    /// A state letter which is unknown to this crate.
+    Other(char),
+}
+
+impl ProcessState {
+    /// Returns the state with the provided letter, as found in `/proc/[pid]/stat`.
+    ///
+    /// `W` is interpreted as `Paging`, since `/proc/[pid]/stat` does not distinguish it from
+    /// `Waking`.
+    pub fn from_char(c: char) -> ProcessState {
+        match c {
+            'R' => ProcessState::Running,
+            'S' => ProcessState::Sleeping,
+            'D' => ProcessState::DiskSleep,
+            'Z' => ProcessState::Zombie,
+            'T' => ProcessState::Stopped,
+            't' => ProcessState::TracingStop,
+            'W' => ProcessState::Paging,
+            'X' | 'x' => ProcessState::Dead,
+            'K' => ProcessState::Wakekill,
+            'P' => ProcessState::Parked,
+            'I' => ProcessState::Idle,
+            c => ProcessState::Other(c),
+        }
+    }
+
+    /// Returns the letter of the state.
+    pub fn as_char(self) -> char {
+        match self {
+            ProcessState::Running => 'R',
+            ProcessState::Sleeping => 'S',
+            ProcessState::DiskSleep => 'D',
+            ProcessState::Zombie => 'Z',
+            ProcessState::Stopped => 'T',
+            ProcessState::TracingStop => 't',
+            ProcessState::Paging | ProcessState::Waking => 'W',
+            ProcessState::Dead => 'X',
+            ProcessState::Wakekill => 'K',
+            ProcessState::Parked => 'P',
+            ProcessState::Idle => 'I',
+            ProcessState::Other(c) => c,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ProcessState;
+
+    #[test]
+    fn test_from_char() {
+        for &c in &['R', 'S', 'D', 'Z', 'T', 't', 'W', 'X', 'K', 'P', 'I', 'Q'] {
+            assert_eq!(c, ProcessState::from_char(c).as_char());
+        }
+        assert_eq!(ProcessState::Dead, ProcessState::from_char('x'));
+        assert_eq!(ProcessState::Other('Q'), ProcessState::from_char('Q'));
+        assert_eq!('W', ProcessState::Waking.as_char());
+    }
+}
@@ -4,68 +4,102 @@ use std::fs::File;
 use std::io::Result;
 
 use libc::pid_t;
-use nom::{digit, line_ending, space};
 
-use parsers::{map_result, parse_usize, read_to_end};
+use parsers::{Fields, parse_file, parse_line, read_to_end};
+use sysconf::page_size;
 
 /// Process memory usage information.
 ///
-/// All values are in units of pages.
+/// All values are in units of pages. The `*_bytes` methods convert them to bytes.
 ///
 /// See `man 5 proc` and `Linux/fs/proc/array.c`.
 #[derive(Debug, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "export", derive(Serialize))]
 pub struct Statm {
     /// Total virtual memory size.
-    pub size: usize,
+    pub size: u64,
     /// Resident non-swapped memory.
-    pub resident: usize,
+    pub resident: u64,
     /// Shared memory.
-    pub share: usize,
+    pub share: u64,
     /// Resident executable memory.
-    pub text: usize,
+    pub text: u64,
     /// Resident data and stack memory.
-    pub data: usize,
+    pub data: u64,
 }
 
 /// Parses the statm file format.
-named!(parse_statm<Statm>,
-    chain!(size: parse_usize     ~ space ~
-           resident: parse_usize ~ space ~
-           share: parse_usize    ~ space ~
-           text: parse_usize     ~ space ~
-           digit                 ~ space ~         // lib - unused since linux 2.6
-           data: parse_usize     ~ space ~
-           digit                 ~ line_ending,    // dt - unused since linux 2.6
-           || { Statm { size: size,
-                        resident: resident,
-                        share: share,
-                        text: text,
-                        data: data } }));
+fn parse_statm(input: &[u8]) -> Result<Statm> {
+    let mut fields = Fields::new(parse_line(input)?);
+    let size = fields.parse()?;
+    let resident = fields.parse()?;
+    let share = fields.parse()?;
+    let text = fields.parse()?;
+    let _lib = fields.parse::<usize>()?; // unused since linux 2.6
+    let data = fields.parse()?;
+    let _dt = fields.parse::<usize>()?; // unused since linux 2.6
+    fields.finish()?;
+    Ok(Statm { size, resident, share, text, data })
+}
+
+impl Statm {
+    /// Parses the contents of a `/proc/[pid]/statm` file.
+    pub fn parse(input: &[u8]) -> Result<Statm> {
+        parse_statm(input)
+    }
+
+    /// Returns the total virtual memory size in bytes.
+    pub fn size_bytes(&self) -> u64 {
+        self.size * page_size() as u64
+    }
+
+    /// Returns the resident non-swapped memory in bytes.
+    pub fn resident_bytes(&self) -> u64 {
+        self.resident * page_size() as u64
+    }
+
+    /// Returns the shared memory in bytes.
+    pub fn share_bytes(&self) -> u64 {
+        self.share * page_size() as u64
+    }
+
+    /// Returns the resident executable memory in bytes.
+    pub fn text_bytes(&self) -> u64 {
+        self.text * page_size() as u64
+    }
+
+    /// Returns the resident data and stack memory in bytes.
+    pub fn data_bytes(&self) -> u64 {
+        self.data * page_size() as u64
+    }
+}
 
 /// Parses the provided statm file.
-fn statm_file(file: &mut File) -> Result<Statm> {
+pub(crate) fn statm_file(file: &mut File) -> Result<Statm> {
     let mut buf = [0; 256]; // A typical statm file is about 25 bytes
-    map_result(parse_statm(try!(read_to_end(file, &mut buf))))
+    Statm::parse(&read_to_end(file, &mut buf)?)
 }
 
 /// Returns memory status information for the process with the provided pid.
 pub fn statm(pid: pid_t) -> Result<Statm> {
-    statm_file(&mut try!(File::open(&format!("/proc/{}/statm", pid))))
+    parse_file(format!("/proc/{}/statm", pid), statm_file)
 }
 
 /// Returns memory status information for the current process.
 pub fn statm_self() -> Result<Statm> {
-    statm_file(&mut try!(File::open("/proc/self/statm")))
+    parse_file("/proc/self/statm", statm_file)
 }
 
-/// Returns memory status information from the thread with the provided parent process ID and thread ID.
+/// Returns memory status information from the thread with the provided parent process ID and
+/// thread ID.
 pub fn statm_task(process_id: pid_t, thread_id: pid_t) -> Result<Statm> {
-    statm_file(&mut try!(File::open(&format!("/proc/{}/task/{}/statm", process_id, thread_id))))
+    parse_file(format!("/proc/{}/task/{}/statm", process_id, thread_id), statm_file)
 }
 
 #[cfg(test)]
 mod tests {
-    use parsers::tests::unwrap;
+    use sysconf::page_size;
+
     use super::{parse_statm, statm, statm_self};
 
     /// Test that the system statm files can be parsed.
@@ -78,33 +112,12 @@ mod tests {
     #[test]
     fn test_parse_statm() {
         let statm_text = b"11837 2303 1390 330 0 890 0\n";
-        let statm = unwrap(parse_statm(statm_text));
+        let statm = parse_statm(statm_text).unwrap();
         assert_eq!(11837, statm.size);
         assert_eq!(2303, statm.resident);
         assert_eq!(1390, statm.share);
         assert_eq!(330, statm.text);
         assert_eq!(890, statm.data);
-    }
-}
-
-#[cfg(all(test, rustc_nightly))]
-mod benches {
-    extern crate test;
-
-    use std::fs::File;
-
-    use parsers::read_to_end;
-    use super::{parse_statm, statm};
-
-    #[bench]
-    fn bench_statm(b: &mut test::Bencher) {
-        b.iter(|| test::black_box(statm(1)));
-    }
-
-    #[bench]
-    fn bench_statm_parse(b: &mut test::Bencher) {
-        let mut buf = [0; 256];
-        let statm = read_to_end(&mut File::open("/proc/1/statm").unwrap(), &mut buf).unwrap();
-        b.iter(|| test::black_box(parse_statm(statm)));
+        assert_eq!(2303 * page_size() as u64, statm.resident_bytes());
     }
 }
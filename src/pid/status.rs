@@ -1,62 +1,536 @@
 //! Process status information information from `/proc/[pid]/status`.
 
+use std::borrow::Cow;
+use std::fmt;
 use std::fs::File;
 use std::io::Result;
 
-use libc::{gid_t, mode_t, pid_t, uid_t};
-use nom::{IResult, line_ending, multispace, not_line_ending, space};
+use libc::{
+    c_int,
+    pid_t,
+    SIGABRT, SIGALRM, SIGBUS, SIGCHLD, SIGCONT, SIGFPE, SIGHUP, SIGILL, SIGINT, SIGIO, SIGKILL,
+    SIGPIPE, SIGPROF, SIGPWR, SIGQUIT, SIGSEGV, SIGSTKFLT, SIGSTOP, SIGSYS, SIGTERM, SIGTRAP,
+    SIGTSTP, SIGTTIN, SIGTTOU, SIGURG, SIGUSR1, SIGUSR2, SIGVTALRM, SIGWINCH, SIGXCPU, SIGXFSZ,
+};
 
+use cpuset::{CpuSet, NodeSet};
 use parsers::{
-    map_result,
-    parse_i32,
+    Fields,
+    ParseWarning,
+    invalid_input,
+    parse,
     parse_bit,
-    parse_i32s,
+    parse_file,
     parse_kb,
-    parse_line,
-    parse_u32,
+    parse_list,
+    parse_str,
     parse_u32_mask_list,
     parse_u32_octal,
-    parse_u32s,
-    parse_u64,
     parse_u64_hex,
-    read_to_end
+    read_to_end,
+    split_label,
+    with_line,
 };
-use pid::State;
+use pid::ProcessState;
 
 /// The Secure Computing state of a process.
-#[derive(Debug, PartialEq, Eq, Hash)]
+#[derive(Debug, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "export", derive(Serialize))]
 pub enum SeccompMode {
+    #[default]
     Disabled,
     Strict,
     Filter,
+    /// A mode which is unknown to this crate.
+    Other(u32),
+}
+
+fn parse_seccomp_mode(input: &str) -> Result<SeccompMode> {
+    let mode = match parse(input)? {
+        0 => SeccompMode::Disabled,
+        1 => SeccompMode::Strict,
+        2 => SeccompMode::Filter,
+        mode => SeccompMode::Other(mode),
+    };
+    Ok(mode)
+}
+
+/// The Speculative Store Bypass (Spectre variant 4) mitigation state of a process.
+///
+/// See `Linux/Documentation/userspace-api/spec_ctrl.rst`.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "export", derive(Serialize))]
+pub enum SpeculationStoreBypass {
+    /// The mitigation state can not be determined.
+    Unknown,
+    /// The CPU is not affected by the vulnerability.
+    NotVulnerable,
+    /// The mitigation is enabled for the thread, and can not be disabled again.
+    ThreadForceMitigated,
+    /// The mitigation is enabled for the thread.
+    ThreadMitigated,
+    /// The mitigation is disabled for the thread, but may be enabled by `prctl(2)`.
+    ThreadVulnerable,
+    /// The mitigation is enabled system-wide.
+    GloballyMitigated,
+    /// The mitigation is disabled system-wide.
+    Vulnerable,
+    /// A state which is unknown to this crate.
+    Other(String),
+}
+
+fn parse_speculation_store_bypass(input: &str) -> SpeculationStoreBypass {
+    match input {
+        "unknown" => SpeculationStoreBypass::Unknown,
+        "not vulnerable" => SpeculationStoreBypass::NotVulnerable,
+        "thread force mitigated" => SpeculationStoreBypass::ThreadForceMitigated,
+        "thread mitigated" => SpeculationStoreBypass::ThreadMitigated,
+        "thread vulnerable" => SpeculationStoreBypass::ThreadVulnerable,
+        "globally mitigated" => SpeculationStoreBypass::GloballyMitigated,
+        "vulnerable" => SpeculationStoreBypass::Vulnerable,
+        _ => SpeculationStoreBypass::Other(input.to_owned()),
+    }
+}
+
+/// The indirect branch speculation (Spectre variant 2) mitigation state of a process.
+///
+/// See `Linux/Documentation/userspace-api/spec_ctrl.rst`.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "export", derive(Serialize))]
+pub enum SpeculationIndirectBranch {
+    /// The kernel does not support controlling indirect branch speculation.
+    Unsupported,
+    /// The CPU is not affected by the vulnerability.
+    NotAffected,
+    /// Speculation is disabled for the thread, and can not be enabled again.
+    ConditionalForceDisabled,
+    /// Speculation is disabled for the thread.
+    ConditionalDisabled,
+    /// Speculation is enabled for the thread, but may be disabled by `prctl(2)`.
+    ConditionalEnabled,
+    /// Speculation is enabled system-wide.
+    AlwaysEnabled,
+    /// Speculation is disabled system-wide.
+    AlwaysDisabled,
+    /// The mitigation state can not be determined.
+    Unknown,
+    /// A state which is unknown to this crate.
+    Other(String),
+}
+
+fn parse_speculation_indirect_branch(input: &str) -> SpeculationIndirectBranch {
+    match input {
+        "unsupported" => SpeculationIndirectBranch::Unsupported,
+        "not affected" => SpeculationIndirectBranch::NotAffected,
+        "conditional force disabled" => SpeculationIndirectBranch::ConditionalForceDisabled,
+        "conditional disabled" => SpeculationIndirectBranch::ConditionalDisabled,
+        "conditional enabled" => SpeculationIndirectBranch::ConditionalEnabled,
+        "always enabled" => SpeculationIndirectBranch::AlwaysEnabled,
+        "always disabled" => SpeculationIndirectBranch::AlwaysDisabled,
+        "unknown" => SpeculationIndirectBranch::Unknown,
+        _ => SpeculationIndirectBranch::Other(input.to_owned()),
+    }
+}
+
+/// A signal.
+///
+/// See `signal(7)`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "export", derive(Serialize))]
+pub enum Signal {
+    /// `SIGHUP`: hangup detected on controlling terminal or death of controlling process.
+    Hup,
+    /// `SIGINT`: interrupt from keyboard.
+    Int,
+    /// `SIGQUIT`: quit from keyboard.
+    Quit,
+    /// `SIGILL`: illegal instruction.
+    Ill,
+    /// `SIGTRAP`: trace or breakpoint trap.
+    Trap,
+    /// `SIGABRT`: abort signal from `abort(3)`.
+    Abrt,
+    /// `SIGBUS`: bus error (bad memory access).
+    Bus,
+    /// `SIGFPE`: floating-point exception.
+    Fpe,
+    /// `SIGKILL`: kill signal.
+    Kill,
+    /// `SIGUSR1`: user-defined signal 1.
+    Usr1,
+    /// `SIGSEGV`: invalid memory reference.
+    Segv,
+    /// `SIGUSR2`: user-defined signal 2.
+    Usr2,
+    /// `SIGPIPE`: broken pipe: write to pipe with no readers.
+    Pipe,
+    /// `SIGALRM`: timer signal from `alarm(2)`.
+    Alrm,
+    /// `SIGTERM`: termination signal.
+    Term,
+    /// `SIGSTKFLT`: stack fault on coprocessor (unused).
+    Stkflt,
+    /// `SIGCHLD`: child stopped or terminated.
+    Chld,
+    /// `SIGCONT`: continue if stopped.
+    Cont,
+    /// `SIGSTOP`: stop process.
+    Stop,
+    /// `SIGTSTP`: stop typed at terminal.
+    Tstp,
+    /// `SIGTTIN`: terminal input for background process.
+    Ttin,
+    /// `SIGTTOU`: terminal output for background process.
+    Ttou,
+    /// `SIGURG`: urgent condition on socket.
+    Urg,
+    /// `SIGXCPU`: CPU time limit exceeded.
+    Xcpu,
+    /// `SIGXFSZ`: file size limit exceeded.
+    Xfsz,
+    /// `SIGVTALRM`: virtual alarm clock.
+    Vtalrm,
+    /// `SIGPROF`: profiling timer expired.
+    Prof,
+    /// `SIGWINCH`: window resize signal.
+    Winch,
+    /// `SIGIO`: I/O now possible.
+    Io,
+    /// `SIGPWR`: power failure.
+    Pwr,
+    /// `SIGSYS`: bad system call.
+    Sys,
+    /// A real-time signal, identified by its signal number.
+    Realtime(c_int),
+}
+
+/// The lowest real-time signal number, as defined by the kernel.
+///
+/// Note that glibc reserves the first two real-time signals, so `SIGRTMIN` as seen by C programs
+/// is usually larger.
+const KERNEL_SIGRTMIN: c_int = 32;
+
+impl Signal {
+    /// Returns the signal with the provided number, or `None` if the number is not a valid signal.
+    pub fn from_number(number: c_int) -> Option<Signal> {
+        let signal = match number {
+            SIGHUP => Signal::Hup,
+            SIGINT => Signal::Int,
+            SIGQUIT => Signal::Quit,
+            SIGILL => Signal::Ill,
+            SIGTRAP => Signal::Trap,
+            SIGABRT => Signal::Abrt,
+            SIGBUS => Signal::Bus,
+            SIGFPE => Signal::Fpe,
+            SIGKILL => Signal::Kill,
+            SIGUSR1 => Signal::Usr1,
+            SIGSEGV => Signal::Segv,
+            SIGUSR2 => Signal::Usr2,
+            SIGPIPE => Signal::Pipe,
+            SIGALRM => Signal::Alrm,
+            SIGTERM => Signal::Term,
+            SIGSTKFLT => Signal::Stkflt,
+            SIGCHLD => Signal::Chld,
+            SIGCONT => Signal::Cont,
+            SIGSTOP => Signal::Stop,
+            SIGTSTP => Signal::Tstp,
+            SIGTTIN => Signal::Ttin,
+            SIGTTOU => Signal::Ttou,
+            SIGURG => Signal::Urg,
+            SIGXCPU => Signal::Xcpu,
+            SIGXFSZ => Signal::Xfsz,
+            SIGVTALRM => Signal::Vtalrm,
+            SIGPROF => Signal::Prof,
+            SIGWINCH => Signal::Winch,
+            SIGIO => Signal::Io,
+            SIGPWR => Signal::Pwr,
+            SIGSYS => Signal::Sys,
+            number if (KERNEL_SIGRTMIN..=64).contains(&number) => Signal::Realtime(number),
+            _ => return None,
+        };
+        Some(signal)
+    }
+
+    /// Returns the signal number.
+    pub fn number(self) -> c_int {
+        match self {
+            Signal::Hup => SIGHUP,
+            Signal::Int => SIGINT,
+            Signal::Quit => SIGQUIT,
+            Signal::Ill => SIGILL,
+            Signal::Trap => SIGTRAP,
+            Signal::Abrt => SIGABRT,
+            Signal::Bus => SIGBUS,
+            Signal::Fpe => SIGFPE,
+            Signal::Kill => SIGKILL,
+            Signal::Usr1 => SIGUSR1,
+            Signal::Segv => SIGSEGV,
+            Signal::Usr2 => SIGUSR2,
+            Signal::Pipe => SIGPIPE,
+            Signal::Alrm => SIGALRM,
+            Signal::Term => SIGTERM,
+            Signal::Stkflt => SIGSTKFLT,
+            Signal::Chld => SIGCHLD,
+            Signal::Cont => SIGCONT,
+            Signal::Stop => SIGSTOP,
+            Signal::Tstp => SIGTSTP,
+            Signal::Ttin => SIGTTIN,
+            Signal::Ttou => SIGTTOU,
+            Signal::Urg => SIGURG,
+            Signal::Xcpu => SIGXCPU,
+            Signal::Xfsz => SIGXFSZ,
+            Signal::Vtalrm => SIGVTALRM,
+            Signal::Prof => SIGPROF,
+            Signal::Winch => SIGWINCH,
+            Signal::Io => SIGIO,
+            Signal::Pwr => SIGPWR,
+            Signal::Sys => SIGSYS,
+            Signal::Realtime(number) => number,
+        }
+    }
+
+    /// Returns the conventional name of the signal, e.g. `SIGTERM`.
+    ///
+    /// Real-time signals are named relative to the kernel's lowest real-time signal, e.g.
+    /// `SIGRTMIN+2`.
+    pub fn name(self) -> Cow<'static, str> {
+        let name = match self {
+            Signal::Hup => "SIGHUP",
+            Signal::Int => "SIGINT",
+            Signal::Quit => "SIGQUIT",
+            Signal::Ill => "SIGILL",
+            Signal::Trap => "SIGTRAP",
+            Signal::Abrt => "SIGABRT",
+            Signal::Bus => "SIGBUS",
+            Signal::Fpe => "SIGFPE",
+            Signal::Kill => "SIGKILL",
+            Signal::Usr1 => "SIGUSR1",
+            Signal::Segv => "SIGSEGV",
+            Signal::Usr2 => "SIGUSR2",
+            Signal::Pipe => "SIGPIPE",
+            Signal::Alrm => "SIGALRM",
+            Signal::Term => "SIGTERM",
+            Signal::Stkflt => "SIGSTKFLT",
+            Signal::Chld => "SIGCHLD",
+            Signal::Cont => "SIGCONT",
+            Signal::Stop => "SIGSTOP",
+            Signal::Tstp => "SIGTSTP",
+            Signal::Ttin => "SIGTTIN",
+            Signal::Ttou => "SIGTTOU",
+            Signal::Urg => "SIGURG",
+            Signal::Xcpu => "SIGXCPU",
+            Signal::Xfsz => "SIGXFSZ",
+            Signal::Vtalrm => "SIGVTALRM",
+            Signal::Prof => "SIGPROF",
+            Signal::Winch => "SIGWINCH",
+            Signal::Io => "SIGIO",
+            Signal::Pwr => "SIGPWR",
+            Signal::Sys => "SIGSYS",
+            Signal::Realtime(KERNEL_SIGRTMIN) => "SIGRTMIN",
+            Signal::Realtime(number) => {
+                return Cow::Owned(format!("SIGRTMIN+{}", number - KERNEL_SIGRTMIN))
+            }
+        };
+        Cow::Borrowed(name)
+    }
+}
+
+impl fmt::Display for Signal {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(&self.name())
+    }
+}
+
+/// A set of signals, as found in the signal masks of `/proc/[pid]/status`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "export", derive(Serialize))]
+pub struct SignalSet {
+    bits: u64,
+}
+
+impl SignalSet {
+    /// Creates a signal set from a mask in which bit `n` represents signal number `n + 1`.
+    pub fn from_bits(bits: u64) -> SignalSet {
+        SignalSet { bits }
+    }
+
+    /// Returns the mask of the signal set, in which bit `n` represents signal number `n + 1`.
+    pub fn bits(&self) -> u64 {
+        self.bits
+    }
+
+    /// Returns `true` if the set contains the provided signal.
+    pub fn contains(&self, signal: Signal) -> bool {
+        let number = signal.number();
+        number > 0 && number <= 64 && self.bits & (1 << (number - 1)) != 0
+    }
+
+    /// Returns `true` if the set contains no signals.
+    pub fn is_empty(&self) -> bool {
+        self.bits == 0
+    }
+
+    /// Returns the number of signals in the set.
+    pub fn len(&self) -> usize {
+        self.bits.count_ones() as usize
+    }
+
+    /// Returns an iterator over the signals in the set, in order of signal number.
+    pub fn iter(&self) -> SignalSetIter {
+        SignalSetIter { bits: self.bits }
+    }
+}
+
+impl IntoIterator for &SignalSet {
+    type Item = Signal;
+    type IntoIter = SignalSetIter;
+    fn into_iter(self) -> SignalSetIter {
+        self.iter()
+    }
+}
+
+/// An iterator over the signals in a `SignalSet`.
+#[derive(Clone, Debug)]
+pub struct SignalSetIter {
+    bits: u64,
+}
+
+impl Iterator for SignalSetIter {
+    type Item = Signal;
+    fn next(&mut self) -> Option<Signal> {
+        while self.bits != 0 {
+            let bit = self.bits.trailing_zeros();
+            self.bits &= !(1 << bit);
+            if let Some(signal) = Signal::from_number(bit as c_int + 1) {
+                return Some(signal);
+            }
+        }
+        None
+    }
+}
+
+fn parse_signal_set(input: &str) -> Result<SignalSet> {
+    parse_u64_hex(input).map(SignalSet::from_bits)
 }
 
-impl Default for SeccompMode {
-    fn default() -> SeccompMode {
-        SeccompMode::Disabled
+bitflags! {
+    /// A set of process capabilities.
+    ///
+    /// Each flag corresponds to the `CAP_*` constant of the same name. Capabilities unknown to this
+    /// crate are not retained.
+    ///
+    /// See `capabilities(7)`.
+    #[derive(Default)]
+    #[cfg_attr(feature = "export", derive(Serialize))]
+    pub struct Capabilities: u64 {
+        /// Make arbitrary changes to file UIDs and GIDs.
+        const CHOWN = 1 << 0;
+        /// Bypass file read, write, and execute permission checks.
+        const DAC_OVERRIDE = 1 << 1;
+        /// Bypass file read and directory read and execute permission checks.
+        const DAC_READ_SEARCH = 1 << 2;
+        /// Bypass permission checks on operations requiring the file owner's UID.
+        const FOWNER = 1 << 3;
+        /// Don't clear set-user-ID and set-group-ID bits when a file is modified.
+        const FSETID = 1 << 4;
+        /// Bypass permission checks for sending signals.
+        const KILL = 1 << 5;
+        /// Make arbitrary manipulations of process GIDs.
+        const SETGID = 1 << 6;
+        /// Make arbitrary manipulations of process UIDs.
+        const SETUID = 1 << 7;
+        /// Modify the capability bounding set and securebits.
+        const SETPCAP = 1 << 8;
+        /// Set the immutable and append-only inode flags.
+        const LINUX_IMMUTABLE = 1 << 9;
+        /// Bind a socket to privileged ports.
+        const NET_BIND_SERVICE = 1 << 10;
+        /// Make socket broadcasts and listen to multicasts (unused).
+        const NET_BROADCAST = 1 << 11;
+        /// Perform network administration operations.
+        const NET_ADMIN = 1 << 12;
+        /// Use raw and packet sockets.
+        const NET_RAW = 1 << 13;
+        /// Lock memory.
+        const IPC_LOCK = 1 << 14;
+        /// Bypass permission checks for System V IPC operations.
+        const IPC_OWNER = 1 << 15;
+        /// Load and unload kernel modules.
+        const SYS_MODULE = 1 << 16;
+        /// Perform I/O port operations and access `/proc/kcore`.
+        const SYS_RAWIO = 1 << 17;
+        /// Use `chroot(2)`.
+        const SYS_CHROOT = 1 << 18;
+        /// Trace arbitrary processes using `ptrace(2)`.
+        const SYS_PTRACE = 1 << 19;
+        /// Use `acct(2)`.
+        const SYS_PACCT = 1 << 20;
+        /// Perform a range of system administration operations.
+        const SYS_ADMIN = 1 << 21;
+        /// Use `reboot(2)` and `kexec_load(2)`.
+        const SYS_BOOT = 1 << 22;
+        /// Raise process nice values and change scheduling policies.
+        const SYS_NICE = 1 << 23;
+        /// Override resource limits.
+        const SYS_RESOURCE = 1 << 24;
+        /// Set the system clock and real-time hardware clock.
+        const SYS_TIME = 1 << 25;
+        /// Use `vhangup(2)` and privileged terminal `ioctl(2)` operations.
+        const SYS_TTY_CONFIG = 1 << 26;
+        /// Create special files using `mknod(2)`.
+        const MKNOD = 1 << 27;
+        /// Establish leases on arbitrary files.
+        const LEASE = 1 << 28;
+        /// Write records to the kernel auditing log.
+        const AUDIT_WRITE = 1 << 29;
+        /// Enable and disable kernel auditing and change auditing rules.
+        const AUDIT_CONTROL = 1 << 30;
+        /// Set arbitrary capabilities on a file.
+        const SETFCAP = 1 << 31;
+        /// Override Mandatory Access Control.
+        const MAC_OVERRIDE = 1 << 32;
+        /// Change Mandatory Access Control configuration.
+        const MAC_ADMIN = 1 << 33;
+        /// Perform privileged `syslog(2)` operations (since Linux 2.6.37).
+        const SYSLOG = 1 << 34;
+        /// Trigger something that will wake up the system (since Linux 3.0).
+        const WAKE_ALARM = 1 << 35;
+        /// Employ features that can block system suspend (since Linux 3.5).
+        const BLOCK_SUSPEND = 1 << 36;
+        /// Read the audit log via multicast netlink (since Linux 3.16).
+        const AUDIT_READ = 1 << 37;
+        /// Employ performance monitoring mechanisms (since Linux 5.8).
+        const PERFMON = 1 << 38;
+        /// Employ privileged BPF operations (since Linux 5.8).
+        const BPF = 1 << 39;
+        /// Employ checkpoint and restore operations (since Linux 5.9).
+        const CHECKPOINT_RESTORE = 1 << 40;
     }
 }
 
-named!(parse_seccomp_mode<SeccompMode>,
-       alt!(tag!("0") => { |_| SeccompMode::Disabled }
-          | tag!("1") => { |_| SeccompMode::Strict   }
-          | tag!("2") => { |_| SeccompMode::Filter   }));
+fn parse_capabilities(input: &str) -> Result<Capabilities> {
+    parse_u64_hex(input).map(Capabilities::from_bits_truncate)
+}
 
 /// Process status information.
 ///
+/// Fields which are only reported by some kernel versions or configurations are `None` when the
+/// kernel does not report them.
+///
 /// See `man 5 proc` and `Linux/fs/proc/array.c`.
 #[derive(Default, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "export", derive(Serialize))]
 pub struct Status {
     /// Filename of the executable.
     pub command: String,
     /// File mode creation mask (since Linux 4.7).
-    pub umask: mode_t,
+    pub umask: Option<u32>,
     /// Current state of the process.
-    pub state: State,
+    pub state: ProcessState,
     /// Process ID (i.e., Thread Group ID).
     pub pid: pid_t,
-    /// NUMA group ID.
-    pub numa_gid: pid_t,
+    /// NUMA group ID (since Linux 3.13).
+    pub numa_gid: Option<pid_t>,
     /// Thread ID.
     pub tid: pid_t,
     /// Process ID of parent process.
@@ -64,25 +538,25 @@ pub struct Status {
     /// Process ID of the process tracing this process (0 if not being traced).
     pub tracer_pid: pid_t,
     /// Real user ID.
-    pub uid_real: uid_t,
+    pub uid_real: u32,
     /// Effective user ID.
-    pub uid_effective: uid_t,
+    pub uid_effective: u32,
     /// Saved user ID.
-    pub uid_saved: uid_t,
+    pub uid_saved: u32,
     /// Filesystem user ID.
-    pub uid_fs: uid_t,
+    pub uid_fs: u32,
     /// Real group ID.
-    pub gid_real: gid_t,
+    pub gid_real: u32,
     /// Effective group ID.
-    pub gid_effective: gid_t,
+    pub gid_effective: u32,
     /// Saved group ID.
-    pub gid_saved: gid_t,
+    pub gid_saved: u32,
     /// Filesystem group ID.
-    pub gid_fs: gid_t,
+    pub gid_fs: u32,
     /// Number of file descriptor slots currently allocated.
     pub fd_allocated: u32,
     /// Supplementary group list.
-    pub groups: Vec<gid_t>,
+    pub groups: Vec<u32>,
     /// Process IDs for each namespace which the process belongs to.
     pub ns_pids: Vec<pid_t>,
     /// Thread IDs for each namespace which the process belongs to.
@@ -92,44 +566,47 @@ pub struct Status {
     /// Session IDs of the process for each namespace to which it belongs.
     pub ns_sids: Vec<pid_t>,
     /// Peak virtual memory size (kB).
-    pub vm_peak: usize,
+    pub vm_peak: u64,
     /// Virtual memory size (kB).
-    pub vm_size: usize,
+    pub vm_size: u64,
     /// Locked memory size (kB) (see mlock(3)).
-    pub vm_locked: usize,
+    pub vm_locked: u64,
     /// Pinned memory size (since Linux 3.2). These are pages that can't be moved because
     /// something needs to directly access physical memory.
-    pub vm_pin: usize,
+    pub vm_pin: Option<u64>,
     /// Peak resident size (kB) ("high water mark").
-    pub vm_hwm: usize,
+    pub vm_hwm: u64,
     /// Resident set size (kB). Comprised of `vm_rss_anon`, `vm_rss_file`,
     /// and `vm_rss_shared`.
-    pub vm_rss: usize,
+    pub vm_rss: u64,
     /// Size of resident anonymous memory (kB) (since Linux 4.5).
-    pub vm_rss_anon: usize,
+    pub vm_rss_anon: Option<u64>,
     /// Size of resident file mappings (kB) (since Linux 4.5).
-    pub vm_rss_file: usize,
+    pub vm_rss_file: Option<u64>,
     /// Size of resident shared memory (kB) (since Linux 4.5). Includes SysV
     /// shm, mapping of tmpfs and shared anonymous mappings.
-    pub vm_rss_shared: usize,
+    pub vm_rss_shared: Option<u64>,
     /// Size of data segments (kB).
-    pub vm_data: usize,
+    pub vm_data: u64,
     /// Size of stack segments (kB).
-    pub vm_stack: usize,
+    pub vm_stack: u64,
     /// Size of text (executable) segments (kB).
-    pub vm_exe: usize,
+    pub vm_exe: u64,
     /// Shared library code size (kB).
-    pub vm_lib: usize,
+    pub vm_lib: u64,
     /// Page table entries size (since Linux 2.6.10).
-    pub vm_pte: usize,
+    pub vm_pte: u64,
     /// Size of second-level page tables (since Linux 4.0).
-    pub vm_pmd: usize,
+    pub vm_pmd: Option<u64>,
     /// Swapped-out-virtual memory size (since Linux 2.6.34).
-    pub vm_swap: usize,
+    pub vm_swap: Option<u64>,
     /// Size of hugetlb memory portions (since Linux 4.4).
-    pub hugetlb_pages: usize,
+    pub hugetlb_pages: Option<u64>,
     /// Process's memory is currently being dumped (since Linux 4.15).
-    pub core_dumping: bool,
+    pub core_dumping: Option<bool>,
+    /// Whether transparent huge pages may be used for the process, i.e. whether it has not been
+    /// disabled with `PR_SET_THP_DISABLE` (since Linux 5.0, see prctl(2)).
+    pub thp_enabled: Option<bool>,
     /// Number of threads in process containing this thread.
     pub threads: u32,
     /// The number of currently queued signals for this real user ID
@@ -137,227 +614,283 @@ pub struct Status {
     pub sig_queued: u64,
     /// The resource limit on the number of queued signals for this process.
     pub sig_queued_max: u64,
-    /// Number of signals pending for the thread (see pthreads(7)).
-    pub sig_pending_thread: u64,
-    /// Number of signals pending for the process (see signal(7)).
-    pub sig_pending_process: u64,
-    /// Mask indicating signals being blocked.
-    pub sig_blocked: u64,
-    /// Mask indicating signals being ignored.
-    pub sig_ignored: u64,
-    /// Mask indicating signals being caught.
-    pub sig_caught: u64,
-    /// Mask of capabilities enabled in inheritable sets (see capabilities(7)).
-    pub cap_inherited: u64,
-    /// Mask of capabilities enabled in permitted sets.
-    pub cap_permitted: u64,
-    /// Mask of capabilities enabled in effective sets.
-    pub cap_effective: u64,
+    /// Signals pending for the thread (see pthreads(7)).
+    pub sig_pending_thread: SignalSet,
+    /// Signals pending for the process as a whole (see signal(7)).
+    pub sig_pending_process: SignalSet,
+    /// Signals being blocked.
+    pub sig_blocked: SignalSet,
+    /// Signals being ignored.
+    pub sig_ignored: SignalSet,
+    /// Signals being caught.
+    pub sig_caught: SignalSet,
+    /// Capabilities enabled in the inheritable set (see capabilities(7)).
+    pub cap_inherited: Capabilities,
+    /// Capabilities enabled in the permitted set.
+    pub cap_permitted: Capabilities,
+    /// Capabilities enabled in the effective set.
+    pub cap_effective: Capabilities,
     /// Capability Bounding set (since Linux 2.6.26).
-    pub cap_bounding: u64,
+    pub cap_bounding: Capabilities,
     /// Ambient capability set (since Linux 4.3).
-    pub cap_ambient: u64,
+    pub cap_ambient: Option<Capabilities>,
     /// Whether the process can acquire new privileges (since Linux 4.10)
-    pub no_new_privs: bool,
+    pub no_new_privs: Option<bool>,
     /// Secure Computing mode of the process (since Linux 3.8, see seccomp(2)).
     /// This field is provided only if the kernel was built with the
     /// `CONFIG_SECCOMP` kernel configuration option enabled.
-    pub seccomp: SeccompMode,
+    pub seccomp: Option<SeccompMode>,
+    /// Number of Secure Computing filters attached to the process (since Linux 5.9).
+    pub seccomp_filters: Option<u32>,
+    /// Speculative Store Bypass mitigation state (since Linux 4.17).
+    pub speculation_store_bypass: Option<SpeculationStoreBypass>,
+    /// Indirect branch speculation mitigation state (since Linux 5.1).
+    pub speculation_indirect_branch: Option<SpeculationIndirectBranch>,
     /// CPUs on which this process may run (since Linux 2.6.24, see cpuset(7)).
     ///
     /// The slice represents a bitmask in the same format as `BitVec`.
     pub cpus_allowed: Box<[u8]>,
+    /// CPUs on which this process may run, from the range list form of `cpus_allowed` (since
+    /// Linux 2.6.26).
+    pub cpus_allowed_list: Option<CpuSet>,
     /// Memory nodes allowed to this process (since Linux 2.6.24, see cpuset(7)).
-    ///
-    /// The slice represents a bitmask in the same format as `BitVec`.
-    pub mems_allowed: Box<[u8]>,
+    pub mems_allowed: NodeSet,
+    /// Memory nodes allowed to this process, from the range list form of `mems_allowed` (since
+    /// Linux 2.6.26).
+    pub mems_allowed_list: Option<NodeSet>,
     /// Number of voluntary context switches.
     pub voluntary_ctxt_switches: u64,
     /// Number of involuntary context switches.
     pub nonvoluntary_ctxt_switches: u64,
 }
 
-/// Parse the status state format.
-named!(parse_status_state<State>,
-       alt!(tag!("R (running)") => { |_| State::Running  }
-          | tag!("S (sleeping)") => { |_| State::Sleeping }
-          | tag!("D (disk sleep)") => { |_| State::Waiting }
-          | tag!("T (stopped)") => { |_| State::Stopped }
-          | tag!("t (tracing stop)") => { |_| State::TraceStopped }
-          | tag!("X (dead)") => { |_| State::Dead }
-          | tag!("Z (zombie)") => { |_| State::Zombie }));
-
-named!(parse_command<String>,   delimited!(tag!("Name:\t"),      parse_line,         line_ending));
-named!(parse_umask<mode_t>,     delimited!(tag!("Umask:\t"),     parse_u32_octal,    line_ending));
-named!(parse_state<State>,      delimited!(tag!("State:\t"),     parse_status_state, line_ending));
-named!(parse_pid<pid_t>,        delimited!(tag!("Tgid:\t"),      parse_i32,          line_ending));
-named!(parse_numa_gid<pid_t>,   delimited!(tag!("Ngid:\t"),      parse_i32,          line_ending));
-named!(parse_tid<pid_t>,        delimited!(tag!("Pid:\t"),       parse_i32,          line_ending));
-named!(parse_ppid<pid_t>,       delimited!(tag!("PPid:\t"),      parse_i32,          line_ending));
-named!(parse_tracer_pid<pid_t>, delimited!(tag!("TracerPid:\t"), parse_i32,          line_ending));
-
-named!(parse_uid<(uid_t, uid_t, uid_t, uid_t)>, chain!(tag!("Uid:\t") ~ real: parse_u32 ~ space ~ effective: parse_u32
-                                                                      ~ space ~ saved: parse_u32 ~ space ~ fs: parse_u32 ~ line_ending,
-                                                                   || { (real, effective, saved, fs) }));
-named!(parse_gid<(gid_t, gid_t, gid_t, gid_t)>, chain!(tag!("Gid:\t") ~ real: parse_u32 ~ space ~ effective: parse_u32
-                                                                      ~ space ~ saved: parse_u32 ~ space ~ fs: parse_u32 ~ line_ending,
-                                                                   || { (real, effective, saved, fs) }));
-
-named!(parse_fd_allocated<u32>,   delimited!(tag!("FDSize:\t"), parse_u32,  line_ending));
-named!(parse_groups<Vec<gid_t> >, delimited!(tag!("Groups:\t"), parse_u32s, multispace));
-
-named!(parse_ns_pids<Vec<pid_t> >,  delimited!(tag!("NStgid:\t"), parse_i32s, line_ending));
-named!(parse_ns_tids<Vec<pid_t> >,  delimited!(tag!("NSpid:\t"),  parse_i32s, line_ending));
-named!(parse_ns_pgids<Vec<pid_t> >, delimited!(tag!("NSpgid:\t"), parse_i32s, line_ending));
-named!(parse_ns_sids<Vec<pid_t> >,  delimited!(tag!("NSsid:\t"),  parse_i32s, line_ending));
-
-named!(parse_vm_peak<usize>,        delimited!(tag!("VmPeak:"),       parse_kb, line_ending));
-named!(parse_vm_size<usize>,        delimited!(tag!("VmSize:"),       parse_kb, line_ending));
-named!(parse_vm_locked<usize>,      delimited!(tag!("VmLck:"),        parse_kb, line_ending));
-named!(parse_vm_pin<usize>,         delimited!(tag!("VmPin:"),        parse_kb, line_ending));
-named!(parse_vm_hwm<usize>,         delimited!(tag!("VmHWM:"),        parse_kb, line_ending));
-named!(parse_vm_rss<usize>,         delimited!(tag!("VmRSS:"),        parse_kb, line_ending));
-named!(parse_vm_rss_anon<usize>,    delimited!(tag!("RssAnon:"),      parse_kb, line_ending));
-named!(parse_vm_rss_file<usize>,    delimited!(tag!("RssFile:"),      parse_kb, line_ending));
-named!(parse_vm_rss_shared<usize>,  delimited!(tag!("RssShmem:"),     parse_kb, line_ending));
-named!(parse_vm_data<usize>,        delimited!(tag!("VmData:"),       parse_kb, line_ending));
-named!(parse_vm_stack<usize>,       delimited!(tag!("VmStk:"),        parse_kb, line_ending));
-named!(parse_vm_exe<usize>,         delimited!(tag!("VmExe:"),        parse_kb, line_ending));
-named!(parse_vm_lib<usize>,         delimited!(tag!("VmLib:"),        parse_kb, line_ending));
-named!(parse_vm_pte<usize>,         delimited!(tag!("VmPTE:"),        parse_kb, line_ending));
-named!(parse_vm_pmd<usize>,         delimited!(tag!("VmPMD:"),        parse_kb, line_ending));
-named!(parse_vm_swap<usize>,        delimited!(tag!("VmSwap:"),       parse_kb, line_ending));
-named!(parse_hugetlb_pages<usize>,  delimited!(tag!("HugetlbPages:"), parse_kb, line_ending));
-
-named!(parse_core_dumping<bool>, delimited!(tag!("CoreDumping:\t"), parse_bit, line_ending));
-
-named!(parse_threads<u32>, delimited!(tag!("Threads:\t"), parse_u32, line_ending));
-
-named!(parse_sig_queued<(u64, u64)>, delimited!(tag!("SigQ:\t"), separated_pair!(parse_u64, tag!("/"), parse_u64), line_ending));
-
-named!(parse_sig_pending_thread<u64>,  delimited!(tag!("SigPnd:\t"), parse_u64_hex, line_ending));
-named!(parse_sig_pending_process<u64>, delimited!(tag!("ShdPnd:\t"), parse_u64_hex, line_ending));
-named!(parse_sig_blocked<u64>,         delimited!(tag!("SigBlk:\t"), parse_u64_hex, line_ending));
-named!(parse_sig_ignored<u64>,         delimited!(tag!("SigIgn:\t"), parse_u64_hex, line_ending));
-named!(parse_sig_caught<u64>,          delimited!(tag!("SigCgt:\t"), parse_u64_hex, line_ending));
-
-named!(parse_cap_inherited<u64>, delimited!(tag!("CapInh:\t"), parse_u64_hex, line_ending));
-named!(parse_cap_permitted<u64>, delimited!(tag!("CapPrm:\t"), parse_u64_hex, line_ending));
-named!(parse_cap_effective<u64>, delimited!(tag!("CapEff:\t"), parse_u64_hex, line_ending));
-named!(parse_cap_bounding<u64>,  delimited!(tag!("CapBnd:\t"), parse_u64_hex, line_ending));
-named!(parse_cap_ambient<u64>,  delimited!(tag!("CapAmb:\t"), parse_u64_hex, line_ending));
-
-named!(parse_no_new_privs<bool>,       delimited!(tag!("NoNewPrivs:\t"),   parse_bit,           line_ending));
-named!(parse_seccomp<SeccompMode>,     delimited!(tag!("Seccomp:\t"),      parse_seccomp_mode,  line_ending));
-named!(parse_cpus_allowed<Box<[u8]> >, delimited!(tag!("Cpus_allowed:\t"), parse_u32_mask_list, line_ending));
-named!(parse_mems_allowed<Box<[u8]> >, delimited!(tag!("Mems_allowed:\t"), parse_u32_mask_list, line_ending));
-
-named!(parse_cpus_allowed_list<()>, chain!(tag!("Cpus_allowed_list:\t") ~ not_line_ending ~ line_ending, || { () }));
-named!(parse_mems_allowed_list<()>, chain!(tag!("Mems_allowed_list:\t") ~ not_line_ending ~ line_ending, || { () }));
-
-named!(parse_voluntary_ctxt_switches<u64>,    delimited!(tag!("voluntary_ctxt_switches:\t"),    parse_u64, line_ending));
-named!(parse_nonvoluntary_ctxt_switches<u64>, delimited!(tag!("nonvoluntary_ctxt_switches:\t"), parse_u64, line_ending));
+/// Parse the status state format, a state letter followed by a parenthesized description.
+///
+/// The description is only needed to tell `W (waking)` from `W (paging)`.
+fn parse_status_state(input: &str) -> Result<ProcessState> {
+    let mut chars = input.chars();
+    let (c, description) = match (chars.next(), chars.as_str().strip_prefix(' ')) {
+        (Some(c), Some(description)) if description.starts_with('(')
+                                        && description.ends_with(')') => (c, description),
+        _ => return Err(invalid_input(format!("invalid state: {:?}", input))),
+    };
+    if c == 'W' && description == "(waking)" {
+        Ok(ProcessState::Waking)
+    } else {
+        Ok(ProcessState::from_char(c))
+    }
+}
+
+/// Parses the four real, effective, saved and filesystem IDs of a `Uid` or `Gid` line.
+fn parse_ids(input: &str) -> Result<(u32, u32, u32, u32)> {
+    let mut fields = Fields::new(input);
+    let ids = (fields.parse()?, fields.parse()?, fields.parse()?, fields.parse()?);
+    fields.finish()?;
+    Ok(ids)
+}
+
+/// Parses a line of the status format into the corresponding field of the status.
+///
+/// Lines are dispatched on their label, and lines with unrecognized labels are skipped.
+fn parse_status_line(status: &mut Status, line: &str) -> Result<()> {
+    let (label, raw_value) = match split_label(line) {
+        Some(split) => split,
+        None => return Ok(()),
+    };
+    let value = raw_value.trim();
+    match label {
+        // The command is not trimmed, since it may begin or end with spaces.
+        "Name" => status.command = raw_value.strip_prefix('\t').unwrap_or(raw_value).to_owned(),
+        "Umask"      => status.umask      = Some(parse_u32_octal(value)?),
+        "State"      => status.state      = parse_status_state(value)?,
+        "Tgid"       => status.pid        = parse(value)?,
+        "Ngid"       => status.numa_gid   = Some(parse(value)?),
+        "Pid"        => status.tid        = parse(value)?,
+        "PPid"       => status.ppid       = parse(value)?,
+        "TracerPid"  => status.tracer_pid = parse(value)?,
+        "Uid" => {
+            let (real, effective, saved, fs) = parse_ids(value)?;
+            status.uid_real = real;
+            status.uid_effective = effective;
+            status.uid_saved = saved;
+            status.uid_fs = fs;
+        }
+        "Gid" => {
+            let (real, effective, saved, fs) = parse_ids(value)?;
+            status.gid_real = real;
+            status.gid_effective = effective;
+            status.gid_saved = saved;
+            status.gid_fs = fs;
+        }
+        "FDSize"       => status.fd_allocated  = parse(value)?,
+        "Groups"       => status.groups        = parse_list(value)?,
+        "NStgid"       => status.ns_pids       = parse_list(value)?,
+        "NSpid"        => status.ns_tids       = parse_list(value)?,
+        "NSpgid"       => status.ns_pgids      = parse_list(value)?,
+        "NSsid"        => status.ns_sids       = parse_list(value)?,
+        "VmPeak"       => status.vm_peak       = parse_kb(value)?,
+        "VmSize"       => status.vm_size       = parse_kb(value)?,
+        "VmLck"        => status.vm_locked     = parse_kb(value)?,
+        "VmPin"        => status.vm_pin        = Some(parse_kb(value)?),
+        "VmHWM"        => status.vm_hwm        = parse_kb(value)?,
+        "VmRSS"        => status.vm_rss        = parse_kb(value)?,
+        "RssAnon"      => status.vm_rss_anon   = Some(parse_kb(value)?),
+        "RssFile"      => status.vm_rss_file   = Some(parse_kb(value)?),
+        "RssShmem"     => status.vm_rss_shared = Some(parse_kb(value)?),
+        "VmData"       => status.vm_data       = parse_kb(value)?,
+        "VmStk"        => status.vm_stack      = parse_kb(value)?,
+        "VmExe"        => status.vm_exe        = parse_kb(value)?,
+        "VmLib"        => status.vm_lib        = parse_kb(value)?,
+        "VmPTE"        => status.vm_pte        = parse_kb(value)?,
+        "VmPMD"        => status.vm_pmd        = Some(parse_kb(value)?),
+        "VmSwap"       => status.vm_swap       = Some(parse_kb(value)?),
+        "HugetlbPages" => status.hugetlb_pages = Some(parse_kb(value)?),
+        "CoreDumping"  => status.core_dumping  = Some(parse_bit(value)?),
+        "THP_enabled"  => status.thp_enabled   = Some(parse_bit(value)?),
+
+        "Threads" => status.threads = parse(value)?,
+        "SigQ" => {
+            let (count, max) = value.split_once('/').ok_or_else(|| {
+                invalid_input(format!("invalid queued signal count: {:?}", value))
+            })?;
+            status.sig_queued = parse(count)?;
+            status.sig_queued_max = parse(max)?;
+        }
+        "SigPnd" => status.sig_pending_thread  = parse_signal_set(value)?,
+        "ShdPnd" => status.sig_pending_process = parse_signal_set(value)?,
+        "SigBlk" => status.sig_blocked         = parse_signal_set(value)?,
+        "SigIgn" => status.sig_ignored         = parse_signal_set(value)?,
+        "SigCgt" => status.sig_caught          = parse_signal_set(value)?,
+
+        "CapInh" => status.cap_inherited = parse_capabilities(value)?,
+        "CapPrm" => status.cap_permitted = parse_capabilities(value)?,
+        "CapEff" => status.cap_effective = parse_capabilities(value)?,
+        "CapBnd" => status.cap_bounding  = parse_capabilities(value)?,
+        "CapAmb" => status.cap_ambient   = Some(parse_capabilities(value)?),
+
+        "NoNewPrivs"      => status.no_new_privs    = Some(parse_bit(value)?),
+        "Seccomp"         => status.seccomp         = Some(parse_seccomp_mode(value)?),
+        "Seccomp_filters" => status.seccomp_filters = Some(parse(value)?),
+        "Speculation_Store_Bypass" => {
+            status.speculation_store_bypass = Some(parse_speculation_store_bypass(value))
+        }
+        "SpeculationIndirectBranch" => {
+            status.speculation_indirect_branch = Some(parse_speculation_indirect_branch(value))
+        }
+        "Cpus_allowed" => status.cpus_allowed = parse_u32_mask_list(value)?,
+        "Mems_allowed" => status.mems_allowed = NodeSet::from_mask(&parse_u32_mask_list(value)?),
+        "Cpus_allowed_list" => status.cpus_allowed_list = Some(CpuSet::parse_list(value)?),
+        "Mems_allowed_list" => status.mems_allowed_list = Some(NodeSet::parse_list(value)?),
+        "voluntary_ctxt_switches"    => status.voluntary_ctxt_switches    = parse(value)?,
+        "nonvoluntary_ctxt_switches" => status.nonvoluntary_ctxt_switches = parse(value)?,
+        _ => (),
+    }
+    Ok(())
+}
 
 /// Parse the status format.
-fn parse_status(i: &[u8]) -> IResult<&[u8], Status> {
+fn parse_status(input: &[u8]) -> Result<Status> {
+    let mut status: Status = Default::default();
+    for (i, line) in parse_str(input)?.lines().enumerate() {
+        parse_status_line(&mut status, line).map_err(|error| with_line(error, i + 1))?;
+    }
+    Ok(status)
+}
+
+/// Parse the status format, skipping lines which can not be parsed.
+fn parse_status_lossy(input: &[u8]) -> (Status, Vec<ParseWarning>) {
     let mut status: Status = Default::default();
-    map!(i,
-        many0!( // TODO: use a loop here instead of many0 to avoid allocating a vec.
-            alt!(parse_command      => { |value| status.command     = value }
-               | parse_umask        => { |value| status.umask       = value }
-               | parse_state        => { |value| status.state       = value }
-               | parse_pid          => { |value| status.pid         = value }
-               | parse_numa_gid     => { |value| status.numa_gid    = value }
-               | parse_tid          => { |value| status.tid         = value }
-               | parse_ppid         => { |value| status.ppid        = value }
-               | parse_tracer_pid   => { |value| status.tracer_pid  = value }
-               | parse_uid => { |(real, effective, saved, fs)| { status.uid_real = real;
-                                                                 status.uid_effective = effective;
-                                                                 status.uid_saved = saved;
-                                                                 status.uid_fs = fs; } }
-               | parse_gid => { |(real, effective, saved, fs)| { status.gid_real = real;
-                                                                 status.gid_effective = effective;
-                                                                 status.gid_saved = saved;
-                                                                 status.gid_fs = fs; } }
-               | parse_fd_allocated      => { |value| status.fd_allocated   = value }
-               | parse_groups            => { |value| status.groups         = value }
-               | parse_ns_pids           => { |value| status.ns_pids        = value }
-               | parse_ns_tids           => { |value| status.ns_tids        = value }
-               | parse_ns_pgids          => { |value| status.ns_pgids       = value }
-               | parse_ns_sids           => { |value| status.ns_sids        = value }
-               | parse_vm_peak           => { |value| status.vm_peak        = value }
-               | parse_vm_size           => { |value| status.vm_size        = value }
-               | parse_vm_locked         => { |value| status.vm_locked      = value }
-               | parse_vm_pin            => { |value| status.vm_pin         = value }
-               | parse_vm_hwm            => { |value| status.vm_hwm         = value }
-               | parse_vm_rss            => { |value| status.vm_rss         = value }
-               | parse_vm_rss_anon       => { |value| status.vm_rss_anon    = value }
-               | parse_vm_rss_file       => { |value| status.vm_rss_file    = value }
-               | parse_vm_rss_shared     => { |value| status.vm_rss_shared  = value }
-               | parse_vm_data           => { |value| status.vm_data        = value }
-               | parse_vm_stack          => { |value| status.vm_stack       = value }
-               | parse_vm_exe            => { |value| status.vm_exe         = value }
-               | parse_vm_lib            => { |value| status.vm_lib         = value }
-               | parse_vm_pte            => { |value| status.vm_pte         = value }
-               | parse_vm_pmd            => { |value| status.vm_pmd         = value }
-               | parse_vm_swap           => { |value| status.vm_swap        = value }
-               | parse_hugetlb_pages     => { |value| status.hugetlb_pages  = value }
-               | parse_core_dumping      => { |value| status.core_dumping   = value }
-
-               | parse_threads              => { |value| status.threads                 = value }
-               | parse_sig_queued           => { |(count, max)| { status.sig_queued     = count;
-                                                                  status.sig_queued_max = max } }
-               | parse_sig_pending_thread   => { |value| status.sig_pending_thread      = value }
-               | parse_sig_pending_process  => { |value| status.sig_pending_process     = value }
-               | parse_sig_blocked          => { |value| status.sig_blocked             = value }
-               | parse_sig_ignored          => { |value| status.sig_ignored             = value }
-               | parse_sig_caught           => { |value| status.sig_caught              = value }
-
-               | parse_cap_inherited => { |value| status.cap_inherited = value }
-               | parse_cap_permitted => { |value| status.cap_permitted = value }
-               | parse_cap_effective => { |value| status.cap_effective = value }
-               | parse_cap_bounding  => { |value| status.cap_bounding  = value }
-               | parse_cap_ambient   => { |value| status.cap_ambient   = value }
-
-               | parse_no_new_privs  => { |value| status.no_new_privs  = value }
-               | parse_seccomp       => { |value| status.seccomp       = value }
-               | parse_cpus_allowed  => { |value| status.cpus_allowed  = value }
-               | parse_cpus_allowed_list
-               | parse_mems_allowed  => { |value| status.mems_allowed  = value }
-               | parse_mems_allowed_list
-               | parse_voluntary_ctxt_switches    => { |value| status.voluntary_ctxt_switches    = value }
-               | parse_nonvoluntary_ctxt_switches => { |value| status.nonvoluntary_ctxt_switches = value }
-            )
-        ),
-        { |_| { status }})
+    let mut warnings = Vec::new();
+    for (i, line) in String::from_utf8_lossy(input).lines().enumerate() {
+        if let Err(error) = parse_status_line(&mut status, line) {
+            warnings.push(ParseWarning {
+                line: i + 1,
+                text: line.to_owned(),
+                message: error.to_string(),
+            });
+        }
+    }
+    (status, warnings)
+}
+
+impl Status {
+    /// Parses the contents of a `/proc/[pid]/status` file.
+    pub fn parse(input: &[u8]) -> Result<Status> {
+        parse_status(input)
+    }
+
+    /// Parses the contents of a `/proc/[pid]/status` file, skipping lines which can not be
+    /// parsed instead of failing.
+    ///
+    /// The fields of skipped lines keep their default values, and a warning is returned for each
+    /// skipped line.
+    pub fn parse_lossy(input: &[u8]) -> (Status, Vec<ParseWarning>) {
+        parse_status_lossy(input)
+    }
+
+    /// Returns the peak resident set size in bytes.
+    pub fn vm_hwm_bytes(&self) -> u64 {
+        self.vm_hwm * 1024
+    }
+
+    /// Returns the resident set size in bytes.
+    pub fn vm_rss_bytes(&self) -> u64 {
+        self.vm_rss * 1024
+    }
+
+    /// Returns the size of resident anonymous memory in bytes.
+    pub fn vm_rss_anon_bytes(&self) -> Option<u64> {
+        self.vm_rss_anon.map(|kb| kb * 1024)
+    }
+
+    /// Returns the size of resident file mappings in bytes.
+    pub fn vm_rss_file_bytes(&self) -> Option<u64> {
+        self.vm_rss_file.map(|kb| kb * 1024)
+    }
+
+    /// Returns the size of resident shared memory in bytes.
+    pub fn vm_rss_shared_bytes(&self) -> Option<u64> {
+        self.vm_rss_shared.map(|kb| kb * 1024)
+    }
+
+    /// Returns the size of second-level page tables in bytes.
+    pub fn vm_pmd_bytes(&self) -> Option<u64> {
+        self.vm_pmd.map(|kb| kb * 1024)
+    }
+
+    /// Returns the size of hugetlb memory portions in bytes.
+    pub fn hugetlb_pages_bytes(&self) -> Option<u64> {
+        self.hugetlb_pages.map(|kb| kb * 1024)
+    }
 }
 
 /// Parses the provided status file.
-fn status_file(file: &mut File) -> Result<Status> {
+pub(crate) fn status_file(file: &mut File) -> Result<Status> {
     let mut buf = [0; 2048]; // A typical status file is about 1000 bytes
-    map_result(parse_status(try!(read_to_end(file, &mut buf))))
+    Status::parse(&read_to_end(file, &mut buf)?)
 }
 
 /// Returns memory status information for the process with the provided pid.
 pub fn status(pid: pid_t) -> Result<Status> {
-    status_file(&mut try!(File::open(&format!("/proc/{}/status", pid))))
+    parse_file(format!("/proc/{}/status", pid), status_file)
 }
 
 /// Returns memory status information for the current process.
 pub fn status_self() -> Result<Status> {
-    status_file(&mut try!(File::open("/proc/self/status")))
+    parse_file("/proc/self/status", status_file)
 }
 
-/// Returns memory status information from the thread with the provided parent process ID and thread ID.
+/// Returns status information from the thread with the provided parent process ID and thread ID.
 pub fn status_task(process_id: pid_t, thread_id: pid_t) -> Result<Status> {
-    status_file(&mut try!(File::open(&format!("/proc/{}/task/{}/status", process_id, thread_id))))
+    parse_file(format!("/proc/{}/task/{}/status", process_id, thread_id), status_file)
 }
 
 #[cfg(test)]
 mod tests {
-    use parsers::tests::unwrap;
-    use super::{SeccompMode, parse_status, status, status_self};
-    use pid::State;
+    use super::{Capabilities, SeccompMode, Signal, SignalSet, SpeculationIndirectBranch,
+                SpeculationStoreBypass, Status, parse_status, status, status_self};
+    use cpuset::{CpuSet, NodeSet};
+    use pid::ProcessState;
 
     /// Test that the system status files can be parsed.
     #[test]
@@ -402,6 +935,7 @@ mod tests {
                             VmSwap:\t      0 kB\n\
                             HugetlbPages:\t          0 kB\n\
                             CoreDumping:\t0\n\
+                            THP_enabled:\t1\n\
                             Threads:\t1\n\
                             SigQ:\t0/257232\n\
                             SigPnd:\t0000000000000000\n\
@@ -415,7 +949,10 @@ mod tests {
                             CapBnd:\t0000003fffffffff\n\
                             CapAmb:\t0000000000000000\n\
                             NoNewPrivs:\t0\n\
-                            Seccomp:\t0\n\
+                            Seccomp:\t2\n\
+                            Seccomp_filters:\t1\n\
+                            Speculation_Store_Bypass:\tthread vulnerable\n\
+                            SpeculationIndirectBranch:\tconditional enabled\n\
                             Cpus_allowed:\tffff\n\
                             Cpus_allowed_list:\t0-15\n\
                             Mems_allowed:\t00000000,00000000,00000000,00000000,00000000,00000000,00000000,00000000,00000000,00000000,00000000,00000000,00000000,00000000,00000000,00000001\n\
@@ -423,12 +960,12 @@ mod tests {
                             voluntary_ctxt_switches:\t242129\n\
                             nonvoluntary_ctxt_switches:\t1748\n";
 
-        let status = unwrap(parse_status(status_text));
+        let status = parse_status(status_text).unwrap();
         assert_eq!("systemd", status.command);
-        assert_eq!(18, status.umask);
-        assert_eq!(State::Sleeping, status.state);
+        assert_eq!(Some(18), status.umask);
+        assert_eq!(ProcessState::Sleeping, status.state);
         assert_eq!(1, status.pid);
-        assert_eq!(0, status.numa_gid);
+        assert_eq!(Some(0), status.numa_gid);
         assert_eq!(1, status.tid);
         assert_eq!(0, status.ppid);
         assert_eq!(0, status.tracer_pid);
@@ -449,63 +986,187 @@ mod tests {
         assert_eq!(10927688, status.vm_peak);
         assert_eq!(47348, status.vm_size);
         assert_eq!(0, status.vm_locked);
-        assert_eq!(0, status.vm_pin);
+        assert_eq!(Some(0), status.vm_pin);
         assert_eq!(9212, status.vm_hwm);
         assert_eq!(9212, status.vm_rss);
-        assert_eq!(3700, status.vm_rss_anon);
-        assert_eq!(5768, status.vm_rss_file);
-        assert_eq!(0, status.vm_rss_shared);
+        assert_eq!(9212 * 1024, status.vm_rss_bytes());
+        assert_eq!(Some(3700), status.vm_rss_anon);
+        assert_eq!(Some(5768), status.vm_rss_file);
+        assert_eq!(Some(0), status.vm_rss_shared);
         assert_eq!(3424, status.vm_data);
         assert_eq!(136, status.vm_stack);
         assert_eq!(1320, status.vm_exe);
         assert_eq!(3848, status.vm_lib);
         assert_eq!(108, status.vm_pte);
-        assert_eq!(12, status.vm_pmd);
-        assert_eq!(0, status.vm_swap);
-        assert_eq!(0, status.hugetlb_pages);
-        assert_eq!(false, status.core_dumping);
+        assert_eq!(Some(12), status.vm_pmd);
+        assert_eq!(Some(0), status.vm_swap);
+        assert_eq!(Some(0), status.hugetlb_pages);
+        assert_eq!(Some(false), status.core_dumping);
+        assert_eq!(Some(true), status.thp_enabled);
+        assert_eq!(Some(12 * 1024), status.vm_pmd_bytes());
+        assert_eq!(Some(0), status.hugetlb_pages_bytes());
         assert_eq!(1, status.threads);
         assert_eq!(0, status.sig_queued);
         assert_eq!(257232, status.sig_queued_max);
-        assert_eq!(0x0000000000000000, status.sig_pending_thread);
-        assert_eq!(0x0000000000000000, status.sig_pending_process);
-        assert_eq!(0x7be3c0fe28014a03, status.sig_blocked);
-        assert_eq!(0x0000000000001000, status.sig_ignored);
-        assert_eq!(0x00000001800004ec, status.sig_caught);
-        assert_eq!(0x0000000000000000, status.cap_inherited);
-        assert_eq!(0x0000003fffffffff, status.cap_permitted);
-        assert_eq!(0x0000003fffffffff, status.cap_effective);
-        assert_eq!(0x0000003fffffffff, status.cap_bounding);
-        assert_eq!(0x0000000000000000, status.cap_ambient);
-        assert_eq!(false, status.no_new_privs);
-        assert_eq!(SeccompMode::Disabled, status.seccomp);
+        assert_eq!(0x0000000000000000, status.sig_pending_thread.bits());
+        assert_eq!(0x0000000000000000, status.sig_pending_process.bits());
+        assert_eq!(0x7be3c0fe28014a03, status.sig_blocked.bits());
+        assert_eq!(0x0000000000001000, status.sig_ignored.bits());
+        assert_eq!(0x00000001800004ec, status.sig_caught.bits());
+        assert!(status.sig_pending_thread.is_empty());
+        assert_eq!(vec![Signal::Pipe], status.sig_ignored.iter().collect::<Vec<_>>());
+        assert_eq!(vec![Signal::Quit, Signal::Ill, Signal::Abrt, Signal::Bus, Signal::Fpe,
+                        Signal::Segv, Signal::Realtime(32), Signal::Realtime(33)],
+                   status.sig_caught.iter().collect::<Vec<_>>());
+        assert!(status.sig_blocked.contains(Signal::Hup));
+        assert!(!status.sig_blocked.contains(Signal::Quit));
+        assert_eq!(0x0000000000000000, status.cap_inherited.bits());
+        assert_eq!(0x0000003fffffffff, status.cap_permitted.bits());
+        assert_eq!(0x0000003fffffffff, status.cap_effective.bits());
+        assert_eq!(0x0000003fffffffff, status.cap_bounding.bits());
+        assert_eq!(Some(Capabilities::empty()), status.cap_ambient);
+        assert!(status.cap_effective.contains(Capabilities::SYS_ADMIN | Capabilities::AUDIT_READ));
+        assert!(!status.cap_effective.contains(Capabilities::PERFMON));
+        assert!(status.cap_inherited.is_empty());
+        assert_eq!(Some(false), status.no_new_privs);
+        assert_eq!(Some(SeccompMode::Filter), status.seccomp);
+        assert_eq!(Some(1), status.seccomp_filters);
+        assert_eq!(Some(SpeculationStoreBypass::ThreadVulnerable), status.speculation_store_bypass);
+        assert_eq!(Some(SpeculationIndirectBranch::ConditionalEnabled),
+                   status.speculation_indirect_branch);
         assert_eq!(&[0xff, 0xff, 0x00, 0x00], &*status.cpus_allowed);
-        let mems_allowed: &mut [u8] = &mut [0; 64];
-        mems_allowed[0] = 0x80;
-        assert_eq!(mems_allowed, &*status.mems_allowed);
+        assert_eq!(NodeSet::parse_list("0").unwrap(), status.mems_allowed);
+        assert_eq!(Some(CpuSet::parse_list("0-15").unwrap()), status.cpus_allowed_list);
+        assert_eq!(Some(CpuSet::from_mask(&status.cpus_allowed)), status.cpus_allowed_list);
+        assert_eq!(Some(status.mems_allowed), status.mems_allowed_list);
         assert_eq!(242129, status.voluntary_ctxt_switches);
         assert_eq!(1748, status.nonvoluntary_ctxt_switches);
     }
-}
 
-#[cfg(all(test, rustc_nightly))]
-mod benches {
-    extern crate test;
+    /// Test that lines with unrecognized labels, such as those added by newer kernels, are skipped.
+    #[test]
+    fn test_parse_status_unknown_lines() {
+        let status_text = b"Name:\tbash\n\
+                            Umask:\t0022\n\
+                            State:\tS (sleeping)\n\
+                            Tgid:\t4201\n\
+                            Kthread:\t0\n\
+                            x86_Thread_features:\t\n\
+                            untag_mask:\t0xffffffffffffffff\n\
+                            Threads:\t1\n";
 
-    use std::fs::File;
+        let status = parse_status(status_text).unwrap();
+        assert_eq!("bash", status.command);
+        assert_eq!(4201, status.pid);
+        assert_eq!(1, status.threads);
+    }
 
-    use parsers::read_to_end;
-    use super::{parse_status, status};
+    /// Test that fields which are not reported by older kernels are missing.
+    #[test]
+    fn test_parse_status_old_kernel() {
+        // Linux 3.10.
+        let status_text = b"Name:\tsshd\n\
+                            State:\tS (sleeping)\n\
+                            Tgid:\t1021\n\
+                            Pid:\t1021\n\
+                            PPid:\t1\n\
+                            TracerPid:\t0\n\
+                            Uid:\t0\t0\t0\t0\n\
+                            Gid:\t0\t0\t0\t0\n\
+                            FDSize:\t64\n\
+                            Groups:\t\n\
+                            VmPeak:\t  105996 kB\n\
+                            VmSize:\t  105996 kB\n\
+                            VmLck:\t       0 kB\n\
+                            VmPin:\t       0 kB\n\
+                            VmHWM:\t    4232 kB\n\
+                            VmRSS:\t    4232 kB\n\
+                            VmData:\t     872 kB\n\
+                            VmStk:\t     136 kB\n\
+                            VmExe:\t     804 kB\n\
+                            VmLib:\t    7736 kB\n\
+                            VmPTE:\t     212 kB\n\
+                            VmSwap:\t       0 kB\n\
+                            Threads:\t1\n\
+                            SigQ:\t0/14999\n\
+                            CapBnd:\t0000001fffffffff\n\
+                            Seccomp:\t0\n\
+                            voluntary_ctxt_switches:\t41\n\
+                            nonvoluntary_ctxt_switches:\t12\n";
+
+        let status = parse_status(status_text).unwrap();
+        assert_eq!(None, status.umask);
+        assert_eq!(None, status.numa_gid);
+        assert_eq!(Vec::<u32>::new(), status.groups);
+        assert_eq!(Some(0), status.vm_pin);
+        assert_eq!(None, status.vm_rss_anon);
+        assert_eq!(None, status.vm_rss_anon_bytes());
+        assert_eq!(None, status.vm_pmd);
+        assert_eq!(None, status.hugetlb_pages);
+        assert_eq!(None, status.core_dumping);
+        assert_eq!(None, status.thp_enabled);
+        assert_eq!(None, status.cap_ambient);
+        assert_eq!(None, status.no_new_privs);
+        assert_eq!(Some(SeccompMode::Disabled), status.seccomp);
+        assert_eq!(None, status.seccomp_filters);
+        assert_eq!(None, status.speculation_store_bypass);
+        assert_eq!(None, status.speculation_indirect_branch);
 
-    #[bench]
-    fn bench_status(b: &mut test::Bencher) {
-        b.iter(|| test::black_box(status(1)));
+        assert_eq!(ProcessState::Wakekill,
+                   parse_status(b"State:\tK (wakekill)\n").unwrap().state);
+        assert_eq!(ProcessState::Dead,
+                   parse_status(b"State:\tx (dead)\n").unwrap().state);
+        assert_eq!(ProcessState::Waking,
+                   parse_status(b"State:\tW (waking)\n").unwrap().state);
+        assert_eq!(ProcessState::Paging,
+                   parse_status(b"State:\tW (paging)\n").unwrap().state);
+        assert_eq!(ProcessState::Other('Q'),
+                   parse_status(b"State:\tQ (quiescent)\n").unwrap().state);
     }
 
-    #[bench]
-    fn bench_status_parse(b: &mut test::Bencher) {
-        let mut buf = [0; 2048];
-        let status = read_to_end(&mut File::open("/proc/1/status").unwrap(), &mut buf).unwrap();
-        b.iter(|| test::black_box(parse_status(status)));
+    /// Test that values of enumerated fields added by later kernels do not fail parsing.
+    #[test]
+    fn test_parse_status_unknown_values() {
+        let status = parse_status(b"Seccomp:\t3\n\
+                                    Speculation_Store_Bypass:\tthread partially mitigated\n\
+                                    SpeculationIndirectBranch:\tconditional sometimes\n").unwrap();
+        assert_eq!(Some(SeccompMode::Other(3)), status.seccomp);
+        assert_eq!(Some(SpeculationStoreBypass::Other("thread partially mitigated".to_owned())),
+                   status.speculation_store_bypass);
+        assert_eq!(Some(SpeculationIndirectBranch::Other("conditional sometimes".to_owned())),
+                   status.speculation_indirect_branch);
+        parse_status(b"Seccomp:\tfilter\n").unwrap_err();
+    }
+
+    #[test]
+    fn test_parse_status_lossy() {
+        let status_text = b"Name:\tbash\n\
+                            State:\tQ\n\
+                            Tgid:\t4201\n\
+                            VmRSS:\t    9212 MB\n\
+                            Threads:\t1\n";
+
+        parse_status(status_text).unwrap_err();
+        let (status, warnings) = Status::parse_lossy(status_text);
+        assert_eq!("bash", status.command);
+        assert_eq!(ProcessState::Running, status.state);
+        assert_eq!(4201, status.pid);
+        assert_eq!(0, status.vm_rss);
+        assert_eq!(1, status.threads);
+        assert_eq!(vec![2, 4], warnings.iter().map(|warning| warning.line).collect::<Vec<_>>());
+        assert_eq!("State:\tQ", warnings[0].text);
+        assert!(warnings[1].message.contains("kB"));
+    }
+
+    #[test]
+    fn test_signal_set() {
+        let set = SignalSet::from_bits(0x0000000180004a02);
+        assert_eq!(6, set.len());
+        assert_eq!(vec!["SIGINT", "SIGUSR1", "SIGUSR2", "SIGTERM", "SIGRTMIN", "SIGRTMIN+1"],
+                   set.iter().map(|signal| signal.to_string()).collect::<Vec<_>>());
+        assert_eq!(Some(Signal::Term), Signal::from_number(15));
+        assert_eq!(None, Signal::from_number(0));
+        assert_eq!(None, Signal::from_number(65));
+        assert!(SignalSet::default().is_empty());
     }
 }
@@ -0,0 +1,189 @@
+//! Per-thread CPU times of a process, from `/proc/[pid]/task/[tid]/stat`.
+
+use std::cmp::Ordering;
+use std::fs;
+use std::io::Result;
+use std::time::{Duration, Instant};
+
+use libc::pid_t;
+
+use delta::{ProcessCpuUsage, tick_percent};
+use pid::pids::is_exited;
+use pid::stat::{Stat, stat_task};
+use sysconf::ticks_to_duration;
+
+/// The CPU times of a thread, measured in clock ticks (divide by `ticks_per_second()`).
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "export", derive(Serialize))]
+pub struct ThreadTimes {
+    /// The thread ID.
+    pub tid: pid_t,
+    /// The name of the thread, as set by `pthread_setname_np(3)`, or the command name of the
+    /// process if unset.
+    pub name: String,
+    /// Time the thread has been scheduled in user mode.
+    pub utime: u64,
+    /// Time the thread has been scheduled in kernel mode.
+    pub stime: u64,
+    /// The time the thread started after system boot, which tells apart threads with a reused
+    /// thread ID.
+    pub start_time: u64,
+}
+
+impl ThreadTimes {
+    fn from_stat(stat: Stat) -> ThreadTimes {
+        ThreadTimes {
+            tid: stat.pid,
+            name: stat.command,
+            utime: stat.utime,
+            stime: stat.stime,
+            start_time: stat.start_time,
+        }
+    }
+
+    /// Returns the time the thread has been scheduled in user and kernel mode.
+    pub fn total_duration(&self) -> Duration {
+        ticks_to_duration(self.utime + self.stime)
+    }
+}
+
+/// A reading of the CPU times of every thread of a process.
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "export", derive(Serialize))]
+pub struct ThreadCpu {
+    /// The process ID.
+    pub pid: pid_t,
+    /// The monotonic time at which the reading was taken, used to compute deltas.
+    #[cfg_attr(feature = "export", serde(skip))]
+    instant: Instant,
+    /// The CPU times of each thread, in thread ID order.
+    pub threads: Vec<ThreadTimes>,
+}
+
+/// The CPU usage of a thread between two readings.
+#[derive(Clone, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "export", derive(Serialize))]
+pub struct ThreadCpuUsage {
+    /// The thread ID.
+    pub tid: pid_t,
+    /// The name of the thread at the later reading.
+    pub name: String,
+    /// The percentage of a CPU used by the thread.
+    pub usage: ProcessCpuUsage,
+}
+
+impl ThreadCpu {
+    /// Returns the CPU usage of each thread running during both readings, busiest first.
+    ///
+    /// Threads which started or exited between the readings are left out.
+    pub fn delta(&self, prev: &ThreadCpu) -> Vec<ThreadCpuUsage> {
+        let interval = self.instant.saturating_duration_since(prev.instant);
+        let mut usages = self.threads.iter().filter_map(|later| {
+            let index = prev.threads.binary_search_by_key(&later.tid, |earlier| earlier.tid).ok()?;
+            let earlier = &prev.threads[index];
+            if earlier.start_time != later.start_time {
+                return None;
+            }
+            Some(ThreadCpuUsage {
+                tid: later.tid,
+                name: later.name.clone(),
+                usage: ProcessCpuUsage {
+                    user: tick_percent(earlier.utime, later.utime, interval),
+                    system: tick_percent(earlier.stime, later.stime, interval),
+                },
+            })
+        }).collect::<Vec<_>>();
+        usages.sort_by(|a, b| {
+            b.usage.total().partial_cmp(&a.usage.total()).unwrap_or(Ordering::Equal)
+        });
+        usages
+    }
+}
+
+/// Returns the CPU times of every thread of the process with the provided pid.
+///
+/// Threads which exit while the reading is taken are left out.
+pub fn thread_cpu(pid: pid_t) -> Result<ThreadCpu> {
+    let instant = Instant::now();
+    let mut threads = Vec::new();
+    for entry in fs::read_dir(format!("/proc/{}/task", pid))? {
+        let entry = entry?;
+        let tid = match entry.file_name().to_str().and_then(|name| name.parse().ok()) {
+            Some(tid) => tid,
+            None => continue,
+        };
+        match stat_task(pid, tid) {
+            Ok(stat) => threads.push(ThreadTimes::from_stat(stat)),
+            Err(ref error) if is_exited(error) => continue,
+            Err(error) => return Err(error),
+        }
+    }
+    threads.sort_by_key(|thread| thread.tid);
+    Ok(ThreadCpu { pid, instant, threads })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicBool, Ordering};
+    use std::sync::{Arc, mpsc};
+    use std::thread;
+    use std::time::{Duration, Instant};
+
+    use libc;
+
+    use super::{ThreadCpu, ThreadTimes, thread_cpu};
+
+    #[test]
+    fn test_thread_cpu() {
+        let pid = unsafe { libc::getpid() };
+        let stop = Arc::new(AtomicBool::new(false));
+        let (started, running) = mpsc::channel();
+        let spinner = {
+            let stop = stop.clone();
+            // The name of the thread is set by the thread itself when it starts.
+            thread::Builder::new().name("procinfo-spin".to_owned()).spawn(move || {
+                started.send(()).unwrap();
+                while !stop.load(Ordering::Relaxed) {}
+            }).unwrap()
+        };
+        running.recv().unwrap();
+
+        let earlier = thread_cpu(pid).unwrap();
+        assert_eq!(pid, earlier.pid);
+        assert!(earlier.threads.iter().any(|thread| thread.tid == pid));
+        assert!(earlier.threads.iter().any(|thread| thread.name == "procinfo-spin"));
+        thread::sleep(Duration::from_millis(100));
+        let later = thread_cpu(pid).unwrap();
+        stop.store(true, Ordering::Relaxed);
+        spinner.join().unwrap();
+
+        let usages = later.delta(&earlier);
+        assert!(!usages.is_empty());
+        assert!(usages.windows(2).all(|pair| pair[0].usage.total() >= pair[1].usage.total()));
+        let spin = usages.iter().find(|usage| usage.name == "procinfo-spin").unwrap();
+        assert!(spin.usage.total() > 0.0);
+    }
+
+    #[test]
+    fn test_delta() {
+        let instant = Instant::now();
+        let thread = |tid, utime, start_time| ThreadTimes {
+            tid,
+            name: format!("thread-{}", tid),
+            utime,
+            stime: 0,
+            start_time,
+        };
+        let prev = ThreadCpu { pid: 1, instant, threads: vec![thread(1, 0, 0), thread(2, 0, 0)] };
+        let later = ThreadCpu {
+            pid: 1,
+            instant: instant + Duration::from_secs(1),
+            threads: vec![thread(1, 10, 0), thread(2, 50, 5), thread(3, 0, 9)],
+        };
+        // Thread 2 was replaced by a thread with the same ID, and thread 3 is new.
+        let usages = later.delta(&prev);
+        assert_eq!(1, usages.len());
+        assert_eq!(1, usages[0].tid);
+        assert_eq!("thread-1", usages[0].name);
+    }
+}
@@ -0,0 +1,92 @@
+//! Timer slack of a process from `/proc/[pid]/timerslack_ns`.
+
+use std::fs::{File, OpenOptions};
+use std::io::{Error, ErrorKind, Result, Write};
+use std::time::Duration;
+
+use libc::pid_t;
+
+use parsers::{parse, parse_file, parse_line, read_to_end};
+
+/// A timer slack value to apply to a process.
+///
+/// See `prctl(2)` `PR_SET_TIMERSLACK`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "export", derive(Serialize))]
+pub enum TimerSlack {
+    /// Reset the timer slack to the process's default value, which is inherited from the parent
+    /// at creation time.
+    Default,
+    /// Use the provided timer slack. Must not be zero.
+    Slack(Duration),
+}
+
+/// Parses the timerslack_ns file format.
+fn parse_timerslack_ns(input: &[u8]) -> Result<Duration> {
+    parse(parse_line(input)?).map(Duration::from_nanos)
+}
+
+/// Parses the provided timerslack_ns file.
+pub(crate) fn timerslack_ns_file(file: &mut File) -> Result<Duration> {
+    let mut buf = [0; 32]; // A timerslack_ns file is at most 21 bytes.
+    parse_timerslack_ns(&read_to_end(file, &mut buf)?)
+}
+
+/// Returns the current timer slack of the process with the provided pid (since Linux 4.6).
+///
+/// Reading the timer slack of another process requires ptrace access to it.
+pub fn timerslack_ns(pid: pid_t) -> Result<Duration> {
+    parse_file(format!("/proc/{}/timerslack_ns", pid), timerslack_ns_file)
+}
+
+/// Returns the current timer slack of the current process (since Linux 4.6).
+pub fn timerslack_ns_self() -> Result<Duration> {
+    parse_file("/proc/self/timerslack_ns", timerslack_ns_file)
+}
+
+/// Sets the timer slack of the current process (since Linux 4.6).
+///
+/// The kernel interprets a slack of zero as a request to restore the default, so
+/// `TimerSlack::Slack` with a zero duration is rejected with `ErrorKind::InvalidInput`; use
+/// `TimerSlack::Default` instead.
+pub fn set_timerslack_ns_self(slack: TimerSlack) -> Result<()> {
+    let nanos = match slack {
+        TimerSlack::Default => 0,
+        TimerSlack::Slack(duration) if duration.as_nanos() == 0 => {
+            return Err(Error::new(ErrorKind::InvalidInput, "timer slack must not be zero"));
+        }
+        TimerSlack::Slack(duration) => duration.as_nanos(),
+    };
+    let mut file = OpenOptions::new().write(true).open("/proc/self/timerslack_ns")?;
+    file.write_all(format!("{}\n", nanos).as_bytes())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::{TimerSlack, parse_timerslack_ns, set_timerslack_ns_self, timerslack_ns_self};
+
+    /// Test that the timerslack_ns file of the current process can be parsed.
+    #[test]
+    fn test_timerslack_ns() {
+        timerslack_ns_self().unwrap();
+    }
+
+    /// Test that writing the current slack back is accepted.
+    #[test]
+    fn test_set_timerslack_ns_self() {
+        let slack = timerslack_ns_self().unwrap();
+        if slack != Duration::from_secs(0) {
+            set_timerslack_ns_self(TimerSlack::Slack(slack)).unwrap();
+            assert_eq!(slack, timerslack_ns_self().unwrap());
+        }
+        set_timerslack_ns_self(TimerSlack::Slack(Duration::from_secs(0))).unwrap_err();
+    }
+
+    #[test]
+    fn test_parse_timerslack_ns() {
+        assert_eq!(Duration::new(0, 50000), parse_timerslack_ns(b"50000\n").unwrap());
+        assert_eq!(Duration::new(2, 5), parse_timerslack_ns(b"2000000005\n").unwrap());
+    }
+}
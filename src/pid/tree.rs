@@ -0,0 +1,224 @@
+//! The process tree, built from the parent pids in `/proc/[pid]/stat`.
+
+use std::collections::HashMap;
+use std::io::Result;
+
+use libc::pid_t;
+
+use pid::pids::{is_exited, pids};
+use pid::stat::stat;
+
+#[derive(Debug)]
+struct Node {
+    ppid: pid_t,
+    command: String,
+    children: Vec<pid_t>,
+}
+
+/// A snapshot of the parent/child relationships between processes.
+///
+/// Processes whose parent is not in the tree (pid 1, `kthreadd`, and processes whose parent
+/// exited while the tree was being built) are roots.
+#[derive(Debug)]
+pub struct ProcessTree {
+    nodes: HashMap<pid_t, Node>,
+    roots: Vec<pid_t>,
+}
+
+impl ProcessTree {
+    /// Builds a tree from `(pid, ppid, command)` entries.
+    fn from_entries<I>(entries: I) -> ProcessTree
+    where I: IntoIterator<Item = (pid_t, pid_t, String)> {
+        let mut nodes: HashMap<pid_t, Node> = entries.into_iter()
+            .map(|(pid, ppid, command)| (pid, Node { ppid, command, children: Vec::new() }))
+            .collect();
+
+        let mut roots = Vec::new();
+        let links: Vec<(pid_t, pid_t)> =
+            nodes.iter().map(|(&pid, node)| (pid, node.ppid)).collect();
+        for (pid, ppid) in links {
+            match nodes.get_mut(&ppid) {
+                Some(parent) if ppid != pid => parent.children.push(pid),
+                _ => roots.push(pid),
+            }
+        }
+        for node in nodes.values_mut() {
+            node.children.sort();
+        }
+        roots.sort();
+
+        ProcessTree { nodes, roots }
+    }
+
+    /// Returns the number of processes in the tree.
+    pub fn len(&self) -> usize {
+        self.nodes.len()
+    }
+
+    /// Returns `true` if the tree contains no processes.
+    pub fn is_empty(&self) -> bool {
+        self.nodes.is_empty()
+    }
+
+    /// Returns `true` if the tree contains the process with the provided pid.
+    pub fn contains(&self, pid: pid_t) -> bool {
+        self.nodes.contains_key(&pid)
+    }
+
+    /// Returns the pids of the processes without a parent in the tree, in ascending order.
+    pub fn roots(&self) -> &[pid_t] {
+        &self.roots
+    }
+
+    /// Returns the parent pid of the process, or `None` if the process is not in the tree.
+    ///
+    /// The parent of a root process is not in the tree.
+    pub fn parent(&self, pid: pid_t) -> Option<pid_t> {
+        self.nodes.get(&pid).map(|node| node.ppid)
+    }
+
+    /// Returns the pids of the children of the process, in ascending order.
+    pub fn children(&self, pid: pid_t) -> &[pid_t] {
+        self.nodes.get(&pid).map(|node| &node.children[..]).unwrap_or(&[])
+    }
+
+    /// Returns the command name of the process, or `None` if the process is not in the tree.
+    pub fn command(&self, pid: pid_t) -> Option<&str> {
+        self.nodes.get(&pid).map(|node| &node.command[..])
+    }
+
+    /// Returns an iterator over the process and all of its descendants, in depth-first pre-order.
+    ///
+    /// The iterator is empty if the process is not in the tree.
+    pub fn subtree(&self, pid: pid_t) -> Subtree<'_> {
+        let stack = if self.contains(pid) { vec![(pid, 0)] } else { Vec::new() };
+        Subtree { tree: self, stack, remaining: self.len() }
+    }
+
+    /// Returns an iterator over the ancestors of the process, starting with its parent.
+    pub fn ancestors(&self, pid: pid_t) -> Ancestors<'_> {
+        Ancestors { tree: self, pid, remaining: self.len() }
+    }
+}
+
+/// An iterator over a process and its descendants, yielding each pid with its depth relative to
+/// the root of the subtree.
+///
+/// The tree is not read atomically and pids are reused, so the parent pids may form a cycle. The
+/// iterator yields at most as many pids as there are processes in the tree, so that it terminates.
+#[derive(Debug)]
+pub struct Subtree<'a> {
+    tree: &'a ProcessTree,
+    stack: Vec<(pid_t, usize)>,
+    remaining: usize,
+}
+
+impl<'a> Iterator for Subtree<'a> {
+    type Item = (pid_t, usize);
+
+    fn next(&mut self) -> Option<(pid_t, usize)> {
+        self.remaining = self.remaining.checked_sub(1)?;
+        let (pid, depth) = self.stack.pop()?;
+        self.stack.extend(self.tree.children(pid).iter().rev().map(|&child| (child, depth + 1)));
+        Some((pid, depth))
+    }
+}
+
+/// An iterator over the ancestors of a process.
+///
+/// The iterator yields at most as many pids as there are processes in the tree, so that it
+/// terminates if the parent pids form a cycle.
+#[derive(Debug)]
+pub struct Ancestors<'a> {
+    tree: &'a ProcessTree,
+    pid: pid_t,
+    remaining: usize,
+}
+
+impl<'a> Iterator for Ancestors<'a> {
+    type Item = pid_t;
+
+    fn next(&mut self) -> Option<pid_t> {
+        self.remaining = self.remaining.checked_sub(1)?;
+        let ppid = self.tree.parent(self.pid)?;
+        if !self.tree.contains(ppid) || ppid == self.pid {
+            return None;
+        }
+        self.pid = ppid;
+        Some(ppid)
+    }
+}
+
+/// Returns the tree of all processes.
+///
+/// Processes which exit while the tree is being built are omitted.
+pub fn tree() -> Result<ProcessTree> {
+    let mut entries = Vec::new();
+    for pid in pids()? {
+        match stat(pid?) {
+            Ok(stat) => entries.push((stat.pid, stat.ppid, stat.command)),
+            Err(ref error) if is_exited(error) => continue,
+            Err(error) => return Err(error),
+        }
+    }
+    Ok(ProcessTree::from_entries(entries))
+}
+
+#[cfg(test)]
+mod tests {
+    use libc;
+
+    use super::{ProcessTree, tree};
+
+    fn sample() -> ProcessTree {
+        ProcessTree::from_entries(vec![(1, 0, "init".to_owned()),
+                                       (2, 0, "kthreadd".to_owned()),
+                                       (10, 1, "sshd".to_owned()),
+                                       (12, 10, "bash".to_owned()),
+                                       (11, 10, "bash".to_owned()),
+                                       (20, 11, "vim".to_owned()),
+                                       (30, 99, "orphan".to_owned())])
+    }
+
+    #[test]
+    fn test_tree() {
+        let tree = tree().unwrap();
+        let pid = unsafe { libc::getpid() };
+        assert!(tree.contains(1));
+        assert!(tree.contains(pid));
+        assert!(tree.subtree(1).count() > 1);
+    }
+
+    #[test]
+    fn test_navigation() {
+        let tree = sample();
+        assert_eq!(7, tree.len());
+        assert_eq!(&[1, 2, 30], tree.roots());
+        assert_eq!(Some(10), tree.parent(11));
+        assert_eq!(&[11, 12], tree.children(10));
+        assert!(tree.children(20).is_empty());
+        assert!(tree.children(1000).is_empty());
+        assert_eq!(Some("vim"), tree.command(20));
+        assert_eq!(vec![11, 10, 1], tree.ancestors(20).collect::<Vec<_>>());
+        assert_eq!(0, tree.ancestors(30).count());
+    }
+
+    #[test]
+    fn test_subtree() {
+        let tree = sample();
+        assert_eq!(vec![(10, 0), (11, 1), (20, 2), (12, 1)], tree.subtree(10).collect::<Vec<_>>());
+        assert_eq!(vec![(20, 0)], tree.subtree(20).collect::<Vec<_>>());
+        assert_eq!(0, tree.subtree(1000).count());
+    }
+
+    /// Test that iteration terminates when reused pids make the parent pids form a cycle.
+    #[test]
+    fn test_cycle() {
+        let tree = ProcessTree::from_entries(vec![(1, 0, "init".to_owned()),
+                                                  (40, 41, "a".to_owned()),
+                                                  (41, 40, "b".to_owned())]);
+        assert_eq!(&[1], tree.roots());
+        assert!(tree.ancestors(40).count() <= tree.len());
+        assert!(tree.subtree(40).count() <= tree.len());
+    }
+}
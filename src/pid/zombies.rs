@@ -0,0 +1,111 @@
+//! Enumeration of zombie processes.
+
+use std::collections::hash_map::{Entry, HashMap};
+use std::io::{Error, Result};
+use std::mem;
+use std::time::Duration;
+
+use libc::{self, pid_t};
+
+use pid::ProcessState;
+use pid::pids::is_exited;
+use pid::stat::{stat, stat_all};
+use sysconf::ticks_to_duration;
+
+/// A zombie process: one which has exited, but has not been reaped by its parent.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "export", derive(Serialize))]
+pub struct Zombie {
+    /// The pid of the zombie.
+    pub pid: pid_t,
+    /// The command name of the zombie.
+    pub command: String,
+    /// The pid of the parent, which is responsible for reaping the zombie.
+    pub ppid: pid_t,
+    /// The command name of the parent, or `None` if the parent exited during the scan.
+    pub parent_command: Option<String>,
+    /// The exit status, as returned by `wait(2)` (since Linux 3.5).
+    pub exit_code: Option<i32>,
+    /// The time since the process started.
+    ///
+    /// The kernel does not record when a process exits, so this is an upper bound on the time
+    /// since the zombie died.
+    pub age: Duration,
+}
+
+/// Returns the time since boot, including time spent suspended, which is the clock of the start
+/// times of processes.
+fn boot_clock() -> Result<Duration> {
+    let mut ts: libc::timespec = unsafe { mem::zeroed() };
+    if unsafe { libc::clock_gettime(libc::CLOCK_BOOTTIME, &mut ts) } < 0 {
+        return Err(Error::last_os_error());
+    }
+    Ok(Duration::new(ts.tv_sec as u64, ts.tv_nsec as u32))
+}
+
+/// Returns all zombie processes, in no particular order.
+///
+/// Zombies which are reaped during the scan are skipped.
+pub fn zombies() -> Result<Vec<Zombie>> {
+    let zombies = stat_all()?.filter_map(|stat| match stat {
+        Ok(ref stat) if stat.state != ProcessState::Zombie => None,
+        stat => Some(stat),
+    }).collect::<Result<Vec<_>>>()?;
+    let now = boot_clock()?;
+
+    let mut parents: HashMap<pid_t, Option<String>> = HashMap::new();
+    let mut result = Vec::with_capacity(zombies.len());
+    for zombie in zombies {
+        let parent_command = match parents.entry(zombie.ppid) {
+            Entry::Occupied(entry) => entry.get().clone(),
+            Entry::Vacant(entry) => {
+                let command = match stat(zombie.ppid) {
+                    Ok(parent) => Some(parent.command),
+                    Err(ref error) if is_exited(error) => None,
+                    Err(error) => return Err(error),
+                };
+                entry.insert(command).clone()
+            },
+        };
+        result.push(Zombie {
+            pid: zombie.pid,
+            age: now.saturating_sub(ticks_to_duration(zombie.start_time)),
+            command: zombie.command,
+            ppid: zombie.ppid,
+            parent_command,
+            exit_code: zombie.exit_code,
+        });
+    }
+    Ok(result)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::process::Command;
+    use std::thread;
+    use std::time::Duration;
+
+    use libc;
+
+    use pid::{ProcessState, stat};
+    use super::zombies;
+
+    #[test]
+    fn test_zombies() {
+        let mut child = Command::new("true").spawn().unwrap();
+        let pid = child.id() as i32;
+        while stat(pid).unwrap().state != ProcessState::Zombie {
+            thread::sleep(Duration::from_millis(1));
+        }
+
+        let zombies = zombies().unwrap();
+        child.wait().unwrap();
+        let zombie = zombies.iter().find(|zombie| zombie.pid == pid).unwrap();
+        assert_eq!(unsafe { libc::getpid() }, zombie.ppid);
+        assert_eq!("true", zombie.command);
+        assert!(zombie.parent_command.is_some());
+        assert_eq!(Some(0), zombie.exit_code);
+        assert!(zombie.age < Duration::from_secs(60));
+        assert!(zombies.iter().all(|zombie| zombie.ppid > 0));
+    }
+}
@@ -0,0 +1,269 @@
+//! Pressure stall information from `/proc/pressure`.
+
+use std::fs::{File, OpenOptions};
+use std::io::{Error, ErrorKind, Result, Write};
+use std::os::unix::io::{AsRawFd, RawFd};
+use std::time::Duration;
+
+use libc;
+
+use parsers::{invalid_input, parse, parse_file, parse_str, read_to_end, with_line};
+
+/// A resource for which pressure stall information is tracked.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "export", derive(Serialize))]
+pub enum PressureResource {
+    /// CPU time.
+    Cpu,
+    /// Block IO.
+    Io,
+    /// Memory, including time spent reclaiming and swapping.
+    Memory,
+    /// Interrupt handling (since Linux 6.1, with `CONFIG_IRQ_TIME_ACCOUNTING`).
+    ///
+    /// Only `full` pressure is tracked for interrupts, so `PsiTrigger::register` is rejected by
+    /// the kernel for this resource; use `PsiTrigger::register_full`.
+    Irq,
+}
+
+impl PressureResource {
+    /// Returns the path of the pressure file of the resource.
+    fn path(self) -> &'static str {
+        match self {
+            PressureResource::Cpu    => "/proc/pressure/cpu",
+            PressureResource::Io     => "/proc/pressure/io",
+            PressureResource::Memory => "/proc/pressure/memory",
+            PressureResource::Irq    => "/proc/pressure/irq",
+        }
+    }
+}
+
+/// The share of time in which tasks were stalled on a resource.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "export", derive(Serialize))]
+pub struct PressureRecord {
+    /// The percentage of time stalled over the last 10 seconds.
+    pub avg10: f32,
+    /// The percentage of time stalled over the last 60 seconds.
+    pub avg60: f32,
+    /// The percentage of time stalled over the last 300 seconds.
+    pub avg300: f32,
+    /// The total time stalled, in microseconds.
+    pub total: u64,
+}
+
+/// Pressure stall information for a resource.
+///
+/// See `Linux/Documentation/accounting/psi.rst`.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "export", derive(Serialize))]
+pub struct Pressure {
+    /// Time in which at least some tasks were stalled on the resource.
+    ///
+    /// Not reported for interrupt pressure.
+    pub some: Option<PressureRecord>,
+    /// Time in which all non-idle tasks were stalled on the resource simultaneously.
+    ///
+    /// Not reported for CPU pressure before Linux 5.13.
+    pub full: Option<PressureRecord>,
+}
+
+/// Parses a pressure line, of the form `some avg10=0.00 avg60=0.00 avg300=0.00 total=0`.
+///
+/// Fields which are unknown to this crate are ignored.
+fn parse_record(line: &str) -> Result<(&str, PressureRecord)> {
+    let mut fields = line.split_whitespace();
+    let kind = fields.next().ok_or_else(|| invalid_input("empty pressure line"))?;
+    let (mut avg10, mut avg60, mut avg300, mut total) = (None, None, None, None);
+    for field in fields {
+        let (key, value) = field.split_once('=').ok_or_else(|| {
+            invalid_input(format!("invalid pressure field: {:?}", field))
+        })?;
+        match key {
+            "avg10" => avg10 = Some(parse(value)?),
+            "avg60" => avg60 = Some(parse(value)?),
+            "avg300" => avg300 = Some(parse(value)?),
+            "total" => total = Some(parse(value)?),
+            _ => (),
+        }
+    }
+    let missing = |key| move || invalid_input(format!("missing {} pressure field", key));
+    let record = PressureRecord {
+        avg10: avg10.ok_or_else(missing("avg10"))?,
+        avg60: avg60.ok_or_else(missing("avg60"))?,
+        avg300: avg300.ok_or_else(missing("avg300"))?,
+        total: total.ok_or_else(missing("total"))?,
+    };
+    Ok((kind, record))
+}
+
+impl Pressure {
+    /// Parses the contents of a `/proc/pressure/<resource>` file, or of a cgroup
+    /// `<resource>.pressure` file.
+    ///
+    /// Lines of kinds other than `some` and `full` are ignored.
+    pub fn parse(input: &[u8]) -> Result<Pressure> {
+        let mut pressure = Pressure::default();
+        for (i, line) in parse_str(input)?.lines().enumerate() {
+            match parse_record(line).map_err(|error| with_line(error, i + 1))? {
+                ("some", record) => pressure.some = Some(record),
+                ("full", record) => pressure.full = Some(record),
+                _ => (),
+            }
+        }
+        Ok(pressure)
+    }
+}
+
+/// Returns the system-wide pressure stall information for the resource.
+///
+/// Requires a kernel built with `CONFIG_PSI` (since Linux 4.20), and booted without `psi=0`.
+pub fn pressure(resource: PressureResource) -> Result<Pressure> {
+    parse_file(resource.path(), |file| {
+        let mut buf = [0; 256]; // A pressure file is about 110 bytes.
+        Pressure::parse(&read_to_end(file, &mut buf)?)
+    })
+}
+
+/// A registered pressure stall trigger.
+///
+/// The trigger fires when tasks are stalled on the resource for longer than a threshold within a
+/// time window. The trigger is registered for as long as the value is alive, and fires at most
+/// once per window. The file descriptor may be polled for `POLLPRI` with `poll` or `epoll`; `wait`
+/// does so for a single trigger.
+///
+/// Unprivileged processes may register triggers since Linux 6.5, with windows which are a multiple
+/// of 2 seconds.
+#[derive(Debug)]
+pub struct PsiTrigger {
+    file: File,
+}
+
+impl PsiTrigger {
+    /// Registers a trigger which fires when at least some tasks are stalled on the resource for
+    /// `stall_us` microseconds within a window of `window_us` microseconds.
+    ///
+    /// The window must be between 500ms and 10s. The kernel rejects `some` triggers for
+    /// `PressureResource::Irq`.
+    pub fn register(resource: PressureResource, stall_us: u64, window_us: u64)
+                    -> Result<PsiTrigger> {
+        PsiTrigger::register_kind(resource, "some", stall_us, window_us)
+    }
+
+    /// Registers a trigger which fires when all non-idle tasks are stalled on the resource for
+    /// `stall_us` microseconds within a window of `window_us` microseconds.
+    pub fn register_full(resource: PressureResource, stall_us: u64, window_us: u64)
+                         -> Result<PsiTrigger> {
+        PsiTrigger::register_kind(resource, "full", stall_us, window_us)
+    }
+
+    fn register_kind(resource: PressureResource, kind: &str, stall_us: u64, window_us: u64)
+                     -> Result<PsiTrigger> {
+        let mut file = OpenOptions::new().read(true).write(true).open(resource.path())?;
+        // The kernel parses the trigger from a single write.
+        file.write_all(format!("{} {} {}", kind, stall_us, window_us).as_bytes())?;
+        Ok(PsiTrigger { file })
+    }
+
+    /// Blocks until the trigger fires.
+    pub fn wait(&self) -> Result<()> {
+        while !self.poll(-1)? {}
+        Ok(())
+    }
+
+    /// Blocks until the trigger fires or the timeout elapses, and returns whether the trigger
+    /// fired.
+    pub fn wait_timeout(&self, timeout: Duration) -> Result<bool> {
+        self.poll(timeout.as_millis().min(libc::c_int::MAX as u128) as libc::c_int)
+    }
+
+    /// Polls the trigger, with a timeout in milliseconds or `-1` to block indefinitely.
+    fn poll(&self, timeout: libc::c_int) -> Result<bool> {
+        let mut fd = libc::pollfd { fd: self.file.as_raw_fd(), events: libc::POLLPRI, revents: 0 };
+        loop {
+            match unsafe { libc::poll(&mut fd, 1, timeout) } {
+                -1 => {
+                    let error = Error::last_os_error();
+                    if error.kind() != ErrorKind::Interrupted {
+                        return Err(error);
+                    }
+                },
+                0 => return Ok(false),
+                _ if fd.revents & libc::POLLERR != 0 => {
+                    return Err(Error::other("pressure trigger is no longer valid"));
+                },
+                _ => return Ok(fd.revents & libc::POLLPRI != 0),
+            }
+        }
+    }
+}
+
+impl AsRawFd for PsiTrigger {
+    fn as_raw_fd(&self) -> RawFd {
+        self.file.as_raw_fd()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::ErrorKind;
+    use std::time::Duration;
+
+    use super::{Pressure, PressureRecord, PressureResource, PsiTrigger, pressure};
+
+    /// Test that the pressure files can be parsed, if the kernel tracks pressure.
+    #[test]
+    fn test_pressure() {
+        for &resource in &[PressureResource::Cpu,
+                           PressureResource::Io,
+                           PressureResource::Memory,
+                           PressureResource::Irq] {
+            if let Err(error) = pressure(resource) {
+                assert_eq!(ErrorKind::NotFound, error.kind());
+            }
+        }
+    }
+
+    /// Test that a trigger can be registered and polled, if permitted.
+    #[test]
+    fn test_psi_trigger() {
+        match PsiTrigger::register(PressureResource::Memory, 100_000, 2_000_000) {
+            Ok(trigger) => {
+                trigger.wait_timeout(Duration::from_millis(1)).unwrap();
+            },
+            // Some kernels reject triggers with EINVAL, e.g. when PSI is only partially enabled.
+            Err(error) => assert!(error.kind() == ErrorKind::NotFound
+                                  || error.kind() == ErrorKind::PermissionDenied
+                                  || error.kind() == ErrorKind::InvalidInput),
+        }
+    }
+
+    #[test]
+    fn test_parse_pressure() {
+        let text = b"some avg10=1.53 avg60=0.87 avg300=0.00 total=1245789\n\
+                     full avg10=0.00 avg60=0.12 avg300=0.00 total=5123\n";
+        let pressure = Pressure::parse(text).unwrap();
+        assert_eq!(PressureRecord { avg10: 1.53, avg60: 0.87, avg300: 0.0, total: 1245789 },
+                   pressure.some.unwrap());
+        assert_eq!(Some(5123), pressure.full.map(|full| full.total));
+
+        let text = b"some avg10=0.00 avg60=0.00 avg300=0.00 total=0\n";
+        assert_eq!(None, Pressure::parse(text).unwrap().full);
+
+        // Interrupt pressure only has a full line.
+        let text = b"full avg10=0.00 avg60=0.00 avg300=0.00 total=7\n";
+        let pressure = Pressure::parse(text).unwrap();
+        assert_eq!(None, pressure.some);
+        assert_eq!(Some(7), pressure.full.map(|full| full.total));
+
+        // Line kinds and fields added by later kernels are ignored.
+        let text = b"some avg10=0.00 avg60=0.00 avg300=0.00 total=3 extra=1\n\
+                     other avg10=0.00 avg60=0.00 avg300=0.00 total=0\n";
+        let pressure = Pressure::parse(text).unwrap();
+        assert_eq!(Some(3), pressure.some.map(|some| some.total));
+        assert_eq!(None, pressure.full);
+
+        Pressure::parse(b"some avg10=0.00 avg60=0.00 total=0\n").unwrap_err();
+        Pressure::parse(b"some avg10=0.00 avg60=zero avg300=0.00 total=0\n").unwrap_err();
+    }
+}
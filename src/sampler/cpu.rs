@@ -0,0 +1,146 @@
+//! CPU utilization sampling from `/proc/stat` and `/proc/[pid]/stat`.
+
+use std::io::Result;
+use std::time::{Duration, Instant};
+
+use libc::pid_t;
+
+use cpu::{Cpu, cpus};
+use delta::{CpuUsage, ProcessCpuUsage};
+use pid::{Stat, stat};
+use sampler::Sampler;
+
+/// CPU utilization between two readings.
+#[derive(Clone, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "export", derive(Serialize))]
+pub struct CpuSample {
+    /// The time elapsed between the readings.
+    pub interval: Duration,
+    /// Utilization of all CPUs together.
+    pub total: CpuUsage,
+    /// Utilization of each CPU online during both readings, by CPU number.
+    pub cpus: Vec<(usize, CpuUsage)>,
+    /// CPU usage of each sampled process running during both readings, by pid.
+    pub processes: Vec<(pid_t, ProcessCpuUsage)>,
+}
+
+/// A single reading of the CPU times.
+#[derive(Debug)]
+struct Reading {
+    instant: Instant,
+    cpus: Vec<Cpu>,
+    processes: Vec<(pid_t, Stat)>,
+}
+
+/// Samples system-wide, per-CPU, and per-process CPU utilization.
+///
+/// # Example
+///
+/// ```no_run
+/// use std::time::Duration;
+///
+/// use procinfo::sampler::CpuSampler;
+///
+/// let mut sampler = CpuSampler::new(Duration::from_secs(1));
+/// sampler.add_process(1);
+/// let sample = sampler.sample().unwrap();
+/// println!("busy: {:.1}%", sample.total.busy());
+/// ```
+#[derive(Debug)]
+pub struct CpuSampler {
+    sampler: Sampler<Reading>,
+    pids: Vec<pid_t>,
+}
+
+impl CpuSampler {
+    /// Creates a sampler which waits `interval` between the readings of its first sample.
+    pub fn new(interval: Duration) -> CpuSampler {
+        CpuSampler { sampler: Sampler::new(interval), pids: Vec::new() }
+    }
+
+    /// Adds the process with the provided pid to the processes whose CPU usage is sampled.
+    pub fn add_process(&mut self, pid: pid_t) {
+        if !self.pids.contains(&pid) {
+            self.pids.push(pid);
+        }
+    }
+
+    /// Removes the process with the provided pid from the sampled processes.
+    pub fn remove_process(&mut self, pid: pid_t) {
+        self.pids.retain(|&p| p != pid);
+    }
+
+    /// Returns the CPU utilization since the previous sample.
+    ///
+    /// If this is the first sample, two readings are taken `interval` apart.
+    pub fn sample(&mut self) -> Result<CpuSample> {
+        let pids = &self.pids;
+        self.sampler.sample(|| read(pids), compare)
+    }
+}
+
+/// Takes a reading of the CPU times and of the processes with the provided pids.
+///
+/// Processes which have exited are left out of the reading.
+fn read(pids: &[pid_t]) -> Result<Reading> {
+    let instant = Instant::now();
+    let cpus = cpus()?;
+    let processes = pids.iter().filter_map(|&pid| stat(pid).ok().map(|s| (pid, s))).collect();
+    Ok(Reading { instant, cpus, processes })
+}
+
+/// Computes the utilization between two readings.
+fn compare(prev: &Reading, reading: &Reading) -> CpuSample {
+    let interval = reading.instant.saturating_duration_since(prev.instant);
+    let mut sample = CpuSample { interval, ..Default::default() };
+    for cpu in &reading.cpus {
+        let earlier = match prev.cpus.iter().find(|earlier| earlier.id == cpu.id) {
+            Some(earlier) => earlier,
+            None => continue,
+        };
+        match cpu.id {
+            Some(id) => sample.cpus.push((id, cpu.delta(earlier))),
+            None => sample.total = cpu.delta(earlier),
+        }
+    }
+    for (pid, stat) in &reading.processes {
+        // A pid which was reused between the readings belongs to a different process.
+        let earlier = prev.processes.iter().find(|(p, earlier)| {
+            p == pid && earlier.start_time == stat.start_time
+        });
+        if let Some((_, earlier)) = earlier {
+            sample.processes.push((*pid, ProcessCpuUsage::between(earlier, stat, interval)));
+        }
+    }
+    sample
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use libc;
+
+    use super::CpuSampler;
+
+    #[test]
+    fn test_cpu_sampler() {
+        let pid = unsafe { libc::getpid() };
+        let mut sampler = CpuSampler::new(Duration::from_millis(20));
+        sampler.add_process(pid);
+        sampler.add_process(pid);
+        // A pid which does not exist is skipped.
+        sampler.add_process(i32::MAX);
+
+        let sample = sampler.sample().unwrap();
+        assert!(sample.interval >= Duration::from_millis(20));
+        assert!(!sample.cpus.is_empty());
+        assert!(sample.total.busy() <= 100.0);
+        assert_eq!(1, sample.processes.len());
+        assert_eq!(pid, sample.processes[0].0);
+
+        sampler.remove_process(pid);
+        let sample = sampler.sample().unwrap();
+        assert!(sample.processes.is_empty());
+    }
+}
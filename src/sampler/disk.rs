@@ -0,0 +1,149 @@
+//! Block device I/O sampling from `/proc/diskstats`, in the style of `iostat(1)`.
+
+use std::io::Result;
+use std::time::{Duration, Instant};
+
+use delta::DiskRates;
+use diskstats::{DiskStat, diskstats};
+use sampler::Sampler;
+
+/// Block device I/O rates between two readings.
+#[derive(Clone, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "export", derive(Serialize))]
+pub struct DiskSample {
+    /// The time elapsed between the readings.
+    pub interval: Duration,
+    /// The I/O rates of each sampled device present during both readings, by device name.
+    pub disks: Vec<(String, DiskRates)>,
+}
+
+/// A single reading of the block device statistics.
+#[derive(Debug)]
+struct Reading {
+    instant: Instant,
+    disks: Vec<DiskStat>,
+}
+
+/// Samples the IOPS, throughput, latency, and utilization of block devices.
+///
+/// All devices listed in `/proc/diskstats` are sampled, unless devices are added with
+/// `add_device`, in which case only those devices are sampled.
+///
+/// # Example
+///
+/// ```no_run
+/// use std::time::Duration;
+///
+/// use procinfo::sampler::DiskSampler;
+///
+/// let mut sampler = DiskSampler::new(Duration::from_secs(1));
+/// for (name, rates) in sampler.sample().unwrap().disks {
+///     println!("{}: {:.1} IOPS, {:.1}% util", name, rates.iops(), rates.utilization);
+/// }
+/// ```
+#[derive(Debug)]
+pub struct DiskSampler {
+    sampler: Sampler<Reading>,
+    devices: Vec<String>,
+}
+
+impl DiskSampler {
+    /// Creates a sampler which waits `interval` between the readings of its first sample.
+    pub fn new(interval: Duration) -> DiskSampler {
+        DiskSampler { sampler: Sampler::new(interval), devices: Vec::new() }
+    }
+
+    /// Adds the device with the provided name, such as `sda`, to the sampled devices.
+    pub fn add_device(&mut self, name: &str) {
+        if !self.devices.iter().any(|device| device == name) {
+            self.devices.push(name.to_owned());
+        }
+    }
+
+    /// Removes the device with the provided name from the sampled devices.
+    pub fn remove_device(&mut self, name: &str) {
+        self.devices.retain(|device| device != name);
+    }
+
+    /// Returns the I/O rates since the previous sample.
+    ///
+    /// If this is the first sample, two readings are taken `interval` apart.
+    pub fn sample(&mut self) -> Result<DiskSample> {
+        let devices = &self.devices;
+        self.sampler.sample(|| read(devices), compare)
+    }
+}
+
+/// Takes a reading of the statistics of the provided block devices, or of all devices if none
+/// are provided.
+fn read(devices: &[String]) -> Result<Reading> {
+    let instant = Instant::now();
+    let mut disks = diskstats()?;
+    if !devices.is_empty() {
+        disks.retain(|disk| devices.contains(&disk.name));
+    }
+    Ok(Reading { instant, disks })
+}
+
+/// Computes the I/O rates between two readings.
+fn compare(prev: &Reading, reading: &Reading) -> DiskSample {
+    let interval = reading.instant.saturating_duration_since(prev.instant);
+    let disks = reading.disks.iter().filter_map(|disk| {
+        // A device name which was reused between the readings belongs to a different device.
+        prev.disks.iter()
+                  .find(|earlier| {
+                      earlier.name == disk.name
+                          && earlier.major == disk.major
+                          && earlier.minor == disk.minor
+                  })
+                  .map(|earlier| (disk.name.clone(), DiskRates::between(earlier, disk, interval)))
+    }).collect();
+    DiskSample { interval, disks }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::{Duration, Instant};
+
+    use diskstats::{DiskStat, diskstats};
+    use super::{DiskSampler, Reading, compare};
+
+    #[test]
+    fn test_disk_sampler() {
+        let mut sampler = DiskSampler::new(Duration::from_millis(20));
+        let sample = sampler.sample().unwrap();
+        assert!(sample.interval >= Duration::from_millis(20));
+        assert_eq!(diskstats().unwrap().len(), sample.disks.len());
+        for (_, rates) in &sample.disks {
+            assert!(rates.utilization <= 100.0);
+        }
+
+        // A device which does not exist is never sampled.
+        sampler.add_device("procinfo-missing");
+        assert!(sampler.sample().unwrap().disks.is_empty());
+        sampler.remove_device("procinfo-missing");
+    }
+
+    #[test]
+    fn test_compare() {
+        let disk = |minor, reads_completed| DiskStat {
+            major: 8,
+            minor,
+            name: "sda".to_owned(),
+            reads_completed,
+            ..Default::default()
+        };
+        let instant = Instant::now();
+        let prev = Reading { instant, disks: vec![disk(0, 100)] };
+        let reading = Reading { instant: instant + Duration::from_secs(2),
+                                disks: vec![disk(0, 300)] };
+        let sample = compare(&prev, &reading);
+        assert_eq!(Duration::from_secs(2), sample.interval);
+        assert_eq!(1, sample.disks.len());
+        assert_eq!("sda", sample.disks[0].0);
+        assert_eq!(100.0, sample.disks[0].1.reads);
+
+        let reading = Reading { instant, disks: vec![disk(16, 300)] };
+        assert!(compare(&prev, &reading).disks.is_empty());
+    }
+}
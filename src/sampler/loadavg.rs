@@ -0,0 +1,222 @@
+//! Load average sampling from `/proc/loadavg`, with a bounded history of samples.
+
+use std::collections::VecDeque;
+use std::collections::vec_deque::Iter;
+use std::fmt;
+use std::io::Result;
+use std::time::{Duration, Instant, SystemTime};
+
+use loadavg::{LoadAvg, loadavg};
+use sampler::Sampler;
+
+/// A load average reading.
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "export", derive(Serialize))]
+pub struct LoadAvgSample {
+    /// The wall clock time at which the reading was taken.
+    pub time: SystemTime,
+    /// The monotonic time at which the reading was taken, used for windowing.
+    #[cfg_attr(feature = "export", serde(skip))]
+    instant: Instant,
+    /// The load average.
+    pub load_avg: LoadAvg,
+}
+
+/// Statistics of the 1-minute load average over a window of samples.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "export", derive(Serialize))]
+pub struct LoadAvgStats {
+    /// The lowest load average.
+    pub min: f32,
+    /// The highest load average.
+    pub max: f32,
+    /// The mean load average.
+    pub mean: f32,
+    /// The number of samples in the window.
+    pub samples: usize,
+}
+
+/// A callback which is called with a sample, and whether the load rose above the threshold (as
+/// opposed to falling back to or below it).
+type Callback = Box<dyn FnMut(&LoadAvgSample, bool)>;
+
+/// Samples the load average, keeping a bounded history of samples.
+///
+/// # Example
+///
+/// ```no_run
+/// use std::thread;
+/// use std::time::Duration;
+///
+/// use procinfo::sampler::LoadAvgSampler;
+///
+/// let mut sampler = LoadAvgSampler::new(Duration::from_secs(5), 720);
+/// sampler.on_threshold(8.0, |sample, rising| {
+///     let direction = if rising { "above" } else { "below" };
+///     println!("load {} threshold: {}", direction, sample.load_avg.load_avg_1_min);
+/// });
+/// loop {
+///     sampler.sample().unwrap();
+///     println!("{:?}", sampler.stats(Duration::from_secs(300)));
+///     thread::sleep(Duration::from_secs(5));
+/// }
+/// ```
+pub struct LoadAvgSampler {
+    sampler: Sampler<LoadAvgSample>,
+    capacity: usize,
+    samples: VecDeque<LoadAvgSample>,
+    threshold: Option<(f32, Callback)>,
+    above: bool,
+}
+
+impl LoadAvgSampler {
+    /// Creates a sampler which waits `interval` between the readings of its first sample, and
+    /// keeps the latest `capacity` samples.
+    pub fn new(interval: Duration, capacity: usize) -> LoadAvgSampler {
+        LoadAvgSampler {
+            sampler: Sampler::new(interval),
+            capacity: capacity.max(1),
+            samples: VecDeque::with_capacity(capacity.max(1)),
+            threshold: None,
+            above: false,
+        }
+    }
+
+    /// Sets a callback which is called when the 1-minute load average of a sample crosses the
+    /// threshold, replacing any previous callback.
+    ///
+    /// The callback is called with the sample and `true` when the load rises above the threshold,
+    /// and with `false` when it falls back to or below it.
+    pub fn on_threshold<F>(&mut self, threshold: f32, callback: F)
+    where F: FnMut(&LoadAvgSample, bool) + 'static {
+        self.threshold = Some((threshold, Box::new(callback)));
+        self.above = self.latest().is_some_and(|sample| sample.load_avg.load_avg_1_min > threshold);
+    }
+
+    /// Reads and records the load average.
+    ///
+    /// If this is the first sample, two readings are taken `interval` apart, and the later one is
+    /// recorded.
+    pub fn sample(&mut self) -> Result<LoadAvgSample> {
+        let sample = self.sampler.sample(read, |_, &reading| reading)?;
+        self.record(sample);
+        Ok(sample)
+    }
+
+    /// Records a sample, evicting the oldest if the history is full, and calls the threshold
+    /// callback if the load crossed the threshold.
+    fn record(&mut self, sample: LoadAvgSample) {
+        if self.samples.len() == self.capacity {
+            self.samples.pop_front();
+        }
+        self.samples.push_back(sample);
+        if let Some((threshold, ref mut callback)) = self.threshold {
+            let above = sample.load_avg.load_avg_1_min > threshold;
+            if above != self.above {
+                self.above = above;
+                callback(&sample, above);
+            }
+        }
+    }
+
+    /// Returns the most recent sample.
+    pub fn latest(&self) -> Option<&LoadAvgSample> {
+        self.samples.back()
+    }
+
+    /// Returns an iterator over the recorded samples, from oldest to newest.
+    pub fn samples(&self) -> Iter<'_, LoadAvgSample> {
+        self.samples.iter()
+    }
+
+    /// Returns statistics of the 1-minute load average over the samples taken within `window` of
+    /// the most recent sample, or `None` if no samples have been taken.
+    pub fn stats(&self, window: Duration) -> Option<LoadAvgStats> {
+        let latest = self.latest()?.instant;
+        let mut stats = LoadAvgStats { min: f32::MAX, max: f32::MIN, mean: 0.0, samples: 0 };
+        let mut sum = 0.0;
+        for sample in self.samples.iter().rev() {
+            if latest.duration_since(sample.instant) > window {
+                break;
+            }
+            let load = sample.load_avg.load_avg_1_min;
+            stats.min = stats.min.min(load);
+            stats.max = stats.max.max(load);
+            stats.samples += 1;
+            sum += load;
+        }
+        stats.mean = sum / stats.samples as f32;
+        Some(stats)
+    }
+}
+
+impl fmt::Debug for LoadAvgSampler {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("LoadAvgSampler")
+         .field("sampler", &self.sampler)
+         .field("capacity", &self.capacity)
+         .field("samples", &self.samples)
+         .field("threshold", &self.threshold.as_ref().map(|&(threshold, _)| threshold))
+         .field("above", &self.above)
+         .finish()
+    }
+}
+
+/// Takes a reading of the load average.
+fn read() -> Result<LoadAvgSample> {
+    let time = SystemTime::now();
+    let instant = Instant::now();
+    Ok(LoadAvgSample { time, instant, load_avg: loadavg()? })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::RefCell;
+    use std::rc::Rc;
+    use std::time::{Duration, Instant, SystemTime};
+
+    use loadavg::LoadAvg;
+    use super::{LoadAvgSample, LoadAvgSampler};
+
+    #[test]
+    fn test_loadavg_sampler() {
+        let mut sampler = LoadAvgSampler::new(Duration::from_millis(10), 2);
+        assert_eq!(None, sampler.stats(Duration::from_secs(60)));
+        let start = Instant::now();
+        let first = sampler.sample().unwrap();
+        assert!(start.elapsed() >= Duration::from_millis(10));
+        assert_eq!(1, sampler.samples().count());
+        sampler.sample().unwrap();
+        sampler.sample().unwrap();
+        assert_eq!(2, sampler.samples().count());
+        assert!(sampler.samples().all(|sample| sample.instant > first.instant));
+        assert_eq!(2, sampler.stats(Duration::from_secs(60)).unwrap().samples);
+    }
+
+    /// Test the window statistics and threshold callback over recorded samples.
+    #[test]
+    fn test_loadavg_sampler_record() {
+        let crossings = Rc::new(RefCell::new(Vec::new()));
+        let mut sampler = LoadAvgSampler::new(Duration::from_secs(1), 4);
+        {
+            let crossings = crossings.clone();
+            sampler.on_threshold(2.0, move |sample, rising| {
+                crossings.borrow_mut().push((sample.load_avg.load_avg_1_min, rising));
+            });
+        }
+        let now = Instant::now();
+        for (i, &load) in [9.0, 1.0, 3.0, 2.5, 2.0].iter().enumerate() {
+            let load_avg = LoadAvg { load_avg_1_min: load, ..LoadAvg::default() };
+            let instant = now + Duration::from_secs(i as u64);
+            sampler.record(LoadAvgSample { time: SystemTime::now(), instant, load_avg });
+        }
+        assert_eq!(vec![(9.0, true), (1.0, false), (3.0, true), (2.0, false)], *crossings.borrow());
+
+        let stats = sampler.stats(Duration::from_secs(60)).unwrap();
+        assert_eq!((1.0, 3.0, 4), (stats.min, stats.max, stats.samples));
+        assert_eq!(2.125, stats.mean);
+
+        let stats = sampler.stats(Duration::from_secs(1)).unwrap();
+        assert_eq!((2.0, 2.5, 2), (stats.min, stats.max, stats.samples));
+    }
+}
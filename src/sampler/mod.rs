@@ -0,0 +1,59 @@
+//! Samplers which take periodic readings of `/proc` files and report utilization and rates
+//! between them.
+//!
+//! A sampler holds on to its previous reading. The first call to `sample` takes two readings
+//! separated by the sampler's interval; each later call takes a single reading and compares it to
+//! the previous one, so calling `sample` on a timer yields rates over the timer's period.
+
+use std::io::Result;
+use std::thread;
+use std::time::Duration;
+
+mod cpu;
+mod disk;
+mod loadavg;
+mod net;
+mod process;
+mod vmstat;
+
+pub use sampler::cpu::{CpuSample, CpuSampler};
+pub use sampler::disk::{DiskSample, DiskSampler};
+pub use sampler::loadavg::{LoadAvgSample, LoadAvgSampler, LoadAvgStats};
+pub use sampler::net::{NetSample, NetSampler};
+pub use sampler::process::{ProcessSample, ProcessTable, ProcessTableSample, SortKey};
+pub use sampler::vmstat::{VmstatSample, VmstatSampler};
+
+/// The previous reading of a sampler, and the interval between the readings of its first sample.
+///
+/// Each sampler supplies the functions which take a reading and compare two readings.
+#[derive(Debug)]
+struct Sampler<R> {
+    interval: Duration,
+    prev: Option<R>,
+}
+
+impl<R> Sampler<R> {
+    fn new(interval: Duration) -> Sampler<R> {
+        Sampler { interval, prev: None }
+    }
+
+    /// Takes a reading and compares it to the previous reading.
+    ///
+    /// If there is no previous reading, two readings are taken `interval` apart.
+    fn sample<S, F, C>(&mut self, mut read: F, compare: C) -> Result<S>
+    where F: FnMut() -> Result<R>,
+          C: FnOnce(&R, &R) -> S {
+        let prev = match self.prev.take() {
+            Some(prev) => prev,
+            None => {
+                let prev = read()?;
+                thread::sleep(self.interval);
+                prev
+            },
+        };
+        let reading = read()?;
+        let sample = compare(&prev, &reading);
+        self.prev = Some(reading);
+        Ok(sample)
+    }
+}
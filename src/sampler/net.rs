@@ -0,0 +1,176 @@
+//! Network interface traffic sampling from `/proc/net/dev`.
+
+use std::io::Result;
+use std::time::{Duration, Instant};
+
+use delta::NetRates;
+use net::dev::{DeviceStatus, dev};
+use sampler::Sampler;
+
+/// Network interface traffic rates between two readings.
+#[derive(Clone, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "export", derive(Serialize))]
+pub struct NetSample {
+    /// The time elapsed between the readings.
+    pub interval: Duration,
+    /// The traffic rates of each sampled interface present during both readings, by interface
+    /// name.
+    pub interfaces: Vec<(String, NetRates)>,
+    /// The sampled interfaces which appeared, or were recreated, between the readings.
+    pub added: Vec<String>,
+    /// The sampled interfaces which disappeared between the readings.
+    pub removed: Vec<String>,
+}
+
+/// A single reading of the network device statistics.
+#[derive(Debug)]
+struct Reading {
+    instant: Instant,
+    devices: Vec<DeviceStatus>,
+}
+
+/// Samples the receive and transmit rates of network interfaces.
+///
+/// All interfaces listed in `/proc/net/dev` are sampled, unless interfaces are added with
+/// `add_interface`, in which case only those interfaces are sampled. Interfaces which come and go
+/// between samples, such as container veths, are reported in `added` and `removed` rather than
+/// with rates.
+///
+/// # Example
+///
+/// ```no_run
+/// use std::time::Duration;
+///
+/// use procinfo::sampler::NetSampler;
+///
+/// let mut sampler = NetSampler::new(Duration::from_secs(1));
+/// for (interface, rates) in sampler.sample().unwrap().interfaces {
+///     println!("{}: rx {:.0} B/s, tx {:.0} B/s",
+///              interface, rates.receive_bytes, rates.transmit_bytes);
+/// }
+/// ```
+#[derive(Debug)]
+pub struct NetSampler {
+    sampler: Sampler<Reading>,
+    interfaces: Vec<String>,
+}
+
+impl NetSampler {
+    /// Creates a sampler which waits `interval` between the readings of its first sample.
+    pub fn new(interval: Duration) -> NetSampler {
+        NetSampler { sampler: Sampler::new(interval), interfaces: Vec::new() }
+    }
+
+    /// Adds the interface with the provided name, such as `eth0`, to the sampled interfaces.
+    pub fn add_interface(&mut self, name: &str) {
+        if !self.interfaces.iter().any(|interface| interface == name) {
+            self.interfaces.push(name.to_owned());
+        }
+    }
+
+    /// Removes the interface with the provided name from the sampled interfaces.
+    pub fn remove_interface(&mut self, name: &str) {
+        self.interfaces.retain(|interface| interface != name);
+    }
+
+    /// Returns the traffic rates since the previous sample.
+    ///
+    /// If this is the first sample, two readings are taken `interval` apart.
+    pub fn sample(&mut self) -> Result<NetSample> {
+        let interfaces = &self.interfaces;
+        self.sampler.sample(|| read(interfaces), compare)
+    }
+}
+
+/// Takes a reading of the statistics of the provided network interfaces, or of all interfaces if
+/// none are provided.
+fn read(interfaces: &[String]) -> Result<Reading> {
+    let instant = Instant::now();
+    let mut devices = dev()?;
+    if !interfaces.is_empty() {
+        devices.retain(|device| interfaces.contains(&device.interface));
+    }
+    Ok(Reading { instant, devices })
+}
+
+/// Returns whether the counters of a device went backwards between two readings, which happens
+/// when the interface is deleted and recreated with the same name.
+///
+/// The counters are 64 bits wide on all kernels, so they do not wrap in practice.
+fn is_reset(earlier: &DeviceStatus, later: &DeviceStatus) -> bool {
+    later.receive_bytes < earlier.receive_bytes
+        || later.receive_packets < earlier.receive_packets
+        || later.transmit_bytes < earlier.transmit_bytes
+        || later.transmit_packets < earlier.transmit_packets
+}
+
+/// Computes the traffic rates between two readings.
+fn compare(prev: &Reading, reading: &Reading) -> NetSample {
+    let interval = reading.instant.saturating_duration_since(prev.instant);
+    let mut sample = NetSample { interval, ..Default::default() };
+    for device in &reading.devices {
+        match prev.devices.iter().find(|earlier| earlier.interface == device.interface) {
+            Some(earlier) if !is_reset(earlier, device) => {
+                sample.interfaces.push((device.interface.clone(),
+                                        NetRates::between(earlier, device, interval)));
+            },
+            _ => sample.added.push(device.interface.clone()),
+        }
+    }
+    for earlier in &prev.devices {
+        if !reading.devices.iter().any(|device| device.interface == earlier.interface) {
+            sample.removed.push(earlier.interface.clone());
+        }
+    }
+    sample
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::{Duration, Instant};
+
+    use net::dev::DeviceStatus;
+    use super::{NetSampler, Reading, compare};
+
+    #[test]
+    fn test_net_sampler() {
+        let mut sampler = NetSampler::new(Duration::from_millis(20));
+        sampler.add_interface("lo");
+        let sample = sampler.sample().unwrap();
+        assert!(sample.interval >= Duration::from_millis(20));
+        assert!(sample.interfaces.len() <= 1);
+        assert!(sample.interfaces.iter().all(|(interface, _)| interface == "lo"));
+
+        // An interface which does not exist is never sampled.
+        sampler.remove_interface("lo");
+        sampler.add_interface("procinfo-missing");
+        let sample = sampler.sample().unwrap();
+        assert!(sample.interfaces.is_empty());
+        assert!(sample.added.is_empty());
+    }
+
+    #[test]
+    fn test_compare() {
+        let device = |interface: &str, receive_bytes| DeviceStatus {
+            interface: interface.to_owned(),
+            receive_bytes,
+            ..Default::default()
+        };
+        let instant = Instant::now();
+        let prev = Reading {
+            instant,
+            devices: vec![device("eth0", 1000), device("veth1", 500), device("veth2", 500)],
+        };
+        let reading = Reading {
+            instant: instant + Duration::from_secs(2),
+            devices: vec![device("eth0", 5000), device("veth2", 100), device("veth3", 0)],
+        };
+        let sample = compare(&prev, &reading);
+        assert_eq!(Duration::from_secs(2), sample.interval);
+        assert_eq!(1, sample.interfaces.len());
+        assert_eq!("eth0", sample.interfaces[0].0);
+        assert_eq!(2000.0, sample.interfaces[0].1.receive_bytes);
+        assert_eq!(vec!["veth2".to_owned(), "veth3".to_owned()], sample.added);
+        assert_eq!(vec!["veth1".to_owned()], sample.removed);
+    }
+}
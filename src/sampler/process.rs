@@ -0,0 +1,244 @@
+//! Per-process activity sampling from `/proc/[pid]/{stat,statm,io}`.
+
+use std::cmp::Ordering;
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{Read, Result};
+use std::time::{Duration, Instant};
+
+use libc::pid_t;
+
+use delta::{IoRates, ProcessCpuUsage};
+use pid::{Io, Stat, Statm, pids};
+use sampler::Sampler;
+use sysconf::page_size;
+
+/// The activity of a process between two readings.
+#[derive(Clone, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "export", derive(Serialize))]
+pub struct ProcessSample {
+    /// The process ID.
+    pub pid: pid_t,
+    /// The filename of the executable.
+    pub command: String,
+    /// The CPU usage of the process.
+    pub cpu: ProcessCpuUsage,
+    /// The resident set size of the process at the later reading, in bytes.
+    pub rss: u64,
+    /// The I/O rates of the process, or `None` if its I/O statistics can not be read, which
+    /// requires ptrace access to the process.
+    pub io: Option<IoRates>,
+}
+
+impl ProcessSample {
+    /// Returns the bytes read from and written to the storage layer per second, or 0 if the I/O
+    /// statistics of the process can not be read.
+    pub fn io_total(&self) -> f64 {
+        self.io.map_or(0.0, |io| io.read_bytes + io.write_bytes)
+    }
+}
+
+/// The key by which process samples are sorted.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "export", derive(Serialize))]
+pub enum SortKey {
+    /// Total CPU usage.
+    Cpu,
+    /// Resident set size.
+    Rss,
+    /// Bytes read from and written to the storage layer per second.
+    Io,
+}
+
+/// The activity of all processes between two readings.
+#[derive(Clone, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "export", derive(Serialize))]
+pub struct ProcessTableSample {
+    /// The time elapsed between the readings.
+    pub interval: Duration,
+    /// The processes running during both readings, in no particular order.
+    pub processes: Vec<ProcessSample>,
+}
+
+impl ProcessTableSample {
+    /// Sorts the processes by the key, in descending order. Processes which compare equal are
+    /// ordered by pid.
+    pub fn sort_by(&mut self, key: SortKey) {
+        let value = |process: &ProcessSample| match key {
+            SortKey::Cpu => process.cpu.total(),
+            SortKey::Rss => process.rss as f64,
+            SortKey::Io => process.io_total(),
+        };
+        self.processes.sort_by(|a, b| {
+            value(b).partial_cmp(&value(a)).unwrap_or(Ordering::Equal).then(a.pid.cmp(&b.pid))
+        });
+    }
+
+    /// Returns the processes sorted by the key, in descending order.
+    pub fn sorted_by(mut self, key: SortKey) -> Vec<ProcessSample> {
+        self.sort_by(key);
+        self.processes
+    }
+}
+
+/// A single reading of a process.
+#[derive(Debug)]
+struct ProcessReading {
+    stat: Stat,
+    statm: Statm,
+    io: Option<Io>,
+}
+
+/// A single reading of all processes.
+#[derive(Debug)]
+struct Reading {
+    instant: Instant,
+    processes: HashMap<pid_t, ProcessReading>,
+}
+
+/// Samples the CPU usage, memory usage, and I/O rates of all processes, like `top(1)`.
+///
+/// Processes which start or exit between two readings are left out of the sample, as are
+/// processes whose pid is reused between the readings.
+///
+/// # Example
+///
+/// ```no_run
+/// use std::time::Duration;
+///
+/// use procinfo::sampler::{ProcessTable, SortKey};
+///
+/// let mut table = ProcessTable::new(Duration::from_secs(1));
+/// for process in table.sample().unwrap().sorted_by(SortKey::Cpu).iter().take(10) {
+///     println!("{:>7} {:>5.1}% {}", process.pid, process.cpu.total(), process.command);
+/// }
+/// ```
+#[derive(Debug)]
+pub struct ProcessTable {
+    sampler: Sampler<Reading>,
+    /// The buffer into which files are read, which is reused across processes and readings.
+    buf: Vec<u8>,
+}
+
+impl ProcessTable {
+    /// Creates a process table which waits `interval` between the readings of its first sample.
+    pub fn new(interval: Duration) -> ProcessTable {
+        ProcessTable { sampler: Sampler::new(interval), buf: Vec::with_capacity(1024) }
+    }
+
+    /// Returns the activity of all processes since the previous sample.
+    ///
+    /// If this is the first sample, two readings are taken `interval` apart.
+    pub fn sample(&mut self) -> Result<ProcessTableSample> {
+        let buf = &mut self.buf;
+        self.sampler.sample(|| read(buf), compare)
+    }
+}
+
+/// Reads the file into the buffer, replacing its contents.
+fn read_file<'a>(buf: &'a mut Vec<u8>, path: &str) -> Result<&'a [u8]> {
+    buf.clear();
+    File::open(path)?.read_to_end(buf)?;
+    Ok(buf)
+}
+
+/// Takes a reading of a process, reading its files into the buffer.
+fn read_process(buf: &mut Vec<u8>, pid: pid_t) -> Result<ProcessReading> {
+    let stat = Stat::parse(read_file(buf, &format!("/proc/{}/stat", pid))?)?;
+    let statm = Statm::parse(read_file(buf, &format!("/proc/{}/statm", pid))?)?;
+    let io = read_file(buf, &format!("/proc/{}/io", pid)).and_then(Io::parse).ok();
+    Ok(ProcessReading { stat, statm, io })
+}
+
+/// Takes a reading of all processes, reading their files into the buffer.
+///
+/// Processes which exit while the reading is taken are left out of the reading.
+fn read(buf: &mut Vec<u8>) -> Result<Reading> {
+    let instant = Instant::now();
+    let mut processes = HashMap::new();
+    for pid in pids()? {
+        let pid = pid?;
+        if let Ok(process) = read_process(buf, pid) {
+            processes.insert(pid, process);
+        }
+    }
+    Ok(Reading { instant, processes })
+}
+
+/// Computes the activity of the processes between two readings.
+fn compare(prev: &Reading, reading: &Reading) -> ProcessTableSample {
+    let interval = reading.instant.saturating_duration_since(prev.instant);
+    let page_size = page_size() as u64;
+    let mut processes = Vec::with_capacity(reading.processes.len());
+    for (&pid, process) in &reading.processes {
+        // A pid which was reused between the readings belongs to a different process.
+        let earlier = match prev.processes.get(&pid) {
+            Some(earlier) if earlier.stat.start_time == process.stat.start_time => earlier,
+            _ => continue,
+        };
+        let io = match (earlier.io, process.io) {
+            (Some(earlier), Some(later)) => Some(IoRates::between(&earlier, &later, interval)),
+            _ => None,
+        };
+        processes.push(ProcessSample {
+            pid,
+            command: process.stat.command.clone(),
+            cpu: ProcessCpuUsage::between(&earlier.stat, &process.stat, interval),
+            rss: process.statm.resident * page_size,
+            io,
+        });
+    }
+    ProcessTableSample { interval, processes }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use libc;
+
+    use delta::{IoRates, ProcessCpuUsage};
+    use super::{ProcessSample, ProcessTable, ProcessTableSample, SortKey};
+
+    #[test]
+    fn test_process_table() {
+        let pid = unsafe { libc::getpid() };
+        let mut table = ProcessTable::new(Duration::from_millis(20));
+        let sample = table.sample().unwrap();
+        assert!(sample.interval >= Duration::from_millis(20));
+        let process = sample.processes.iter().find(|process| process.pid == pid).unwrap();
+        assert!(process.rss > 0);
+        assert!(process.io.is_some());
+
+        let sample = table.sample().unwrap();
+        assert!(sample.processes.iter().any(|process| process.pid == pid));
+    }
+
+    #[test]
+    fn test_sort_by() {
+        let process = |pid, cpu, rss, write_bytes| ProcessSample {
+            pid,
+            cpu: ProcessCpuUsage { user: cpu, system: 0.0 },
+            rss,
+            io: Some(IoRates { write_bytes, ..Default::default() }),
+            ..Default::default()
+        };
+        let mut sample = ProcessTableSample {
+            interval: Duration::from_secs(1),
+            processes: vec![process(1, 5.0, 300, 0.0),
+                            process(2, 50.0, 100, 10.0),
+                            process(3, 5.0, 200, 20.0)],
+        };
+        let pids = |sample: &ProcessTableSample| {
+            sample.processes.iter().map(|process| process.pid).collect::<Vec<_>>()
+        };
+        sample.sort_by(SortKey::Cpu);
+        assert_eq!(vec![2, 1, 3], pids(&sample));
+        sample.sort_by(SortKey::Rss);
+        assert_eq!(vec![1, 3, 2], pids(&sample));
+        sample.sort_by(SortKey::Io);
+        assert_eq!(vec![3, 2, 1], pids(&sample));
+        assert_eq!(vec![3, 2, 1],
+                   sample.sorted_by(SortKey::Io).iter().map(|p| p.pid).collect::<Vec<_>>());
+    }
+}
@@ -0,0 +1,111 @@
+//! Paging, swapping, and scheduler activity sampling from `/proc/vmstat` and `/proc/stat`, in the
+//! style of `vmstat(8)`.
+
+use std::io::Result;
+use std::time::{Duration, Instant};
+
+use cpu::{StatCounters, stat_counters};
+use delta::{StatRates, VmstatRates};
+use sampler::Sampler;
+use vmstat::{Vmstat, vmstat};
+
+/// Paging and scheduler activity between two readings.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "export", derive(Serialize))]
+pub struct VmstatSample {
+    /// The time elapsed between the readings.
+    pub interval: Duration,
+    /// Paging, swapping, and page fault rates.
+    pub vm: VmstatRates,
+    /// Interrupt, context switch, and fork rates.
+    pub stat: StatRates,
+    /// Threads runnable at the later reading.
+    pub procs_running: u64,
+    /// Threads blocked waiting for I/O at the later reading.
+    pub procs_blocked: u64,
+}
+
+/// A single reading of the virtual memory statistics and kernel activity counters.
+#[derive(Debug)]
+struct Reading {
+    instant: Instant,
+    vmstat: Vmstat,
+    counters: StatCounters,
+}
+
+/// Samples system-wide paging, swapping, page fault, interrupt, and context switch rates.
+///
+/// # Example
+///
+/// ```no_run
+/// use std::time::Duration;
+///
+/// use procinfo::sampler::VmstatSampler;
+///
+/// let mut sampler = VmstatSampler::new(Duration::from_secs(1));
+/// loop {
+///     let sample = sampler.sample().unwrap();
+///     println!("r: {} b: {} si: {:.0} so: {:.0} cs: {:.0}",
+///              sample.procs_running, sample.procs_blocked,
+///              sample.vm.pswpin, sample.vm.pswpout, sample.stat.ctxt);
+/// }
+/// ```
+#[derive(Debug)]
+pub struct VmstatSampler {
+    sampler: Sampler<Reading>,
+}
+
+impl VmstatSampler {
+    /// Creates a sampler which waits `interval` between the readings of its first sample.
+    pub fn new(interval: Duration) -> VmstatSampler {
+        VmstatSampler { sampler: Sampler::new(interval) }
+    }
+
+    /// Returns the activity since the previous sample.
+    ///
+    /// If this is the first sample, two readings are taken `interval` apart.
+    pub fn sample(&mut self) -> Result<VmstatSample> {
+        self.sampler.sample(read, compare)
+    }
+}
+
+/// Takes a reading of the virtual memory statistics and kernel activity counters.
+fn read() -> Result<Reading> {
+    let instant = Instant::now();
+    let vmstat = vmstat()?;
+    let counters = stat_counters()?;
+    Ok(Reading { instant, vmstat, counters })
+}
+
+/// Computes the activity between two readings.
+fn compare(prev: &Reading, reading: &Reading) -> VmstatSample {
+    let interval = reading.instant.saturating_duration_since(prev.instant);
+    VmstatSample {
+        interval,
+        vm: VmstatRates::between(&prev.vmstat, &reading.vmstat, interval),
+        stat: StatRates::between(&prev.counters, &reading.counters, interval),
+        procs_running: reading.counters.procs_running,
+        procs_blocked: reading.counters.procs_blocked,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::VmstatSampler;
+
+    #[test]
+    fn test_vmstat_sampler() {
+        let mut sampler = VmstatSampler::new(Duration::from_millis(20));
+
+        let sample = sampler.sample().unwrap();
+        assert!(sample.interval >= Duration::from_millis(20));
+        assert!(sample.stat.ctxt > 0.0);
+        // The sampling thread itself is runnable.
+        assert!(sample.procs_running > 0);
+
+        let sample = sampler.sample().unwrap();
+        assert!(sample.vm.pgfault >= sample.vm.pgmajfault);
+    }
+}
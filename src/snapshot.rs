@@ -0,0 +1,105 @@
+//! Point-in-time snapshots of system-wide statistics.
+
+use std::io::Result;
+use std::time::{Duration, Instant, SystemTime};
+
+use cpu::{Cpu, cpu};
+use delta::{CpuUsage, DiskRates, NetRates};
+use diskstats::{DiskStat, diskstats};
+use loadavg::{LoadAvg, loadavg};
+use meminfo::{Meminfo, meminfo};
+use net::dev::{DeviceStatus, dev};
+
+/// System-wide statistics captured together.
+///
+/// The files are read back to back, and the snapshot carries a single timestamp taken when the
+/// capture started.
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "export", derive(Serialize))]
+pub struct Snapshot {
+    /// The wall clock time at which the snapshot was captured.
+    pub time: SystemTime,
+    /// The monotonic time at which the snapshot was captured, used to compute deltas.
+    #[cfg_attr(feature = "export", serde(skip))]
+    instant: Instant,
+    /// The system load average, from `/proc/loadavg`.
+    pub loadavg: LoadAvg,
+    /// System memory usage, from `/proc/meminfo`.
+    pub meminfo: Meminfo,
+    /// Time spent by all CPUs in each mode, from `/proc/stat`.
+    pub cpu: Cpu,
+    /// Block device I/O statistics, from `/proc/diskstats`.
+    pub diskstats: Vec<DiskStat>,
+    /// Network device statistics, from `/proc/net/dev`.
+    pub net_dev: Vec<DeviceStatus>,
+}
+
+/// Rates of change between two snapshots.
+#[derive(Clone, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "export", derive(Serialize))]
+pub struct SnapshotDelta {
+    /// The time elapsed between the snapshots.
+    pub interval: Duration,
+    /// CPU utilization.
+    pub cpu: CpuUsage,
+    /// I/O rates of the block devices present in both snapshots, by device name.
+    pub disks: Vec<(String, DiskRates)>,
+    /// Traffic rates of the network devices present in both snapshots, by interface name.
+    pub net: Vec<(String, NetRates)>,
+}
+
+impl Snapshot {
+    /// Captures a snapshot of the current system statistics.
+    pub fn capture() -> Result<Snapshot> {
+        let time = SystemTime::now();
+        let instant = Instant::now();
+        Ok(Snapshot {
+            time,
+            instant,
+            loadavg: loadavg()?,
+            meminfo: meminfo()?,
+            cpu: cpu()?,
+            diskstats: diskstats()?,
+            net_dev: dev()?,
+        })
+    }
+
+    /// Returns the rates of change since the `prev` snapshot.
+    pub fn delta(&self, prev: &Snapshot) -> SnapshotDelta {
+        let interval = self.instant.saturating_duration_since(prev.instant);
+        let disks = self.diskstats.iter().filter_map(|later| {
+            prev.diskstats.iter()
+                          .find(|earlier| earlier.name == later.name)
+                          .map(|earlier| {
+                              (later.name.clone(), DiskRates::between(earlier, later, interval))
+                          })
+        }).collect();
+        let net = self.net_dev.iter().filter_map(|later| {
+            prev.net_dev.iter()
+                        .find(|earlier| earlier.interface == later.interface)
+                        .map(|earlier| {
+                            (later.interface.clone(), NetRates::between(earlier, later, interval))
+                        })
+        }).collect();
+        SnapshotDelta { interval, cpu: self.cpu.delta(&prev.cpu), disks, net }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::thread;
+    use std::time::Duration;
+
+    use super::Snapshot;
+
+    #[test]
+    fn test_snapshot() {
+        let prev = Snapshot::capture().unwrap();
+        thread::sleep(Duration::from_millis(20));
+        let snapshot = Snapshot::capture().unwrap();
+        let delta = snapshot.delta(&prev);
+        assert!(delta.interval >= Duration::from_millis(20));
+        assert_eq!(snapshot.net_dev.len(), delta.net.len());
+        assert!(delta.cpu.busy() <= 100.0);
+    }
+}
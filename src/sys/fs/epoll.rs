@@ -0,0 +1,29 @@
+//! epoll limits from `/proc/sys/fs/epoll`.
+
+use std::io::Result;
+
+use sys::{read_sysctl, write_sysctl};
+
+/// Returns the maximum number of file descriptors which each user may register across all of
+/// their epoll instances.
+///
+/// Registering a file descriptor beyond the limit fails with `ENOSPC`.
+pub fn max_user_watches() -> Result<u64> {
+    read_sysctl("/proc/sys/fs/epoll/max_user_watches")
+}
+
+/// Sets the maximum number of file descriptors which each user may register across all of their
+/// epoll instances.
+pub fn set_max_user_watches(watches: u64) -> Result<()> {
+    write_sysctl("/proc/sys/fs/epoll/max_user_watches", watches)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::max_user_watches;
+
+    #[test]
+    fn test_epoll() {
+        assert!(max_user_watches().unwrap() > 0);
+    }
+}
@@ -0,0 +1,55 @@
+//! inotify limits from `/proc/sys/fs/inotify`.
+
+use std::io::Result;
+
+use sys::{read_sysctl, write_sysctl};
+
+/// Returns the maximum number of watches which each user may create.
+///
+/// Adding a watch beyond the limit fails with `ENOSPC`.
+pub fn max_user_watches() -> Result<u32> {
+    read_sysctl("/proc/sys/fs/inotify/max_user_watches")
+}
+
+/// Sets the maximum number of watches which each user may create.
+pub fn set_max_user_watches(watches: u32) -> Result<()> {
+    write_sysctl("/proc/sys/fs/inotify/max_user_watches", watches)
+}
+
+/// Returns the maximum number of inotify instances which each user may create.
+///
+/// Creating an instance beyond the limit fails with `EMFILE`.
+pub fn max_user_instances() -> Result<u32> {
+    read_sysctl("/proc/sys/fs/inotify/max_user_instances")
+}
+
+/// Sets the maximum number of inotify instances which each user may create.
+pub fn set_max_user_instances(instances: u32) -> Result<()> {
+    write_sysctl("/proc/sys/fs/inotify/max_user_instances", instances)
+}
+
+/// Returns the maximum number of events which may be queued on an inotify instance.
+///
+/// Events beyond the limit are dropped, and an `IN_Q_OVERFLOW` event is queued instead.
+pub fn max_queued_events() -> Result<u32> {
+    read_sysctl("/proc/sys/fs/inotify/max_queued_events")
+}
+
+/// Sets the maximum number of events which may be queued on an inotify instance.
+///
+/// The new limit applies to instances created afterwards.
+pub fn set_max_queued_events(events: u32) -> Result<()> {
+    write_sysctl("/proc/sys/fs/inotify/max_queued_events", events)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{max_queued_events, max_user_instances, max_user_watches};
+
+    #[test]
+    fn test_inotify() {
+        assert!(max_user_watches().unwrap() > 0);
+        assert!(max_user_instances().unwrap() > 0);
+        assert!(max_queued_events().unwrap() > 0);
+    }
+}
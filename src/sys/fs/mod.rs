@@ -1 +1,3 @@
+pub mod epoll;
 pub mod file_max;
+pub mod inotify;
@@ -0,0 +1,116 @@
+//! Kernel configuration from `/proc/sys/kernel`.
+
+use std::io::Result;
+
+use libc::pid_t;
+
+use sys::{read_sysctl, read_sysctl_bit};
+
+pub mod random;
+
+/// Returns the value at which process IDs wrap around; new process IDs are always less than it.
+pub fn pid_max() -> Result<pid_t> {
+    read_sysctl("/proc/sys/kernel/pid_max")
+}
+
+/// Returns the system-wide limit on the number of threads.
+pub fn threads_max() -> Result<u64> {
+    read_sysctl("/proc/sys/kernel/threads-max")
+}
+
+/// Returns the hostname of the UTS namespace of the current process.
+pub fn hostname() -> Result<String> {
+    read_sysctl("/proc/sys/kernel/hostname")
+}
+
+/// Returns the NIS/YP domain name of the UTS namespace of the current process.
+///
+/// The domain name is `(none)` if it has not been set.
+pub fn domainname() -> Result<String> {
+    read_sysctl("/proc/sys/kernel/domainname")
+}
+
+/// Returns the kernel release, e.g. `6.1.0-18-amd64`.
+pub fn osrelease() -> Result<String> {
+    read_sysctl("/proc/sys/kernel/osrelease")
+}
+
+/// Returns the kernel name, which is always `Linux`.
+pub fn ostype() -> Result<String> {
+    read_sysctl("/proc/sys/kernel/ostype")
+}
+
+/// Returns the number of seconds the kernel waits before rebooting on a panic.
+///
+/// Zero means the kernel does not reboot on a panic, and a negative value means the kernel reboots
+/// immediately.
+pub fn panic() -> Result<i64> {
+    read_sysctl("/proc/sys/kernel/panic")
+}
+
+/// Returns whether the kernel panics, rather than attempting to continue, when an oops or BUG is
+/// encountered.
+pub fn panic_on_oops() -> Result<bool> {
+    read_sysctl_bit("/proc/sys/kernel/panic_on_oops")
+}
+
+/// Returns the timeslice of `SCHED_RR` threads, in milliseconds.
+pub fn sched_rr_timeslice_ms() -> Result<u64> {
+    read_sysctl("/proc/sys/kernel/sched_rr_timeslice_ms")
+}
+
+/// Returns the period over which real-time thread bandwidth is enforced, in microseconds.
+pub fn sched_rt_period_us() -> Result<u64> {
+    read_sysctl("/proc/sys/kernel/sched_rt_period_us")
+}
+
+/// Returns the time real-time threads may run within each period, in microseconds.
+///
+/// A value of `-1` means real-time threads may use the whole period.
+pub fn sched_rt_runtime_us() -> Result<i64> {
+    read_sysctl("/proc/sys/kernel/sched_rt_runtime_us")
+}
+
+/// Returns whether threads are automatically grouped by session for CPU scheduling.
+///
+/// Only present when the kernel is built with `CONFIG_SCHED_AUTOGROUP`.
+pub fn sched_autogroup_enabled() -> Result<bool> {
+    read_sysctl_bit("/proc/sys/kernel/sched_autogroup_enabled")
+}
+
+/// Returns whether a child process is scheduled before its parent after `fork`.
+///
+/// Only present when the kernel is built with `CONFIG_SCHED_DEBUG`.
+pub fn sched_child_runs_first() -> Result<bool> {
+    read_sysctl_bit("/proc/sys/kernel/sched_child_runs_first")
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::ErrorKind;
+
+    use super::{domainname, hostname, osrelease, ostype, panic, panic_on_oops, pid_max,
+                sched_autogroup_enabled, sched_child_runs_first, sched_rr_timeslice_ms,
+                sched_rt_period_us, sched_rt_runtime_us, threads_max};
+
+    #[test]
+    fn test_kernel() {
+        assert!(pid_max().unwrap() > 0);
+        assert!(threads_max().unwrap() > 0);
+        assert!(!hostname().unwrap().is_empty());
+        domainname().unwrap();
+        assert!(!osrelease().unwrap().is_empty());
+        assert_eq!("Linux", ostype().unwrap());
+        panic().unwrap();
+        panic_on_oops().unwrap();
+        assert!(sched_rr_timeslice_ms().unwrap() > 0);
+        assert!(sched_rt_runtime_us().unwrap() <= sched_rt_period_us().unwrap() as i64);
+
+        // These depend on the kernel configuration.
+        for result in &[sched_autogroup_enabled(), sched_child_runs_first()] {
+            if let Err(ref error) = *result {
+                assert_eq!(ErrorKind::NotFound, error.kind());
+            }
+        }
+    }
+}
@@ -0,0 +1,46 @@
+//! Kernel random number generator information from `/proc/sys/kernel/random`.
+
+use std::io::Result;
+
+use sys::read_sysctl;
+
+/// Returns the number of bits of entropy available in the input pool.
+///
+/// Since Linux 5.18 this is always equal to the pool size once the pool has been initialized.
+pub fn entropy_avail() -> Result<u32> {
+    read_sysctl("/proc/sys/kernel/random/entropy_avail")
+}
+
+/// Returns the size of the input pool, in bits.
+pub fn poolsize() -> Result<u32> {
+    read_sysctl("/proc/sys/kernel/random/poolsize")
+}
+
+/// Returns a new random UUID, in its hyphenated string form.
+///
+/// Each call returns a different UUID.
+pub fn uuid() -> Result<String> {
+    read_sysctl("/proc/sys/kernel/random/uuid")
+}
+
+/// Returns a random UUID generated once per boot, in its hyphenated string form.
+pub fn boot_id() -> Result<String> {
+    read_sysctl("/proc/sys/kernel/random/boot_id")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{boot_id, entropy_avail, poolsize, uuid};
+
+    #[test]
+    fn test_random() {
+        assert!(entropy_avail().unwrap() <= poolsize().unwrap());
+
+        let uuid = uuid().unwrap();
+        assert_eq!(36, uuid.len());
+        assert_eq!(4, uuid.matches('-').count());
+        assert_ne!(uuid, super::uuid().unwrap());
+
+        assert_eq!(boot_id().unwrap(), boot_id().unwrap());
+    }
+}
@@ -1 +1,100 @@
+//! System configuration from `/proc/sys`.
+
+use std::fmt::Display;
+use std::fs::OpenOptions;
+use std::io::{Result, Write};
+use std::str::FromStr;
+
+use parsers::{invalid_input, parse, parse_bit, parse_file, parse_line, read_to_end};
+
 pub mod fs;
+pub mod kernel;
+pub mod net;
+pub mod vm;
+
+/// Reads a single-line value from a file in `/proc/sys`.
+pub(crate) fn read_sysctl<T: FromStr>(path: &str) -> Result<T> {
+    parse_file(path, |file| {
+        let mut buf = [0; 128]; // Most values are a single number.
+        parse(parse_line(&read_to_end(file, &mut buf)?)?)
+    })
+}
+
+/// Reads a boolean value, represented as `0` or `1`, from a file in `/proc/sys`.
+pub(crate) fn read_sysctl_bit(path: &str) -> Result<bool> {
+    parse_bit(&read_sysctl::<String>(path)?)
+}
+
+/// Writes a single-line value to a file in `/proc/sys`.
+///
+/// Writing requires `CAP_SYS_ADMIN` (or ownership of the file) and a writable `/proc/sys` mount.
+pub(crate) fn write_sysctl<T: Display>(path: &str, value: T) -> Result<()> {
+    // The kernel parses the value from a single write.
+    let value = format!("{}\n", value);
+    OpenOptions::new().write(true).open(path)?.write_all(value.as_bytes())
+}
+
+/// Returns the value of the sysctl with the provided name, as a string.
+///
+/// The name may be given in either the dotted (`kernel.pid_max`) or the slashed (`kernel/pid_max`)
+/// form, relative to `/proc/sys`. As with `sysctl(8)`, a `/` in the dotted form stands for a `.`
+/// in a path component, such as in `net.ipv4.conf.eth0/100.forwarding`. Values with multiple
+/// lines, such as tables, have their lines joined with `\n`, and the trailing line terminator is
+/// removed.
+pub fn sysctl(name: &str) -> Result<String> {
+    let mut value = ::std::fs::read_to_string(format!("/proc/sys/{}", sysctl_path(name)?))?;
+    if value.ends_with('\n') {
+        value.pop();
+    }
+    Ok(value)
+}
+
+/// Returns the path relative to `/proc/sys` of the sysctl with the provided name.
+///
+/// The name is in the dotted form if its first separator is a `.`, in which case `.` and `/` are
+/// swapped.
+fn sysctl_path(name: &str) -> Result<String> {
+    let dotted = name.find(['.', '/']).is_some_and(|i| name.as_bytes()[i] == b'.');
+    let path = if dotted {
+        name.chars().map(|c| match c {
+            '.' => '/',
+            '/' => '.',
+            c => c,
+        }).collect()
+    } else {
+        name.to_owned()
+    };
+    if path.split('/').any(|c| c.is_empty() || c == "." || c == "..") {
+        return Err(invalid_input(format!("invalid sysctl name: {:?}", name)));
+    }
+    Ok(path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{sysctl, sysctl_path};
+
+    #[test]
+    fn test_sysctl() {
+        assert_eq!(sysctl("kernel.ostype").unwrap(), sysctl("kernel/ostype").unwrap());
+        assert_eq!("Linux", sysctl("kernel.ostype").unwrap());
+        sysctl("kernel.no_such_sysctl").unwrap_err();
+        sysctl("kernel/../../etc/passwd").unwrap_err();
+        sysctl("").unwrap_err();
+    }
+
+    #[test]
+    fn test_sysctl_path() {
+        assert_eq!("kernel/pid_max", sysctl_path("kernel.pid_max").unwrap());
+        assert_eq!("kernel/pid_max", sysctl_path("kernel/pid_max").unwrap());
+        assert_eq!("net/ipv4/conf/eth0.100/forwarding",
+                   sysctl_path("net.ipv4.conf.eth0/100.forwarding").unwrap());
+        assert_eq!("net/ipv4/conf/eth0.100/forwarding",
+                   sysctl_path("net/ipv4/conf/eth0.100/forwarding").unwrap());
+        assert_eq!("kernel", sysctl_path("kernel").unwrap());
+        sysctl_path("kernel..pid_max").unwrap_err();
+        sysctl_path("net/ipv4/conf/../forwarding").unwrap_err();
+        sysctl_path("net.ipv4.conf./.forwarding").unwrap_err();
+        sysctl_path("").unwrap_err();
+    }
+}
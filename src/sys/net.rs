@@ -0,0 +1,92 @@
+//! Network configuration from `/proc/sys/net`.
+//!
+//! The values are those of the network namespace of the current process.
+
+use std::io::Result;
+use std::ops::RangeInclusive;
+
+use parsers::{Fields, invalid_input, parse_list};
+use sys::{read_sysctl, read_sysctl_bit};
+
+/// Returns whether IPv4 packets are forwarded between interfaces.
+pub fn ip_forward() -> Result<bool> {
+    read_sysctl_bit("/proc/sys/net/ipv4/ip_forward")
+}
+
+/// Returns the maximum length of the accept queue of a listening socket.
+pub fn somaxconn() -> Result<u32> {
+    read_sysctl("/proc/sys/net/core/somaxconn")
+}
+
+/// Returns the maximum number of TCP connection requests which have not yet been acknowledged by
+/// the remote end, per listening socket.
+pub fn tcp_max_syn_backlog() -> Result<u32> {
+    read_sysctl("/proc/sys/net/ipv4/tcp_max_syn_backlog")
+}
+
+/// Parses a port range of the form `first\tlast`.
+fn parse_port_range(s: &str) -> Result<RangeInclusive<u16>> {
+    let mut fields = Fields::new(s);
+    let first = fields.parse()?;
+    let last = fields.parse()?;
+    fields.finish()?;
+    if first > last {
+        return Err(invalid_input(format!("invalid port range: {:?}", s)));
+    }
+    Ok(first..=last)
+}
+
+/// Returns the range of local ports chosen for TCP and UDP sockets which are not explicitly bound
+/// to a port.
+pub fn ip_local_port_range() -> Result<RangeInclusive<u16>> {
+    parse_port_range(&read_sysctl::<String>("/proc/sys/net/ipv4/ip_local_port_range")?)
+}
+
+/// Returns the default TCP congestion control algorithm, e.g. `cubic`.
+pub fn tcp_congestion_control() -> Result<String> {
+    read_sysctl("/proc/sys/net/ipv4/tcp_congestion_control")
+}
+
+/// Returns the TCP congestion control algorithms which are loaded and available to sockets.
+pub fn tcp_available_congestion_control() -> Result<Vec<String>> {
+    parse_list(&read_sysctl::<String>("/proc/sys/net/ipv4/tcp_available_congestion_control")?)
+}
+
+/// Returns the TCP congestion control algorithms which unprivileged processes may select.
+pub fn tcp_allowed_congestion_control() -> Result<Vec<String>> {
+    parse_list(&read_sysctl::<String>("/proc/sys/net/ipv4/tcp_allowed_congestion_control")?)
+}
+
+/// Returns the maximum number of received packets queued per CPU before they are processed.
+pub fn netdev_max_backlog() -> Result<u32> {
+    read_sysctl("/proc/sys/net/core/netdev_max_backlog")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ip_forward, ip_local_port_range, netdev_max_backlog, parse_port_range, somaxconn,
+                tcp_allowed_congestion_control, tcp_available_congestion_control,
+                tcp_congestion_control, tcp_max_syn_backlog};
+
+    #[test]
+    fn test_net() {
+        ip_forward().unwrap();
+        assert!(somaxconn().unwrap() > 0);
+        assert!(tcp_max_syn_backlog().unwrap() > 0);
+        assert!(!ip_local_port_range().unwrap().is_empty());
+        assert!(netdev_max_backlog().unwrap() > 0);
+
+        let congestion_control = tcp_congestion_control().unwrap();
+        assert!(tcp_available_congestion_control().unwrap().contains(&congestion_control));
+        tcp_allowed_congestion_control().unwrap();
+    }
+
+    #[test]
+    fn test_parse_port_range() {
+        assert_eq!(32768..=60999, parse_port_range("32768\t60999").unwrap());
+        assert_eq!(1024..=1024, parse_port_range("1024 1024").unwrap());
+        parse_port_range("60999\t32768").unwrap_err();
+        parse_port_range("32768").unwrap_err();
+        parse_port_range("32768\t65536").unwrap_err();
+    }
+}
@@ -0,0 +1,186 @@
+//! Virtual memory configuration from `/proc/sys/vm`.
+//!
+//! The setters require `CAP_SYS_ADMIN` and a writable `/proc/sys` mount, which containers commonly
+//! do not have.
+
+use std::io::Result;
+
+use libc;
+
+use parsers::invalid_input;
+use sys::{read_sysctl, write_sysctl};
+
+/// The virtual memory overcommit policy.
+///
+/// See `Linux/Documentation/mm/overcommit-accounting.rst`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "export", derive(Serialize))]
+pub enum OvercommitMode {
+    /// Obvious overcommits of address space are refused (`0`, the default).
+    Heuristic,
+    /// Memory is always overcommitted (`1`).
+    Always,
+    /// Commits are limited to swap plus a ratio or amount of RAM (`2`).
+    Never,
+}
+
+impl OvercommitMode {
+    /// Returns the value of the mode in `/proc/sys/vm/overcommit_memory`.
+    fn as_raw(self) -> u8 {
+        match self {
+            OvercommitMode::Heuristic => 0,
+            OvercommitMode::Always    => 1,
+            OvercommitMode::Never     => 2,
+        }
+    }
+}
+
+/// Returns the virtual memory overcommit policy.
+pub fn overcommit_memory() -> Result<OvercommitMode> {
+    match read_sysctl("/proc/sys/vm/overcommit_memory")? {
+        0 => Ok(OvercommitMode::Heuristic),
+        1 => Ok(OvercommitMode::Always),
+        2 => Ok(OvercommitMode::Never),
+        mode => Err(invalid_input(format!("invalid overcommit mode: {}", mode))),
+    }
+}
+
+/// Sets the virtual memory overcommit policy.
+pub fn set_overcommit_memory(mode: OvercommitMode) -> Result<()> {
+    write_sysctl("/proc/sys/vm/overcommit_memory", mode.as_raw())
+}
+
+/// Returns the percentage of RAM which may be committed, in addition to swap, when the overcommit
+/// policy is `Never`.
+pub fn overcommit_ratio() -> Result<u64> {
+    read_sysctl("/proc/sys/vm/overcommit_ratio")
+}
+
+/// Sets the percentage of RAM which may be committed when the overcommit policy is `Never`.
+pub fn set_overcommit_ratio(ratio: u64) -> Result<()> {
+    write_sysctl("/proc/sys/vm/overcommit_ratio", ratio)
+}
+
+/// Returns the relative cost of swapping compared to filesystem paging, from 0 to 200.
+pub fn swappiness() -> Result<u32> {
+    read_sysctl("/proc/sys/vm/swappiness")
+}
+
+/// Sets the relative cost of swapping compared to filesystem paging, from 0 to 200.
+pub fn set_swappiness(swappiness: u32) -> Result<()> {
+    write_sysctl("/proc/sys/vm/swappiness", swappiness)
+}
+
+/// Returns the percentage of available memory which may be dirty before processes writing data
+/// are throttled.
+///
+/// Zero if the limit is instead set in bytes by `dirty_bytes`.
+pub fn dirty_ratio() -> Result<u32> {
+    read_sysctl("/proc/sys/vm/dirty_ratio")
+}
+
+/// Sets the percentage of available memory which may be dirty before processes writing data are
+/// throttled.
+pub fn set_dirty_ratio(ratio: u32) -> Result<()> {
+    write_sysctl("/proc/sys/vm/dirty_ratio", ratio)
+}
+
+/// Returns the percentage of available memory which may be dirty before background writeback
+/// starts.
+///
+/// Zero if the limit is instead set in bytes by `dirty_background_bytes`.
+pub fn dirty_background_ratio() -> Result<u32> {
+    read_sysctl("/proc/sys/vm/dirty_background_ratio")
+}
+
+/// Sets the percentage of available memory which may be dirty before background writeback starts.
+pub fn set_dirty_background_ratio(ratio: u32) -> Result<()> {
+    write_sysctl("/proc/sys/vm/dirty_background_ratio", ratio)
+}
+
+/// Returns the amount of memory the kernel keeps free, in kibibytes.
+pub fn min_free_kbytes() -> Result<u64> {
+    read_sysctl("/proc/sys/vm/min_free_kbytes")
+}
+
+/// Sets the amount of memory the kernel keeps free, in kibibytes.
+pub fn set_min_free_kbytes(kbytes: u64) -> Result<()> {
+    write_sysctl("/proc/sys/vm/min_free_kbytes", kbytes)
+}
+
+/// Returns the maximum number of memory maps a process may have.
+pub fn max_map_count() -> Result<u64> {
+    read_sysctl("/proc/sys/vm/max_map_count")
+}
+
+/// Sets the maximum number of memory maps a process may have.
+pub fn set_max_map_count(count: u64) -> Result<()> {
+    write_sysctl("/proc/sys/vm/max_map_count", count)
+}
+
+/// Returns the number of persistent huge pages of the default size in the pool.
+pub fn nr_hugepages() -> Result<u64> {
+    read_sysctl("/proc/sys/vm/nr_hugepages")
+}
+
+/// Sets the number of persistent huge pages of the default size in the pool.
+///
+/// The kernel may allocate fewer pages than requested; read back `nr_hugepages` to check.
+pub fn set_nr_hugepages(count: u64) -> Result<()> {
+    write_sysctl("/proc/sys/vm/nr_hugepages", count)
+}
+
+/// The caches dropped by `drop_caches`.
+///
+/// See `Linux/Documentation/admin-guide/sysctl/vm.rst`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "export", derive(Serialize))]
+pub enum DropCaches {
+    /// The page cache (`1`).
+    PageCache,
+    /// Reclaimable slab objects, including the dentry and inode caches (`2`).
+    DentriesAndInodes,
+    /// Both the page cache and reclaimable slab objects (`3`).
+    All,
+}
+
+impl DropCaches {
+    /// Returns the value of the caches in `/proc/sys/vm/drop_caches`.
+    fn as_raw(self) -> u8 {
+        match self {
+            DropCaches::PageCache         => 1,
+            DropCaches::DentriesAndInodes => 2,
+            DropCaches::All               => 3,
+        }
+    }
+}
+
+/// Drops clean caches, freeing the memory they hold.
+///
+/// Only clean pages and objects are dropped, so dirty data stays cached unless `sync` is set, in
+/// which case all filesystems are synced with `sync(2)` first. Dropping caches is a one-off
+/// action, and the caches fill again as files are accessed.
+pub fn drop_caches(caches: DropCaches, sync: bool) -> Result<()> {
+    if sync {
+        unsafe { libc::sync() };
+    }
+    write_sysctl("/proc/sys/vm/drop_caches", caches.as_raw())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{dirty_background_ratio, dirty_ratio, max_map_count, min_free_kbytes,
+                nr_hugepages, overcommit_memory, overcommit_ratio, swappiness};
+
+    #[test]
+    fn test_vm() {
+        overcommit_memory().unwrap();
+        overcommit_ratio().unwrap();
+        assert!(swappiness().unwrap() <= 200);
+        assert!(dirty_ratio().unwrap() <= 100);
+        assert!(dirty_background_ratio().unwrap() <= 100);
+        assert!(min_free_kbytes().unwrap() > 0);
+        assert!(max_map_count().unwrap() > 0);
+        nr_hugepages().unwrap();
+    }
+}
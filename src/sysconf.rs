@@ -0,0 +1,49 @@
+//! System configuration values which are needed to interpret `/proc` files.
+
+use std::time::Duration;
+
+use libc::{self, _SC_CLK_TCK, _SC_PAGESIZE};
+
+/// Returns the size of a memory page in bytes.
+///
+/// Fields which count pages, such as those of `pid::Statm`, are converted to bytes by multiplying
+/// by this value.
+pub fn page_size() -> usize {
+    unsafe { libc::sysconf(_SC_PAGESIZE) as usize }
+}
+
+/// Returns the number of clock ticks per second.
+///
+/// CPU times, such as those of `cpu::Cpu` and `pid::Stat`, are measured in clock ticks.
+pub fn ticks_per_second() -> u64 {
+    unsafe { libc::sysconf(_SC_CLK_TCK) as u64 }
+}
+
+/// Converts a number of clock ticks to a `Duration`.
+pub(crate) fn ticks_to_duration(ticks: u64) -> Duration {
+    let ticks_per_second = ticks_per_second();
+    let nanos = (ticks % ticks_per_second) * 1_000_000_000 / ticks_per_second;
+    Duration::new(ticks / ticks_per_second, nanos as u32)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::{page_size, ticks_per_second, ticks_to_duration};
+
+    #[test]
+    fn test_page_size() {
+        let page_size = page_size();
+        assert!(page_size >= 4096);
+        assert!(page_size.is_power_of_two());
+    }
+
+    #[test]
+    fn test_ticks_to_duration() {
+        let ticks_per_second = ticks_per_second();
+        assert_eq!(Duration::from_secs(0), ticks_to_duration(0));
+        assert_eq!(Duration::from_secs(3), ticks_to_duration(3 * ticks_per_second));
+        assert_eq!(Duration::from_millis(2500), ticks_to_duration(5 * ticks_per_second / 2));
+    }
+}
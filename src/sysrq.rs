@@ -0,0 +1,119 @@
+//! Magic SysRq commands through `/proc/sysrq-trigger`.
+//!
+//! Several of the commands reboot, power off, or crash the system without syncing or unmounting
+//! filesystems, or kill every process, so this module is only built with the `sysrq` feature.
+
+use std::fs::OpenOptions;
+use std::io::{Result, Write};
+
+use parsers::invalid_input;
+
+const SYSRQ_TRIGGER_FILE: &str = "/proc/sysrq-trigger";
+
+/// A magic SysRq command.
+///
+/// See `Linux/Documentation/admin-guide/sysrq.rst`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "export", derive(Serialize))]
+pub enum SysrqCommand {
+    /// Immediately reboots the system, without syncing or unmounting filesystems (`b`).
+    Reboot,
+    /// Crashes the system by a NULL pointer dereference, taking a crash dump if configured (`c`).
+    Crash,
+    /// Shows all held locks (`d`).
+    ShowHeldLocks,
+    /// Sends `SIGTERM` to all processes except init (`e`).
+    TerminateAll,
+    /// Invokes the OOM killer to kill a memory hog (`f`).
+    OomKill,
+    /// Sends `SIGKILL` to all processes except init (`i`).
+    KillAll,
+    /// Thaws filesystems frozen by the `FIFREEZE` ioctl (`j`).
+    ThawFilesystems,
+    /// Kills all processes on the current virtual console (`k`).
+    SecureAccessKey,
+    /// Shows a stack backtrace of all active CPUs (`l`).
+    ShowBacktraces,
+    /// Shows the current memory information (`m`).
+    ShowMemory,
+    /// Makes all real-time tasks niceable (`n`).
+    NiceRealtimeTasks,
+    /// Powers off the system (`o`).
+    PowerOff,
+    /// Shows the current registers and flags (`p`).
+    ShowRegisters,
+    /// Shows the armed high resolution timers and clock event devices of each CPU (`q`).
+    ShowTimers,
+    /// Switches the keyboard from raw mode to XLATE (`r`).
+    UnrawKeyboard,
+    /// Syncs all mounted filesystems (`s`).
+    Sync,
+    /// Shows the current tasks and their information (`t`).
+    ShowTasks,
+    /// Remounts all mounted filesystems read-only (`u`).
+    RemountReadOnly,
+    /// Shows the tasks which are in an uninterruptible (blocked) state (`w`).
+    ShowBlockedTasks,
+    /// Dumps the ftrace buffer (`z`).
+    DumpFtraceBuffer,
+    /// Sets the console log level, from 0 (emergency messages only) to 9 (`0` to `9`).
+    SetLogLevel(u8),
+}
+
+impl SysrqCommand {
+    /// Returns the character of the command in `/proc/sysrq-trigger`, or `None` if the log level
+    /// is out of range.
+    pub fn as_char(self) -> Option<char> {
+        let c = match self {
+            SysrqCommand::Reboot            => 'b',
+            SysrqCommand::Crash             => 'c',
+            SysrqCommand::ShowHeldLocks     => 'd',
+            SysrqCommand::TerminateAll      => 'e',
+            SysrqCommand::OomKill           => 'f',
+            SysrqCommand::KillAll           => 'i',
+            SysrqCommand::ThawFilesystems   => 'j',
+            SysrqCommand::SecureAccessKey   => 'k',
+            SysrqCommand::ShowBacktraces    => 'l',
+            SysrqCommand::ShowMemory        => 'm',
+            SysrqCommand::NiceRealtimeTasks => 'n',
+            SysrqCommand::PowerOff          => 'o',
+            SysrqCommand::ShowRegisters     => 'p',
+            SysrqCommand::ShowTimers        => 'q',
+            SysrqCommand::UnrawKeyboard     => 'r',
+            SysrqCommand::Sync              => 's',
+            SysrqCommand::ShowTasks         => 't',
+            SysrqCommand::RemountReadOnly   => 'u',
+            SysrqCommand::ShowBlockedTasks  => 'w',
+            SysrqCommand::DumpFtraceBuffer  => 'z',
+            SysrqCommand::SetLogLevel(level) => return char::from_digit(u32::from(level), 10),
+        };
+        Some(c)
+    }
+}
+
+/// Triggers the magic SysRq command.
+///
+/// Writing to `/proc/sysrq-trigger` requires `CAP_SYS_ADMIN`, and works regardless of the
+/// `kernel.sysrq` setting, which only restricts the keyboard combination. The output of the
+/// informational commands goes to the kernel log.
+pub fn trigger(command: SysrqCommand) -> Result<()> {
+    let c = command.as_char()
+                   .ok_or_else(|| invalid_input(format!("invalid SysRq command: {:?}", command)))?;
+    // The kernel handles the first character of each write.
+    OpenOptions::new().write(true).open(SYSRQ_TRIGGER_FILE)?.write_all(&[c as u8])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::SysrqCommand;
+
+    #[test]
+    fn test_as_char() {
+        assert_eq!(Some('s'), SysrqCommand::Sync.as_char());
+        assert_eq!(Some('u'), SysrqCommand::RemountReadOnly.as_char());
+        assert_eq!(Some('w'), SysrqCommand::ShowBlockedTasks.as_char());
+        assert_eq!(Some('0'), SysrqCommand::SetLogLevel(0).as_char());
+        assert_eq!(Some('9'), SysrqCommand::SetLogLevel(9).as_char());
+        assert_eq!(None, SysrqCommand::SetLogLevel(10).as_char());
+    }
+}
@@ -0,0 +1,164 @@
+//! Registered tty drivers from `/proc/tty/drivers`.
+
+use std::io::{Read, Result};
+use std::ops::RangeInclusive;
+use std::path::PathBuf;
+
+use parsers::{Fields, invalid_input, parse, parse_file, parse_lines, parse_str};
+
+/// The type of a tty driver.
+///
+/// See `Linux/include/linux/tty_driver.h`.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "export", derive(Serialize))]
+pub enum TtyDriverType {
+    /// A system driver without a subtype (`/dev/ptmx`).
+    System,
+    /// The controlling terminal driver (`/dev/tty`).
+    SystemTty,
+    /// The system console driver (`/dev/console`).
+    SystemConsole,
+    /// The current virtual console driver (`/dev/tty0`).
+    SystemVtMaster,
+    /// The virtual console driver.
+    Console,
+    /// A serial port driver.
+    Serial,
+    /// A pseudoterminal driver without a subtype.
+    Pty,
+    /// The pseudoterminal master driver.
+    PtyMaster,
+    /// The pseudoterminal slave driver.
+    PtySlave,
+    /// Another driver type, as printed by the kernel (e.g. `type:5.0`).
+    Other(String),
+}
+
+/// Parses a tty driver type.
+fn parse_tty_driver_type(s: &str) -> TtyDriverType {
+    match s {
+        "system"           => TtyDriverType::System,
+        "system:/dev/tty"  => TtyDriverType::SystemTty,
+        "system:console"   => TtyDriverType::SystemConsole,
+        "system:vtmaster"  => TtyDriverType::SystemVtMaster,
+        "console"          => TtyDriverType::Console,
+        "serial"           => TtyDriverType::Serial,
+        "pty"              => TtyDriverType::Pty,
+        "pty:master"       => TtyDriverType::PtyMaster,
+        "pty:slave"        => TtyDriverType::PtySlave,
+        other              => TtyDriverType::Other(other.to_owned()),
+    }
+}
+
+/// Parses a minor number, or an inclusive range of minor numbers of the form `first-last`.
+fn parse_minors(s: &str) -> Result<RangeInclusive<u32>> {
+    let (first, last) = match s.split_once('-') {
+        Some((first, last)) => (parse(first)?, parse(last)?),
+        None => {
+            let minor = parse(s)?;
+            (minor, minor)
+        },
+    };
+    if first > last {
+        return Err(invalid_input(format!("invalid minor range: {:?}", s)));
+    }
+    Ok(first..=last)
+}
+
+/// A registered tty driver.
+///
+/// A driver with several device number ranges has an entry per range.
+///
+/// See `Linux/fs/proc/proc_tty.c`.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "export", derive(Serialize))]
+pub struct TtyDriver {
+    /// The driver name, or `unknown` if the driver has no name.
+    pub name: String,
+    /// The default device node, which is the prefix of the device nodes of the range, e.g.
+    /// `/dev/ttyS` for `/dev/ttyS0`.
+    pub path: PathBuf,
+    /// The major device number.
+    pub major: u32,
+    /// The range of minor device numbers.
+    pub minors: RangeInclusive<u32>,
+    /// The driver type.
+    pub driver_type: TtyDriverType,
+}
+
+impl TtyDriver {
+    /// Parses a line of a `/proc/tty/drivers` file, without the line terminator.
+    pub fn parse_line(input: &[u8]) -> Result<TtyDriver> {
+        let mut fields = Fields::new(parse_str(input)?);
+        let name = fields.next_str()?.to_owned();
+        let path = PathBuf::from(fields.next_str()?);
+        let major = fields.parse()?;
+        let minors = parse_minors(fields.next_str()?)?;
+        let driver_type = parse_tty_driver_type(fields.next_str()?);
+        fields.finish()?;
+
+        Ok(TtyDriver { name, path, major, minors, driver_type })
+    }
+}
+
+/// Parses the tty drivers file, which has a line per device number range.
+fn parse_tty_drivers(input: &[u8]) -> Result<Vec<TtyDriver>> {
+    parse_lines(input, 0, TtyDriver::parse_line)
+}
+
+/// Returns the registered tty drivers.
+pub fn tty_drivers() -> Result<Vec<TtyDriver>> {
+    parse_file("/proc/tty/drivers", |file| {
+        let mut buf = Vec::new();
+        file.read_to_end(&mut buf)?;
+        parse_tty_drivers(&buf)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use super::{TtyDriver, TtyDriverType, parse_minors, parse_tty_drivers, tty_drivers};
+
+    /// Test that the system tty drivers file can be parsed.
+    #[test]
+    fn test_tty_drivers() {
+        let drivers = tty_drivers().unwrap();
+        assert!(drivers.iter().any(|driver| driver.driver_type == TtyDriverType::SystemTty));
+    }
+
+    #[test]
+    fn test_parse_tty_drivers() {
+        let text = b"/dev/tty             /dev/tty        5       0 system:/dev/tty\n\
+                     serial               /dev/ttyS       4      64 serial\n\
+                     pty_slave            /dev/pts      136 0-1048575 pty:slave\n\
+                     unknown              /dev/tty        4 1-63 console\n\
+                     foo                  /dev/foo      240 0-3 type:7.1\n";
+        let drivers = parse_tty_drivers(text).unwrap();
+        assert_eq!(5, drivers.len());
+        assert_eq!(TtyDriver {
+                       name: "serial".to_owned(),
+                       path: PathBuf::from("/dev/ttyS"),
+                       major: 4,
+                       minors: 64..=64,
+                       driver_type: TtyDriverType::Serial,
+                   },
+                   drivers[1]);
+        assert_eq!(136, drivers[2].major);
+        assert_eq!(0..=1048575, drivers[2].minors);
+        assert_eq!(TtyDriverType::PtySlave, drivers[2].driver_type);
+        assert_eq!(TtyDriverType::Console, drivers[3].driver_type);
+        assert_eq!(TtyDriverType::Other("type:7.1".to_owned()), drivers[4].driver_type);
+
+        TtyDriver::parse_line(b"serial /dev/ttyS 4 64").unwrap_err();
+    }
+
+    #[test]
+    fn test_parse_minors() {
+        assert_eq!(0..=0, parse_minors("0").unwrap());
+        assert_eq!(1..=63, parse_minors("1-63").unwrap());
+        parse_minors("63-1").unwrap_err();
+        parse_minors("a").unwrap_err();
+    }
+}
@@ -0,0 +1,89 @@
+//! Escaping and unescaping of paths in `/proc` files.
+//!
+//! The kernel escapes problematic characters in paths with `mangle_path`, which replaces each
+//! character in an escape set with a backslash followed by its three digit octal value. The escape
+//! set differs between files; for instance `/proc/[pid]/maps` escapes only newlines, while
+//! `/proc/[pid]/mountinfo` escapes spaces, tabs, newlines and backslashes.
+
+/// Reverses the kernel's `mangle_path` escaping of the characters in `escaped`.
+///
+/// Octal escape sequences which do not decode to a character in `escaped` are left as is, since
+/// the kernel would not have produced them.
+pub fn unmangled_path(path: &[u8], escaped: &[u8]) -> Vec<u8> {
+    let mut unmangled = Vec::with_capacity(path.len());
+    let mut i = 0;
+    while i < path.len() {
+        if path[i] == b'\\' {
+            if let Some(c) = path.get(i + 1..i + 4).and_then(parse_octal) {
+                if escaped.contains(&c) {
+                    unmangled.push(c);
+                    i += 4;
+                    continue;
+                }
+            }
+        }
+        unmangled.push(path[i]);
+        i += 1;
+    }
+    unmangled
+}
+
+/// Applies the kernel's `mangle_path` escaping to the characters in `escaped`.
+///
+/// This is the inverse of `unmangled_path`, and reconstructs paths as they appear in `/proc`.
+pub fn mangled_path(path: &[u8], escaped: &[u8]) -> Vec<u8> {
+    let mut mangled = Vec::with_capacity(path.len());
+    for &c in path {
+        if escaped.contains(&c) {
+            mangled.extend_from_slice(&[b'\\',
+                                        b'0' + (c >> 6),
+                                        b'0' + ((c >> 3) & 7),
+                                        b'0' + (c & 7)]);
+        } else {
+            mangled.push(c);
+        }
+    }
+    mangled
+}
+
+/// Parses a three digit octal escape into the character it represents.
+fn parse_octal(digits: &[u8]) -> Option<u8> {
+    digits.iter().try_fold(0u8, |acc, &d| match d {
+        b'0'..=b'7' => acc.checked_mul(8).map(|acc| acc | (d - b'0')),
+        _ => None,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{mangled_path, unmangled_path};
+
+    #[test]
+    fn test_unmangled_path() {
+        assert_eq!(b"/tmp/foo".to_vec(), unmangled_path(b"/tmp/foo", b"\n"));
+        assert_eq!(b"/tmp/f\no".to_vec(), unmangled_path(b"/tmp/f\\012o", b"\n"));
+        assert_eq!(b"/a b\\c".to_vec(), unmangled_path(b"/a\\040b\\134c", b" \t\n\\"));
+
+        // Escapes of characters outside of the escape set are left as is.
+        assert_eq!(b"/a\\040b".to_vec(), unmangled_path(b"/a\\040b", b"\n"));
+        // As are incomplete and invalid escapes.
+        assert_eq!(b"/a\\01".to_vec(), unmangled_path(b"/a\\01", b"\n"));
+        assert_eq!(b"/a\\777".to_vec(), unmangled_path(b"/a\\777", b"\n"));
+        assert_eq!(b"/a\\".to_vec(), unmangled_path(b"/a\\", b"\n"));
+    }
+
+    #[test]
+    fn test_mangled_path() {
+        assert_eq!(b"/tmp/foo".to_vec(), mangled_path(b"/tmp/foo", b"\n"));
+        assert_eq!(b"/tmp/f\\012o".to_vec(), mangled_path(b"/tmp/f\no", b"\n"));
+        assert_eq!(b"/a\\040b\\134c".to_vec(), mangled_path(b"/a b\\c", b" \t\n\\"));
+        // Backslashes are only escaped if they are in the escape set.
+        assert_eq!(b"/a\\040b".to_vec(), mangled_path(b"/a\\040b", b"\n"));
+        assert_eq!(b"\\377".to_vec(), mangled_path(b"\xff", b"\xff"));
+
+        for &path in &[&b"/a b\tc\nd\\e"[..], b"/a\\040b", b""] {
+            assert_eq!(path.to_vec(), unmangled_path(&mangled_path(path, b" \t\n\\"),
+                                                     b" \t\n\\"));
+        }
+    }
+}
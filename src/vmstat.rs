@@ -0,0 +1,169 @@
+//! Virtual memory statistics from `/proc/vmstat`.
+
+use std::fs::File;
+use std::io::{Error, ErrorKind, Read, Result};
+use std::path::Path;
+use std::str;
+
+use parsers::{with_line, with_path};
+
+/// Virtual memory statistics.
+///
+/// The `nr_*` fields are the current number of pages in each state, and the remaining fields are
+/// cumulative event counters since boot. Fields which are not reported by the running kernel are
+/// zero.
+///
+/// See `Linux/include/linux/vm_event_item.h` and `Linux/mm/vmstat.c`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "export", derive(Serialize))]
+pub struct Vmstat {
+    /// Free pages.
+    pub nr_free_pages: u64,
+    /// Pages on the inactive anonymous LRU list.
+    pub nr_inactive_anon: u64,
+    /// Pages on the active anonymous LRU list.
+    pub nr_active_anon: u64,
+    /// Pages on the inactive file LRU list.
+    pub nr_inactive_file: u64,
+    /// Pages on the active file LRU list.
+    pub nr_active_file: u64,
+    /// Anonymous pages mapped into user-space page tables.
+    pub nr_anon_pages: u64,
+    /// File pages mapped into user-space page tables.
+    pub nr_mapped: u64,
+    /// Pages in the page cache.
+    pub nr_file_pages: u64,
+    /// Pages waiting to be written back to disk.
+    pub nr_dirty: u64,
+    /// Pages being written back to disk.
+    pub nr_writeback: u64,
+    /// Shared memory and `tmpfs` pages.
+    pub nr_shmem: u64,
+    /// kB paged in from block devices.
+    pub pgpgin: u64,
+    /// kB paged out to block devices.
+    pub pgpgout: u64,
+    /// Pages swapped in.
+    pub pswpin: u64,
+    /// Pages swapped out.
+    pub pswpout: u64,
+    /// Pages freed.
+    pub pgfree: u64,
+    /// Pages moved to the active LRU lists.
+    pub pgactivate: u64,
+    /// Pages moved to the inactive LRU lists.
+    pub pgdeactivate: u64,
+    /// Page faults, both minor and major.
+    pub pgfault: u64,
+    /// Major page faults, which required a read from disk.
+    pub pgmajfault: u64,
+    /// Pages scanned by `kswapd`.
+    pub pgscan_kswapd: u64,
+    /// Pages scanned by direct reclaim.
+    pub pgscan_direct: u64,
+    /// Pages reclaimed by `kswapd`.
+    pub pgsteal_kswapd: u64,
+    /// Pages reclaimed by direct reclaim.
+    pub pgsteal_direct: u64,
+    /// Processes killed by the OOM killer (since Linux 4.13).
+    pub oom_kill: u64,
+}
+
+impl Vmstat {
+    /// Parses the contents of a `/proc/vmstat` file.
+    ///
+    /// Lines are dispatched on their name, and lines with unrecognized names are skipped.
+    pub fn parse(input: &[u8]) -> Result<Vmstat> {
+        let input = str::from_utf8(input)
+                        .map_err(|_| Error::new(ErrorKind::InvalidInput, "vmstat is not UTF-8"))?;
+        let mut vmstat = Vmstat::default();
+        for (i, line) in input.lines().enumerate() {
+            let mut parts = line.split_whitespace();
+            let name = parts.next().unwrap_or("");
+            let value = match parts.next() {
+                Some(value) => value,
+                None => continue,
+            };
+            let field = match name {
+                "nr_free_pages"     => &mut vmstat.nr_free_pages,
+                "nr_inactive_anon"  => &mut vmstat.nr_inactive_anon,
+                "nr_active_anon"    => &mut vmstat.nr_active_anon,
+                "nr_inactive_file"  => &mut vmstat.nr_inactive_file,
+                "nr_active_file"    => &mut vmstat.nr_active_file,
+                "nr_anon_pages"     => &mut vmstat.nr_anon_pages,
+                "nr_mapped"         => &mut vmstat.nr_mapped,
+                "nr_file_pages"     => &mut vmstat.nr_file_pages,
+                "nr_dirty"          => &mut vmstat.nr_dirty,
+                "nr_writeback"      => &mut vmstat.nr_writeback,
+                "nr_shmem"          => &mut vmstat.nr_shmem,
+                "pgpgin"            => &mut vmstat.pgpgin,
+                "pgpgout"           => &mut vmstat.pgpgout,
+                "pswpin"            => &mut vmstat.pswpin,
+                "pswpout"           => &mut vmstat.pswpout,
+                "pgfree"            => &mut vmstat.pgfree,
+                "pgactivate"        => &mut vmstat.pgactivate,
+                "pgdeactivate"      => &mut vmstat.pgdeactivate,
+                "pgfault"           => &mut vmstat.pgfault,
+                "pgmajfault"        => &mut vmstat.pgmajfault,
+                "pgscan_kswapd"     => &mut vmstat.pgscan_kswapd,
+                "pgscan_direct"     => &mut vmstat.pgscan_direct,
+                "pgsteal_kswapd"    => &mut vmstat.pgsteal_kswapd,
+                "pgsteal_direct"    => &mut vmstat.pgsteal_direct,
+                "oom_kill"          => &mut vmstat.oom_kill,
+                _ => continue,
+            };
+            *field = value.parse().map_err(|_| {
+                let error = Error::new(ErrorKind::InvalidInput,
+                                       format!("invalid vmstat line: {:?}", line));
+                with_line(error, i + 1)
+            })?;
+        }
+        Ok(vmstat)
+    }
+}
+
+/// Returns virtual memory statistics.
+pub fn vmstat() -> Result<Vmstat> {
+    let mut buf = Vec::new();
+    File::open("/proc/vmstat")?.read_to_end(&mut buf)?;
+    Vmstat::parse(&buf).map_err(|error| with_path(error, Path::new("/proc/vmstat")))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Vmstat, vmstat};
+
+    /// Test that the system vmstat file can be parsed.
+    #[test]
+    fn test_vmstat() {
+        let vmstat = vmstat().unwrap();
+        assert!(vmstat.nr_free_pages > 0);
+        assert!(vmstat.pgfault >= vmstat.pgmajfault);
+    }
+
+    #[test]
+    fn test_parse_vmstat() {
+        let text = b"nr_free_pages 240402\n\
+                     nr_dirty 60\n\
+                     numa_hit 57291947\n\
+                     pgpgin 4455084\n\
+                     pgpgout 13245764\n\
+                     pswpin 12\n\
+                     pswpout 34\n\
+                     pgfault 84912331\n\
+                     pgmajfault 8821\n\
+                     nr_unaccepted\n";
+        let vmstat = Vmstat::parse(text).unwrap();
+        assert_eq!(240402, vmstat.nr_free_pages);
+        assert_eq!(60, vmstat.nr_dirty);
+        assert_eq!(4455084, vmstat.pgpgin);
+        assert_eq!(13245764, vmstat.pgpgout);
+        assert_eq!(12, vmstat.pswpin);
+        assert_eq!(34, vmstat.pswpout);
+        assert_eq!(84912331, vmstat.pgfault);
+        assert_eq!(8821, vmstat.pgmajfault);
+        assert_eq!(0, vmstat.oom_kill);
+
+        Vmstat::parse(b"pgfault many\n").unwrap_err();
+    }
+}